@@ -0,0 +1,79 @@
+//! Parse one transaction with the universal parser, outside the listener.
+//!
+//! ```text
+//! cargo run --example parse_single -- <SIGNATURE> <RPC_URL> [WALLET]
+//! ```
+//!
+//! `WALLET` is the trader whose balance changes are analyzed; it defaults to
+//! the transaction's fee payer.
+
+use copy_tradin::{TradeDirection, TransactionFetcher, UniversalParser};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiMessage,
+};
+use std::env;
+use std::str::FromStr;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        eprintln!("Usage: parse_single <SIGNATURE> <RPC_URL> [WALLET]");
+        std::process::exit(1);
+    }
+
+    let signature = Signature::from_str(&args[1])?;
+    let client = RpcClient::new_with_commitment(args[2].clone(), CommitmentConfig::confirmed());
+    let transaction = TransactionFetcher::get_transaction(&client, &signature)?;
+
+    let wallet = match args.get(3) {
+        Some(wallet) => Pubkey::from_str(wallet)?,
+        None => fee_payer(&transaction).ok_or("Could not read the fee payer; pass WALLET")?,
+    };
+    println!("Analyzing {} for wallet {}", signature, wallet);
+
+    let parser = UniversalParser::new(wallet);
+    let Some(signal) = parser.parse(transaction)? else {
+        println!("No swap detected");
+        return Ok(());
+    };
+
+    println!("Type:       {}", signal.swap_type);
+    println!("Input:      {}", signal.format_input());
+    println!("Output:     {}", signal.format_output());
+    println!(
+        "DEX:        {}",
+        signal.likely_dex.as_deref().unwrap_or("-")
+    );
+    println!("Confidence: {:.2}", signal.confidence);
+
+    match signal.direction() {
+        TradeDirection::Buy { token, payment } => {
+            println!("Direction:  BUY {} with {}", token, payment)
+        }
+        TradeDirection::Sell { token, receives } => {
+            println!("Direction:  SELL {} for {}", token, receives)
+        }
+        TradeDirection::Swap {
+            from_token,
+            to_token,
+        } => println!("Direction:  SWAP {} -> {}", from_token, to_token),
+    }
+
+    Ok(())
+}
+
+/// First account key of a `jsonParsed` transaction
+fn fee_payer(transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Option<Pubkey> {
+    let EncodedTransaction::Json(tx) = &transaction.transaction.transaction else {
+        return None;
+    };
+    let key = match &tx.message {
+        UiMessage::Parsed(message) => &message.account_keys.first()?.pubkey,
+        UiMessage::Raw(message) => message.account_keys.first()?,
+    };
+    Pubkey::from_str(key).ok()
+}
@@ -0,0 +1,190 @@
+//! Read-only HTTP JSON API for dashboards
+//!
+//! Serves the shared portfolio and a ring buffer of recently detected
+//! signals. Handlers only hold locks long enough to copy data out, so the
+//! trading tasks are never blocked on a slow client.
+
+use crate::detection::types::UniversalSwapSignal;
+use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::portfolio::{
+    ClosedPosition, JupiterPriceProvider, NormalizedStats, PortfolioStats, PortfolioTracker,
+    Position,
+};
+use axum::extract::{Query, State};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tracing::info;
+
+/// Signals returned by `/signals` when no `limit` is given
+const DEFAULT_SIGNAL_LIMIT: usize = 20;
+
+/// Fixed-size buffer of the most recent signals, newest last
+pub struct RecentSignals {
+    capacity: usize,
+    signals: Mutex<VecDeque<UniversalSwapSignal>>,
+}
+
+impl RecentSignals {
+    /// Create a buffer holding at most `capacity` signals
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            signals: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Record a signal, evicting the oldest once full
+    pub fn push(&self, signal: UniversalSwapSignal) {
+        let mut signals = self.signals.lock().unwrap();
+        if signals.len() == self.capacity {
+            signals.pop_front();
+        }
+        signals.push_back(signal);
+    }
+
+    /// Up to `limit` most recent signals, newest first
+    pub fn latest(&self, limit: usize) -> Vec<UniversalSwapSignal> {
+        let signals = self.signals.lock().unwrap();
+        signals.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+/// State shared with the request handlers
+#[derive(Clone)]
+pub struct ApiState {
+    pub portfolio: Arc<Mutex<PortfolioTracker>>,
+    pub signals: Arc<RecentSignals>,
+    /// Convert `/portfolio/stats` into this currency, if set
+    pub display_currency: Option<DisplayCurrency>,
+}
+
+/// `display_currency` and the prices to convert into it
+#[derive(Clone)]
+pub struct DisplayCurrency {
+    pub currency: Pubkey,
+    pub prices: Arc<JupiterPriceProvider>,
+}
+
+/// Body of `/portfolio/stats`: converted when a display currency is set
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum StatsResponse {
+    Raw(PortfolioStats),
+    Normalized(NormalizedStats),
+}
+
+/// Body of `/portfolio`
+#[derive(Debug, Serialize)]
+pub struct PortfolioResponse {
+    pub stats: PortfolioStats,
+    pub positions: Vec<Position>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignalsQuery {
+    limit: Option<usize>,
+}
+
+/// Build the API routes
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/portfolio", get(portfolio))
+        .route("/portfolio/stats", get(portfolio_stats))
+        .route("/portfolio/positions", get(portfolio_positions))
+        .route("/portfolio/history", get(portfolio_history))
+        .route("/signals", get(signals))
+        .with_state(state)
+}
+
+/// Serve the API on `addr` until the task is dropped
+pub async fn serve(addr: SocketAddr, state: ApiState) -> MonitorResult<()> {
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| MonitorError::ConfigError(format!("Failed to bind API on {}: {}", addr, e)))?;
+
+    info!("🌐 HTTP API listening on http://{}", addr);
+    axum::serve(listener, router(state))
+        .await
+        .map_err(|e| MonitorError::ConnectionFailed(format!("HTTP API stopped: {}", e)))
+}
+
+/// Open positions, oldest first
+fn sorted_positions(portfolio: &PortfolioTracker) -> Vec<Position> {
+    let mut positions: Vec<Position> = portfolio.get_all_positions().into_iter().cloned().collect();
+    positions.sort_by_key(|p| p.entry_time);
+    positions
+}
+
+async fn portfolio(State(state): State<ApiState>) -> Json<PortfolioResponse> {
+    let portfolio = state.portfolio.lock().unwrap();
+
+    Json(PortfolioResponse {
+        stats: portfolio.get_stats(),
+        positions: sorted_positions(&portfolio),
+    })
+}
+
+async fn portfolio_stats(State(state): State<ApiState>) -> Json<StatsResponse> {
+    let Some(display) = &state.display_currency else {
+        return Json(StatsResponse::Raw(
+            state.portfolio.lock().unwrap().get_stats(),
+        ));
+    };
+
+    // Rates come over HTTP, so the portfolio isn't locked while they load
+    let stats = PortfolioTracker::shared_stats_in(
+        &state.portfolio,
+        display.prices.as_ref(),
+        &display.currency,
+    )
+    .await;
+    Json(StatsResponse::Normalized(stats))
+}
+
+async fn portfolio_positions(State(state): State<ApiState>) -> Json<Vec<Position>> {
+    Json(sorted_positions(&state.portfolio.lock().unwrap()))
+}
+
+/// Closed positions, in the order they were closed
+async fn portfolio_history(State(state): State<ApiState>) -> Json<Vec<ClosedPosition>> {
+    Json(state.portfolio.lock().unwrap().get_history().to_vec())
+}
+
+async fn signals(
+    State(state): State<ApiState>,
+    Query(query): Query<SignalsQuery>,
+) -> Json<Vec<UniversalSwapSignal>> {
+    let limit = query.limit.unwrap_or(DEFAULT_SIGNAL_LIMIT);
+    Json(state.signals.latest(limit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(slot: u64) -> UniversalSwapSignal {
+        UniversalSwapSignal {
+            slot,
+            ..UniversalSwapSignal::test_swap()
+        }
+    }
+
+    #[test]
+    fn test_recent_signals_ring_buffer() {
+        let recent = RecentSignals::new(3);
+        for slot in 1..=5 {
+            recent.push(signal(slot));
+        }
+
+        let slots: Vec<u64> = recent.latest(10).iter().map(|s| s.slot).collect();
+        assert_eq!(slots, vec![5, 4, 3]);
+
+        let slots: Vec<u64> = recent.latest(2).iter().map(|s| s.slot).collect();
+        assert_eq!(slots, vec![5, 4]);
+    }
+}
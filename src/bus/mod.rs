@@ -0,0 +1,137 @@
+//! In-process stream of detected swaps
+//!
+//! The pipeline publishes one [`SignalEvent`] per detected swap to a
+//! [`SignalBus`]; any number of consumers (dashboards, notifiers, custom
+//! executors) can [`subscribe`](SignalBus::subscribe) without touching the
+//! pipeline. Publishing never blocks: a consumer that falls more than the
+//! bus capacity behind skips the oldest events and is told how many it
+//! missed.
+
+use crate::decision::TradeDirection;
+use crate::detection::types::UniversalSwapSignal;
+use crate::portfolio::{ClosedPosition, EntryKind, Position};
+use tokio::sync::broadcast;
+
+/// Events kept for slow subscribers by [`SignalBus::default`]
+pub const DEFAULT_BUS_CAPACITY: usize = 256;
+
+/// How a signal changed the mirrored portfolio
+#[derive(Debug, Clone)]
+pub enum PortfolioDelta {
+    /// A buy opened a position or added to one
+    Opened {
+        entry: EntryKind,
+        /// Tokens bought (raw units)
+        amount: u64,
+        /// What they cost (raw units of the payment token)
+        cost: u64,
+        /// The position after the buy
+        position: Position,
+    },
+
+    /// A sell closed all or part of a position
+    Closed(ClosedPosition),
+}
+
+/// A detected swap, what kind of trade it is, and what it did to the
+/// portfolio (`None` if it wasn't tracked, e.g. filtered out or a token swap)
+#[derive(Debug, Clone)]
+pub struct SignalEvent {
+    pub signal: UniversalSwapSignal,
+    pub direction: TradeDirection,
+    pub portfolio: Option<PortfolioDelta>,
+}
+
+/// Broadcasts [`SignalEvent`]s to every subscriber
+#[derive(Debug, Clone)]
+pub struct SignalBus {
+    sender: broadcast::Sender<SignalEvent>,
+}
+
+impl SignalBus {
+    /// Create a bus keeping up to `capacity` events for slow subscribers
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self { sender }
+    }
+
+    /// Receive every event published from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<SignalEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Send `event` to all current subscribers, returning how many there
+    /// are. Without subscribers the event is simply dropped.
+    pub fn publish(&self, event: SignalEvent) -> usize {
+        self.sender.send(event).unwrap_or(0)
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for SignalBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUS_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::broadcast::error::RecvError;
+
+    fn event() -> SignalEvent {
+        let signal = UniversalSwapSignal::test_swap();
+        SignalEvent {
+            direction: TradeDirection::Buy {
+                token: signal.output_mint,
+                payment: signal.input_mint,
+            },
+            signal,
+            portfolio: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_every_subscriber_receives_events() {
+        let bus = SignalBus::default();
+        // Nobody listening yet: dropped, not an error
+        assert_eq!(bus.publish(event()), 0);
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        let sent = event();
+        assert_eq!(bus.publish(sent.clone()), 2);
+
+        assert_eq!(
+            first.recv().await.unwrap().signal.signature,
+            sent.signal.signature
+        );
+        assert_eq!(
+            second.recv().await.unwrap().signal.signature,
+            sent.signal.signature
+        );
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscriber_skips_oldest() {
+        let bus = SignalBus::new(2);
+        let mut slow = bus.subscribe();
+        let events: Vec<SignalEvent> = (0..3).map(|_| event()).collect();
+        for event in &events {
+            bus.publish(event.clone());
+        }
+
+        assert!(matches!(slow.recv().await, Err(RecvError::Lagged(1))));
+        assert_eq!(
+            slow.recv().await.unwrap().signal.signature,
+            events[1].signal.signature
+        );
+        assert_eq!(
+            slow.recv().await.unwrap().signal.signature,
+            events[2].signal.signature
+        );
+    }
+}
@@ -1,26 +1,129 @@
 use crate::monitor::error::{MonitorError, MonitorResult};
-use crate::types::MonitorConfig;
+use crate::types::{
+    ApiConfig, CostBasisMethod, DEFAULT_JUPITER_API_URL, DexPolicy, DexType, ExecutionMode,
+    ExecutorConfig, ListenerMode, MonitorConfig, NotificationsConfig, RiskConfig, SellPolicy,
+    StorageBackend,
+};
 use config::{Config, File};
 use serde::Deserialize;
 use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::net::IpAddr;
 use std::path::Path;
 use std::str::FromStr;
-use tracing::info;
+use tracing::{Level, info};
+
+pub mod reload;
+
+pub use reload::{ConfigWatcher, SharedConfig, reload_config, watch_config};
 
 #[derive(Debug, Deserialize)]
 struct RawConfig {
     monitor: RawMonitorConfig,
     logging: Option<LoggingConfig>,
+    executor: Option<RawExecutorConfig>,
+    risk: Option<RawRiskConfig>,
+    api: Option<RawApiConfig>,
+    notifications: Option<NotificationsConfig>,
+    dex: Option<HashMap<String, RawDexPolicy>>,
 }
 
 #[derive(Debug, Deserialize)]
 struct RawMonitorConfig {
-    target_wallet: String,
+    target_wallet: Option<String>,
+    target_wallets: Option<Vec<String>>,
+    target_wallets_file: Option<String>,
     rpc_endpoints: Vec<String>,
+    fetch_delay_ms: Option<u64>,
+    catch_up: Option<bool>,
+    catch_up_limit: Option<usize>,
+    last_signature_path: Option<String>,
+    listener_mode: Option<String>,
+    poll_interval_ms: Option<u64>,
     websocket_endpoint: String,
+    max_ws_message_bytes: Option<usize>,
+    max_notifications_per_sec: Option<u32>,
     connection_timeout_secs: Option<u64>,
+    keepalive_interval_secs: Option<u64>,
+    keepalive_timeout_secs: Option<u64>,
     max_reconnect_attempts: Option<u32>,
+    max_reconnect_backoff_secs: Option<u64>,
     use_confirmed_commitment: Option<bool>,
+    verify_finalized: Option<bool>,
+    finality_check_delay_secs: Option<u64>,
+    instruction_fallback: Option<bool>,
+    prefer_inner_dex: Option<bool>,
+    require_base_asset: Option<bool>,
+    min_confidence: Option<f32>,
+    min_priority_fee_lamports: Option<u64>,
+    max_slippage_bps: Option<u16>,
+    allow_nfts: Option<bool>,
+    ignored_mints: Option<Vec<String>>,
+    extra_stablecoins: Option<Vec<String>>,
+    token_allowlist: Option<Vec<String>>,
+    token_denylist: Option<Vec<String>>,
+    treat_wsol_as_tradeable: Option<bool>,
+    min_wsol_delta_lamports: Option<u64>,
+    only_new_mints: Option<bool>,
+    new_mint_max_age_secs: Option<u64>,
+    min_trader_win_rate: Option<f64>,
+    min_trader_pnl: Option<i64>,
+    max_round_trip_loss_bps: Option<u16>,
+    semantic_dedup_window_secs: Option<u64>,
+    max_signal_age_secs: Option<u64>,
+    max_open_positions: Option<usize>,
+    min_input_value: Option<f64>,
+    sell_policy: Option<String>,
+    cost_basis_method: Option<String>,
+    portfolio_path: Option<String>,
+    storage_backend: Option<String>,
+    sqlite_path: Option<String>,
+    snapshot_interval_secs: Option<u64>,
+    snapshot_path: Option<String>,
+    display_currency: Option<String>,
+    dead_letter_path: Option<String>,
+    dead_letter_raw_json: Option<bool>,
+    log_unknown_patterns: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawExecutorConfig {
+    simulate_before_send: Option<bool>,
+    copy_delay_ms: Option<u64>,
+    copy_delay_jitter_ms: Option<u64>,
+    sol_fee_reserve_lamports: Option<u64>,
+    mode: Option<String>,
+    max_input_amount: Option<u64>,
+    keypair_path: Option<String>,
+    jupiter_api_url: Option<String>,
+    slippage_bps: Option<u16>,
+    copy_ratio: Option<f64>,
+    max_position_usd: Option<f64>,
+    min_copy_amount: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawRiskConfig {
+    stop_loss_percent: Option<f64>,
+    take_profit_percent: Option<f64>,
+    ema_alpha: Option<f64>,
+    ema_window: Option<u32>,
+    trailing_stop_percent: Option<f64>,
+    trailing_stop_activation_percent: Option<f64>,
+    check_interval_secs: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawApiConfig {
+    enabled: Option<bool>,
+    bind_address: Option<String>,
+    port: Option<u16>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RawDexPolicy {
+    enabled: Option<bool>,
+    size_multiplier: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,9 +144,56 @@ pub fn load_config<P: AsRef<Path>>(path: P) -> MonitorResult<MonitorConfig> {
         .try_deserialize()
         .map_err(|e| MonitorError::ConfigError(format!("Failed to parse config: {}", e)))?;
 
-    // Parse target wallet pubkey
-    let target_wallet = Pubkey::from_str(&raw.monitor.target_wallet)
-        .map_err(|e| MonitorError::ConfigError(format!("Invalid target wallet address: {}", e)))?;
+    // Parse target wallet pubkeys (inline and from file), deduped in order
+    let mut target_wallets: Vec<Pubkey> = Vec::new();
+
+    let inline_wallets = raw
+        .monitor
+        .target_wallet
+        .iter()
+        .chain(raw.monitor.target_wallets.iter().flatten());
+
+    for wallet in inline_wallets {
+        let pubkey = Pubkey::from_str(wallet).map_err(|e| {
+            MonitorError::ConfigError(format!("Invalid target wallet address {}: {}", wallet, e))
+        })?;
+        target_wallets.push(pubkey);
+    }
+
+    if let Some(wallets_file) = &raw.monitor.target_wallets_file {
+        let contents = std::fs::read_to_string(wallets_file).map_err(|e| {
+            MonitorError::ConfigError(format!(
+                "Failed to read wallets file {}: {}",
+                wallets_file, e
+            ))
+        })?;
+        target_wallets.extend(parse_wallets_file(&contents)?);
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    target_wallets.retain(|wallet| seen.insert(*wallet));
+
+    let Some(&target_wallet) = target_wallets.first() else {
+        return Err(MonitorError::ConfigError(
+            "At least one target wallet is required".to_string(),
+        ));
+    };
+
+    let ignored_mints = parse_mints(&raw.monitor.ignored_mints, "ignored mint")?;
+    let extra_stablecoins = parse_mints(&raw.monitor.extra_stablecoins, "extra stablecoin")?;
+    let token_allowlist = parse_mints(&raw.monitor.token_allowlist, "token_allowlist mint")?;
+    let token_denylist = parse_mints(&raw.monitor.token_denylist, "token_denylist mint")?;
+
+    let display_currency = raw
+        .monitor
+        .display_currency
+        .as_deref()
+        .map(|mint| {
+            Pubkey::from_str(mint).map_err(|e| {
+                MonitorError::ConfigError(format!("Invalid display_currency {}: {}", mint, e))
+            })
+        })
+        .transpose()?;
 
     // Validate RPC endpoints
     if raw.monitor.rpc_endpoints.is_empty() {
@@ -70,46 +220,572 @@ pub fn load_config<P: AsRef<Path>>(path: P) -> MonitorResult<MonitorConfig> {
         ));
     }
 
+    if raw.monitor.max_ws_message_bytes == Some(0) {
+        return Err(MonitorError::ConfigError(
+            "max_ws_message_bytes must be greater than 0".to_string(),
+        ));
+    }
+
+    let executor = raw.executor.unwrap_or_default();
+    let dex_policies = parse_dex_policies(raw.dex.unwrap_or_default())?;
+    let execution_mode = match &executor.mode {
+        Some(mode) => ExecutionMode::from_str(mode)
+            .map_err(|e| MonitorError::ConfigError(format!("Invalid executor mode: {}", e)))?,
+        None => ExecutionMode::default(),
+    };
+    if execution_mode == ExecutionMode::Jupiter && executor.keypair_path.is_none() {
+        return Err(MonitorError::ConfigError(
+            "executor mode \"jupiter\" needs a keypair_path".to_string(),
+        ));
+    }
+
+    let copy_ratio = executor.copy_ratio.unwrap_or(1.0);
+    if !(copy_ratio.is_finite() && copy_ratio >= 0.0) {
+        return Err(MonitorError::ConfigError(format!(
+            "copy_ratio must be 0 or more, got {}",
+            copy_ratio
+        )));
+    }
+    if let Some(max) = executor.max_position_usd
+        && (max.is_nan() || max <= 0.0)
+    {
+        return Err(MonitorError::ConfigError(format!(
+            "max_position_usd must be positive, got {}",
+            max
+        )));
+    }
+
+    let listener_mode = match &raw.monitor.listener_mode {
+        Some(mode) => ListenerMode::from_str(mode)
+            .map_err(|e| MonitorError::ConfigError(format!("Invalid listener_mode: {}", e)))?,
+        None => ListenerMode::default(),
+    };
+    let storage_backend = match &raw.monitor.storage_backend {
+        Some(backend) => StorageBackend::from_str(backend)
+            .map_err(|e| MonitorError::ConfigError(format!("Invalid storage_backend: {}", e)))?,
+        None => StorageBackend::default(),
+    };
+    if raw.monitor.max_open_positions == Some(0) {
+        return Err(MonitorError::ConfigError(
+            "max_open_positions must be greater than 0".to_string(),
+        ));
+    }
+    if let Some(min) = raw.monitor.min_input_value
+        && !(min.is_finite() && min >= 0.0)
+    {
+        return Err(MonitorError::ConfigError(format!(
+            "min_input_value must be 0 or more, got {}",
+            min
+        )));
+    }
+    if raw.monitor.poll_interval_ms == Some(0) {
+        return Err(MonitorError::ConfigError(
+            "poll_interval_ms must be greater than 0".to_string(),
+        ));
+    }
+
+    let bind_address = match raw.api.as_ref().and_then(|api| api.bind_address.as_deref()) {
+        Some(address) => IpAddr::from_str(address).map_err(|e| {
+            MonitorError::ConfigError(format!("Invalid api bind_address {}: {}", address, e))
+        })?,
+        None => ApiConfig::default().bind_address,
+    };
+
+    let sell_policy = match &raw.monitor.sell_policy {
+        Some(policy) => SellPolicy::from_str(policy)
+            .map_err(|e| MonitorError::ConfigError(format!("Invalid sell_policy: {}", e)))?,
+        None => SellPolicy::default(),
+    };
+
+    let cost_basis_method = match &raw.monitor.cost_basis_method {
+        Some(method) => CostBasisMethod::from_str(method)
+            .map_err(|e| MonitorError::ConfigError(format!("Invalid cost_basis_method: {}", e)))?,
+        None => CostBasisMethod::default(),
+    };
+
+    // EMA smoothing: explicit alpha wins, otherwise derive it from the window
+    let risk = raw.risk.unwrap_or_default();
+    let ema_alpha = risk
+        .ema_alpha
+        .or_else(|| risk.ema_window.map(|n| 2.0 / (n as f64 + 1.0)));
+
+    if let Some(alpha) = ema_alpha
+        && !(alpha > 0.0 && alpha <= 1.0)
+    {
+        return Err(MonitorError::ConfigError(format!(
+            "EMA alpha must be in (0, 1], got {}",
+            alpha
+        )));
+    }
+    if risk.check_interval_secs == Some(0) {
+        return Err(MonitorError::ConfigError(
+            "check_interval_secs must be greater than 0".to_string(),
+        ));
+    }
+
     let monitor_config = MonitorConfig {
         target_wallet,
+        target_wallets,
         rpc_endpoints: raw.monitor.rpc_endpoints,
+        fetch_delay_ms: raw.monitor.fetch_delay_ms.unwrap_or(500),
+        catch_up: raw.monitor.catch_up.unwrap_or(false),
+        catch_up_limit: raw.monitor.catch_up_limit.unwrap_or(100),
+        last_signature_path: raw
+            .monitor
+            .last_signature_path
+            .unwrap_or_else(|| "last_signature.txt".to_string()),
+        listener_mode,
+        poll_interval_ms: raw.monitor.poll_interval_ms.unwrap_or(2_000),
         websocket_endpoint: raw.monitor.websocket_endpoint,
+        max_ws_message_bytes: raw.monitor.max_ws_message_bytes.unwrap_or(1024 * 1024),
+        max_notifications_per_sec: raw.monitor.max_notifications_per_sec.unwrap_or(200),
         connection_timeout_secs: raw.monitor.connection_timeout_secs.unwrap_or(30),
+        keepalive_interval_secs: raw.monitor.keepalive_interval_secs.unwrap_or(30),
+        keepalive_timeout_secs: raw.monitor.keepalive_timeout_secs.unwrap_or(10),
         max_reconnect_attempts: raw.monitor.max_reconnect_attempts.unwrap_or(5),
+        max_reconnect_backoff_secs: raw.monitor.max_reconnect_backoff_secs.unwrap_or(60),
         use_confirmed_commitment: raw.monitor.use_confirmed_commitment.unwrap_or(true),
+        verify_finalized: raw.monitor.verify_finalized.unwrap_or(false),
+        finality_check_delay_secs: raw.monitor.finality_check_delay_secs.unwrap_or(10),
+        instruction_fallback: raw.monitor.instruction_fallback.unwrap_or(false),
+        prefer_inner_dex: raw.monitor.prefer_inner_dex.unwrap_or(false),
+        require_base_asset: raw.monitor.require_base_asset.unwrap_or(false),
+        min_confidence: raw.monitor.min_confidence.unwrap_or(0.0),
+        min_priority_fee_lamports: raw.monitor.min_priority_fee_lamports.unwrap_or(0),
+        max_slippage_bps: raw.monitor.max_slippage_bps,
+        allow_nfts: raw.monitor.allow_nfts.unwrap_or(false),
+        ignored_mints,
+        extra_stablecoins,
+        token_allowlist,
+        token_denylist,
+        treat_wsol_as_tradeable: raw.monitor.treat_wsol_as_tradeable.unwrap_or(false),
+        min_wsol_delta_lamports: raw.monitor.min_wsol_delta_lamports.unwrap_or(10_000_000),
+        only_new_mints: raw.monitor.only_new_mints.unwrap_or(false),
+        new_mint_max_age_secs: raw.monitor.new_mint_max_age_secs,
+        min_trader_win_rate: raw.monitor.min_trader_win_rate,
+        min_trader_pnl: raw.monitor.min_trader_pnl,
+        max_round_trip_loss_bps: raw.monitor.max_round_trip_loss_bps,
+        semantic_dedup_window_secs: raw.monitor.semantic_dedup_window_secs,
+        max_signal_age_secs: raw.monitor.max_signal_age_secs,
+        max_open_positions: raw.monitor.max_open_positions,
+        min_input_value: raw.monitor.min_input_value,
+        sell_policy,
+        cost_basis_method,
+        portfolio_path: raw
+            .monitor
+            .portfolio_path
+            .unwrap_or_else(|| "portfolio.json".to_string()),
+        storage_backend,
+        sqlite_path: raw
+            .monitor
+            .sqlite_path
+            .unwrap_or_else(|| "sniper.db".to_string()),
+        snapshot_interval_secs: raw.monitor.snapshot_interval_secs,
+        snapshot_path: raw
+            .monitor
+            .snapshot_path
+            .unwrap_or_else(|| "snapshots.jsonl".to_string()),
+        display_currency,
+        dead_letter_path: raw.monitor.dead_letter_path,
+        dead_letter_raw_json: raw.monitor.dead_letter_raw_json.unwrap_or(false),
+        log_unknown_patterns: raw.monitor.log_unknown_patterns.unwrap_or(false),
+        log_level: raw
+            .logging
+            .and_then(|l| l.level)
+            .unwrap_or_else(|| "info".to_string()),
+        executor: ExecutorConfig {
+            simulate_before_send: executor.simulate_before_send.unwrap_or(true),
+            copy_delay_ms: executor.copy_delay_ms.unwrap_or(0),
+            copy_delay_jitter_ms: executor.copy_delay_jitter_ms.unwrap_or(0),
+            sol_fee_reserve_lamports: executor.sol_fee_reserve_lamports.unwrap_or(20_000_000),
+            mode: execution_mode,
+            max_input_amount: executor.max_input_amount,
+            keypair_path: executor.keypair_path,
+            jupiter_api_url: executor
+                .jupiter_api_url
+                .unwrap_or_else(|| DEFAULT_JUPITER_API_URL.to_string()),
+            slippage_bps: executor.slippage_bps.unwrap_or(100),
+            copy_ratio,
+            max_position_usd: executor.max_position_usd,
+            min_copy_amount: executor.min_copy_amount.unwrap_or(1),
+        },
+        risk: RiskConfig {
+            stop_loss_percent: risk.stop_loss_percent,
+            take_profit_percent: risk.take_profit_percent,
+            ema_alpha,
+            trailing_stop_percent: risk.trailing_stop_percent,
+            trailing_stop_activation_percent: risk.trailing_stop_activation_percent,
+            check_interval_secs: risk
+                .check_interval_secs
+                .unwrap_or(RiskConfig::default().check_interval_secs),
+        },
+        api: {
+            let api = raw.api.unwrap_or_default();
+            let defaults = ApiConfig::default();
+            ApiConfig {
+                enabled: api.enabled.unwrap_or(defaults.enabled),
+                bind_address,
+                port: api.port.unwrap_or(defaults.port),
+            }
+        },
+        notifications: raw.notifications.unwrap_or_default(),
+        dex_policies,
     };
 
     info!("Configuration loaded successfully");
-    info!("Target wallet: {}", monitor_config.target_wallet);
+    info!("Target wallets: {:?}", monitor_config.target_wallets);
     info!("RPC endpoints: {:?}", monitor_config.rpc_endpoints);
 
     Ok(monitor_config)
 }
 
+/// Parse a list of mint addresses; `what` names the list in errors
+fn parse_mints(mints: &Option<Vec<String>>, what: &str) -> MonitorResult<Vec<Pubkey>> {
+    mints
+        .iter()
+        .flatten()
+        .map(|mint| {
+            Pubkey::from_str(mint)
+                .map_err(|e| MonitorError::ConfigError(format!("Invalid {} {}: {}", what, mint, e)))
+        })
+        .collect()
+}
+
+/// Parse `[dex.<name>]` sections, keyed by DEX
+fn parse_dex_policies(
+    raw: HashMap<String, RawDexPolicy>,
+) -> MonitorResult<HashMap<DexType, DexPolicy>> {
+    let mut policies = HashMap::new();
+
+    for (name, policy) in raw {
+        let dex = DexType::from_str(&name)
+            .map_err(|e| MonitorError::ConfigError(format!("Invalid [dex.{}]: {}", name, e)))?;
+
+        let defaults = DexPolicy::default();
+        let size_multiplier = policy.size_multiplier.unwrap_or(defaults.size_multiplier);
+        if !(size_multiplier >= 0.0 && size_multiplier.is_finite()) {
+            return Err(MonitorError::ConfigError(format!(
+                "[dex.{}] size_multiplier must be non-negative, got {}",
+                name, size_multiplier
+            )));
+        }
+
+        policies.insert(
+            dex,
+            DexPolicy {
+                enabled: policy.enabled.unwrap_or(defaults.enabled),
+                size_multiplier,
+            },
+        );
+    }
+
+    Ok(policies)
+}
+
+/// Map a `[logging] level` string to a tracing level
+pub fn parse_log_level(level: &str) -> Option<Level> {
+    match level.trim().to_ascii_lowercase().as_str() {
+        "trace" => Some(Level::TRACE),
+        "debug" => Some(Level::DEBUG),
+        "info" => Some(Level::INFO),
+        "warn" | "warning" => Some(Level::WARN),
+        "error" => Some(Level::ERROR),
+        _ => None,
+    }
+}
+
+/// Parse a wallets file: one base58 pubkey per line, `#` starts a comment
+pub fn parse_wallets_file(contents: &str) -> MonitorResult<Vec<Pubkey>> {
+    let mut wallets = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let entry = line.split('#').next().unwrap_or("").trim();
+        if entry.is_empty() {
+            continue;
+        }
+
+        let wallet = Pubkey::from_str(entry).map_err(|e| {
+            MonitorError::ConfigError(format!(
+                "Invalid wallet address on line {}: {} ({})",
+                index + 1,
+                entry,
+                e
+            ))
+        })?;
+        wallets.push(wallet);
+    }
+
+    Ok(wallets)
+}
+
 /// Create a default configuration file
 pub fn create_default_config<P: AsRef<Path>>(path: P) -> MonitorResult<()> {
     let default_config = r#"[monitor]
 # The Solana wallet address to monitor and copy trades from
 target_wallet = "YOUR_TARGET_WALLET_ADDRESS_HERE"
 
+# Additional wallets can be listed inline or in a file (one address per line, # comments)
+# target_wallets = ["..."]
+# target_wallets_file = "wallets.txt"
+
 # List of RPC endpoints (for failover)
+# Edits to rpc_endpoints and websocket_endpoint are picked up on the next
+# reconnect while the bot is running; other settings need a restart
 rpc_endpoints = [
     "https://api.mainnet-beta.solana.com",
     "https://solana-api.projectserum.com"
 ]
 
+# Transactions are fetched immediately; if the RPC node doesn't have one yet,
+# retry after this many milliseconds (doubling each retry)
+fetch_delay_ms = 500
+
+# On startup, replay transactions missed while the bot was offline (newer than
+# the last one processed, saved in last_signature_path), at most catch_up_limit
+catch_up = false
+catch_up_limit = 100
+last_signature_path = "last_signature.txt"
+
+# How new transactions are found:
+#   websocket - logsSubscribe notifications (default, lowest latency)
+#   polling   - ask RPC for new signatures every poll_interval_ms
+#   hybrid    - both, deduplicated; keeps polling if the WebSocket gives up
+listener_mode = "websocket"
+poll_interval_ms = 2000
+
 # WebSocket endpoint for real-time updates
 websocket_endpoint = "wss://api.mainnet-beta.solana.com"
 
+# Reject WebSocket messages larger than this many bytes without parsing them
+max_ws_message_bytes = 1048576
+
+# Drop notifications beyond this many per second (0 = no limit)
+max_notifications_per_sec = 200
+
 # Connection timeout in seconds
 connection_timeout_secs = 30
 
+# Ping the WebSocket after this many seconds without a message (0 = never),
+# and reconnect if nothing comes back within keepalive_timeout_secs
+keepalive_interval_secs = 30
+keepalive_timeout_secs = 10
+
 # Maximum number of reconnection attempts
 max_reconnect_attempts = 5
 
+# Longest wait between reconnection attempts in seconds; waits double from
+# 2s up to this, each randomized by ±25%
+max_reconnect_backoff_secs = 60
+
 # Use "confirmed" commitment level (faster) instead of "finalized" (safer)
 use_confirmed_commitment = true
 
+# Re-check each trade at "finalized" commitment before saving it to the portfolio
+verify_finalized = false
+finality_check_delay_secs = 10
+
+# Fall back to instruction-based parsing for known DEXs when balance changes are ambiguous
+instruction_fallback = false
+
+# Name the DEX called through CPI (e.g. the Raydium pool under a Jupiter route)
+# instead of the top-level program. The inner DEX is always used when no
+# top-level program is recognized.
+prefer_inner_dex = false
+
+# Only report swaps with a stablecoin or SOL on one side, dropping
+# token-to-token swaps that are neither a clear buy nor a sell
+require_base_asset = false
+
+# Drop detected swaps whose confidence score (0.0 - 1.0) is below this
+min_confidence = 0.0
+
+# Only copy swaps where the trader paid more than this priority fee (lamports).
+# Skipped trades log the observed fee, to help pick a threshold. 0 = off
+min_priority_fee_lamports = 0
+
+# Skip trades where the trader's slippage tolerance (instruction-parsed swaps)
+# exceeds this many basis points. The jupiter executor also refuses other buys
+# whose output falls this far short of a fresh quote (one quote call per buy).
+# max_slippage_bps = 500
+
+# Balance changes of NFTs (0 decimals, amount 1) are ignored unless allowed
+allow_nfts = false
+
+# Mints whose balance changes are always ignored (e.g. airdropped spam tokens)
+# ignored_mints = []
+
+# Mints treated as stablecoins (buy/sell currencies) on top of the built-in
+# list of USDC, USDT, PYUSD, USDe and friends
+# extra_stablecoins = []
+
+# Never copy buys of denylisted mints (e.g. known rugs). A non-empty allowlist
+# means only its mints are copied.
+# token_allowlist = []
+# token_denylist = []
+
+# WSOL balance changes are dropped as fees by default, hiding SOL-funded trades.
+# Enable to treat WSOL as a swap leg; changes below the threshold (lamports)
+# are still ignored as fee/rent dust
+treat_wsol_as_tradeable = false
+min_wsol_delta_lamports = 10000000
+
+# Only copy buys of tokens the target wallet didn't hold before (fresh launches)
+only_new_mints = false
+
+# Also require the mint to be younger than this many seconds.
+# Costs one extra getSignaturesForAddress RPC call per candidate buy.
+# new_mint_max_age_secs = 600
+
+# Only copy buys while the trader is winning. Based on the trades observed
+# since the bot started tracking (the mirrored portfolio), not the wallet's
+# full history. With no closed trades yet the win rate counts as 0%.
+# min_trader_win_rate = 50.0
+# min_trader_pnl = 0
+
+# Liquidity guard: quote buying the token and selling it straight back, and
+# skip the buy if that round trip loses more than this (basis points).
+# Only the jupiter executor checks it, before each buy it sends; dry runs
+# don't. Costs two quote calls per buy.
+# max_round_trip_loss_bps = 300
+
+# Skip a buy of the same mint for the same amount as one seen within this many
+# seconds. Catches forked transactions replaced under a new signature, but also
+# skips genuine identical repeat buys - each skip is logged.
+# semantic_dedup_window_secs = 10
+
+# Ignore trades whose block time is more than this many seconds old, e.g. ones
+# replayed by a catch-up after an outage. Unlimited when unset.
+# max_signal_age_secs = 60
+
+# Stop opening new positions while this many are open. Buys into tokens we
+# already hold and all sells are still copied. Unlimited when unset.
+# max_open_positions = 10
+
+# Ignore swaps whose input is worth less than this many USD, e.g. a trader's
+# test swaps. Stablecoin inputs are valued directly; anything else costs one
+# price lookup per swap.
+# min_input_value = 1.0
+
+# What to do when the trader sells:
+#   only_owned  - mirror the sell only if we hold the token (default)
+#   always_copy - copy every sell, even without a position
+#   log_only    - never act on sells, just log them
+sell_policy = "only_owned"
+
+# Cost of partial sells:
+#   average - blend every buy into one entry price (default)
+#   fifo    - keep each buy as a lot and sell the oldest lots first
+cost_basis_method = "average"
+
+# Portfolio state file. Use "{wallet}" for one file per target wallet,
+# e.g. "portfolio-{wallet}.json"
+portfolio_path = "portfolio.json"
+
+# Where portfolios are kept:
+#   json   - one JSON file per portfolio_path (default)
+#   sqlite - tables in sqlite_path, which also records every detected signal;
+#            portfolio_path then just names the portfolio inside the database
+storage_backend = "json"
+sqlite_path = "sniper.db"
+
+# Append a P&L snapshot (JSON lines) every N seconds for equity-curve plotting
+# snapshot_interval_secs = 300
+snapshot_path = "snapshots.jsonl"
+
+# Convert portfolio totals into this mint (e.g. USDC) so positions bought with
+# different tokens add up. Unset: totals are raw sums per payment token.
+# display_currency = "EPjFWdd5AufqSSqeM2qN1xyybapC8G4wEGGkZwyTDt1v"
+
+# Record transactions that fail to parse (signature + error) as JSON lines.
+# dead_letter_raw_json also stores the whole transaction so it can be replayed,
+# at a few KB per entry.
+# dead_letter_path = "dead_letters.jsonl"
+dead_letter_raw_json = false
+
+# Debug: log the balance changes of transactions the classifier can't name,
+# and record them to the dead-letter file (if set), to find new patterns
+log_unknown_patterns = false
+
+[executor]
+# Simulate every swap before broadcasting it (disable only for latency-critical snipes)
+simulate_before_send = true
+
+# Wait before copying a buy so our transaction lands after the trader's rather
+# than in the same block, where sandwich bots are watching. Longer delays avoid
+# more MEV but let the price drift further from what the trader paid.
+# The jitter adds up to that many random milliseconds on top. 0 = copy instantly.
+copy_delay_ms = 0
+copy_delay_jitter_ms = 0
+
+# SOL (lamports) never spent on buys, so there's always enough for the exit's
+# fees. SOL-funded buys are shrunk to fit above it. Default 0.02 SOL.
+sol_fee_reserve_lamports = 20000000
+
+# "dry_run" logs what would be copied and sends nothing. "jupiter" swaps
+# through the Jupiter API, signed with keypair_path.
+mode = "dry_run"
+# keypair_path = "~/.config/solana/id.json"
+# jupiter_api_url = "https://quote-api.jup.ag/v6"
+slippage_bps = 100
+
+# Copies spend at most this much of the input token (raw units); bigger
+# trades are copied at this size
+# max_input_amount = 1000000000
+
+# Copy this fraction of the trader's size (0.1 = 10%; 0 copies nothing).
+# max_position_usd caps positions bought or sold for a stablecoin.
+# Copies scaled below min_copy_amount (raw units) are skipped as dust.
+copy_ratio = 1.0
+# max_position_usd = 100.0
+min_copy_amount = 1
+
+[risk]
+# Exit positions that move this far from entry (percent). Disabled when unset.
+# stop_loss_percent = 20.0
+# take_profit_percent = 50.0
+
+# Trailing stop: once a position is up the activation percent, exit if it
+# falls this far (percent) from its highest price since then
+# trailing_stop_percent = 10.0
+# trailing_stop_activation_percent = 20.0
+
+# Smooth sampled prices with an EMA before checking triggers, to avoid
+# whipsaw exits on a single noisy quote. Set a window (samples) or alpha.
+# ema_window = 5
+# ema_alpha = 0.33
+
+# Seconds between price checks of open positions; triggered exits are sold
+check_interval_secs = 30
+
+[api]
+# Read-only JSON API: /portfolio, /portfolio/stats, /portfolio/positions,
+# /portfolio/history and /signals?limit=N. It has no authentication, so
+# only bind beyond localhost on a trusted network.
+enabled = false
+bind_address = "127.0.0.1"
+port = 8080
+
+[notifications]
+# Append every copied buy/sell to this file as JSON lines
+# json_log_path = "trades.jsonl"
+#
+# Message a Telegram chat through a bot (create one with @BotFather)
+# telegram_bot_token = "123456789:AA..."
+# telegram_chat_id = "123456789"
+#
+# Post an embed per trade to a Discord channel webhook
+# discord_webhook_url = "https://discord.com/api/webhooks/..."
+
+# Per-DEX copy settings: skip a DEX entirely or scale the copied size.
+# [dex.unknown] applies to trades whose DEX couldn't be identified. Names:
+# jupiter, raydium, raydium_clmm, orca, meteora, phoenix, unknown.
+# [dex.raydium]
+# enabled = true
+# size_multiplier = 0.5
+#
+# [dex.unknown]
+# enabled = false
+
 [logging]
 # Logging level: trace, debug, info, warn, error
 level = "info"
@@ -129,14 +805,117 @@ mod tests {
     #[test]
     fn test_config_validation() {
         let raw = RawMonitorConfig {
-            target_wallet: "11111111111111111111111111111111".to_string(),
+            target_wallet: Some("11111111111111111111111111111111".to_string()),
+            target_wallets: None,
+            target_wallets_file: None,
             rpc_endpoints: vec![],
+            fetch_delay_ms: None,
+            catch_up: None,
+            catch_up_limit: None,
+            last_signature_path: None,
+            listener_mode: None,
+            poll_interval_ms: None,
             websocket_endpoint: "wss://test.com".to_string(),
+            max_ws_message_bytes: None,
+            max_notifications_per_sec: None,
             connection_timeout_secs: None,
+            keepalive_interval_secs: None,
+            keepalive_timeout_secs: None,
             max_reconnect_attempts: None,
+            max_reconnect_backoff_secs: None,
             use_confirmed_commitment: None,
+            verify_finalized: None,
+            finality_check_delay_secs: None,
+            instruction_fallback: None,
+            prefer_inner_dex: None,
+            require_base_asset: None,
+            min_confidence: None,
+            min_priority_fee_lamports: None,
+            max_slippage_bps: None,
+            allow_nfts: None,
+            ignored_mints: None,
+            extra_stablecoins: None,
+            token_allowlist: None,
+            token_denylist: None,
+            treat_wsol_as_tradeable: None,
+            min_wsol_delta_lamports: None,
+            only_new_mints: None,
+            new_mint_max_age_secs: None,
+            min_trader_win_rate: None,
+            min_trader_pnl: None,
+            max_round_trip_loss_bps: None,
+            semantic_dedup_window_secs: None,
+            max_signal_age_secs: None,
+            max_open_positions: None,
+            min_input_value: None,
+            sell_policy: None,
+            cost_basis_method: None,
+            portfolio_path: None,
+            storage_backend: None,
+            sqlite_path: None,
+            snapshot_interval_secs: None,
+            snapshot_path: None,
+            display_currency: None,
+            dead_letter_path: None,
+            dead_letter_raw_json: None,
+            log_unknown_patterns: None,
         };
         // Should fail with empty RPC endpoints
         assert!(raw.rpc_endpoints.is_empty());
     }
+
+    #[test]
+    fn test_parse_wallets_file() {
+        let wallet_a = Pubkey::new_unique();
+        let wallet_b = Pubkey::new_unique();
+        let contents = format!(
+            "# Desk wallets\n{}\n\n   {}   # momentum trader\n",
+            wallet_a, wallet_b
+        );
+
+        let wallets = parse_wallets_file(&contents).unwrap();
+        assert_eq!(wallets, vec![wallet_a, wallet_b]);
+
+        let bad = format!("{}\n# fine\nnot-a-pubkey\n", wallet_a);
+        let err = parse_wallets_file(&bad).unwrap_err();
+        assert!(err.to_string().contains("line 3"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_log_level() {
+        assert_eq!(parse_log_level("trace"), Some(Level::TRACE));
+        assert_eq!(parse_log_level("debug"), Some(Level::DEBUG));
+        assert_eq!(parse_log_level("info"), Some(Level::INFO));
+        assert_eq!(parse_log_level(" WARN "), Some(Level::WARN));
+        assert_eq!(parse_log_level("error"), Some(Level::ERROR));
+        assert_eq!(parse_log_level("verbose"), None);
+    }
+
+    #[test]
+    fn test_parse_dex_policies() {
+        let raw = HashMap::from([
+            (
+                "raydium".to_string(),
+                RawDexPolicy {
+                    enabled: None,
+                    size_multiplier: Some(0.25),
+                },
+            ),
+            (
+                "unknown".to_string(),
+                RawDexPolicy {
+                    enabled: Some(false),
+                    size_multiplier: None,
+                },
+            ),
+        ]);
+
+        let policies = parse_dex_policies(raw).unwrap();
+        assert_eq!(policies[&DexType::Raydium].size_multiplier, 0.25);
+        assert!(policies[&DexType::Raydium].enabled);
+        assert!(!policies[&DexType::Unknown].enabled);
+
+        let bad = HashMap::from([("pumpfun".to_string(), RawDexPolicy::default())]);
+        assert!(parse_dex_policies(bad).is_err());
+    }
 }
@@ -0,0 +1,111 @@
+use crate::config::load_config;
+use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::types::MonitorConfig;
+use arc_swap::ArcSwap;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Config handle shared with long-running tasks, replaced on every valid reload
+pub type SharedConfig = Arc<ArcSwap<MonitorConfig>>;
+
+/// Re-load the config file and swap it in.
+///
+/// An invalid file is rejected and the current config is left untouched.
+pub fn reload_config<P: AsRef<Path>>(path: P, shared: &SharedConfig) -> MonitorResult<()> {
+    let config = load_config(path)?;
+    shared.store(Arc::new(config));
+    Ok(())
+}
+
+/// Keeps the file watcher alive; reloading stops when this is dropped
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+/// Watch the config file and reload it into `shared` whenever it changes
+pub fn watch_config<P: AsRef<Path>>(path: P, shared: SharedConfig) -> MonitorResult<ConfigWatcher> {
+    let path = path.as_ref().to_path_buf();
+    let file_name = path.file_name().map(|name| name.to_os_string());
+
+    // Watch the directory, not the file: editors often save by replacing the
+    // file, which would silently end a watch on the old inode
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let watched = path.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        let event = match res {
+            Ok(event) => event,
+            Err(e) => {
+                warn!("Config watch error: {}", e);
+                return;
+            }
+        };
+
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+            return;
+        }
+        if !event
+            .paths
+            .iter()
+            .any(|p| p.file_name() == file_name.as_deref())
+        {
+            return;
+        }
+
+        match reload_config(&watched, &shared) {
+            Ok(()) => info!("🔄 Reloaded configuration from {:?}", watched),
+            Err(e) => warn!("Rejected config reload, keeping previous config: {}", e),
+        }
+    })
+    .map_err(|e| MonitorError::ConfigError(format!("Failed to watch config: {}", e)))?;
+
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| MonitorError::ConfigError(format!("Failed to watch config: {}", e)))?;
+
+    info!("👀 Watching {:?} for config changes", path);
+    Ok(ConfigWatcher { _watcher: watcher })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn write_config(path: &Path, websocket_endpoint: &str) {
+        let contents = format!(
+            r#"[monitor]
+target_wallet = "{}"
+rpc_endpoints = ["https://rpc.example.com"]
+websocket_endpoint = "{}"
+"#,
+            Pubkey::new_unique(),
+            websocket_endpoint
+        );
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_reload_keeps_previous_config() {
+        let path = std::env::temp_dir().join(format!("reload-{}.toml", Pubkey::new_unique()));
+
+        write_config(&path, "wss://old.example.com");
+        let shared: SharedConfig = Arc::new(ArcSwap::from_pointee(load_config(&path).unwrap()));
+
+        write_config(&path, "wss://new.example.com");
+        reload_config(&path, &shared).unwrap();
+        assert_eq!(shared.load().websocket_endpoint, "wss://new.example.com");
+
+        // Not a WebSocket URL - rejected by validation
+        write_config(&path, "https://broken.example.com");
+        assert!(reload_config(&path, &shared).is_err());
+        assert_eq!(shared.load().websocket_endpoint, "wss://new.example.com");
+
+        std::fs::remove_file(&path).ok();
+    }
+}
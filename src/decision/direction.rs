@@ -1,4 +1,11 @@
-use solana_sdk::pubkey::Pubkey;
+use crate::types::{MonitorConfig, wsol_pubkey};
+use solana_sdk::{pubkey::Pubkey, system_program};
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+/// Built-in stablecoins only, for the free functions
+static DEFAULT_REGISTRY: LazyLock<StablecoinRegistry> = LazyLock::new(StablecoinRegistry::default);
 
 pub struct Stablecoins;
 
@@ -11,10 +18,142 @@ impl Stablecoins {
 
     pub const USD1: &'static str = "USD1ttGY1N17NEEHLmELoaybftRBUSErhqYiQzvEmuB";
 
+    // PayPal USD
+    pub const PYUSD: &'static str = "2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo";
+
+    // Ethena USDe
+    pub const USDE: &'static str = "DEkqHyPN7GMRJ5cArtQFAWefqbZb33Hyf6s5iCwjEonT";
+
+    // Sky Dollar
+    pub const USDS: &'static str = "USDSwr9ApdHk5bvJKMjzff41FfuX8bSxdKcR81vTwcA";
+
+    // Hubble USDH
+    pub const USDH: &'static str = "USDH1SM1ojwWUga67PGrgFWUHibbjqMvuMaDkRJTgkX";
+
+    // UXD
+    pub const UXD: &'static str = "7kbnvuGBxxj8AG9qp8Scn56muWGaRaFqxg1FsRp3PaFT";
+
+    // Wormhole-bridged USDC / USDT from Ethereum
+    pub const USDC_ET: &'static str = "A9mUU4qviSctJVPJdBJWkb28deg915LYJKrzQ19ji3FM";
+    pub const USDT_ET: &'static str = "Dn4noZ5jgGfkntzcQSUZ8czkreiZ1ForXYoV2H8Dm7S1";
+
+    /// Every mint recognized as a stablecoin by default
+    pub const ALL: &'static [&'static str] = &[
+        Self::USDC,
+        Self::USDT,
+        Self::USD1,
+        Self::PYUSD,
+        Self::USDE,
+        Self::USDS,
+        Self::USDH,
+        Self::UXD,
+        Self::USDC_ET,
+        Self::USDT_ET,
+    ];
+
     pub fn is_stablecoin(mint: &Pubkey) -> bool {
         let mint_str = mint.to_string();
 
-        mint_str == Self::USDC || mint_str == Self::USDT || mint_str == Self::USD1
+        Self::ALL.contains(&mint_str.as_str())
+    }
+
+    /// Both sides are stablecoins (a rebalance, not a trade worth copying)
+    pub fn is_stable_pair(a: &Pubkey, b: &Pubkey) -> bool {
+        Self::is_stablecoin(a) && Self::is_stablecoin(b)
+    }
+}
+
+/// What tokens are bought with and sold for: stablecoins and (wrapped) SOL
+pub struct BaseCurrencies;
+
+impl BaseCurrencies {
+    /// Native SOL, as parsers that report it as a mint spell it (the System
+    /// Program id)
+    pub fn native_sol() -> Pubkey {
+        system_program::id()
+    }
+
+    pub fn is_base_currency(mint: &Pubkey) -> bool {
+        Stablecoins::is_stablecoin(mint) || Self::is_sol(mint)
+    }
+
+    /// WSOL or native SOL
+    pub fn is_sol(mint: &Pubkey) -> bool {
+        *mint == wsol_pubkey() || *mint == Self::native_sol()
+    }
+}
+
+/// Stablecoin mints known at runtime: the built-in [`Stablecoins::ALL`] plus
+/// any added from config
+#[derive(Debug, Clone)]
+pub struct StablecoinRegistry {
+    mints: HashSet<Pubkey>,
+}
+
+impl Default for StablecoinRegistry {
+    fn default() -> Self {
+        Self {
+            mints: Stablecoins::ALL
+                .iter()
+                .map(|mint| Pubkey::from_str(mint).expect("Invalid stablecoin pubkey"))
+                .collect(),
+        }
+    }
+}
+
+impl StablecoinRegistry {
+    /// The built-in stablecoins plus `extra` mints
+    pub fn with_extra(extra: &[Pubkey]) -> Self {
+        let mut registry = Self::default();
+        registry.mints.extend(extra);
+        registry
+    }
+
+    /// The built-in stablecoins plus the config's `extra_stablecoins`
+    pub fn from_config(config: &MonitorConfig) -> Self {
+        Self::with_extra(&config.extra_stablecoins)
+    }
+
+    /// Recognize `mint` as a stablecoin; `false` if it already was
+    pub fn insert(&mut self, mint: Pubkey) -> bool {
+        self.mints.insert(mint)
+    }
+
+    pub fn is_stablecoin(&self, mint: &Pubkey) -> bool {
+        self.mints.contains(mint)
+    }
+
+    /// Both sides are stablecoins (a rebalance, not a trade worth copying)
+    pub fn is_stable_pair(&self, a: &Pubkey, b: &Pubkey) -> bool {
+        self.is_stablecoin(a) && self.is_stablecoin(b)
+    }
+
+    /// A stablecoin from this registry, or (wrapped) SOL
+    pub fn is_base_currency(&self, mint: &Pubkey) -> bool {
+        self.is_stablecoin(mint) || BaseCurrencies::is_sol(mint)
+    }
+
+    /// Paying a base currency (stablecoin or SOL) for a token is a buy, the
+    /// reverse a sell. Anything else - including base to base - is a swap.
+    pub fn detect_direction(&self, input_mint: &Pubkey, output_mint: &Pubkey) -> TradeDirection {
+        let input_is_base = self.is_base_currency(input_mint);
+        let output_is_base = self.is_base_currency(output_mint);
+
+        match (input_is_base, output_is_base) {
+            (true, false) => TradeDirection::Buy {
+                token: *output_mint,
+                payment: *input_mint,
+            },
+
+            (false, true) => TradeDirection::Sell {
+                token: *input_mint,
+                receives: *output_mint,
+            },
+            _ => TradeDirection::Swap {
+                from_token: *input_mint,
+                to_token: *output_mint,
+            },
+        }
     }
 }
 
@@ -22,12 +161,12 @@ impl Stablecoins {
 pub enum TradeDirection {
     Buy {
         token: Pubkey,   // The token being bought
-        payment: Pubkey, // The stablecoin used to buy
+        payment: Pubkey, // The base currency used to buy
     },
 
     Sell {
         token: Pubkey,    // The token being sold
-        receives: Pubkey, // The stablecoin received
+        receives: Pubkey, // The base currency received
     },
 
     Swap {
@@ -36,23 +175,137 @@ pub enum TradeDirection {
     },
 }
 
+/// [`StablecoinRegistry::detect_direction`] with the built-in stablecoins
 pub fn detect_direction(input_mint: &Pubkey, output_mint: &Pubkey) -> TradeDirection {
-    let input_is_stable = Stablecoins::is_stablecoin(input_mint);
-    let output_is_stable = Stablecoins::is_stablecoin(output_mint);
-
-    match (input_is_stable, output_is_stable) {
-        (true, false) => TradeDirection::Buy {
-            token: *output_mint,
-            payment: *input_mint,
-        },
-
-        (false, true) => TradeDirection::Sell {
-            token: *output_mint,
-            receives: *input_mint,
-        },
-        _ => TradeDirection::Swap {
-            from_token: *input_mint,
-            to_token: *output_mint,
-        },
+    DEFAULT_REGISTRY.detect_direction(input_mint, output_mint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_known_stablecoins() {
+        for mint in Stablecoins::ALL {
+            let pubkey = Pubkey::from_str(mint).unwrap();
+            assert!(
+                Stablecoins::is_stablecoin(&pubkey),
+                "{} not recognized",
+                mint
+            );
+        }
+
+        let pyusd = Pubkey::from_str(Stablecoins::PYUSD).unwrap();
+        let usdc_et = Pubkey::from_str(Stablecoins::USDC_ET).unwrap();
+        assert!(Stablecoins::is_stablecoin(&pyusd));
+        assert!(Stablecoins::is_stablecoin(&usdc_et));
+        assert!(!Stablecoins::is_stablecoin(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_stable_pair() {
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        let usde = Pubkey::from_str(Stablecoins::USDE).unwrap();
+        let token = Pubkey::new_unique();
+
+        assert!(Stablecoins::is_stable_pair(&usdc, &usde));
+        assert!(!Stablecoins::is_stable_pair(&usdc, &token));
+        assert!(!Stablecoins::is_stable_pair(&token, &usde));
+    }
+
+    #[test]
+    fn test_wsol_buy() {
+        let wsol = wsol_pubkey();
+        let token = Pubkey::new_unique();
+
+        assert_eq!(
+            detect_direction(&wsol, &token),
+            TradeDirection::Buy {
+                token,
+                payment: wsol
+            }
+        );
+
+        let sol = BaseCurrencies::native_sol();
+        assert_eq!(
+            detect_direction(&sol, &token),
+            TradeDirection::Buy {
+                token,
+                payment: sol
+            }
+        );
+    }
+
+    #[test]
+    fn test_wsol_sell() {
+        let wsol = wsol_pubkey();
+        let token = Pubkey::new_unique();
+
+        assert_eq!(
+            detect_direction(&token, &wsol),
+            TradeDirection::Sell {
+                token,
+                receives: wsol
+            }
+        );
+    }
+
+    #[test]
+    fn test_token_to_token_and_base_pairs_are_swaps() {
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        let wsol = wsol_pubkey();
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(
+            detect_direction(&a, &b),
+            TradeDirection::Swap {
+                from_token: a,
+                to_token: b
+            }
+        );
+        assert_eq!(
+            detect_direction(&wsol, &usdc),
+            TradeDirection::Swap {
+                from_token: wsol,
+                to_token: usdc
+            }
+        );
+    }
+
+    #[test]
+    fn test_registry_with_custom_stablecoin() {
+        let custom = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+
+        // Unknown to the built-in list, so it's just another token
+        assert_eq!(
+            detect_direction(&custom, &token),
+            TradeDirection::Swap {
+                from_token: custom,
+                to_token: token
+            }
+        );
+
+        let registry = StablecoinRegistry::with_extra(&[custom]);
+        assert!(registry.is_stablecoin(&custom));
+        assert_eq!(
+            registry.detect_direction(&custom, &token),
+            TradeDirection::Buy {
+                token,
+                payment: custom
+            }
+        );
+        assert_eq!(
+            registry.detect_direction(&token, &custom),
+            TradeDirection::Sell {
+                token,
+                receives: custom
+            }
+        );
+
+        // The built-in stablecoins are still there
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        assert!(registry.is_stable_pair(&usdc, &custom));
     }
 }
@@ -0,0 +1,112 @@
+use crate::detection::types::UniversalSwapSignal;
+use crate::types::MonitorConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Skips buys that look like a repeat of one seen moments ago.
+///
+/// With "confirmed" commitment a forked-then-replaced transaction arrives
+/// under a new signature, so signature dedup can't catch it. A buy of the
+/// same mint for the same input amount within the window is treated as the
+/// same trade. Heuristic: a trader genuinely buying twice in quick succession
+/// with identical size is skipped too, so every hit is logged.
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateBuyFilter {
+    /// How far back (in seconds) a matching buy counts as a duplicate
+    pub window_secs: Option<u64>,
+
+    /// Recent buys as (mint, input amount, timestamp), oldest first
+    recent: VecDeque<(Pubkey, u64, i64)>,
+}
+
+impl DuplicateBuyFilter {
+    /// Build the filter from config
+    pub fn from_config(config: &MonitorConfig) -> Self {
+        Self {
+            window_secs: config.semantic_dedup_window_secs,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Is a window configured?
+    pub fn is_enabled(&self) -> bool {
+        self.window_secs.is_some()
+    }
+
+    /// `Ok` and remember the buy if it's new, otherwise why it looks like a duplicate
+    pub fn check(&mut self, signal: &UniversalSwapSignal) -> Result<(), String> {
+        let Some(window_secs) = self.window_secs else {
+            return Ok(());
+        };
+
+        // Block time is missing for some transactions; fall back to now
+        let timestamp = if signal.timestamp > 0 {
+            signal.timestamp
+        } else {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0)
+        };
+
+        let cutoff = timestamp - window_secs as i64;
+        while self.recent.front().is_some_and(|&(_, _, t)| t < cutoff) {
+            self.recent.pop_front();
+        }
+
+        if let Some(&(_, _, previous)) = self
+            .recent
+            .iter()
+            .find(|&&(mint, amount, _)| mint == signal.output_mint && amount == signal.input_amount)
+        {
+            return Err(format!(
+                "same buy of {} for {} seen {}s earlier",
+                signal.output_mint,
+                signal.input_amount,
+                timestamp - previous
+            ));
+        }
+
+        self.recent
+            .push_back((signal.output_mint, signal.input_amount, timestamp));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buy(mint: Pubkey, input_amount: u64, timestamp: i64) -> UniversalSwapSignal {
+        UniversalSwapSignal {
+            timestamp,
+            ..UniversalSwapSignal::test_swap()
+                .with_mints(Pubkey::new_unique(), mint)
+                .with_amounts(input_amount, 1_000)
+        }
+    }
+
+    #[test]
+    fn test_duplicate_buy_within_window() {
+        let mint = Pubkey::new_unique();
+        let mut filter = DuplicateBuyFilter {
+            window_secs: Some(10),
+            ..DuplicateBuyFilter::default()
+        };
+
+        assert!(filter.check(&buy(mint, 500, 1_000)).is_ok());
+        // Replacement transaction, new signature, same trade
+        assert!(filter.check(&buy(mint, 500, 1_004)).is_err());
+        // Different size or mint is a different trade
+        assert!(filter.check(&buy(mint, 600, 1_004)).is_ok());
+        assert!(filter.check(&buy(Pubkey::new_unique(), 500, 1_004)).is_ok());
+        // Outside the window it's a fresh buy
+        assert!(filter.check(&buy(mint, 500, 1_020)).is_ok());
+
+        let mut disabled = DuplicateBuyFilter::default();
+        assert!(!disabled.is_enabled());
+        assert!(disabled.check(&buy(mint, 500, 1_000)).is_ok());
+        assert!(disabled.check(&buy(mint, 500, 1_000)).is_ok());
+    }
+}
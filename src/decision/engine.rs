@@ -0,0 +1,470 @@
+use crate::decision::direction::{BaseCurrencies, StablecoinRegistry, TradeDirection};
+use crate::decision::fee_reserve::FeeReserve;
+use crate::decision::sell_policy::{SellAction, decide_sell};
+use crate::decision::sizing::{CopySize, PositionSizer};
+use crate::decision::token_filter::TokenFilter;
+use crate::detection::types::UniversalSwapSignal;
+use crate::portfolio::{EntryKind, PortfolioTracker};
+use crate::types::{DexPolicy, DexType, MonitorConfig, SellPolicy};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use tracing::info;
+
+/// What to do about one detected swap
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    /// Copy the trade (a buy, or a sell under `always_copy`) at our size
+    Copy { size: CopySize },
+    /// Mirror the trader's sell against our position in `token`
+    SellExisting { token: Pubkey },
+    /// Leave it alone
+    Skip { reason: DecisionSkip },
+}
+
+/// Why a signal isn't copied, typed so skips can be counted by kind
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecisionSkip {
+    /// The token filter rejects the bought token (denylist or allowlist)
+    Blacklisted(String),
+
+    /// The DEX policy disables buys through this DEX
+    WrongDex { dex: String },
+
+    /// A new position would go over `max_open_positions`
+    MaxOpenPositions { open: usize, max: usize },
+
+    /// Our copy sizes to nothing worth buying
+    Size(String),
+
+    /// A SOL-funded buy would eat into the fee reserve
+    FeeReserve(String),
+
+    /// The input is worth less than `min_input_value`
+    Dust(String),
+
+    /// The sell policy only logs sells
+    LogOnly,
+
+    /// A sell of a token we don't hold
+    NotOwned { token: Pubkey },
+
+    /// Token-to-token swaps are neither a buy nor a sell
+    TokenSwap,
+}
+
+impl std::fmt::Display for DecisionSkip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecisionSkip::Blacklisted(reason)
+            | DecisionSkip::Size(reason)
+            | DecisionSkip::FeeReserve(reason)
+            | DecisionSkip::Dust(reason) => write!(f, "{}", reason),
+            DecisionSkip::WrongDex { dex } => write!(f, "DEX {} disabled", dex),
+            DecisionSkip::MaxOpenPositions { open, max } => {
+                write!(f, "{} positions open, the maximum is {}", open, max)
+            }
+            DecisionSkip::LogOnly => write!(f, "sells are logged only (log_only)"),
+            DecisionSkip::NotOwned { token } => write!(f, "we don't own {}", token),
+            DecisionSkip::TokenSwap => write!(f, "token-to-token swap"),
+        }
+    }
+}
+
+/// The copy policy: turns a signal and the portfolio it would change into
+/// a [`Verdict`].
+///
+/// Buys must pass the token filter, the DEX policy and the open-position
+/// cap and size above the dust minimum; SOL-funded buys are shrunk to keep
+/// the fee reserve when the wallet balance is known. Sells follow the sell
+/// policy. Checks that remember what they've seen (duplicate buys) or only
+/// gate execution while the position is still tracked (trader performance)
+/// stay with the caller.
+#[derive(Debug, Clone, Default)]
+pub struct DecisionEngine {
+    pub stablecoins: StablecoinRegistry,
+    pub token_filter: TokenFilter,
+    pub sizer: PositionSizer,
+    pub sell_policy: SellPolicy,
+    pub dex_policies: HashMap<DexType, DexPolicy>,
+    /// Buys that would open a new position are skipped at this many open
+    pub max_open_positions: Option<usize>,
+    /// SOL kept back from SOL-funded buys
+    pub fee_reserve: FeeReserve,
+}
+
+impl DecisionEngine {
+    /// Build the engine from config
+    pub fn from_config(config: &MonitorConfig) -> Self {
+        Self {
+            stablecoins: StablecoinRegistry::from_config(config),
+            token_filter: TokenFilter::from_config(config),
+            sizer: PositionSizer::from_config(&config.executor),
+            sell_policy: config.sell_policy,
+            dex_policies: config.dex_policies.clone(),
+            max_open_positions: config.max_open_positions,
+            fee_reserve: FeeReserve::from_config(&config.executor),
+        }
+    }
+
+    /// Which way `signal` trades, by the engine's stablecoins
+    pub fn direction(&self, signal: &UniversalSwapSignal) -> TradeDirection {
+        signal.direction_with(&self.stablecoins)
+    }
+
+    /// Decide what to do about `signal` given the trader's mirrored `portfolio`
+    pub fn decide(&self, signal: &UniversalSwapSignal, portfolio: &PortfolioTracker) -> Verdict {
+        self.decide_with_balance(signal, portfolio, None)
+    }
+
+    /// [`decide`](Self::decide), with SOL-funded buys sized to leave the fee
+    /// reserve in a wallet holding `sol_balance` lamports
+    pub fn decide_with_balance(
+        &self,
+        signal: &UniversalSwapSignal,
+        portfolio: &PortfolioTracker,
+        sol_balance: Option<u64>,
+    ) -> Verdict {
+        match self.direction(signal) {
+            TradeDirection::Buy { token, .. } => {
+                match (self.decide_buy(signal, &token, portfolio), sol_balance) {
+                    (Verdict::Copy { size }, Some(balance))
+                        if BaseCurrencies::is_sol(&signal.input_mint) =>
+                    {
+                        match self.fee_reserve.size_sol_buy(balance, size.input_amount) {
+                            Ok(input_amount) => match self.sizer.check_min(input_amount) {
+                                Ok(()) => Verdict::Copy {
+                                    size: size.with_input(input_amount),
+                                },
+                                Err(reason) => skip(DecisionSkip::Size(reason)),
+                            },
+                            Err(reason) => skip(DecisionSkip::FeeReserve(reason)),
+                        }
+                    }
+                    (verdict, _) => verdict,
+                }
+            }
+            TradeDirection::Sell { token, .. } => {
+                match decide_sell(self.sell_policy, portfolio, &token) {
+                    SellAction::Close => Verdict::SellExisting { token },
+                    SellAction::CopyUnowned => self.sized(self.sizer, signal),
+                    SellAction::LogOnly => skip(DecisionSkip::LogOnly),
+                    SellAction::Skip => skip(DecisionSkip::NotOwned { token }),
+                }
+            }
+            TradeDirection::Swap { .. } => skip(DecisionSkip::TokenSwap),
+        }
+    }
+
+    fn decide_buy(
+        &self,
+        signal: &UniversalSwapSignal,
+        token: &Pubkey,
+        portfolio: &PortfolioTracker,
+    ) -> Verdict {
+        if let Err(reason) = self.token_filter.check(token) {
+            return skip(DecisionSkip::Blacklisted(reason));
+        }
+
+        // Adding to a position we hold doesn't count against the cap
+        if let Some(max) = self.max_open_positions
+            && portfolio.classify_entry(token) == EntryKind::New
+        {
+            let open = portfolio.get_stats().active_positions;
+            if open >= max {
+                return skip(DecisionSkip::MaxOpenPositions { open, max });
+            }
+        }
+
+        let dex_policy = DexPolicy::lookup(&self.dex_policies, signal.likely_dex.as_deref());
+        if !dex_policy.enabled {
+            return skip(DecisionSkip::WrongDex {
+                dex: signal
+                    .likely_dex
+                    .as_deref()
+                    .unwrap_or("Unknown")
+                    .to_string(),
+            });
+        }
+
+        // Our size: the copy ratio times the DEX's multiplier
+        if dex_policy.size_multiplier != 1.0 {
+            info!(
+                "📏 Copying at {}x size for this DEX",
+                dex_policy.size_multiplier
+            );
+        }
+        self.sized(self.sizer.scaled(dex_policy.size_multiplier), signal)
+    }
+
+    fn sized(&self, sizer: PositionSizer, signal: &UniversalSwapSignal) -> Verdict {
+        match sizer.size(signal, &self.stablecoins) {
+            Ok(size) => Verdict::Copy { size },
+            Err(reason) => skip(DecisionSkip::Size(reason)),
+        }
+    }
+}
+
+fn skip(reason: DecisionSkip) -> Verdict {
+    Verdict::Skip { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::direction::Stablecoins;
+    use std::str::FromStr;
+
+    fn usdc() -> Pubkey {
+        Pubkey::from_str(Stablecoins::USDC).unwrap()
+    }
+
+    fn signal(input_mint: Pubkey, output_mint: Pubkey) -> UniversalSwapSignal {
+        UniversalSwapSignal::test_swap()
+            .with_mints(input_mint, output_mint)
+            .with_amounts(1_000_000, 5_000)
+            .with_dex("Raydium")
+    }
+
+    fn engine() -> DecisionEngine {
+        DecisionEngine {
+            sizer: PositionSizer {
+                copy_ratio: 0.5,
+                max_position_usd: None,
+                min_amount: 1_000,
+            },
+            ..DecisionEngine::default()
+        }
+    }
+
+    fn is_skip(verdict: &Verdict) -> bool {
+        matches!(verdict, Verdict::Skip { .. })
+    }
+
+    #[test]
+    fn test_copies_buy_at_our_size() {
+        let buy = signal(usdc(), Pubkey::new_unique());
+
+        assert_eq!(
+            engine().decide(&buy, &PortfolioTracker::new()),
+            Verdict::Copy {
+                size: CopySize {
+                    input_amount: 500_000,
+                    output_amount: 2_500,
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_sol_buys_keep_fee_reserve() {
+        let buy = signal(BaseCurrencies::native_sol(), Pubkey::new_unique());
+        let portfolio = PortfolioTracker::new();
+        let engine = DecisionEngine {
+            fee_reserve: FeeReserve {
+                reserve_lamports: 300_000,
+            },
+            ..engine()
+        };
+
+        // Unknown balance, or enough of it: the full copy size
+        let full = Verdict::Copy {
+            size: CopySize {
+                input_amount: 500_000,
+                output_amount: 2_500,
+            },
+        };
+        assert_eq!(engine.decide(&buy, &portfolio), full);
+        assert_eq!(
+            engine.decide_with_balance(&buy, &portfolio, Some(1_000_000)),
+            full
+        );
+
+        // Only 200_000 spendable: output shrinks with the input
+        assert_eq!(
+            engine.decide_with_balance(&buy, &portfolio, Some(500_000)),
+            Verdict::Copy {
+                size: CopySize {
+                    input_amount: 200_000,
+                    output_amount: 1_000,
+                }
+            }
+        );
+        assert!(is_skip(&engine.decide_with_balance(
+            &buy,
+            &portfolio,
+            Some(300_000)
+        )));
+
+        // Just above the reserve: what's left is below the dust minimum
+        assert_eq!(
+            engine.decide_with_balance(&buy, &portfolio, Some(300_500)),
+            skip(DecisionSkip::Size(
+                "copy of 500 is below the 1000 dust minimum".into()
+            ))
+        );
+        assert_eq!(
+            engine.decide_with_balance(&buy, &portfolio, Some(301_000)),
+            Verdict::Copy {
+                size: CopySize {
+                    input_amount: 1_000,
+                    output_amount: 5,
+                }
+            }
+        );
+
+        // Buys paid in stablecoins are left alone
+        let usdc_buy = signal(usdc(), Pubkey::new_unique());
+        assert!(!is_skip(&engine.decide_with_balance(
+            &usdc_buy,
+            &portfolio,
+            Some(0)
+        )));
+    }
+
+    #[test]
+    fn test_skips_filtered_buys() {
+        let token = Pubkey::new_unique();
+        let buy = signal(usdc(), token);
+        let portfolio = PortfolioTracker::new();
+
+        let denied = DecisionEngine {
+            token_filter: TokenFilter::new([], [token]),
+            ..engine()
+        };
+        assert_eq!(
+            denied.decide(&buy, &portfolio),
+            skip(DecisionSkip::Blacklisted(format!(
+                "token {} is denylisted",
+                token
+            )))
+        );
+
+        let mut no_raydium = engine();
+        no_raydium.dex_policies.insert(
+            DexType::Raydium,
+            DexPolicy {
+                enabled: false,
+                size_multiplier: 1.0,
+            },
+        );
+        assert_eq!(
+            no_raydium.decide(&buy, &portfolio),
+            skip(DecisionSkip::WrongDex {
+                dex: "Raydium".to_string()
+            })
+        );
+        assert_eq!(
+            DecisionSkip::WrongDex {
+                dex: "Raydium".to_string()
+            }
+            .to_string(),
+            "DEX Raydium disabled"
+        );
+
+        // Half of 1,500 raw units is under the 1,000 minimum
+        let dust = UniversalSwapSignal {
+            input_amount: 1_500,
+            ..buy
+        };
+        assert!(is_skip(&engine().decide(&dust, &portfolio)));
+    }
+
+    #[test]
+    fn test_dex_multiplier_scales_buys() {
+        let buy = signal(usdc(), Pubkey::new_unique());
+        let mut engine = engine();
+        engine.dex_policies.insert(
+            DexType::Unknown,
+            DexPolicy {
+                enabled: true,
+                size_multiplier: 0.5,
+            },
+        );
+
+        let Verdict::Copy { size } = engine.decide(&buy, &PortfolioTracker::new()) else {
+            panic!("buy should be copied");
+        };
+        assert_eq!(size.input_amount, 250_000);
+    }
+
+    #[test]
+    fn test_sells_follow_sell_policy() {
+        let owned = Pubkey::new_unique();
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(owned, 5_000, usdc(), 1_000_000, "buy".to_string());
+
+        let engine = engine();
+        assert_eq!(
+            engine.decide(&signal(owned, usdc()), &portfolio),
+            Verdict::SellExisting { token: owned }
+        );
+        let unowned = signal(Pubkey::new_unique(), usdc());
+        assert!(is_skip(&engine.decide(&unowned, &portfolio)));
+
+        let always_copy = DecisionEngine {
+            sell_policy: SellPolicy::AlwaysCopy,
+            ..engine.clone()
+        };
+        assert!(matches!(
+            always_copy.decide(&unowned, &portfolio),
+            Verdict::Copy { .. }
+        ));
+
+        let log_only = DecisionEngine {
+            sell_policy: SellPolicy::LogOnly,
+            ..engine
+        };
+        assert!(is_skip(
+            &log_only.decide(&signal(owned, usdc()), &portfolio)
+        ));
+    }
+
+    #[test]
+    fn test_max_open_positions() {
+        let engine = DecisionEngine {
+            max_open_positions: Some(2),
+            ..engine()
+        };
+        let mut portfolio = PortfolioTracker::new();
+        let held: Vec<Pubkey> = (0..2).map(|_| Pubkey::new_unique()).collect();
+        for token in &held {
+            let buy = signal(usdc(), *token);
+            let Verdict::Copy { size } = engine.decide(&buy, &portfolio) else {
+                panic!("buy under the cap should be copied");
+            };
+            portfolio.open_position(
+                *token,
+                size.output_amount,
+                usdc(),
+                size.input_amount,
+                "buy".to_string(),
+            );
+        }
+
+        // At the cap: no new positions...
+        let next = signal(usdc(), Pubkey::new_unique());
+        assert_eq!(
+            engine.decide(&next, &portfolio),
+            skip(DecisionSkip::MaxOpenPositions { open: 2, max: 2 })
+        );
+        // ...but adding to one and selling one still go through
+        assert!(matches!(
+            engine.decide(&signal(usdc(), held[0]), &portfolio),
+            Verdict::Copy { .. }
+        ));
+        assert_eq!(
+            engine.decide(&signal(held[1], usdc()), &portfolio),
+            Verdict::SellExisting { token: held[1] }
+        );
+    }
+
+    #[test]
+    fn test_skips_token_swaps() {
+        let swap = signal(Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(
+            engine().decide(&swap, &PortfolioTracker::new()),
+            skip(DecisionSkip::TokenSwap)
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use crate::types::ExecutorConfig;
+use tracing::info;
+
+/// Keeps enough SOL in the wallet to pay for the exit.
+///
+/// SOL-funded buys are downsized to what's left above the reserve; buys
+/// paid with other tokens only need the reserve itself to be there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeReserve {
+    /// Lamports never spent on buys
+    pub reserve_lamports: u64,
+}
+
+impl FeeReserve {
+    /// Build the reserve from config
+    pub fn from_config(config: &ExecutorConfig) -> Self {
+        Self {
+            reserve_lamports: config.sol_fee_reserve_lamports,
+        }
+    }
+
+    /// Lamports that may be spent out of `sol_balance`
+    pub fn spendable(&self, sol_balance: u64) -> u64 {
+        sol_balance.saturating_sub(self.reserve_lamports)
+    }
+
+    /// Size a SOL-funded buy: `Ok` with the (possibly reduced) amount to
+    /// spend, or why nothing can be spent
+    pub fn size_sol_buy(&self, sol_balance: u64, wanted_lamports: u64) -> Result<u64, String> {
+        let spendable = self.spendable(sol_balance);
+        if spendable == 0 {
+            return Err(format!(
+                "balance {} lamports is within the {} lamport fee reserve",
+                sol_balance, self.reserve_lamports
+            ));
+        }
+
+        if wanted_lamports > spendable {
+            info!(
+                "📉 Downsizing buy from {} to {} lamports to keep the {} lamport fee reserve",
+                wanted_lamports, spendable, self.reserve_lamports
+            );
+            return Ok(spendable);
+        }
+
+        Ok(wanted_lamports)
+    }
+
+    /// `Ok` if the wallet still holds the reserve for a buy paid in another token
+    pub fn check_fee_balance(&self, sol_balance: u64) -> Result<(), String> {
+        if sol_balance < self.reserve_lamports {
+            return Err(format!(
+                "balance {} lamports below the {} lamport fee reserve",
+                sol_balance, self.reserve_lamports
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for FeeReserve {
+    fn default() -> Self {
+        Self::from_config(&ExecutorConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_reserve() {
+        let reserve = FeeReserve {
+            reserve_lamports: 20_000_000,
+        };
+
+        // Plenty left over: spend what was asked
+        assert_eq!(
+            reserve.size_sol_buy(1_000_000_000, 500_000_000),
+            Ok(500_000_000)
+        );
+
+        // Would dip into the reserve: downsized
+        assert_eq!(
+            reserve.size_sol_buy(100_000_000, 500_000_000),
+            Ok(80_000_000)
+        );
+
+        // Nothing above the reserve
+        assert!(reserve.size_sol_buy(20_000_000, 1).is_err());
+        assert!(reserve.size_sol_buy(5_000_000, 1).is_err());
+
+        assert!(reserve.check_fee_balance(20_000_000).is_ok());
+        assert!(reserve.check_fee_balance(19_999_999).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use crate::monitor::error::MonitorResult;
+use crate::types::MonitorConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::future::Future;
+
+/// Rejects buys whose quoted buy-then-sell round trip loses too much.
+///
+/// Costs two quote calls per candidate buy: base → token for the intended
+/// input, then token → base for the quoted output.
+#[derive(Debug, Clone, Default)]
+pub struct LiquidityGuard {
+    /// Maximum round-trip loss in basis points
+    pub max_round_trip_loss_bps: Option<u16>,
+}
+
+/// Result of a quoted round trip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundTrip {
+    /// Base amount we'd spend
+    pub amount_in: u64,
+
+    /// Tokens the buy quote returns
+    pub token_amount: u64,
+
+    /// Base amount selling `token_amount` straight back would return
+    pub amount_back: u64,
+
+    /// Share of `amount_in` lost on the way, in basis points
+    pub loss_bps: u16,
+
+    /// Whether the loss is within the configured maximum
+    pub passed: bool,
+}
+
+impl LiquidityGuard {
+    /// Build the guard from config
+    pub fn from_config(config: &MonitorConfig) -> Self {
+        Self {
+            max_round_trip_loss_bps: config.max_round_trip_loss_bps,
+        }
+    }
+
+    /// Is a maximum loss configured?
+    pub fn is_enabled(&self) -> bool {
+        self.max_round_trip_loss_bps.is_some()
+    }
+
+    /// Quote buying `token_mint` with `amount_in` of `base_mint` and selling
+    /// it straight back.
+    ///
+    /// `quote(input_mint, output_mint, amount)` returns the output amount.
+    /// Passes trivially when the guard is disabled, without quoting.
+    pub async fn check<F, Fut>(
+        &self,
+        quote: F,
+        base_mint: Pubkey,
+        token_mint: Pubkey,
+        amount_in: u64,
+    ) -> MonitorResult<RoundTrip>
+    where
+        F: Fn(Pubkey, Pubkey, u64) -> Fut,
+        Fut: Future<Output = MonitorResult<u64>>,
+    {
+        let Some(max_loss_bps) = self.max_round_trip_loss_bps else {
+            return Ok(RoundTrip {
+                amount_in,
+                token_amount: 0,
+                amount_back: amount_in,
+                loss_bps: 0,
+                passed: true,
+            });
+        };
+
+        let token_amount = quote(base_mint, token_mint, amount_in).await?;
+        let amount_back = if token_amount == 0 {
+            0
+        } else {
+            quote(token_mint, base_mint, token_amount).await?
+        };
+
+        let loss_bps = round_trip_loss_bps(amount_in, amount_back);
+        Ok(RoundTrip {
+            amount_in,
+            token_amount,
+            amount_back,
+            loss_bps,
+            passed: loss_bps <= max_loss_bps,
+        })
+    }
+}
+
+/// Loss from `amount_in` to `amount_back`, in basis points (0 on a gain)
+fn round_trip_loss_bps(amount_in: u64, amount_back: u64) -> u16 {
+    if amount_in == 0 || amount_back >= amount_in {
+        return 0;
+    }
+    ((amount_in - amount_back) as u128 * 10_000 / amount_in as u128) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_round_trip_loss() {
+        let base = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+
+        // 1 base = 1_000 tokens; selling back returns 96% of the value
+        let quote = |input: Pubkey, _output: Pubkey, amount: u64| async move {
+            Ok(if input == base {
+                amount * 1_000
+            } else {
+                amount / 1_000 * 96 / 100
+            })
+        };
+
+        let guard = LiquidityGuard {
+            max_round_trip_loss_bps: Some(500),
+        };
+        let round_trip = guard.check(quote, base, token, 1_000_000).await.unwrap();
+        assert_eq!(round_trip.token_amount, 1_000_000_000);
+        assert_eq!(round_trip.amount_back, 960_000);
+        assert_eq!(round_trip.loss_bps, 400);
+        assert!(round_trip.passed);
+
+        let strict = LiquidityGuard {
+            max_round_trip_loss_bps: Some(300),
+        };
+        let round_trip = strict.check(quote, base, token, 1_000_000).await.unwrap();
+        assert!(!round_trip.passed);
+
+        // Disabled: no quotes, always passes
+        let disabled = LiquidityGuard::default();
+        assert!(!disabled.is_enabled());
+        let never_called = |_: Pubkey, _: Pubkey, _: u64| async { unreachable!() };
+        let round_trip = disabled.check(never_called, base, token, 1).await.unwrap();
+        assert!(round_trip.passed);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::decision::direction::StablecoinRegistry;
+use crate::detection::types::UniversalSwapSignal;
+use crate::monitor::error::MonitorResult;
+use crate::portfolio::price::PriceProvider;
+use crate::types::MonitorConfig;
+
+/// USDC decimals: prices are quoted in raw USDC units
+const USD_DECIMALS: i32 = 6;
+
+/// Skips swaps whose input is worth less than a USD minimum, such as a
+/// trader's test swaps of a few cents.
+#[derive(Debug, Clone, Default)]
+pub struct MinValueFilter {
+    /// Smallest input worth copying, in USD
+    pub min_input_value: Option<f64>,
+}
+
+impl MinValueFilter {
+    /// Build the filter from config
+    pub fn from_config(config: &MonitorConfig) -> Self {
+        Self {
+            min_input_value: config.min_input_value,
+        }
+    }
+
+    /// Is a minimum configured?
+    pub fn is_enabled(&self) -> bool {
+        self.min_input_value.is_some()
+    }
+
+    /// USD value of `signal`'s input.
+    ///
+    /// Stablecoins count at $1 and need no lookup. Anything else, WSOL
+    /// included, is priced through `prices`, which quotes raw USDC units per
+    /// raw unit of the mint.
+    pub async fn input_value<P: PriceProvider>(
+        signal: &UniversalSwapSignal,
+        stablecoins: &StablecoinRegistry,
+        prices: &P,
+    ) -> MonitorResult<f64> {
+        if stablecoins.is_stablecoin(&signal.input_mint) {
+            return Ok(signal.input_amount as f64 / 10f64.powi(signal.input_decimals as i32));
+        }
+
+        let price = prices.get_price(&signal.input_mint).await?;
+        Ok(signal.input_amount as f64 * price / 10f64.powi(USD_DECIMALS))
+    }
+
+    /// `Ok` if an input worth `value` USD meets the minimum, otherwise why not
+    pub fn check(&self, value: f64) -> Result<(), String> {
+        match self.min_input_value {
+            Some(min) if value < min => Err(format!(
+                "input worth ${:.4}, below the ${} minimum",
+                value, min
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::direction::Stablecoins;
+    use crate::monitor::error::MonitorError;
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    /// Every mint costs the same; stablecoins must never be looked up
+    struct FlatPrice(f64);
+
+    impl PriceProvider for FlatPrice {
+        async fn get_price(&self, mint: &Pubkey) -> MonitorResult<f64> {
+            assert!(!Stablecoins::is_stablecoin(mint), "stablecoin was priced");
+            Ok(self.0)
+        }
+    }
+
+    struct NoPrices;
+
+    impl PriceProvider for NoPrices {
+        async fn get_price(&self, _mint: &Pubkey) -> MonitorResult<f64> {
+            Err(MonitorError::InvalidResponse("no price".to_string()))
+        }
+    }
+
+    fn signal(input_mint: Pubkey, input_amount: u64, input_decimals: u8) -> UniversalSwapSignal {
+        UniversalSwapSignal::test_swap()
+            .with_mints(input_mint, Pubkey::new_unique())
+            .with_amounts(input_amount, 1)
+            .with_decimals(input_decimals, 6)
+    }
+
+    #[tokio::test]
+    async fn test_stablecoin_input_value() {
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        let stablecoins = StablecoinRegistry::default();
+
+        // 0.25 USDC
+        let value = MinValueFilter::input_value(&signal(usdc, 250_000, 6), &stablecoins, &NoPrices)
+            .await
+            .unwrap();
+        assert_eq!(value, 0.25);
+    }
+
+    #[tokio::test]
+    async fn test_priced_input_value() {
+        let stablecoins = StablecoinRegistry::default();
+        // 0.001 SOL at 150 USDC/SOL: 1_000_000 lamports at 0.15 raw USDC each
+        let wsol = signal(Pubkey::new_unique(), 1_000_000, 9);
+
+        let value = MinValueFilter::input_value(&wsol, &stablecoins, &FlatPrice(0.15))
+            .await
+            .unwrap();
+        assert!((value - 0.15).abs() < 1e-9);
+
+        assert!(
+            MinValueFilter::input_value(&wsol, &stablecoins, &NoPrices)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_min_value_boundary() {
+        let disabled = MinValueFilter::default();
+        assert!(!disabled.is_enabled());
+        assert!(disabled.check(0.0).is_ok());
+
+        let filter = MinValueFilter {
+            min_input_value: Some(1.0),
+        };
+        assert!(filter.is_enabled());
+        assert!(filter.check(1.0).is_ok());
+        assert!(filter.check(1.01).is_ok());
+        assert_eq!(
+            filter.check(0.99),
+            Err("input worth $0.9900, below the $1 minimum".to_string())
+        );
+    }
+}
@@ -1,3 +1,23 @@
 pub mod direction;
+pub mod duplicate;
+pub mod engine;
+pub mod fee_reserve;
+pub mod liquidity;
+pub mod min_value;
+pub mod sell_policy;
+pub mod sizing;
+pub mod slippage;
+pub mod token_filter;
+pub mod trader_gate;
 
-pub use direction::*;
\ No newline at end of file
+pub use direction::*;
+pub use duplicate::DuplicateBuyFilter;
+pub use engine::{DecisionEngine, DecisionSkip, Verdict};
+pub use fee_reserve::FeeReserve;
+pub use liquidity::{LiquidityGuard, RoundTrip};
+pub use min_value::MinValueFilter;
+pub use sell_policy::{SellAction, decide_sell};
+pub use sizing::{CopySize, PositionSizer};
+pub use slippage::SlippageGate;
+pub use token_filter::TokenFilter;
+pub use trader_gate::TraderGate;
@@ -0,0 +1,84 @@
+use crate::portfolio::PortfolioTracker;
+use crate::types::SellPolicy;
+use solana_sdk::pubkey::Pubkey;
+
+/// What to do with one of the trader's sells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SellAction {
+    /// Mirror the sell against our position
+    Close,
+    /// Copy the sell although we hold none of the token
+    CopyUnowned,
+    /// Record the sell without acting on it
+    LogOnly,
+    /// Ignore it - we don't hold the token
+    Skip,
+}
+
+/// Decide how to handle a sell of `token` under `policy`
+pub fn decide_sell(policy: SellPolicy, portfolio: &PortfolioTracker, token: &Pubkey) -> SellAction {
+    let owned = portfolio.has_position(token);
+
+    match policy {
+        SellPolicy::LogOnly => SellAction::LogOnly,
+        _ if owned => SellAction::Close,
+        SellPolicy::AlwaysCopy => SellAction::CopyUnowned,
+        SellPolicy::OnlyOwned => SellAction::Skip,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn portfolio_holding(token: Pubkey) -> PortfolioTracker {
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(token, 1_000, Pubkey::new_unique(), 100, "sig".to_string());
+        portfolio
+    }
+
+    #[test]
+    fn test_only_owned() {
+        let owned = Pubkey::new_unique();
+        let portfolio = portfolio_holding(owned);
+
+        assert_eq!(
+            decide_sell(SellPolicy::OnlyOwned, &portfolio, &owned),
+            SellAction::Close
+        );
+        assert_eq!(
+            decide_sell(SellPolicy::OnlyOwned, &portfolio, &Pubkey::new_unique()),
+            SellAction::Skip
+        );
+    }
+
+    #[test]
+    fn test_always_copy() {
+        let owned = Pubkey::new_unique();
+        let portfolio = portfolio_holding(owned);
+
+        assert_eq!(
+            decide_sell(SellPolicy::AlwaysCopy, &portfolio, &owned),
+            SellAction::Close
+        );
+        assert_eq!(
+            decide_sell(SellPolicy::AlwaysCopy, &portfolio, &Pubkey::new_unique()),
+            SellAction::CopyUnowned
+        );
+    }
+
+    #[test]
+    fn test_log_only() {
+        let owned = Pubkey::new_unique();
+        let portfolio = portfolio_holding(owned);
+
+        assert_eq!(
+            decide_sell(SellPolicy::LogOnly, &portfolio, &owned),
+            SellAction::LogOnly
+        );
+        assert_eq!(
+            decide_sell(SellPolicy::LogOnly, &portfolio, &Pubkey::new_unique()),
+            SellAction::LogOnly
+        );
+    }
+}
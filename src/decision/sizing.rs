@@ -0,0 +1,239 @@
+use crate::decision::direction::StablecoinRegistry;
+use crate::detection::types::UniversalSwapSignal;
+use crate::types::ExecutorConfig;
+use tracing::info;
+
+/// How much of a trade we copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CopySize {
+    /// Input token we spend (raw units)
+    pub input_amount: u64,
+
+    /// Output token we expect back (raw units), at the trader's price
+    pub output_amount: u64,
+}
+
+impl CopySize {
+    /// The same trade spending only `input_amount`, at the same price
+    pub fn with_input(self, input_amount: u64) -> Self {
+        Self {
+            input_amount,
+            output_amount: proportional(self.output_amount, input_amount, self.input_amount),
+        }
+    }
+}
+
+/// Scales the trader's size down (or up) to ours.
+///
+/// The signal's amounts are multiplied by `copy_ratio`, then capped at
+/// `max_position_usd` when one side of the trade is a stablecoin. Without a
+/// price source the USD cap can't be applied to SOL or token-to-token trades.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionSizer {
+    /// Fraction of the trader's size to copy
+    pub copy_ratio: f64,
+
+    /// Largest position in USD, if capped
+    pub max_position_usd: Option<f64>,
+
+    /// Smallest input amount worth copying (raw units)
+    pub min_amount: u64,
+}
+
+impl PositionSizer {
+    /// Build the sizer from config
+    pub fn from_config(config: &ExecutorConfig) -> Self {
+        Self {
+            copy_ratio: config.copy_ratio,
+            max_position_usd: config.max_position_usd,
+            min_amount: config.min_copy_amount,
+        }
+    }
+
+    /// The same sizer with its ratio multiplied by `multiplier` (e.g. a
+    /// DEX's `size_multiplier`)
+    pub fn scaled(&self, multiplier: f64) -> Self {
+        Self {
+            copy_ratio: self.copy_ratio * multiplier,
+            ..*self
+        }
+    }
+
+    /// Our size for `signal`, or why it shouldn't be copied
+    pub fn size(
+        &self,
+        signal: &UniversalSwapSignal,
+        stablecoins: &StablecoinRegistry,
+    ) -> Result<CopySize, String> {
+        if self.copy_ratio <= 0.0 {
+            return Err("copy ratio is 0".to_string());
+        }
+
+        let mut input_amount = scale(signal.input_amount, self.copy_ratio);
+        let mut output_amount = scale(signal.output_amount, self.copy_ratio);
+
+        if let Some(max_usd) = self.max_position_usd {
+            // Cap whichever side is the stablecoin, then shrink the other to match
+            let capped = if stablecoins.is_stablecoin(&signal.input_mint) {
+                let max = usd_to_raw(max_usd, signal.input_decimals);
+                let output = proportional(output_amount, max, input_amount);
+                (input_amount > max).then_some((max, output))
+            } else if stablecoins.is_stablecoin(&signal.output_mint) {
+                let max = usd_to_raw(max_usd, signal.output_decimals);
+                let input = proportional(input_amount, max, output_amount);
+                (output_amount > max).then_some((input, max))
+            } else {
+                None
+            };
+
+            if let Some((input, output)) = capped {
+                info!(
+                    "📉 Capping copy at ${} ({} → {} input)",
+                    max_usd, input_amount, input
+                );
+                input_amount = input;
+                output_amount = output;
+            }
+        }
+
+        self.check_min(input_amount)?;
+
+        Ok(CopySize {
+            input_amount,
+            output_amount,
+        })
+    }
+
+    /// Whether a copy spending `input_amount` clears the dust minimum
+    pub fn check_min(&self, input_amount: u64) -> Result<(), String> {
+        if input_amount == 0 || input_amount < self.min_amount {
+            return Err(format!(
+                "copy of {} is below the {} dust minimum",
+                input_amount, self.min_amount
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Default for PositionSizer {
+    fn default() -> Self {
+        Self::from_config(&ExecutorConfig::default())
+    }
+}
+
+fn scale(amount: u64, ratio: f64) -> u64 {
+    (amount as f64 * ratio).round() as u64
+}
+
+/// Whole USD amount in raw units of a token with `decimals`
+fn usd_to_raw(usd: f64, decimals: u8) -> u64 {
+    (usd * 10f64.powi(decimals as i32)).round() as u64
+}
+
+/// `amount * numerator / denominator`, without overflowing
+fn proportional(amount: u64, numerator: u64, denominator: u64) -> u64 {
+    if denominator == 0 {
+        return 0;
+    }
+    (amount as u128 * numerator as u128 / denominator as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decision::direction::Stablecoins;
+    use solana_sdk::pubkey::Pubkey;
+    use std::str::FromStr;
+
+    fn signal(
+        input_mint: Pubkey,
+        input_amount: u64,
+        output_mint: Pubkey,
+        output_amount: u64,
+    ) -> UniversalSwapSignal {
+        UniversalSwapSignal::test_swap()
+            .with_mints(input_mint, output_mint)
+            .with_amounts(input_amount, output_amount)
+    }
+
+    fn sizer(copy_ratio: f64, max_position_usd: Option<f64>) -> PositionSizer {
+        PositionSizer {
+            copy_ratio,
+            max_position_usd,
+            min_amount: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_copy_ratio() {
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        let stablecoins = StablecoinRegistry::default();
+        // Trader buys with 10,000 USDC
+        let buy = signal(usdc, 10_000_000_000, Pubkey::new_unique(), 500_000);
+
+        assert_eq!(
+            sizer(0.1, None).size(&buy, &stablecoins),
+            Ok(CopySize {
+                input_amount: 1_000_000_000,
+                output_amount: 50_000,
+            })
+        );
+        assert_eq!(
+            sizer(0.1, None).scaled(0.5).size(&buy, &stablecoins),
+            Ok(CopySize {
+                input_amount: 500_000_000,
+                output_amount: 25_000,
+            })
+        );
+        assert!(sizer(0.0, None).size(&buy, &stablecoins).is_err());
+    }
+
+    #[test]
+    fn test_max_position_usd() {
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        let token = Pubkey::new_unique();
+        let stablecoins = StablecoinRegistry::default();
+
+        // 10% of a $10,000 buy is $1,000, capped at $250
+        let buy = signal(usdc, 10_000_000_000, token, 500_000);
+        assert_eq!(
+            sizer(0.1, Some(250.0)).size(&buy, &stablecoins),
+            Ok(CopySize {
+                input_amount: 250_000_000,
+                output_amount: 12_500,
+            })
+        );
+
+        // Sells are capped on the USDC they bring in
+        let sell = signal(token, 500_000, usdc, 10_000_000_000);
+        assert_eq!(
+            sizer(1.0, Some(100.0)).size(&sell, &stablecoins),
+            Ok(CopySize {
+                input_amount: 5_000,
+                output_amount: 100_000_000,
+            })
+        );
+
+        // No stablecoin side: nothing to price the cap in
+        let swap = signal(Pubkey::new_unique(), 10_000_000_000, token, 500_000);
+        assert_eq!(
+            sizer(1.0, Some(1.0))
+                .size(&swap, &stablecoins)
+                .unwrap()
+                .input_amount,
+            10_000_000_000
+        );
+    }
+
+    #[test]
+    fn test_dust_is_rejected() {
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        let stablecoins = StablecoinRegistry::default();
+
+        // 1% of 50,000 raw units is 500, under the 1,000 minimum
+        let small = signal(usdc, 50_000, Pubkey::new_unique(), 10);
+        assert!(sizer(0.01, None).size(&small, &stablecoins).is_err());
+        assert!(sizer(1.0, None).size(&small, &stablecoins).is_ok());
+    }
+}
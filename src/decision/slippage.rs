@@ -0,0 +1,98 @@
+use crate::detection::types::UniversalSwapSignal;
+use crate::monitor::error::MonitorResult;
+use crate::types::MonitorConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::future::Future;
+
+/// Skips trades where the trader accepted (or suffered) too much slippage,
+/// usually a sign of an illiquid or rushed trade.
+#[derive(Debug, Clone, Default)]
+pub struct SlippageGate {
+    /// Maximum slippage in basis points
+    pub max_slippage_bps: Option<u16>,
+}
+
+impl SlippageGate {
+    /// Build the gate from config
+    pub fn from_config(config: &MonitorConfig) -> Self {
+        Self {
+            max_slippage_bps: config.max_slippage_bps,
+        }
+    }
+
+    /// Is a maximum configured?
+    pub fn is_enabled(&self) -> bool {
+        self.max_slippage_bps.is_some()
+    }
+
+    /// `Ok` if `slippage_bps` is within the maximum, otherwise why not
+    pub fn check(&self, slippage_bps: u16) -> Result<(), String> {
+        match self.max_slippage_bps {
+            Some(max) if slippage_bps > max => Err(format!(
+                "slippage {} bps above maximum {} bps",
+                slippage_bps, max
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Slippage of a detected swap against a fresh quote for the same input.
+///
+/// Balance deltas only show what the trader received, so the expected
+/// output comes from `quote(input_mint, output_mint, amount)`.
+pub async fn quoted_slippage_bps<F, Fut>(
+    quote: F,
+    signal: &UniversalSwapSignal,
+) -> MonitorResult<u16>
+where
+    F: Fn(Pubkey, Pubkey, u64) -> Fut,
+    Fut: Future<Output = MonitorResult<u64>>,
+{
+    let expected_out = quote(signal.input_mint, signal.output_mint, signal.input_amount).await?;
+    Ok(slippage_bps(expected_out, signal.output_amount))
+}
+
+/// Shortfall of `received_out` against `expected_out`, in basis points
+pub fn slippage_bps(expected_out: u64, received_out: u64) -> u16 {
+    if expected_out == 0 || received_out >= expected_out {
+        return 0;
+    }
+    ((expected_out - received_out) as u128 * 10_000 / expected_out as u128) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slippage_gate() {
+        let disabled = SlippageGate::default();
+        assert!(!disabled.is_enabled());
+        assert!(disabled.check(u16::MAX).is_ok());
+
+        let gate = SlippageGate {
+            max_slippage_bps: Some(100),
+        };
+        assert!(gate.check(100).is_ok());
+        assert!(gate.check(101).is_err());
+
+        assert_eq!(slippage_bps(1_000_000, 990_000), 100);
+        assert_eq!(slippage_bps(1_000_000, 1_010_000), 0);
+    }
+
+    #[tokio::test]
+    async fn test_quoted_slippage() {
+        let signal = UniversalSwapSignal::test_swap().with_amounts(1_000, 47_500);
+
+        // The quote says 50 out per 1 in; the trader got 5% less
+        let quote = |_: Pubkey, _: Pubkey, amount: u64| async move { Ok(amount * 50) };
+        assert_eq!(quoted_slippage_bps(quote, &signal).await.unwrap(), 500);
+
+        // Copied at half or double the trader's size, the slippage is the same
+        for copy_ratio in [0.5, 2.0] {
+            let copy = signal.scaled_to_input((1_000.0 * copy_ratio) as u64);
+            assert_eq!(quoted_slippage_bps(quote, &copy).await.unwrap(), 500);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use crate::types::MonitorConfig;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashSet;
+
+/// Decides which tokens' buys are copied.
+///
+/// Denied mints are never copied. A non-empty allowlist restricts copying to
+/// its mints; an empty one allows everything not denied.
+#[derive(Debug, Clone, Default)]
+pub struct TokenFilter {
+    allow: HashSet<Pubkey>,
+    deny: HashSet<Pubkey>,
+}
+
+impl TokenFilter {
+    pub fn new(
+        allow: impl IntoIterator<Item = Pubkey>,
+        deny: impl IntoIterator<Item = Pubkey>,
+    ) -> Self {
+        Self {
+            allow: allow.into_iter().collect(),
+            deny: deny.into_iter().collect(),
+        }
+    }
+
+    /// Build the filter from the config's `token_allowlist`/`token_denylist`
+    pub fn from_config(config: &MonitorConfig) -> Self {
+        Self::new(
+            config.token_allowlist.iter().copied(),
+            config.token_denylist.iter().copied(),
+        )
+    }
+
+    /// Is either list non-empty?
+    pub fn is_enabled(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty()
+    }
+
+    /// `Ok` if buys of `mint` may be copied, otherwise the reason why not
+    pub fn check(&self, mint: &Pubkey) -> Result<(), String> {
+        if self.deny.contains(mint) {
+            return Err(format!("token {} is denylisted", mint));
+        }
+
+        if !self.allow.is_empty() && !self.allow.contains(mint) {
+            return Err(format!("token {} is not on the allowlist", mint));
+        }
+
+        Ok(())
+    }
+
+    pub fn should_copy(&self, mint: &Pubkey) -> bool {
+        self.check(mint).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denylist() {
+        let rug = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let filter = TokenFilter::new([], [rug]);
+
+        assert!(filter.is_enabled());
+        assert!(!filter.should_copy(&rug));
+        assert!(filter.should_copy(&token));
+    }
+
+    #[test]
+    fn test_allowlist() {
+        let allowed = Pubkey::new_unique();
+        let also_denied = Pubkey::new_unique();
+        let filter = TokenFilter::new([allowed, also_denied], [also_denied]);
+
+        assert!(filter.should_copy(&allowed));
+        assert!(!filter.should_copy(&Pubkey::new_unique()));
+        // The denylist wins
+        assert!(!filter.should_copy(&also_denied));
+    }
+
+    #[test]
+    fn test_empty_filter_allows_everything() {
+        let filter = TokenFilter::default();
+        assert!(!filter.is_enabled());
+        assert!(filter.check(&Pubkey::new_unique()).is_ok());
+    }
+
+    #[test]
+    fn test_from_config() {
+        let rug = Pubkey::new_unique();
+        let config = MonitorConfig {
+            token_denylist: vec![rug],
+            ..MonitorConfig::default()
+        };
+
+        assert!(!TokenFilter::from_config(&config).should_copy(&rug));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::portfolio::PortfolioStats;
+use crate::types::MonitorConfig;
+
+/// Skips buys while the trader's mirrored performance is below thresholds.
+///
+/// Stats come from the mirrored portfolio, i.e. only what the bot has observed
+/// since it started tracking - not the wallet's full on-chain history. With no
+/// closed trades yet the win rate is 0%, so a win-rate threshold blocks copying
+/// until the trader has realized at least one winner.
+#[derive(Debug, Clone, Default)]
+pub struct TraderGate {
+    /// Minimum win rate in percent (0 - 100)
+    pub min_win_rate: Option<f64>,
+
+    /// Minimum total realized P&L in payment-token units
+    pub min_pnl: Option<i64>,
+}
+
+impl TraderGate {
+    /// Build the gate from config
+    pub fn from_config(config: &MonitorConfig) -> Self {
+        Self {
+            min_win_rate: config.min_trader_win_rate,
+            min_pnl: config.min_trader_pnl,
+        }
+    }
+
+    /// Is any threshold configured?
+    pub fn is_enabled(&self) -> bool {
+        self.min_win_rate.is_some() || self.min_pnl.is_some()
+    }
+
+    /// `Ok` if the trader clears every threshold, otherwise the reason why not
+    pub fn check(&self, stats: &PortfolioStats) -> Result<(), String> {
+        if let Some(min_win_rate) = self.min_win_rate
+            && stats.win_rate < min_win_rate
+        {
+            return Err(format!(
+                "win rate {:.1}% below minimum {:.1}%",
+                stats.win_rate, min_win_rate
+            ));
+        }
+
+        if let Some(min_pnl) = self.min_pnl
+            && stats.total_realized_pnl < min_pnl
+        {
+            return Err(format!(
+                "realized P&L {} below minimum {}",
+                stats.total_realized_pnl, min_pnl
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(win_rate: f64, total_realized_pnl: i64) -> PortfolioStats {
+        PortfolioStats {
+            active_positions: 0,
+            closed_positions: 4,
+            total_invested: 0,
+            total_realized_pnl,
+            win_rate,
+            market_value: None,
+            unrealized_pnl: None,
+        }
+    }
+
+    #[test]
+    fn test_trader_gate() {
+        let disabled = TraderGate::default();
+        assert!(!disabled.is_enabled());
+        assert!(disabled.check(&stats(0.0, -1_000)).is_ok());
+
+        let gate = TraderGate {
+            min_win_rate: Some(50.0),
+            min_pnl: Some(0),
+        };
+        assert!(gate.check(&stats(75.0, 500)).is_ok());
+        assert!(gate.check(&stats(25.0, 500)).is_err());
+        assert!(gate.check(&stats(75.0, -1)).is_err());
+    }
+}
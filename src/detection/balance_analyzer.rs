@@ -1,26 +1,62 @@
 use crate::detection::types::{BalanceDelta, TokenBalance};
 use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::monitor::parser::resolve_account_keys;
+use crate::types::wsol_pubkey;
 use solana_sdk::pubkey::Pubkey;
-use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
-use std::collections::HashMap;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction};
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use tracing::{debug, warn};
 
 /// Analyzes token balance changes in transactions
 pub struct BalanceAnalyzer {
-    target_wallet: Pubkey,
+    /// Wallets whose balance changes are analyzed
+    target_wallets: Vec<Pubkey>,
+    /// Keep NFT-like balance changes (0 decimals, amount 1)
+    allow_nfts: bool,
+    /// Mints whose balance changes are dropped
+    ignored_mints: HashSet<Pubkey>,
+    /// Keep WSOL changes (as a swap leg) instead of dropping them as fees
+    treat_wsol_as_tradeable: bool,
+    /// Smaller WSOL changes are dropped as fee/rent dust, when WSOL is kept
+    min_wsol_delta_lamports: u64,
 }
 
 impl BalanceAnalyzer {
     /// Create a new balance analyzer
     pub fn new(target_wallet: Pubkey) -> Self {
-        Self { target_wallet }
+        Self::with_filters(&[target_wallet], false, &[])
     }
 
-    /// Extract all token balances before the transaction
+    /// Create a balance analyzer for several wallets that also drops changes
+    /// to the given mints
+    pub fn with_filters(
+        target_wallets: &[Pubkey],
+        allow_nfts: bool,
+        ignored_mints: &[Pubkey],
+    ) -> Self {
+        Self {
+            target_wallets: target_wallets.to_vec(),
+            allow_nfts,
+            ignored_mints: ignored_mints.iter().copied().collect(),
+            treat_wsol_as_tradeable: false,
+            min_wsol_delta_lamports: 0,
+        }
+    }
+
+    /// Keep WSOL changes of at least `min_delta_lamports`, so SOL-funded
+    /// swaps show up as a leg instead of being dropped as fees
+    pub fn with_tradeable_wsol(mut self, min_delta_lamports: u64) -> Self {
+        self.treat_wsol_as_tradeable = true;
+        self.min_wsol_delta_lamports = min_delta_lamports;
+        self
+    }
+
+    /// Extract `wallet`'s token balances before the transaction
     pub fn extract_pre_balances(
         &self,
         transaction: &EncodedConfirmedTransactionWithStatusMeta,
+        wallet: &Pubkey,
     ) -> MonitorResult<HashMap<Pubkey, TokenBalance>> {
         let meta = transaction
             .transaction
@@ -54,8 +90,8 @@ impl BalanceAnalyzer {
                 _ => Pubkey::default(),
             };
 
-            // Only track balances for target wallet
-            if owner != self.target_wallet {
+            // Only track balances for the requested wallet
+            if owner != *wallet {
                 continue;
             }
 
@@ -75,6 +111,7 @@ impl BalanceAnalyzer {
                     owner,
                     amount,
                     decimals,
+                    account_index: balance_info.account_index,
                 },
             );
 
@@ -84,10 +121,11 @@ impl BalanceAnalyzer {
         Ok(balances)
     }
 
-    /// Extract all token balances after the transaction
+    /// Extract `wallet`'s token balances after the transaction
     pub fn extract_post_balances(
         &self,
         transaction: &EncodedConfirmedTransactionWithStatusMeta,
+        wallet: &Pubkey,
     ) -> MonitorResult<HashMap<Pubkey, TokenBalance>> {
         let meta = transaction
             .transaction
@@ -121,8 +159,8 @@ impl BalanceAnalyzer {
                 _ => Pubkey::default(),
             };
 
-            // Only track balances for target wallet
-            if owner != self.target_wallet {
+            // Only track balances for the requested wallet
+            if owner != *wallet {
                 continue;
             }
 
@@ -142,6 +180,7 @@ impl BalanceAnalyzer {
                     owner,
                     amount,
                     decimals,
+                    account_index: balance_info.account_index,
                 },
             );
 
@@ -158,7 +197,7 @@ impl BalanceAnalyzer {
         post_balances: HashMap<Pubkey, TokenBalance>,
     ) -> Vec<BalanceDelta> {
         // Get all unique token mints
-        let all_mints: std::collections::HashSet<_> = pre_balances
+        let all_mints: HashSet<_> = pre_balances
             .keys()
             .chain(post_balances.keys())
             .cloned()
@@ -170,26 +209,28 @@ impl BalanceAnalyzer {
             let pre_balance = pre_balances.get(&mint);
             let post_balance = post_balances.get(&mint);
 
-            let (pre_amount, decimals) = match pre_balance {
-                Some(bal) => (bal.amount, bal.decimals),
-                None => (0, post_balance.map(|b| b.decimals).unwrap_or(9)),
+            let (owner, pre_amount, decimals, account_index) = match (pre_balance, post_balance) {
+                (Some(bal), _) => (bal.owner, bal.amount, bal.decimals, bal.account_index),
+                (None, Some(bal)) => (bal.owner, 0, bal.decimals, bal.account_index),
+                (None, None) => continue,
             };
 
             let post_amount = post_balance.map(|b| b.amount).unwrap_or(0);
 
-            // Calculate delta
-            let delta = post_amount as i64 - pre_amount as i64;
+            // Calculate delta (i128: raw amounts near u64::MAX overflow i64)
+            let delta = post_amount as i128 - pre_amount as i128;
 
             // Skip if no change
             if delta == 0 {
                 continue;
             }
 
-            // Skip native SOL/WSOL (we only care about SPL token swaps)
-            // Note: Wrapped SOL (WSOL) has mint: So11111111111111111111111111111111111111112
-            // We skip it because SOL changes are usually just for fees
-            let wsol_mint = "So11111111111111111111111111111111111111112";
-            if mint.to_string() == wsol_mint {
+            // WSOL changes are usually just fees unless WSOL is tradeable,
+            // and even then small ones are fee/rent dust
+            if mint == wsol_pubkey()
+                && (!self.treat_wsol_as_tradeable
+                    || delta.unsigned_abs() < self.min_wsol_delta_lamports as u128)
+            {
                 debug!(
                     "Skipping SOL/WSOL balance change (likely fees): {} lamports",
                     delta
@@ -197,12 +238,25 @@ impl BalanceAnalyzer {
                 continue;
             }
 
+            if self.ignored_mints.contains(&mint) {
+                debug!("Skipping ignored mint: {}", mint);
+                continue;
+            }
+
+            // A single unit of a 0-decimal token is an NFT, not a swap leg
+            if !self.allow_nfts && decimals == 0 && pre_amount.max(post_amount) == 1 {
+                debug!("Skipping NFT-like balance change: {}", mint);
+                continue;
+            }
+
             let balance_delta = BalanceDelta {
                 mint,
+                owner,
                 delta,
                 pre_balance: pre_amount,
                 post_balance: post_amount,
                 decimals,
+                account_index,
             };
 
             debug!(
@@ -219,26 +273,64 @@ impl BalanceAnalyzer {
             deltas.push(balance_delta);
         }
 
-        // Sort by delta (decreases first, then increases)
-        deltas.sort_by_key(|d| d.delta);
+        // Sort by account order, which follows the route more closely than
+        // the amounts do (and is deterministic, unlike HashMap iteration)
+        deltas.sort_by_key(|d| d.account_index);
 
         deltas
     }
 
-    /// Analyze a transaction and extract balance deltas
+    /// Change in `wallet`'s native SOL (lamports), with the fee added back so
+    /// only transfers, wraps and rent remain.
+    ///
+    /// `None` if the wallet isn't among the transaction's account keys.
+    pub fn native_sol_delta(
+        &self,
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+        wallet: &Pubkey,
+    ) -> Option<i64> {
+        let meta = transaction.transaction.meta.as_ref()?;
+        let EncodedTransaction::Json(tx) = &transaction.transaction.transaction else {
+            return None;
+        };
+
+        // Balances cover lookup table accounts too
+        let index = resolve_account_keys(&tx.message, Some(meta))
+            .iter()
+            .position(|key| key == wallet)?;
+
+        let pre = *meta.pre_balances.get(index)? as i64;
+        let post = *meta.post_balances.get(index)? as i64;
+        // The first account pays the fee
+        let fee = if index == 0 { meta.fee as i64 } else { 0 };
+
+        Some(post - pre + fee)
+    }
+
+    /// Analyze a transaction and extract the balance deltas of every target
+    /// wallet, each tagged with its `owner`, in target wallet order
     pub fn analyze(
         &self,
         transaction: &EncodedConfirmedTransactionWithStatusMeta,
     ) -> MonitorResult<Vec<BalanceDelta>> {
-        let pre_balances = self.extract_pre_balances(transaction)?;
-        let post_balances = self.extract_post_balances(transaction)?;
+        let mut deltas = Vec::new();
+        let mut found_balances = false;
 
-        if pre_balances.is_empty() && post_balances.is_empty() {
-            warn!("No token balances found in transaction");
-            return Ok(Vec::new());
+        for wallet in &self.target_wallets {
+            let pre_balances = self.extract_pre_balances(transaction, wallet)?;
+            let post_balances = self.extract_post_balances(transaction, wallet)?;
+
+            if pre_balances.is_empty() && post_balances.is_empty() {
+                continue;
+            }
+            found_balances = true;
+
+            deltas.extend(self.calculate_deltas(pre_balances, post_balances));
         }
 
-        let deltas = self.calculate_deltas(pre_balances, post_balances);
+        if !found_balances {
+            warn!("No token balances found in transaction");
+        }
 
         Ok(deltas)
     }
@@ -252,10 +344,12 @@ mod tests {
     fn test_balance_delta_calculations() {
         let delta = BalanceDelta {
             mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
             delta: -100_000_000, // Sold 100 tokens (with 6 decimals)
             pre_balance: 1_000_000_000,
             post_balance: 900_000_000,
             decimals: 6,
+            account_index: 0,
         };
 
         assert!(delta.is_decrease());
@@ -263,4 +357,172 @@ mod tests {
         assert_eq!(delta.abs_amount(), 100_000_000);
         assert_eq!(delta.ui_amount(), 100.0);
     }
+
+    #[test]
+    fn test_deltas_follow_account_order() {
+        let wallet = Pubkey::new_unique();
+        let analyzer = BalanceAnalyzer::new(wallet);
+        let balance = |mint: Pubkey, amount: u64, account_index: u8| TokenBalance {
+            mint,
+            owner: wallet,
+            amount,
+            decimals: 6,
+            account_index,
+        };
+
+        // Route USDC → hop → BONK. Sorting by delta would put the hop's
+        // larger loss ahead of USDC and pick it as the input.
+        let usdc = Pubkey::new_unique();
+        let hop = Pubkey::new_unique();
+        let bonk = Pubkey::new_unique();
+
+        let pre = HashMap::from([
+            (usdc, balance(usdc, 500, 1)),
+            (hop, balance(hop, 10_000, 2)),
+        ]);
+        let post = HashMap::from([
+            (usdc, balance(usdc, 400, 1)),
+            (hop, balance(hop, 9_000, 2)),
+            (bonk, balance(bonk, 50_000, 3)),
+        ]);
+
+        let deltas = analyzer.calculate_deltas(pre, post);
+        let mints: Vec<_> = deltas.iter().map(|d| d.mint).collect();
+        assert_eq!(mints, vec![usdc, hop, bonk]);
+        assert_eq!(deltas[2].account_index, 3);
+    }
+
+    #[test]
+    fn test_wsol_is_skipped_by_default() {
+        let wallet = Pubkey::new_unique();
+        let wsol = wsol_pubkey();
+        let bonk = Pubkey::new_unique();
+        let balance = |mint: Pubkey, amount: u64, account_index: u8| TokenBalance {
+            mint,
+            owner: wallet,
+            amount,
+            decimals: 9,
+            account_index,
+        };
+
+        // Paid 2 SOL (as WSOL) for BONK
+        let pre = HashMap::from([(wsol, balance(wsol, 2_000_000_000, 1))]);
+        let post = HashMap::from([
+            (wsol, balance(wsol, 0, 1)),
+            (bonk, balance(bonk, 50_000, 2)),
+        ]);
+
+        let deltas = BalanceAnalyzer::new(wallet).calculate_deltas(pre, post);
+        let mints: Vec<_> = deltas.iter().map(|d| d.mint).collect();
+        assert_eq!(mints, vec![bonk]);
+    }
+
+    #[test]
+    fn test_tradeable_wsol_keeps_swaps_but_drops_dust() {
+        let wallet = Pubkey::new_unique();
+        let wsol = wsol_pubkey();
+        let bonk = Pubkey::new_unique();
+        let balance = |mint: Pubkey, amount: u64, account_index: u8| TokenBalance {
+            mint,
+            owner: wallet,
+            amount,
+            decimals: 9,
+            account_index,
+        };
+        let analyzer = BalanceAnalyzer::new(wallet).with_tradeable_wsol(10_000_000);
+
+        // Paid 2 SOL (as WSOL) for BONK
+        let deltas = analyzer.calculate_deltas(
+            HashMap::from([(wsol, balance(wsol, 2_000_000_000, 1))]),
+            HashMap::from([
+                (wsol, balance(wsol, 0, 1)),
+                (bonk, balance(bonk, 50_000, 2)),
+            ]),
+        );
+        let mints: Vec<_> = deltas.iter().map(|d| d.mint).collect();
+        assert_eq!(mints, vec![wsol, bonk]);
+        assert_eq!(deltas[0].delta, -2_000_000_000);
+
+        // 5,000 lamports of WSOL is fee dust, not a swap leg
+        let deltas = analyzer.calculate_deltas(
+            HashMap::from([(wsol, balance(wsol, 1_000_000_000, 1))]),
+            HashMap::from([(wsol, balance(wsol, 999_995_000, 1))]),
+        );
+        assert!(deltas.is_empty());
+    }
+
+    #[test]
+    fn test_nft_and_ignored_mints_are_excluded() {
+        use crate::detection::trade_classifier::TradeClassifier;
+        use crate::detection::types::TransactionType;
+
+        let wallet = Pubkey::new_unique();
+        let balance = |mint: Pubkey, amount: u64, decimals: u8, account_index: u8| TokenBalance {
+            mint,
+            owner: wallet,
+            amount,
+            decimals,
+            account_index,
+        };
+
+        let usdc = Pubkey::new_unique();
+        let bonk = Pubkey::new_unique();
+        let nft = Pubkey::new_unique();
+        let spam = Pubkey::new_unique();
+
+        let pre = HashMap::from([(usdc, balance(usdc, 500, 6, 1))]);
+        let post = HashMap::from([
+            (usdc, balance(usdc, 400, 6, 1)),
+            (bonk, balance(bonk, 50_000, 5, 2)),
+            (nft, balance(nft, 1, 0, 3)),
+            (spam, balance(spam, 1_000, 6, 4)),
+        ]);
+
+        // Unfiltered, the NFT and spam receipts make it look like a route
+        let unfiltered = BalanceAnalyzer::with_filters(&[wallet], true, &[])
+            .calculate_deltas(pre.clone(), post.clone());
+        let classifier = TradeClassifier::new();
+        assert_eq!(
+            classifier.classify(&unfiltered),
+            TransactionType::MultiHopSwap
+        );
+
+        let analyzer = BalanceAnalyzer::with_filters(&[wallet], false, &[spam]);
+        let deltas = analyzer.calculate_deltas(pre, post);
+        let mints: Vec<_> = deltas.iter().map(|d| d.mint).collect();
+        assert_eq!(mints, vec![usdc, bonk]);
+        assert_eq!(classifier.classify(&deltas), TransactionType::Swap);
+    }
+
+    #[test]
+    fn test_delta_near_u64_max_does_not_overflow() {
+        let wallet = Pubkey::new_unique();
+        let analyzer = BalanceAnalyzer::new(wallet);
+        let mint = Pubkey::new_unique();
+        let balance = |amount: u64| TokenBalance {
+            mint,
+            owner: wallet,
+            amount,
+            decimals: 9,
+            account_index: 1,
+        };
+
+        // Sold (almost) an entire u64-sized balance
+        let deltas = analyzer.calculate_deltas(
+            HashMap::from([(mint, balance(u64::MAX))]),
+            HashMap::from([(mint, balance(1))]),
+        );
+        assert_eq!(deltas[0].delta, -(u64::MAX as i128 - 1));
+        assert_eq!(deltas[0].abs_amount(), u64::MAX - 1);
+        assert!(deltas[0].is_decrease());
+
+        // And bought it back
+        let deltas = analyzer.calculate_deltas(
+            HashMap::from([(mint, balance(0))]),
+            HashMap::from([(mint, balance(u64::MAX))]),
+        );
+        assert_eq!(deltas[0].delta, u64::MAX as i128);
+        assert_eq!(deltas[0].abs_amount(), u64::MAX);
+        assert!(deltas[0].is_increase());
+    }
 }
@@ -0,0 +1,202 @@
+//! Dead-letter log for transactions the parser failed on
+//!
+//! Each failure is appended as a JSON line to a file, so systematic parse
+//! errors can be investigated (and replayed, when the raw transaction is
+//! captured) after the fact.
+
+use crate::detection::types::DeltaPattern;
+use crate::monitor::error::MonitorError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction};
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One line of the dead-letter file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Transaction signature, if it could be read
+    pub signature: Option<String>,
+    pub slot: u64,
+    /// Unix time the failure was recorded
+    pub recorded_at: u64,
+    /// The parse error
+    pub error: String,
+    /// Full transaction as fetched, when raw capture is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<Value>,
+    /// Balance-change pattern, for transactions classified as unknown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<DeltaPattern>,
+}
+
+/// Appends parse failures to a JSON-lines file
+#[derive(Debug, Clone)]
+pub struct DeadLetterSink {
+    path: String,
+    /// Also store the full transaction (large, but replayable)
+    include_raw: bool,
+}
+
+impl DeadLetterSink {
+    pub fn new(path: impl Into<String>, include_raw: bool) -> Self {
+        Self {
+            path: path.into(),
+            include_raw,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Note what's needed from a transaction before parsing consumes it
+    ///
+    /// The error is filled in by [`Self::record`] if parsing fails.
+    pub fn capture(&self, transaction: &EncodedConfirmedTransactionWithStatusMeta) -> DeadLetter {
+        DeadLetter {
+            signature: transaction_signature(transaction),
+            slot: transaction.slot,
+            recorded_at: 0,
+            error: String::new(),
+            transaction: if self.include_raw {
+                serde_json::to_value(transaction).ok()
+            } else {
+                None
+            },
+            pattern: None,
+        }
+    }
+
+    /// Append a captured transaction with the error it failed on
+    pub fn record(&self, mut letter: DeadLetter, error: &MonitorError) -> io::Result<()> {
+        letter.error = error.to_string();
+        self.append(letter)
+    }
+
+    /// Append a transaction whose balance changes fit no known pattern
+    pub fn record_unknown_pattern(
+        &self,
+        mut letter: DeadLetter,
+        pattern: DeltaPattern,
+    ) -> io::Result<()> {
+        letter.error = format!("Unknown pattern: {}", pattern);
+        letter.pattern = Some(pattern);
+        self.append(letter)
+    }
+
+    fn append(&self, mut letter: DeadLetter) -> io::Result<()> {
+        letter.recorded_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&letter)?)
+    }
+}
+
+/// First signature of the transaction, whatever its encoding
+fn transaction_signature(
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Option<String> {
+    match &transaction.transaction.transaction {
+        EncodedTransaction::Json(tx) => tx.signatures.first().cloned(),
+        other => other
+            .decode()
+            .and_then(|tx| tx.signatures.first().map(|s| s.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_transaction_status::EncodedTransactionWithStatusMeta;
+
+    #[test]
+    fn test_record_appends_json_lines() {
+        let transaction = EncodedConfirmedTransactionWithStatusMeta {
+            slot: 42,
+            transaction: EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::LegacyBinary(String::new()),
+                meta: None,
+                version: None,
+            },
+            block_time: None,
+        };
+        let error = MonitorError::ParseError("No transaction metadata".to_string());
+
+        let path = std::env::temp_dir().join(format!("dead-letters-{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        for include_raw in [false, true] {
+            let sink = DeadLetterSink::new(path, include_raw);
+            sink.record(sink.capture(&transaction), &error).unwrap();
+        }
+
+        let contents = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let letters: Vec<DeadLetter> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(letters.len(), 2);
+        assert_eq!(letters[0].slot, 42);
+        assert!(letters[0].error.contains("No transaction metadata"));
+
+        assert!(letters[0].pattern.is_none());
+
+        // Raw JSON only when asked for
+        assert!(letters[0].transaction.is_none());
+        let raw: EncodedConfirmedTransactionWithStatusMeta =
+            serde_json::from_value(letters[1].transaction.clone().unwrap()).unwrap();
+        assert_eq!(raw.slot, 42);
+    }
+
+    #[test]
+    fn test_unknown_pattern_is_recorded() {
+        let transaction = EncodedConfirmedTransactionWithStatusMeta {
+            slot: 7,
+            transaction: EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::LegacyBinary(String::new()),
+                meta: None,
+                version: None,
+            },
+            block_time: None,
+        };
+        let mint = solana_sdk::pubkey::Pubkey::new_unique();
+        let pattern = DeltaPattern::from_deltas(&[crate::detection::types::BalanceDelta {
+            mint,
+            owner: solana_sdk::pubkey::Pubkey::default(),
+            delta: -5,
+            pre_balance: 5,
+            post_balance: 0,
+            decimals: 6,
+            account_index: 1,
+        }]);
+        assert_eq!(pattern.decreases, vec![(mint.to_string(), -5)]);
+        assert!(pattern.increases.is_empty());
+
+        let path =
+            std::env::temp_dir().join(format!("unknown-patterns-{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let sink = DeadLetterSink::new(path, false);
+        sink.record_unknown_pattern(sink.capture(&transaction), pattern.clone())
+            .unwrap();
+
+        let contents = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let letter: DeadLetter = serde_json::from_str(contents.trim()).unwrap();
+        assert_eq!(letter.slot, 7);
+        assert_eq!(letter.pattern, Some(pattern));
+        assert!(letter.error.starts_with("Unknown pattern: 1 decreases"));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::detection::types::BalanceDelta;
+use crate::monitor::error::{MonitorError, MonitorResult};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::debug;
+
+/// Offset of `decimals` in an SPL (and Token-2022) mint account:
+/// mint authority (36) + supply (8)
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// Where decimals come from when a mint isn't cached yet
+pub trait MintDecimalsSource: Send + Sync {
+    fn mint_decimals(&self, mint: &Pubkey) -> MonitorResult<u8>;
+}
+
+impl MintDecimalsSource for RpcClient {
+    fn mint_decimals(&self, mint: &Pubkey) -> MonitorResult<u8> {
+        let account = self.get_account(mint)?;
+        account
+            .data
+            .get(MINT_DECIMALS_OFFSET)
+            .copied()
+            .ok_or_else(|| MonitorError::ParseError(format!("{} is not a token mint", mint)))
+    }
+}
+
+/// Shared mint → decimals lookup.
+///
+/// Filled for free from transaction balance metadata, which carries decimals;
+/// only mints never seen there cost an RPC call, and only once.
+pub struct DecimalsCache {
+    decimals: RwLock<HashMap<Pubkey, u8>>,
+    source: Option<Arc<dyn MintDecimalsSource>>,
+}
+
+impl DecimalsCache {
+    /// A cache that fetches unknown mints from `source`
+    pub fn new(source: Arc<dyn MintDecimalsSource>) -> Self {
+        Self {
+            decimals: RwLock::new(HashMap::new()),
+            source: Some(source),
+        }
+    }
+
+    /// A cache that only knows what it has been told
+    pub fn offline() -> Self {
+        Self {
+            decimals: RwLock::new(HashMap::new()),
+            source: None,
+        }
+    }
+
+    /// Remember a mint's decimals
+    pub fn insert(&self, mint: Pubkey, decimals: u8) {
+        self.decimals.write().unwrap().insert(mint, decimals);
+    }
+
+    /// Remember the decimals of every mint in a set of balance changes
+    pub fn record_deltas(&self, deltas: &[BalanceDelta]) {
+        let mut cache = self.decimals.write().unwrap();
+        for delta in deltas {
+            cache.insert(delta.mint, delta.decimals);
+        }
+    }
+
+    /// Decimals if already known, without fetching
+    pub fn get_cached(&self, mint: &Pubkey) -> Option<u8> {
+        self.decimals.read().unwrap().get(mint).copied()
+    }
+
+    /// Decimals for `mint`, fetching (and caching) them if unknown
+    pub fn get(&self, mint: &Pubkey) -> MonitorResult<u8> {
+        if let Some(decimals) = self.get_cached(mint) {
+            return Ok(decimals);
+        }
+
+        let source = self
+            .source
+            .as_ref()
+            .ok_or_else(|| MonitorError::ParseError(format!("Decimals for {} not cached", mint)))?;
+
+        debug!("Fetching decimals for {}", mint);
+        let decimals = source.mint_decimals(mint)?;
+        self.insert(*mint, decimals);
+        Ok(decimals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Every mint has 9 decimals; counts lookups
+    #[derive(Default)]
+    struct CountingSource(AtomicUsize);
+
+    impl MintDecimalsSource for CountingSource {
+        fn mint_decimals(&self, _mint: &Pubkey) -> MonitorResult<u8> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(9)
+        }
+    }
+
+    #[test]
+    fn test_cached_lookup_fetches_once() {
+        let source = Arc::new(CountingSource::default());
+        let cache = DecimalsCache::new(source.clone());
+        let mint = Pubkey::new_unique();
+
+        assert_eq!(cache.get(&mint).unwrap(), 9);
+        assert_eq!(cache.get(&mint).unwrap(), 9);
+        assert_eq!(source.0.load(Ordering::SeqCst), 1);
+
+        // Mints seen in balance changes never hit the source
+        let seen = Pubkey::new_unique();
+        cache.record_deltas(&[BalanceDelta {
+            mint: seen,
+            owner: Pubkey::default(),
+            delta: 1,
+            pre_balance: 0,
+            post_balance: 1,
+            decimals: 6,
+            account_index: 0,
+        }]);
+        assert_eq!(cache.get(&seen).unwrap(), 6);
+        assert_eq!(source.0.load(Ordering::SeqCst), 1);
+
+        assert!(DecimalsCache::offline().get(&mint).is_err());
+    }
+}
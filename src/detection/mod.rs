@@ -4,72 +4,320 @@
 //! rather than parsing specific DEX instruction formats.
 
 pub mod balance_analyzer;
+pub mod dead_letter;
+pub mod decimals;
+pub mod new_mint;
 pub mod swap_detector;
 pub mod trade_classifier;
 pub mod types;
 
+use crate::decision::{SlippageGate, StablecoinRegistry};
 use crate::monitor::error::MonitorResult;
+use crate::monitor::parser::{TransactionParser, extract_priority_fee};
+use crate::types::MonitorConfig;
 use balance_analyzer::BalanceAnalyzer;
+use dead_letter::DeadLetterSink;
+use decimals::DecimalsCache;
+use new_mint::NewMintChecker;
 use solana_sdk::pubkey::Pubkey;
-use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiMessage};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use swap_detector::SwapDetector;
 use trade_classifier::TradeClassifier;
-use types::UniversalSwapSignal;
+use types::{BalanceDelta, DeltaPattern, ParseOutcome, SkipReason, TransactionType, UniversalSwapSignal};
 use tracing::{info, warn};
 
 /// Universal transaction parser that works with ANY DEX
 pub struct UniversalParser {
-    target_wallet: Pubkey,
+    /// Wallets being copied; each signal names the one that traded
+    target_wallets: Vec<Pubkey>,
     balance_analyzer: BalanceAnalyzer,
     swap_detector: SwapDetector,
     trade_classifier: TradeClassifier,
+    /// Instruction-based parser used when balance deltas are ambiguous
+    instruction_fallback: Option<TransactionParser>,
+    /// Signals scoring below this confidence are dropped
+    min_confidence: f32,
+    /// Signals whose trader paid no more than this priority fee are dropped
+    min_priority_fee_lamports: u64,
+    /// Drops trades whose slippage is too high
+    slippage_gate: SlippageGate,
+    /// Stablecoins for the rebalance check
+    stablecoins: StablecoinRegistry,
+    /// Signals older than this (by block time) are dropped
+    max_signal_age_secs: Option<u64>,
+    /// Only emit buys of freshly launched mints
+    only_new_mints: bool,
+    /// Optional RPC-backed recency check for new mints
+    new_mint_checker: Option<NewMintChecker>,
+    /// Shared decimals cache, fed from every analyzed transaction
+    decimals_cache: Option<Arc<DecimalsCache>>,
+    /// Log the deltas of `Unknown` classifications
+    log_unknown_patterns: bool,
+    /// Where `Unknown` patterns are recorded, if anywhere
+    unknown_pattern_sink: Option<DeadLetterSink>,
 }
 
 impl UniversalParser {
     /// Create a new universal parser
     pub fn new(target_wallet: Pubkey) -> Self {
+        Self::for_wallets(vec![target_wallet])
+    }
+
+    /// Create a universal parser that follows several target wallets
+    pub fn for_wallets(target_wallets: Vec<Pubkey>) -> Self {
         Self {
-            target_wallet,
-            balance_analyzer: BalanceAnalyzer::new(target_wallet),
+            balance_analyzer: BalanceAnalyzer::with_filters(&target_wallets, false, &[]),
+            target_wallets,
             swap_detector: SwapDetector::new(),
             trade_classifier: TradeClassifier::new(),
+            instruction_fallback: None,
+            min_confidence: 0.0,
+            min_priority_fee_lamports: 0,
+            slippage_gate: SlippageGate::default(),
+            stablecoins: StablecoinRegistry::default(),
+            max_signal_age_secs: None,
+            only_new_mints: false,
+            new_mint_checker: None,
+            decimals_cache: None,
+            log_unknown_patterns: false,
+            unknown_pattern_sink: None,
+        }
+    }
+
+    /// Create a universal parser with the options from config
+    pub fn with_config(config: &MonitorConfig) -> Self {
+        let wallets = config.monitored_wallets();
+        let mut parser = Self::for_wallets(wallets.clone());
+        parser.balance_analyzer = BalanceAnalyzer::with_filters(
+            &wallets,
+            config.allow_nfts,
+            &config.ignored_mints,
+        );
+        if config.treat_wsol_as_tradeable {
+            parser.balance_analyzer = parser
+                .balance_analyzer
+                .with_tradeable_wsol(config.min_wsol_delta_lamports);
         }
+        parser.swap_detector.prefer_inner_dex = config.prefer_inner_dex;
+        parser.swap_detector.require_base_asset = config.require_base_asset;
+        if config.instruction_fallback {
+            parser.instruction_fallback = Some(
+                TransactionParser::for_wallets(wallets).prefer_inner_dex(config.prefer_inner_dex),
+            );
+        }
+        parser.min_confidence = config.min_confidence;
+        parser.min_priority_fee_lamports = config.min_priority_fee_lamports;
+        parser.slippage_gate = SlippageGate::from_config(config);
+        parser.stablecoins = StablecoinRegistry::from_config(config);
+        parser.max_signal_age_secs = config.max_signal_age_secs;
+        parser.only_new_mints = config.only_new_mints;
+        parser.log_unknown_patterns = config.log_unknown_patterns;
+        parser.new_mint_checker = config
+            .new_mint_max_age_secs
+            .map(|max_age| NewMintChecker::new(config.rpc_endpoints[0].clone(), max_age));
+        parser
+    }
+
+    /// Record the decimals of every mint the parser sees in `cache`
+    pub fn with_decimals_cache(mut self, cache: Arc<DecimalsCache>) -> Self {
+        self.decimals_cache = Some(cache);
+        self
+    }
+
+    /// Record `Unknown` classifications to `sink` (when `log_unknown_patterns` is on)
+    pub fn with_unknown_pattern_sink(mut self, sink: DeadLetterSink) -> Self {
+        self.unknown_pattern_sink = Some(sink);
+        self
     }
 
     /// Parse a transaction and detect swaps universally
+    ///
+    /// Convenience wrapper around [`Self::parse_outcome`] that drops the
+    /// skip reason.
     pub fn parse(
         &self,
         transaction: EncodedConfirmedTransactionWithStatusMeta,
     ) -> MonitorResult<Option<UniversalSwapSignal>> {
+        Ok(self.parse_outcome(transaction)?.into_signal())
+    }
+
+    /// Parse a transaction, reporting why it was skipped if no swap is emitted
+    ///
+    /// If balance analysis finds no swap pattern at all and instruction
+    /// fallback is enabled, known-DEX swaps are still picked up by the
+    /// instruction parser. Anything recognized as not worth copying
+    /// (liquidity, transfers, rebalances) stays skipped.
+    pub fn parse_outcome(
+        &self,
+        transaction: EncodedConfirmedTransactionWithStatusMeta,
+    ) -> MonitorResult<ParseOutcome> {
+        if let Some(meta) = &transaction.transaction.meta
+            && meta.err.is_some()
+        {
+            info!("⏭️  Transaction failed on-chain - skipping");
+            return Ok(ParseOutcome::Skipped(SkipReason::FailedTx));
+        }
+
+        let mut signal = match self.parse_balances(&transaction)? {
+            ParseOutcome::Signal(signal) => *signal,
+            ParseOutcome::Skipped(reason) => {
+                let ambiguous = matches!(
+                    reason,
+                    SkipReason::NotASwap | SkipReason::NotCopied(TransactionType::Unknown)
+                );
+                let Some(fallback) = self.instruction_fallback.as_ref().filter(|_| ambiguous) else {
+                    return Ok(ParseOutcome::Skipped(reason));
+                };
+                info!("🔁 Falling back to instruction parsing...");
+                let Some(trade_signal) = fallback.parse(transaction)? else {
+                    return Ok(ParseOutcome::Skipped(reason));
+                };
+
+                // Instruction data carries the trader's minimum out
+                if let Some(max) = self.slippage_gate.max_slippage_bps
+                    && let Err(why) = self.slippage_gate.check(trade_signal.slippage_bps)
+                {
+                    info!("⏭️  Trader {} - skipping", why);
+                    return Ok(ParseOutcome::Skipped(SkipReason::ExcessiveSlippage {
+                        slippage_bps: trade_signal.slippage_bps,
+                        max,
+                    }));
+                }
+
+                UniversalSwapSignal::from(trade_signal)
+            }
+        };
+
+        // Block time is 0 when the node didn't report one; don't guess
+        if let Some(max) = self.max_signal_age_secs
+            && signal.timestamp > 0
+        {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            let age_secs = now.saturating_sub(signal.timestamp).max(0) as u64;
+            if age_secs > max {
+                info!("⏭️  Signal is {}s old (max {}s) - skipping", age_secs, max);
+                return Ok(ParseOutcome::Skipped(SkipReason::Stale { age_secs, max }));
+            }
+        }
+
+        if signal.confidence < self.min_confidence {
+            info!(
+                "⏭️  Confidence {:.2} below minimum {:.2} - skipping",
+                signal.confidence, self.min_confidence
+            );
+            return Ok(ParseOutcome::Skipped(SkipReason::LowConfidence {
+                confidence: signal.confidence,
+                min: self.min_confidence,
+            }));
+        }
+
+        if self.min_priority_fee_lamports > 0
+            && signal.priority_fee_lamports <= self.min_priority_fee_lamports
+        {
+            info!(
+                "⏭️  Priority fee {} lamports not above minimum {} - skipping",
+                signal.priority_fee_lamports, self.min_priority_fee_lamports
+            );
+            return Ok(ParseOutcome::Skipped(SkipReason::BelowThreshold {
+                priority_fee_lamports: signal.priority_fee_lamports,
+                min: self.min_priority_fee_lamports,
+            }));
+        }
+
+        self.flag_new_mint(&mut signal);
+
+        if self.only_new_mints && !signal.is_new_mint {
+            info!("⏭️  Not a new mint - skipping (only_new_mints)");
+            return Ok(ParseOutcome::Skipped(SkipReason::NotNewMint));
+        }
+
+        Ok(ParseOutcome::Signal(Box::new(signal)))
+    }
+
+    /// Narrow `is_new_mint` to buys, and to recent mints if the check is on
+    fn flag_new_mint(&self, signal: &mut UniversalSwapSignal) {
+        if !signal.is_new_mint {
+            return;
+        }
+
+        if !signal.is_buy() {
+            signal.is_new_mint = false;
+            return;
+        }
+
+        if let Some(checker) = &self.new_mint_checker {
+            signal.is_new_mint = match checker.is_recent(&signal.output_mint, signal.timestamp) {
+                Ok(recent) => recent,
+                Err(e) => {
+                    warn!("New mint check failed for {}: {}", signal.output_mint, e);
+                    false
+                }
+            };
+        }
+
+        if signal.is_new_mint {
+            info!("🆕 New mint: {}", signal.output_mint);
+        }
+    }
+
+    /// Detect a swap from the target wallet's token balance changes
+    fn parse_balances(
+        &self,
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    ) -> MonitorResult<ParseOutcome> {
         info!("🔍 Analyzing transaction with universal parser...");
 
-        // Get transaction signature
-        let signature = match &transaction.transaction.transaction {
+        // Get transaction signature and priority fee
+        let (signature, priority_fee) = match &transaction.transaction.transaction {
             solana_transaction_status::EncodedTransaction::Json(tx) => {
-                tx.signatures
+                let signature = tx
+                    .signatures
                     .first()
                     .and_then(|s| s.parse().ok())
                     .ok_or_else(|| {
                         crate::monitor::error::MonitorError::ParseError(
                             "No signature found".to_string(),
                         )
-                    })?
+                    })?;
+                let priority_fee = match &tx.message {
+                    UiMessage::Parsed(message) => extract_priority_fee(message),
+                    UiMessage::Raw(_) => 0,
+                };
+                (signature, priority_fee)
             }
             _ => {
                 warn!("Transaction not in JSON format");
-                return Ok(None);
+                return Ok(ParseOutcome::Skipped(SkipReason::NotASwap));
             }
         };
 
         // Get timestamp
         let timestamp = transaction.block_time.unwrap_or(0);
+        let slot = transaction.slot;
 
         // === STEP 1: Analyze Balance Changes ===
-        let deltas = self.balance_analyzer.analyze(&transaction)?;
+        let mut deltas = self.balance_analyzer.analyze(transaction)?;
+        if let Some(cache) = &self.decimals_cache {
+            cache.record_deltas(&deltas);
+        }
 
-        if deltas.is_empty() {
+        // Deltas come in target wallet order, so this is the first
+        // configured wallet that traded
+        let Some(trader) = deltas.first().map(|d| d.owner) else {
             info!("⏭️  No token balance changes detected - skipping");
-            return Ok(None);
+            return Ok(ParseOutcome::Skipped(SkipReason::NotASwap));
+        };
+        if deltas.iter().any(|d| d.owner != trader) {
+            warn!("👥 Several target wallets traded in this transaction - following {}", trader);
+            deltas.retain(|d| d.owner == trader);
+        }
+        if self.target_wallets.len() > 1 {
+            info!("👤 Trader: {}", trader);
         }
 
         info!("📊 Detected {} token balance changes", deltas.len());
@@ -88,45 +336,86 @@ impl UniversalParser {
         }
 
         // === STEP 2: Classify Transaction Type ===
-        let tx_type = self.trade_classifier.classify(&deltas);
+        let native_sol_delta = self.balance_analyzer.native_sol_delta(transaction, &trader);
+        if self.trade_classifier.is_sol_wrap(&deltas, native_sol_delta) {
+            info!("⏭️  SOL wrap/unwrap, not a swap - skipping");
+            return Ok(ParseOutcome::Skipped(SkipReason::NotCopied(TransactionType::Transfer)));
+        }
+
+        // Liquidity needs a positive signal; balance counts alone can't tell it from a route
+        let tx_type = self
+            .trade_classifier
+            .liquidity_action(transaction)
+            .unwrap_or_else(|| self.trade_classifier.classify(&deltas));
         info!("📋 Transaction type: {}", tx_type);
+        if tx_type == TransactionType::Unknown && self.log_unknown_patterns {
+            self.report_unknown_pattern(transaction, &deltas);
+        }
 
         if !tx_type.should_copy() {
             info!("⏭️  Transaction type '{}' should not be copied - skipping", tx_type);
-            return Ok(None);
+            return Ok(ParseOutcome::Skipped(SkipReason::NotCopied(tx_type)));
         }
 
         // === STEP 3: Detect Swap Pattern ===
-        let likely_dex = self.swap_detector.guess_dex(&transaction);
+        let likely_dex = self.swap_detector.guess_dex(transaction);
         
-        let swap_signal = self.swap_detector.detect_swap(
+        let Some(mut signal) = self.swap_detector.detect_swap(
             deltas,
             signature,
+            slot,
             timestamp,
-            self.target_wallet,
+            trader,
             likely_dex.clone(),
-        )?;
-
-        if let Some(ref signal) = swap_signal {
-            info!("🎯 ═══════════════════════════════════════════════");
-            info!("🎯 UNIVERSAL SWAP DETECTED!");
-            info!("🎯 ═══════════════════════════════════════════════");
-            info!("   Type: {}", signal.swap_type);
-            info!("   Input: {} ({})", signal.input_mint, signal.input_amount);
-            info!("   Output: {} ({})", signal.output_mint, signal.output_amount);
-            if let Some(ref dex) = signal.likely_dex {
-                info!("   Likely DEX: {} (doesn't matter!)", dex);
-            }
-            info!("");
-            info!("🔗 Links:");
-            info!("   • Transaction: {}", signal.solscan_url());
-            info!("   • Trader: {}", signal.trader_solscan_url());
-            info!("   • Input Token: https://solscan.io/token/{}", signal.input_mint);
-            info!("   • Output Token: https://solscan.io/token/{}", signal.output_mint);
-            info!("🎯 ═══════════════════════════════════════════════");
+            tx_type,
+        )?
+        else {
+            return Ok(ParseOutcome::Skipped(SkipReason::NotASwap));
+        };
+
+        signal.priority_fee_lamports = priority_fee;
+
+        if self.stablecoins.is_stable_pair(&signal.input_mint, &signal.output_mint) {
+            info!("⏭️  Stablecoin-to-stablecoin rebalance - skipping");
+            return Ok(ParseOutcome::Skipped(SkipReason::StablecoinRebalance));
         }
 
-        Ok(swap_signal)
+        info!("🎯 ═══════════════════════════════════════════════");
+        info!("🎯 UNIVERSAL SWAP DETECTED!");
+        info!("🎯 ═══════════════════════════════════════════════");
+        info!("   Slot: {}", signal.slot);
+        info!("   Type: {}", signal.swap_type);
+        info!("   Confidence: {:.2}", signal.confidence);
+        info!("   Input: {} ({})", signal.input_mint, signal.input_amount);
+        info!("   Output: {} ({})", signal.output_mint, signal.output_amount);
+        if let Some(ref dex) = signal.likely_dex {
+            info!("   Likely DEX: {} (doesn't matter!)", dex);
+        }
+        info!("");
+        info!("🔗 Links:");
+        info!("   • Transaction: {}", signal.solscan_url());
+        info!("   • Trader: {}", signal.trader_solscan_url());
+        info!("   • Input Token: https://solscan.io/token/{}", signal.input_mint);
+        info!("   • Output Token: https://solscan.io/token/{}", signal.output_mint);
+        info!("🎯 ═══════════════════════════════════════════════");
+
+        Ok(ParseOutcome::Signal(Box::new(signal)))
+    }
+
+    /// Log (and record, if a sink is set) deltas the classifier couldn't name
+    fn report_unknown_pattern(
+        &self,
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+        deltas: &[BalanceDelta],
+    ) {
+        let pattern = DeltaPattern::from_deltas(deltas);
+        warn!("🔍 Unknown balance pattern in slot {}: {}", transaction.slot, pattern);
+
+        if let Some(sink) = &self.unknown_pattern_sink
+            && let Err(e) = sink.record_unknown_pattern(sink.capture(transaction), pattern)
+        {
+            warn!("❌ Failed to record unknown pattern: {}", e);
+        }
     }
 }
 
@@ -138,6 +427,89 @@ mod tests {
     fn test_universal_parser_creation() {
         let wallet = Pubkey::new_unique();
         let parser = UniversalParser::new(wallet);
-        assert_eq!(parser.target_wallet, wallet);
+        assert_eq!(parser.target_wallets, vec![wallet]);
+        assert!(parser.instruction_fallback.is_none());
+    }
+
+    #[test]
+    fn test_instruction_fallback_from_config() {
+        let config = MonitorConfig {
+            target_wallet: Pubkey::new_unique(),
+            instruction_fallback: true,
+            ..MonitorConfig::default()
+        };
+        let parser = UniversalParser::with_config(&config);
+        assert!(parser.instruction_fallback.is_some());
+    }
+
+    #[test]
+    fn test_instruction_fallback_keeps_liquidity_skipped() {
+        use crate::types::program_ids;
+        use serde_json::json;
+        use solana_sdk::signature::Signature;
+
+        let wallet = Pubkey::new_unique();
+        let (usdc_account, token_account, lp_account) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (usdc, token, lp) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let key = |pubkey: &Pubkey, signer: bool| {
+            json!({"pubkey": pubkey.to_string(), "writable": true, "signer": signer, "source": "transaction"})
+        };
+        let balance = |index: u8, mint: &Pubkey, amount: u64| {
+            json!({
+                "accountIndex": index,
+                "mint": mint.to_string(),
+                "uiTokenAmount": {"uiAmount": null, "decimals": 6, "amount": amount.to_string(), "uiAmountString": "0"},
+                "owner": wallet.to_string(),
+                "programId": "TokenkegQfeZyiNwAJbNbGqPFXCWuBvf9Ss623VQ5DA"
+            })
+        };
+        // A zap: swap half the USDC for the token (a decodable swapBaseIn,
+        // user source/destination/owner last), then deposit both for LP
+        let mut swap_accounts = vec![4; 14];
+        swap_accounts.extend([1, 2, 0]);
+        let transaction: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(json!({
+            "slot": 1,
+            "blockTime": null,
+            "transaction": {
+                "signatures": [Signature::new_unique().to_string()],
+                "message": {
+                    "accountKeys": [
+                        key(&wallet, true),
+                        key(&usdc_account, false),
+                        key(&token_account, false),
+                        key(&lp_account, false),
+                        key(&program_ids::raydium_v4(), false)
+                    ],
+                    "recentBlockhash": "11111111111111111111111111111111",
+                    "instructions": [
+                        {"programIdIndex": 4, "accounts": swap_accounts, "data": "5uc7oSXmeRfefyqS3icLsrP", "stackHeight": null},
+                        {"programIdIndex": 4, "accounts": [], "data": bs58::encode([3, 0, 0]).into_string(), "stackHeight": null}
+                    ]
+                }
+            },
+            "meta": {
+                "err": null,
+                "status": {"Ok": null},
+                "fee": 5000,
+                "preBalances": [0, 0, 0, 0, 0],
+                "postBalances": [0, 0, 0, 0, 0],
+                "preTokenBalances": [balance(1, &usdc, 200_000_000), balance(2, &token, 0), balance(3, &lp, 0)],
+                "postTokenBalances": [balance(1, &usdc, 0), balance(2, &token, 0), balance(3, &lp, 5_000)]
+            }
+        }))
+        .unwrap();
+
+        let config = MonitorConfig {
+            target_wallet: wallet,
+            instruction_fallback: true,
+            ..MonitorConfig::default()
+        };
+        let outcome = UniversalParser::with_config(&config).parse_outcome(transaction).unwrap();
+        assert!(matches!(
+            outcome,
+            ParseOutcome::Skipped(SkipReason::NotCopied(TransactionType::AddLiquidity))
+        ));
     }
 }
\ No newline at end of file
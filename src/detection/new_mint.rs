@@ -0,0 +1,90 @@
+use crate::monitor::error::MonitorResult;
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use tracing::debug;
+
+/// Signatures fetched per recency check (the RPC maximum for one page)
+const MINT_HISTORY_LIMIT: usize = 1000;
+
+/// Checks whether a mint was created recently.
+///
+/// Costs one `getSignaturesForAddress` call per checked buy. Mints with a
+/// full page of history are treated as established without paging further.
+pub struct NewMintChecker {
+    rpc_client: RpcClient,
+    max_age_secs: u64,
+}
+
+impl NewMintChecker {
+    /// Create a checker against the given RPC endpoint
+    pub fn new(rpc_endpoint: String, max_age_secs: u64) -> Self {
+        Self {
+            rpc_client: RpcClient::new_with_commitment(rpc_endpoint, CommitmentConfig::confirmed()),
+            max_age_secs,
+        }
+    }
+
+    /// Whether the mint's first transaction is younger than `max_age_secs`
+    pub fn is_recent(&self, mint: &Pubkey, now: i64) -> MonitorResult<bool> {
+        let history = self.rpc_client.get_signatures_for_address_with_config(
+            mint,
+            GetConfirmedSignaturesForAddress2Config {
+                limit: Some(MINT_HISTORY_LIMIT),
+                ..Default::default()
+            },
+        )?;
+
+        // Newest first, so the last entry is the oldest we can see
+        let oldest_block_time = history.last().and_then(|s| s.block_time);
+        let full_page = history.len() >= MINT_HISTORY_LIMIT;
+
+        debug!(
+            "Mint {} history: {} signatures, oldest block time {:?}",
+            mint,
+            history.len(),
+            oldest_block_time
+        );
+
+        Ok(is_recent_history(
+            oldest_block_time,
+            full_page,
+            now,
+            self.max_age_secs,
+        ))
+    }
+}
+
+/// Decide recency from the oldest visible signature of a mint
+fn is_recent_history(
+    oldest_block_time: Option<i64>,
+    full_page: bool,
+    now: i64,
+    max_age_secs: u64,
+) -> bool {
+    if full_page {
+        return false;
+    }
+
+    match oldest_block_time {
+        Some(created) => now.saturating_sub(created) <= max_age_secs as i64,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_history() {
+        let now = 1_700_000_000;
+
+        assert!(is_recent_history(Some(now - 60), false, now, 600));
+        assert!(!is_recent_history(Some(now - 3_600), false, now, 600));
+
+        // Busy mints and missing block times are never "new"
+        assert!(!is_recent_history(Some(now - 60), true, now, 600));
+        assert!(!is_recent_history(None, false, now, 600));
+    }
+}
@@ -1,31 +1,61 @@
-use crate::detection::types::{BalanceDelta, SwapType, UniversalSwapSignal};
+use crate::decision::BaseCurrencies;
+use crate::detection::types::{BalanceDelta, SwapType, TransactionType, UniversalSwapSignal};
 use crate::monitor::error::MonitorResult;
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
 use tracing::{debug, info, warn};
 
+/// Amounts below this (in UI units) are treated as dust when scoring
+const DUST_UI_AMOUNT: f64 = 0.001;
+
 /// Detects swap patterns from balance deltas
-pub struct SwapDetector {}
+pub struct SwapDetector {
+    /// Name the DEX found in inner instructions over a top-level one
+    pub prefer_inner_dex: bool,
+    /// Only emit swaps with a stablecoin or SOL on one side
+    pub require_base_asset: bool,
+}
 
 impl SwapDetector {
     /// Create a new swap detector
     pub fn new() -> Self {
-        Self {}
+        Self { prefer_inner_dex: false, require_base_asset: false }
+    }
+
+    /// Does the swap pass `require_base_asset`?
+    fn has_required_base_asset(&self, input: &BalanceDelta, output: &BalanceDelta) -> bool {
+        if !self.require_base_asset
+            || BaseCurrencies::is_base_currency(&input.mint)
+            || BaseCurrencies::is_base_currency(&output.mint)
+        {
+            return true;
+        }
+        info!("⏭️  Token-to-token swap ({} → {}), no base asset - skipping", input.mint, output.mint);
+        false
     }
 
     /// Detect if balance deltas represent a swap
+    #[allow(clippy::too_many_arguments)]
     pub fn detect_swap(
         &self,
         deltas: Vec<BalanceDelta>,
         signature: Signature,
+        slot: u64,
         timestamp: i64,
         target_wallet: Pubkey,
         likely_dex: Option<String>,
+        tx_type: TransactionType,
     ) -> MonitorResult<Option<UniversalSwapSignal>> {
         if deltas.is_empty() {
             debug!("No balance deltas, not a swap");
             return Ok(None);
         }
 
+        // LP provisions/removals can look like multi-hop swaps - never emit them
+        if matches!(tx_type, TransactionType::AddLiquidity | TransactionType::RemoveLiquidity) {
+            info!("⏭️  Liquidity action ({}), not a swap", tx_type);
+            return Ok(None);
+        }
+
         // Separate increases and decreases
         let decreases: Vec<_> = deltas.iter().filter(|d| d.is_decrease()).collect();
         let increases: Vec<_> = deltas.iter().filter(|d| d.is_increase()).collect();
@@ -38,54 +68,88 @@ impl SwapDetector {
             let input = decreases[0];
             let output = increases[0];
 
+            if !self.has_required_base_asset(input, output) {
+                return Ok(None);
+            }
+
             info!("✅ Simple swap detected:");
             info!("   Input:  {} {} ({})", input.ui_amount(), input.mint, input.mint);
             info!("   Output: {} {} ({})", output.ui_amount(), output.mint, output.mint);
 
+            let confidence = score_confidence(input, output, deltas.len(), likely_dex.as_deref());
+
             return Ok(Some(UniversalSwapSignal {
                 signature,
+                slot,
                 timestamp,
                 trader: target_wallet,
                 swap_type: SwapType::Simple,
                 input_mint: input.mint,
                 input_amount: input.abs_amount(),
+                input_pre_balance: input.pre_balance,
+                input_decimals: input.decimals,
                 output_mint: output.mint,
                 output_amount: output.abs_amount(),
+                output_decimals: output.decimals,
                 intermediate_tokens: vec![],
                 likely_dex,
+                confidence,
+                is_new_mint: output.pre_balance == 0,
+                // Filled in by the caller, which has the message
+                priority_fee_lamports: 0,
             }));
         }
 
         // === PATTERN 2: Multi-Hop Swap ===
         // Multiple tokens changed (e.g., USDC → SOL → BONK)
-        // Strategy: First decrease = input, Last increase = output
-        if decreases.len() >= 1 && increases.len() >= 1 {
-            let input = decreases.first().unwrap();
-            let output = increases.last().unwrap();
-
-            // Collect intermediate tokens
-            let intermediate_tokens: Vec<Pubkey> = deltas
-                .iter()
-                .filter(|d| d.mint != input.mint && d.mint != output.mint)
-                .map(|d| d.mint)
-                .collect();
+        // Strategy: Largest decrease = input, Largest increase = output
+        // (routing leftovers are small, and delta order isn't meaningful)
+        if !decreases.is_empty() && !increases.is_empty() {
+            let input = *decreases.iter().max_by_key(|d| d.abs_amount()).unwrap();
+            let output = *increases.iter().max_by_key(|d| d.abs_amount()).unwrap();
+
+            if !self.has_required_base_asset(input, output) {
+                return Ok(None);
+            }
+
+            // Collect intermediate tokens, once each - a token routed through
+            // can show up as both a decrease and an increase
+            let mut intermediate_tokens: Vec<Pubkey> = Vec::new();
+            for delta in &deltas {
+                if delta.mint != input.mint
+                    && delta.mint != output.mint
+                    && !intermediate_tokens.contains(&delta.mint)
+                {
+                    intermediate_tokens.push(delta.mint);
+                }
+            }
 
             info!("✅ Multi-hop swap detected:");
             info!("   Input:  {} {} ({})", input.ui_amount(), input.mint, input.mint);
             info!("   Output: {} {} ({})", output.ui_amount(), output.mint, output.mint);
             info!("   Hops:   {} intermediate tokens", intermediate_tokens.len());
 
+            let confidence = score_confidence(input, output, deltas.len(), likely_dex.as_deref());
+
             return Ok(Some(UniversalSwapSignal {
                 signature,
+                slot,
                 timestamp,
                 trader: target_wallet,
                 swap_type: SwapType::MultiHop,
                 input_mint: input.mint,
                 input_amount: input.abs_amount(),
+                input_pre_balance: input.pre_balance,
+                input_decimals: input.decimals,
                 output_mint: output.mint,
                 output_amount: output.abs_amount(),
+                output_decimals: output.decimals,
                 intermediate_tokens,
                 likely_dex,
+                confidence,
+                is_new_mint: output.pre_balance == 0,
+                // Filled in by the caller, which has the message
+                priority_fee_lamports: 0,
             }));
         }
 
@@ -103,54 +167,63 @@ impl SwapDetector {
         &self,
         transaction: &solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta,
     ) -> Option<String> {
-        // Try to identify DEX from program IDs in instructions
-        use crate::types::program_ids;
+        use crate::monitor::parser::{identify_dex, inner_instructions, message_instructions, resolve_account_keys};
+        use crate::types::DexType;
 
         let tx = match &transaction.transaction.transaction {
             solana_transaction_status::EncodedTransaction::Json(tx) => tx,
             _ => return None,
         };
 
-        let message = match &tx.message {
-            solana_transaction_status::UiMessage::Parsed(msg) => msg,
-            _ => return None,
-        };
+        // Compiled instructions name their program by index into the account
+        // keys, which both message encodings carry (plus lookup table ones)
+        let account_keys = resolve_account_keys(&tx.message, transaction.transaction.meta.as_ref());
+        let instructions = message_instructions(&tx.message);
 
-        // Check each instruction for known program IDs
-        for instruction in &message.instructions {
-            let program_id_str = match instruction {
-                solana_transaction_status::UiInstruction::Parsed(parsed) => {
-                    match parsed {
-                        solana_transaction_status::UiParsedInstruction::Parsed(p) => {
-                            Some(p.program_id.clone())
-                        }
-                        solana_transaction_status::UiParsedInstruction::PartiallyDecoded(p) => {
-                            Some(p.program_id.clone())
-                        }
-                    }
-                }
-                solana_transaction_status::UiInstruction::Compiled(compiled) => {
-                    // Would need to look up program ID from account keys
-                    None
-                }
-            };
+        // Routers call the DEX through CPI, so check inner instructions too
+        let inner = inner_instructions(transaction.transaction.meta.as_ref());
+        let (dex, _) = identify_dex(&instructions, &inner, &account_keys, self.prefer_inner_dex);
 
-            if let Some(program_id) = program_id_str {
-                // Check against known DEX program IDs
-                if program_id == program_ids::jupiter_v6().to_string() {
-                    return Some("Jupiter".to_string());
-                }
-                if program_id == program_ids::raydium_v4().to_string() {
-                    return Some("Raydium".to_string());
-                }
-                if program_id == program_ids::orca_whirlpool().to_string() {
-                    return Some("Orca".to_string());
-                }
-            }
+        match dex {
+            DexType::Unknown => Some("Unknown DEX".to_string()),
+            dex => Some(dex.to_string()),
         }
+    }
+}
+
+/// Score how much we trust a detected swap (0.0 - 1.0)
+///
+/// Starts at 1.0 and deducts for each sign of a messy pattern:
+/// - 0.1 per balance change beyond the two of a simple swap (max 0.3)
+/// - 0.2 if neither side is a base currency (a stablecoin or SOL/WSOL)
+/// - 0.2 if the DEX couldn't be identified
+/// - 0.3 if either side moved less than a dust amount
+pub fn score_confidence(
+    input: &BalanceDelta,
+    output: &BalanceDelta,
+    delta_count: usize,
+    likely_dex: Option<&str>,
+) -> f32 {
+    let mut confidence = 1.0_f32;
 
-        Some("Unknown DEX".to_string())
+    let extra_deltas = delta_count.saturating_sub(2).min(3);
+    confidence -= 0.1 * extra_deltas as f32;
+
+    if !BaseCurrencies::is_base_currency(&input.mint)
+        && !BaseCurrencies::is_base_currency(&output.mint)
+    {
+        confidence -= 0.2;
+    }
+
+    if matches!(likely_dex, None | Some("Unknown DEX")) {
+        confidence -= 0.2;
+    }
+
+    if input.ui_amount() < DUST_UI_AMOUNT || output.ui_amount() < DUST_UI_AMOUNT {
+        confidence -= 0.3;
     }
+
+    confidence.clamp(0.0, 1.0)
 }
 
 impl Default for SwapDetector {
@@ -162,6 +235,8 @@ impl Default for SwapDetector {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::decision::Stablecoins;
+    use std::str::FromStr;
 
     #[test]
     fn test_simple_swap_detection() {
@@ -171,17 +246,21 @@ mod tests {
         let deltas = vec![
             BalanceDelta {
                 mint: Pubkey::new_unique(), // USDC
+                owner: Pubkey::default(),
                 delta: -100_000_000,
                 pre_balance: 1_000_000_000,
                 post_balance: 900_000_000,
                 decimals: 6,
+                account_index: 1,
             },
             BalanceDelta {
                 mint: Pubkey::new_unique(), // BONK
+                owner: Pubkey::default(),
                 delta: 50_000_000_000,
                 pre_balance: 0,
                 post_balance: 50_000_000_000,
                 decimals: 9,
+                account_index: 2,
             },
         ];
 
@@ -189,8 +268,10 @@ mod tests {
             deltas,
             Signature::new_unique(),
             0,
+            0,
             Pubkey::new_unique(),
             None,
+            TransactionType::Swap,
         ).unwrap();
 
         assert!(result.is_some());
@@ -199,4 +280,254 @@ mod tests {
         assert_eq!(signal.input_amount, 100_000_000);
         assert_eq!(signal.output_amount, 50_000_000_000);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_three_hop_swap_detection() {
+        let detector = SwapDetector::new();
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        let sol = Pubkey::new_unique();
+        let ray = Pubkey::new_unique();
+        let bonk = Pubkey::new_unique();
+        let delta = |mint: Pubkey, delta: i128| BalanceDelta {
+            mint,
+            owner: Pubkey::default(),
+            delta,
+            pre_balance: 1_000_000_000_000,
+            post_balance: (1_000_000_000_000 + delta) as u64,
+            decimals: 6,
+            account_index: 0,
+        };
+
+        // USDC → SOL → RAY → BONK, leaving dust of the hops behind in both
+        // directions, listed out of route order
+        let deltas = vec![
+            delta(sol, 5),
+            delta(bonk, 50_000_000_000),
+            delta(ray, -3),
+            delta(usdc, -100_000_000),
+            delta(ray, 2),
+            delta(sol, -1),
+        ];
+
+        let signal = detector.detect_swap(
+            deltas,
+            Signature::new_unique(),
+            0,
+            0,
+            Pubkey::new_unique(),
+            None,
+            TransactionType::Swap,
+        ).unwrap().unwrap();
+
+        assert_eq!(signal.swap_type, SwapType::MultiHop);
+        assert_eq!(signal.input_mint, usdc);
+        assert_eq!(signal.input_amount, 100_000_000);
+        assert_eq!(signal.output_mint, bonk);
+        assert_eq!(signal.output_amount, 50_000_000_000);
+        assert_eq!(signal.intermediate_tokens, vec![sol, ray]);
+    }
+
+    #[test]
+    fn test_confidence_scoring() {
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        let delta = |mint: Pubkey, delta: i128, decimals: u8| BalanceDelta {
+            mint,
+            owner: Pubkey::default(),
+            delta,
+            pre_balance: 0,
+            post_balance: 0,
+            decimals,
+            account_index: 0,
+        };
+
+        // Clean USDC → token via a known DEX
+        let clean = score_confidence(
+            &delta(usdc, -100_000_000, 6),
+            &delta(Pubkey::new_unique(), 50_000_000_000, 9),
+            2,
+            Some("Jupiter"),
+        );
+        assert_eq!(clean, 1.0);
+
+        // SOL-funded buys count as a base asset too
+        let sol_buy = score_confidence(
+            &delta(BaseCurrencies::native_sol(), -1_000_000_000, 9),
+            &delta(Pubkey::new_unique(), 50_000_000_000, 9),
+            2,
+            Some("Jupiter"),
+        );
+        assert_eq!(sol_buy, 1.0);
+
+        // Token → token multi-hop through an unknown DEX
+        let messy = score_confidence(
+            &delta(Pubkey::new_unique(), -100_000_000, 6),
+            &delta(Pubkey::new_unique(), 50_000_000_000, 9),
+            4,
+            Some("Unknown DEX"),
+        );
+        assert!((messy - 0.4).abs() < 1e-6);
+
+        // Dust amounts drag it down further
+        let dust = score_confidence(
+            &delta(usdc, -100, 6),
+            &delta(Pubkey::new_unique(), 50_000_000_000, 9),
+            2,
+            None,
+        );
+        assert!((dust - 0.5).abs() < 1e-6);
+        assert!(dust < clean);
+    }
+
+    #[test]
+    fn test_add_liquidity_is_not_a_swap() {
+        let detector = SwapDetector::new();
+        let classifier = crate::detection::trade_classifier::TradeClassifier::new();
+
+        // Deposit two tokens, receive LP tokens: classified from the AMM
+        // instruction, since the counts alone read as a route
+        let deltas = vec![
+            BalanceDelta {
+                mint: Pubkey::new_unique(),
+                owner: Pubkey::default(),
+                delta: -100_000_000,
+                pre_balance: 1_000_000_000,
+                post_balance: 900_000_000,
+                decimals: 6,
+                account_index: 1,
+            },
+            BalanceDelta {
+                mint: Pubkey::new_unique(),
+                owner: Pubkey::default(),
+                delta: -1_000,
+                pre_balance: 1_000,
+                post_balance: 0,
+                decimals: 9,
+                account_index: 2,
+            },
+            BalanceDelta {
+                mint: Pubkey::new_unique(), // LP token
+                owner: Pubkey::default(),
+                delta: 5_000_000,
+                pre_balance: 0,
+                post_balance: 5_000_000,
+                decimals: 6,
+                account_index: 3,
+            },
+        ];
+        assert_eq!(classifier.classify(&deltas), TransactionType::MultiHopSwap);
+
+        let result = detector.detect_swap(
+            deltas,
+            Signature::new_unique(),
+            0,
+            0,
+            Pubkey::new_unique(),
+            None,
+            TransactionType::AddLiquidity,
+        ).unwrap();
+
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_require_base_asset_drops_token_to_token() {
+        let mut detector = SwapDetector::new();
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        let delta = |mint: Pubkey, delta: i128, account_index: u8| BalanceDelta {
+            mint,
+            owner: Pubkey::default(),
+            delta,
+            pre_balance: delta.unsigned_abs() as u64,
+            post_balance: 0,
+            decimals: 6,
+            account_index,
+        };
+        let detect = |detector: &SwapDetector, deltas: Vec<BalanceDelta>| {
+            detector.detect_swap(
+                deltas,
+                Signature::new_unique(),
+                0,
+                0,
+                Pubkey::new_unique(),
+                None,
+                TransactionType::Swap,
+            ).unwrap()
+        };
+
+        let token_to_token = vec![
+            delta(Pubkey::new_unique(), -1_000, 1),
+            delta(Pubkey::new_unique(), 2_000, 2),
+        ];
+        let buy = vec![delta(usdc, -1_000, 1), delta(Pubkey::new_unique(), 2_000, 2)];
+
+        // Off by default
+        assert!(detect(&detector, token_to_token.clone()).is_some());
+
+        detector.require_base_asset = true;
+        assert!(detect(&detector, token_to_token).is_none());
+        assert!(detect(&detector, buy).is_some());
+    }
+
+    /// A `json`-encoded transaction whose only instruction is compiled and
+    /// calls `program_id`
+    fn compiled_transaction(
+        program_id: Pubkey,
+        parsed: bool,
+    ) -> solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta {
+        let keys = [Pubkey::new_unique(), program_id];
+        let instruction = serde_json::json!({
+            "programIdIndex": 1,
+            "accounts": [0],
+            "data": "",
+            "stackHeight": null
+        });
+        let message = if parsed {
+            serde_json::json!({
+                "accountKeys": keys.iter().map(|key| serde_json::json!({
+                    "pubkey": key.to_string(),
+                    "writable": true,
+                    "signer": false,
+                    "source": "transaction"
+                })).collect::<Vec<_>>(),
+                "recentBlockhash": "11111111111111111111111111111111",
+                "instructions": [instruction]
+            })
+        } else {
+            serde_json::json!({
+                "header": {
+                    "numRequiredSignatures": 1,
+                    "numReadonlySignedAccounts": 0,
+                    "numReadonlyUnsignedAccounts": 1
+                },
+                "accountKeys": keys.iter().map(|key| key.to_string()).collect::<Vec<_>>(),
+                "recentBlockhash": "11111111111111111111111111111111",
+                "instructions": [instruction]
+            })
+        };
+
+        serde_json::from_value(serde_json::json!({
+            "slot": 1,
+            "blockTime": null,
+            "transaction": {
+                "signatures": [Signature::new_unique().to_string()],
+                "message": message
+            },
+            "meta": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_guess_dex_from_compiled_instructions() {
+        use crate::types::program_ids;
+
+        let detector = SwapDetector::new();
+        for parsed in [true, false] {
+            let tx = compiled_transaction(program_ids::jupiter_v6(), parsed);
+            assert_eq!(detector.guess_dex(&tx).as_deref(), Some("Jupiter"), "parsed: {}", parsed);
+        }
+
+        let tx = compiled_transaction(Pubkey::new_unique(), false);
+        assert_eq!(detector.guess_dex(&tx).as_deref(), Some("Unknown DEX"));
+    }
+}
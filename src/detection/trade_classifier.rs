@@ -1,6 +1,24 @@
 use crate::detection::types::{BalanceDelta, TransactionType};
+use crate::monitor::parser::{inner_instructions, instruction_program_id, message_instructions, resolve_account_keys};
+use crate::types::{program_ids, wsol_pubkey};
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiInstruction, UiParsedInstruction};
 use tracing::debug;
 
+/// Rent for a token account, paid when WSOL is wrapped into a new account
+/// and refunded when it's closed on unwrap
+const TOKEN_ACCOUNT_RENT_LAMPORTS: u64 = 2_039_280;
+
+/// Raydium AMM v4 instruction tags for adding and removing liquidity
+const RAYDIUM_DEPOSIT: u8 = 3;
+const RAYDIUM_WITHDRAW: u8 = 4;
+
+/// Anchor discriminators of `increase_liquidity` / `increase_liquidity_v2`
+/// and the `decrease_` pair. Orca Whirlpool and Raydium CLMM share the
+/// instruction names, so they share the discriminators too.
+const INCREASE_LIQUIDITY: [[u8; 8]; 2] = [[46, 156, 243, 118, 13, 205, 251, 178], [133, 29, 89, 223, 69, 238, 176, 10]];
+const DECREASE_LIQUIDITY: [[u8; 8]; 2] = [[160, 38, 208, 111, 104, 91, 44, 1], [58, 127, 188, 62, 79, 82, 196, 96]];
+
 /// Classifies transactions based on balance change patterns
 pub struct TradeClassifier {}
 
@@ -21,7 +39,9 @@ impl TradeClassifier {
             // Simple swap: 1 sold, 1 bought
             (1, 1) => TransactionType::Swap,
 
-            // Multi-hop swap: Multiple changes
+            // Multi-hop swap: Multiple changes. Routes that leave intermediate
+            // dust, or swaps alongside an airdrop, look the same as LP actions
+            // here; those are told apart by `liquidity_action`, not by counts
             (n, m) if n >= 1 && m >= 1 => TransactionType::MultiHopSwap,
 
             // Transfer: Only decreases (sending tokens)
@@ -30,12 +50,6 @@ impl TradeClassifier {
             // Receive: Only increases (receiving tokens)
             (0, n) if n > 0 => TransactionType::Receive,
 
-            // Liquidity provision: 1 decrease (deposit), multiple increases (LP tokens)
-            (1, n) if n > 1 => TransactionType::AddLiquidity,
-
-            // Liquidity removal: Multiple decreases (LP tokens), 1 increase (withdrawal)
-            (n, 1) if n > 1 => TransactionType::RemoveLiquidity,
-
             // Unknown pattern
             _ => TransactionType::Unknown,
         };
@@ -44,12 +58,85 @@ impl TradeClassifier {
         tx_type
     }
 
+    /// A liquidity deposit or withdrawal named by a known AMM instruction,
+    /// top-level or through CPI. Balance changes alone can't tell an LP
+    /// action from a multi-hop swap, so liquidity is only classified from this.
+    pub fn liquidity_action(&self, transaction: &EncodedConfirmedTransactionWithStatusMeta) -> Option<TransactionType> {
+        let EncodedTransaction::Json(tx) = &transaction.transaction.transaction else {
+            return None;
+        };
+        let meta = transaction.transaction.meta.as_ref();
+        let account_keys = resolve_account_keys(&tx.message, meta);
+
+        let action = message_instructions(&tx.message)
+            .iter()
+            .chain(&inner_instructions(meta))
+            .find_map(|instruction| liquidity_instruction(instruction, &account_keys));
+        if let Some(action) = action {
+            debug!("Liquidity instruction found: {}", action);
+        }
+        action
+    }
+
+    /// Is this just SOL being wrapped into WSOL (or unwrapped back)?
+    ///
+    /// True when WSOL is the only token that moved and the wallet's native
+    /// SOL moved the opposite way by about the same amount (allowing for the
+    /// token account's rent). A wrap would otherwise read as "bought WSOL".
+    pub fn is_sol_wrap(&self, deltas: &[BalanceDelta], native_sol_delta: Option<i64>) -> bool {
+        let Some(native) = native_sol_delta else {
+            return false;
+        };
+        let [wsol] = deltas else {
+            return false;
+        };
+        if wsol.mint != wsol_pubkey() || native.signum() as i128 != -wsol.delta.signum() {
+            return false;
+        }
+
+        let difference = native.unsigned_abs().abs_diff(wsol.abs_amount());
+        let is_wrap = difference <= TOKEN_ACCOUNT_RENT_LAMPORTS + wsol.abs_amount() / 100;
+        if is_wrap {
+            debug!("SOL {} of {} lamports", if wsol.is_increase() { "wrap" } else { "unwrap" }, wsol.abs_amount());
+        }
+        is_wrap
+    }
+
     /// Quick check if a transaction should be copied
     pub fn should_copy(&self, deltas: &[BalanceDelta]) -> bool {
         self.classify(deltas).should_copy()
     }
 }
 
+/// `AddLiquidity` / `RemoveLiquidity` if `instruction` is an AMM deposit or withdrawal
+fn liquidity_instruction(instruction: &UiInstruction, account_keys: &[Pubkey]) -> Option<TransactionType> {
+    let program_id = instruction_program_id(instruction, account_keys)?;
+    let data = match instruction {
+        UiInstruction::Compiled(compiled) => &compiled.data,
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => &decoded.data,
+        _ => return None,
+    };
+    let data = bs58::decode(data).into_vec().ok()?;
+
+    if program_id == program_ids::raydium_v4() {
+        return match data.first() {
+            Some(&RAYDIUM_DEPOSIT) => Some(TransactionType::AddLiquidity),
+            Some(&RAYDIUM_WITHDRAW) => Some(TransactionType::RemoveLiquidity),
+            _ => None,
+        };
+    }
+    if program_id == program_ids::orca_whirlpool() || program_id == program_ids::raydium_clmm() {
+        let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+        if INCREASE_LIQUIDITY.contains(&discriminator) {
+            return Some(TransactionType::AddLiquidity);
+        }
+        if DECREASE_LIQUIDITY.contains(&discriminator) {
+            return Some(TransactionType::RemoveLiquidity);
+        }
+    }
+    None
+}
+
 impl Default for TradeClassifier {
     fn default() -> Self {
         Self::new()
@@ -59,7 +146,7 @@ impl Default for TradeClassifier {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_sdk::pubkey::Pubkey;
+    use solana_transaction_status::UiCompiledInstruction;
 
     #[test]
     fn test_simple_swap_classification() {
@@ -68,17 +155,21 @@ mod tests {
         let deltas = vec![
             BalanceDelta {
                 mint: Pubkey::new_unique(),
+                owner: Pubkey::default(),
                 delta: -100,
                 pre_balance: 1000,
                 post_balance: 900,
                 decimals: 6,
+                account_index: 1,
             },
             BalanceDelta {
                 mint: Pubkey::new_unique(),
+                owner: Pubkey::default(),
                 delta: 50,
                 pre_balance: 0,
                 post_balance: 50,
                 decimals: 9,
+                account_index: 2,
             },
         ];
 
@@ -92,10 +183,12 @@ mod tests {
 
         let deltas = vec![BalanceDelta {
             mint: Pubkey::new_unique(),
+            owner: Pubkey::default(),
             delta: -100,
             pre_balance: 1000,
             post_balance: 900,
             decimals: 6,
+            account_index: 0,
         }];
 
         assert_eq!(classifier.classify(&deltas), TransactionType::Transfer);
@@ -108,13 +201,93 @@ mod tests {
 
         let deltas = vec![BalanceDelta {
             mint: Pubkey::new_unique(),
+            owner: Pubkey::default(),
             delta: 100,
             pre_balance: 0,
             post_balance: 100,
             decimals: 6,
+            account_index: 0,
         }];
 
         assert_eq!(classifier.classify(&deltas), TransactionType::Receive);
         assert!(!classifier.should_copy(&deltas));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_sol_wrap_is_not_a_swap() {
+        let classifier = TradeClassifier::new();
+        let wsol = |delta: i128| BalanceDelta {
+            mint: wsol_pubkey(),
+            owner: Pubkey::default(),
+            delta,
+            pre_balance: if delta < 0 { delta.unsigned_abs() as u64 } else { 0 },
+            post_balance: if delta > 0 { delta as u64 } else { 0 },
+            decimals: 9,
+            account_index: 1,
+        };
+
+        // Wrap 1 SOL into a new token account: pays the amount plus rent
+        let wrap = vec![wsol(1_000_000_000)];
+        assert!(classifier.is_sol_wrap(&wrap, Some(-1_002_039_280)));
+
+        // Unwrap and close the account: gets the amount plus the rent back
+        let unwrap = vec![wsol(-1_000_000_000)];
+        assert!(classifier.is_sol_wrap(&unwrap, Some(1_002_039_280)));
+
+        // WSOL bought with something other than native SOL
+        assert!(!classifier.is_sol_wrap(&wrap, Some(-5_000)));
+        assert!(!classifier.is_sol_wrap(&wrap, None));
+
+        // Same direction on both sides isn't a wrap
+        assert!(!classifier.is_sol_wrap(&wrap, Some(1_000_000_000)));
+
+        // Another token moved too
+        let mut swap = wrap.clone();
+        swap.push(BalanceDelta { mint: Pubkey::new_unique(), account_index: 2, ..wsol(-50) });
+        assert!(!classifier.is_sol_wrap(&swap, Some(-1_000_000_000)));
+    }
+
+    #[test]
+    fn test_uneven_patterns_are_multi_hop_swaps() {
+        let classifier = TradeClassifier::new();
+        let delta = |delta: i128, account_index: u8| BalanceDelta {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::default(),
+            delta,
+            pre_balance: 1_000,
+            post_balance: 1_000,
+            decimals: 6,
+            account_index,
+        };
+
+        // A swap plus an airdrop, and a route that left intermediate dust
+        let with_airdrop = vec![delta(-100, 1), delta(50, 2), delta(1, 3)];
+        let with_dust = vec![delta(-100, 1), delta(-1, 2), delta(50, 3)];
+        assert_eq!(classifier.classify(&with_airdrop), TransactionType::MultiHopSwap);
+        assert_eq!(classifier.classify(&with_dust), TransactionType::MultiHopSwap);
+    }
+
+    #[test]
+    fn test_liquidity_instructions() {
+        let amm = Pubkey::new_unique();
+        let account_keys = vec![program_ids::raydium_v4(), program_ids::orca_whirlpool(), amm];
+        let call = |program: u8, data: &[u8]| {
+            UiInstruction::Compiled(UiCompiledInstruction {
+                program_id_index: program,
+                accounts: vec![],
+                data: bs58::encode(data).into_string(),
+                stack_height: None,
+            })
+        };
+
+        assert_eq!(liquidity_instruction(&call(0, &[RAYDIUM_DEPOSIT, 1, 2]), &account_keys), Some(TransactionType::AddLiquidity));
+        assert_eq!(liquidity_instruction(&call(0, &[RAYDIUM_WITHDRAW, 1]), &account_keys), Some(TransactionType::RemoveLiquidity));
+        assert_eq!(liquidity_instruction(&call(1, &INCREASE_LIQUIDITY[1]), &account_keys), Some(TransactionType::AddLiquidity));
+        assert_eq!(liquidity_instruction(&call(1, &DECREASE_LIQUIDITY[0]), &account_keys), Some(TransactionType::RemoveLiquidity));
+
+        // Swaps, and other programs using the same tags, aren't liquidity actions
+        assert_eq!(liquidity_instruction(&call(0, &[9, 1, 2]), &account_keys), None);
+        assert_eq!(liquidity_instruction(&call(2, &[RAYDIUM_DEPOSIT]), &account_keys), None);
+        assert_eq!(liquidity_instruction(&call(1, &[0; 8]), &account_keys), None);
+    }
+}
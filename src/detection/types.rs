@@ -1,4 +1,5 @@
 use crate::decision;
+use crate::types::{DexType, TradeSignal};
 use serde::{Deserialize, Serialize};
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
 
@@ -8,8 +9,12 @@ pub struct BalanceDelta {
     /// The token mint address
     pub mint: Pubkey,
 
-    /// Change in balance (negative = sold, positive = bought)
-    pub delta: i64,
+    /// Wallet whose balance changed
+    pub owner: Pubkey,
+
+    /// Change in balance (negative = sold, positive = bought). Wider than
+    /// the balances themselves so any u64 → u64 change fits.
+    pub delta: i128,
 
     /// Balance before transaction
     pub pre_balance: u64,
@@ -19,12 +24,15 @@ pub struct BalanceDelta {
 
     /// Token decimals
     pub decimals: u8,
+
+    /// Index of the token account in the transaction's account keys
+    pub account_index: u8,
 }
 
 impl BalanceDelta {
     /// Get the absolute amount that changed
     pub fn abs_amount(&self) -> u64 {
-        self.delta.unsigned_abs()
+        u64::try_from(self.delta.unsigned_abs()).unwrap_or(u64::MAX)
     }
 
     /// Check if this represents a token being sold
@@ -43,11 +51,53 @@ impl BalanceDelta {
     }
 }
 
+/// Shape of a transaction's balance changes: which mints went down and up.
+/// Recorded for classifications the classifier couldn't name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeltaPattern {
+    /// Mints whose balance decreased, with the raw change
+    pub decreases: Vec<(String, i128)>,
+    /// Mints whose balance increased, with the raw change
+    pub increases: Vec<(String, i128)>,
+}
+
+impl DeltaPattern {
+    pub fn from_deltas(deltas: &[BalanceDelta]) -> Self {
+        let collect = |keep: fn(&BalanceDelta) -> bool| {
+            deltas.iter().filter(|d| keep(d)).map(|d| (d.mint.to_string(), d.delta)).collect()
+        };
+        Self {
+            decreases: collect(BalanceDelta::is_decrease),
+            increases: collect(BalanceDelta::is_increase),
+        }
+    }
+}
+
+impl std::fmt::Display for DeltaPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |side: &[(String, i128)]| {
+            side.iter().map(|(mint, delta)| format!("{} {:+}", mint, delta)).collect::<Vec<_>>().join(", ")
+        };
+        write!(
+            f,
+            "{} decreases [{}], {} increases [{}]",
+            self.decreases.len(),
+            list(&self.decreases),
+            self.increases.len(),
+            list(&self.increases)
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniversalSwapSignal {
     /// Transaction signature
     pub signature: Signature,
 
+    /// Slot the transaction landed in (finer ordering than `timestamp`)
+    #[serde(default)]
+    pub slot: u64,
+
     /// Block timestamp
     pub timestamp: i64,
 
@@ -63,17 +113,42 @@ pub struct UniversalSwapSignal {
     /// Amount of input token sold
     pub input_amount: u64,
 
+    /// Trader's input-token balance before the swap (0 when unknown)
+    #[serde(default)]
+    pub input_pre_balance: u64,
+
+    /// Decimals of the input token (0 when unknown)
+    #[serde(default)]
+    pub input_decimals: u8,
+
     /// Token that was bought (output)
     pub output_mint: Pubkey,
 
     /// Amount of output token received
     pub output_amount: u64,
 
+    /// Decimals of the output token (0 when unknown)
+    #[serde(default)]
+    pub output_decimals: u8,
+
     /// Intermediate tokens (for multi-hop swaps)
     pub intermediate_tokens: Vec<Pubkey>,
 
     /// Likely DEX used (optional, for logging only)
     pub likely_dex: Option<String>,
+
+    /// How much we trust this detection (0.0 - 1.0), see `score_confidence`
+    #[serde(default)]
+    pub confidence: f32,
+
+    /// Buy of a token the trader didn't hold before (and, if the recency
+    /// check is enabled, one that was minted recently)
+    #[serde(default)]
+    pub is_new_mint: bool,
+
+    /// Priority fee the trader paid, in lamports
+    #[serde(default)]
+    pub priority_fee_lamports: u64,
 }
 
 impl UniversalSwapSignal {
@@ -90,19 +165,61 @@ impl UniversalSwapSignal {
     /// Get human-readable description
     pub fn description(&self) -> String {
         format!(
-            "{} swap: {} {} → {} {}",
+            "{} swap: {} → {}",
             self.swap_type,
-            self.input_amount,
-            self.input_mint,
-            self.output_amount,
-            self.output_mint
+            self.format_input(),
+            self.format_output()
         )
     }
 
+    /// Input amount in UI units, e.g. "123.456789 <mint>"
+    pub fn format_input(&self) -> String {
+        format!("{} {}", format_amount(self.input_amount, self.input_decimals), self.input_mint)
+    }
+
+    /// Output amount in UI units, e.g. "123.456789 <mint>"
+    pub fn format_output(&self) -> String {
+        format!("{} {}", format_amount(self.output_amount, self.output_decimals), self.output_mint)
+    }
+
+    /// Share of their input-token holding the trader sold (0 - 1), if known
+    pub fn sold_fraction(&self) -> Option<f64> {
+        if self.input_pre_balance == 0 {
+            return None;
+        }
+        Some((self.input_amount as f64 / self.input_pre_balance as f64).min(1.0))
+    }
+
+    /// The same swap spending `input_amount`, with the output scaled to the
+    /// trader's price - our copy of it
+    pub fn scaled_to_input(&self, input_amount: u64) -> Self {
+        let output_amount = if self.input_amount == 0 {
+            0
+        } else {
+            let scaled =
+                self.output_amount as u128 * input_amount as u128 / self.input_amount as u128;
+            scaled.min(u64::MAX as u128) as u64
+        };
+
+        Self {
+            input_amount,
+            output_amount,
+            ..self.clone()
+        }
+    }
+
     pub fn direction(&self) -> crate::decision::TradeDirection {
         decision::detect_direction(&self.input_mint, &self.output_mint)
     }
 
+    /// Direction with the stablecoins of `registry` (e.g. from config)
+    pub fn direction_with(
+        &self,
+        registry: &decision::StablecoinRegistry,
+    ) -> crate::decision::TradeDirection {
+        registry.detect_direction(&self.input_mint, &self.output_mint)
+    }
+
     /// Check if this is a buy signal we should copy
     pub fn is_buy(&self) -> bool {
         matches!(
@@ -128,6 +245,136 @@ impl UniversalSwapSignal {
     }
 }
 
+/// Test signals: start from [`test_swap`](UniversalSwapSignal::test_swap)
+/// and adjust with the `with_*` methods or struct update syntax
+#[cfg(test)]
+impl UniversalSwapSignal {
+    /// A confident simple swap of 1 raw unit of a fresh mint for 1 of
+    /// another, both with 6 decimals
+    pub(crate) fn test_swap() -> Self {
+        Self {
+            signature: Signature::new_unique(),
+            slot: 1,
+            timestamp: 0,
+            trader: Pubkey::new_unique(),
+            swap_type: SwapType::Simple,
+            input_mint: Pubkey::new_unique(),
+            input_amount: 1,
+            input_pre_balance: 0,
+            input_decimals: 6,
+            output_mint: Pubkey::new_unique(),
+            output_amount: 1,
+            output_decimals: 6,
+            intermediate_tokens: vec![],
+            likely_dex: None,
+            confidence: 1.0,
+            is_new_mint: false,
+            priority_fee_lamports: 0,
+        }
+    }
+
+    pub(crate) fn with_mints(self, input_mint: Pubkey, output_mint: Pubkey) -> Self {
+        Self { input_mint, output_mint, ..self }
+    }
+
+    pub(crate) fn with_amounts(self, input_amount: u64, output_amount: u64) -> Self {
+        Self { input_amount, output_amount, ..self }
+    }
+
+    pub(crate) fn with_decimals(self, input_decimals: u8, output_decimals: u8) -> Self {
+        Self { input_decimals, output_decimals, ..self }
+    }
+
+    pub(crate) fn with_dex(self, dex: &str) -> Self {
+        Self { likely_dex: Some(dex.to_string()), ..self }
+    }
+}
+
+/// Format a raw token amount with its decimals, without going through f64
+/// (so large amounts never lose precision or turn into scientific notation)
+pub fn format_amount(amount: u64, decimals: u8) -> String {
+    if decimals == 0 {
+        return amount.to_string();
+    }
+
+    // Insert the decimal point into the digits; a 10^decimals scale would
+    // overflow u128 for mints reporting more than 38 decimals
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", amount, width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        return whole.to_string();
+    }
+    format!("{}.{}", whole, fraction)
+}
+
+impl From<TradeSignal> for UniversalSwapSignal {
+    /// Bridge instruction-parser output into the universal pipeline. One
+    /// decoded instruction is one hop, so the swap is always `Simple`.
+    fn from(signal: TradeSignal) -> Self {
+        Self {
+            signature: signal.signature,
+            slot: signal.slot,
+            timestamp: signal.timestamp,
+            trader: signal.trader,
+            swap_type: SwapType::Simple,
+            input_mint: signal.source_mint,
+            input_amount: signal.amount_in,
+            // Instruction data doesn't show the trader's holding
+            input_pre_balance: 0,
+            // Instruction data carries raw amounts only
+            input_decimals: 0,
+            output_mint: signal.destination_mint,
+            output_amount: signal.amount_out,
+            output_decimals: 0,
+            intermediate_tokens: vec![],
+            likely_dex: Some(signal.dex.to_string()),
+            // Decoded straight from a known DEX instruction
+            confidence: 1.0,
+            // No pre-balances to tell from
+            is_new_mint: false,
+            priority_fee_lamports: signal.priority_fee_lamports,
+        }
+    }
+}
+
+impl TryFrom<UniversalSwapSignal> for TradeSignal {
+    type Error = String;
+
+    /// Express a detected swap as a DEX trade. Fails for multi-hop swaps,
+    /// whose intermediate tokens a `TradeSignal` has no room for.
+    fn try_from(signal: UniversalSwapSignal) -> Result<Self, Self::Error> {
+        if signal.swap_type == SwapType::MultiHop || !signal.intermediate_tokens.is_empty() {
+            return Err(format!(
+                "multi-hop swap {} through {} intermediate token(s) has no single-hop form",
+                signal.signature,
+                signal.intermediate_tokens.len()
+            ));
+        }
+
+        let dex = signal.likely_dex.as_deref().and_then(|name| name.parse().ok()).unwrap_or(DexType::Unknown);
+        Ok(Self {
+            signature: signal.signature,
+            slot: signal.slot,
+            timestamp: signal.timestamp,
+            dex,
+            source_mint: signal.input_mint,
+            destination_mint: signal.output_mint,
+            amount_in: signal.input_amount,
+            amount_out: signal.output_amount,
+            // Balance changes show what was received, not the bound the
+            // trader set, so the received amount stands in with no slippage
+            minimum_amount_out: signal.output_amount,
+            slippage_bps: 0,
+            priority_fee_lamports: signal.priority_fee_lamports,
+            accounts: vec![],
+            trader: signal.trader,
+        })
+    }
+}
+
 /// Type of swap detected
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SwapType {
@@ -193,6 +440,86 @@ impl std::fmt::Display for TransactionType {
     }
 }
 
+/// What `UniversalParser` made of a transaction
+#[derive(Debug, Clone)]
+pub enum ParseOutcome {
+    /// A swap worth copying (boxed - it dwarfs the skip reason)
+    Signal(Box<UniversalSwapSignal>),
+
+    /// No signal, and why
+    Skipped(SkipReason),
+}
+
+impl ParseOutcome {
+    /// Collapse to the signal, dropping the skip reason
+    pub fn into_signal(self) -> Option<UniversalSwapSignal> {
+        match self {
+            ParseOutcome::Signal(signal) => Some(*signal),
+            ParseOutcome::Skipped(_) => None,
+        }
+    }
+}
+
+/// Why a transaction didn't produce a signal
+#[derive(Debug, Clone, PartialEq)]
+pub enum SkipReason {
+    /// The transaction failed on-chain
+    FailedTx,
+
+    /// No token balance changes that form a swap
+    NotASwap,
+
+    /// A transfer, liquidity change etc. - not something we copy
+    NotCopied(TransactionType),
+
+    /// Stablecoin-to-stablecoin rebalance
+    StablecoinRebalance,
+
+    /// Confidence score below `min_confidence`
+    LowConfidence { confidence: f32, min: f32 },
+
+    /// Priority fee not above `min_priority_fee_lamports`
+    BelowThreshold { priority_fee_lamports: u64, min: u64 },
+
+    /// `only_new_mints` is set and this buy isn't one
+    NotNewMint,
+
+    /// The trader's slippage is above `max_slippage_bps`
+    ExcessiveSlippage { slippage_bps: u16, max: u16 },
+
+    /// Older than `max_signal_age_secs` - the price has likely moved
+    Stale { age_secs: u64, max: u64 },
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::FailedTx => write!(f, "transaction failed"),
+            SkipReason::NotASwap => write!(f, "not a swap"),
+            SkipReason::NotCopied(tx_type) => write!(f, "{} is not copied", tx_type),
+            SkipReason::StablecoinRebalance => write!(f, "stablecoin rebalance"),
+            SkipReason::LowConfidence { confidence, min } => {
+                write!(f, "confidence {:.2} below minimum {:.2}", confidence, min)
+            }
+            SkipReason::BelowThreshold {
+                priority_fee_lamports,
+                min,
+            } => write!(
+                f,
+                "priority fee {} lamports not above minimum {}",
+                priority_fee_lamports, min
+            ),
+            SkipReason::NotNewMint => write!(f, "not a new mint"),
+            SkipReason::ExcessiveSlippage { slippage_bps, max } => {
+                write!(f, "slippage {} bps above maximum {} bps", slippage_bps, max)
+            }
+            SkipReason::Stale { age_secs, max } => {
+                write!(f, "{}s old, older than maximum {}s", age_secs, max)
+            }
+        }
+    }
+}
+
 /// Token balance information
 #[derive(Debug, Clone)]
 pub struct TokenBalance {
@@ -200,4 +527,105 @@ pub struct TokenBalance {
     pub owner: Pubkey,
     pub amount: u64,
     pub decimals: u8,
+    pub account_index: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trade_signal_conversion() {
+        let trade_signal = TradeSignal {
+            signature: Signature::new_unique(),
+            slot: 250_000_000,
+            timestamp: 1_700_000_000,
+            dex: DexType::Raydium,
+            source_mint: Pubkey::new_unique(),
+            destination_mint: Pubkey::new_unique(),
+            amount_in: 1_000_000,
+            amount_out: 250_000,
+            minimum_amount_out: 240_000,
+            slippage_bps: 400,
+            priority_fee_lamports: 5_000,
+            accounts: vec![],
+            trader: Pubkey::new_unique(),
+        };
+
+        let signal = UniversalSwapSignal::from(trade_signal.clone());
+
+        assert_eq!(signal.signature, trade_signal.signature);
+        assert_eq!(signal.slot, 250_000_000);
+        assert_eq!(signal.timestamp, trade_signal.timestamp);
+        assert_eq!(signal.trader, trade_signal.trader);
+        assert_eq!(signal.swap_type, SwapType::Simple);
+        assert_eq!(signal.input_mint, trade_signal.source_mint);
+        assert_eq!(signal.input_amount, 1_000_000);
+        assert_eq!(signal.output_mint, trade_signal.destination_mint);
+        assert_eq!(signal.output_amount, 250_000);
+        assert!(signal.intermediate_tokens.is_empty());
+        assert_eq!(signal.likely_dex.as_deref(), Some("Raydium"));
+    }
+
+    #[test]
+    fn test_universal_signal_conversion() {
+        let trade_signal = TradeSignal {
+            signature: Signature::new_unique(),
+            slot: 250_000_000,
+            timestamp: 1_700_000_000,
+            dex: DexType::RaydiumClmm,
+            source_mint: Pubkey::new_unique(),
+            destination_mint: Pubkey::new_unique(),
+            amount_in: 1_000_000,
+            amount_out: 250_000,
+            minimum_amount_out: 250_000,
+            slippage_bps: 0,
+            priority_fee_lamports: 5_000,
+            accounts: vec![],
+            trader: Pubkey::new_unique(),
+        };
+
+        // There and back keeps everything a detected swap carries
+        let signal = UniversalSwapSignal::from(trade_signal.clone());
+        let back = TradeSignal::try_from(signal.clone()).unwrap();
+        assert_eq!(back.signature, trade_signal.signature);
+        assert_eq!(back.slot, trade_signal.slot);
+        assert_eq!(back.timestamp, trade_signal.timestamp);
+        assert_eq!(back.trader, trade_signal.trader);
+        assert_eq!(back.dex, DexType::RaydiumClmm);
+        assert_eq!(back.source_mint, trade_signal.source_mint);
+        assert_eq!(back.destination_mint, trade_signal.destination_mint);
+        assert_eq!(back.amount_in, 1_000_000);
+        assert_eq!(back.amount_out, 250_000);
+        assert_eq!(back.minimum_amount_out, 250_000);
+        assert_eq!(back.priority_fee_lamports, 5_000);
+
+        // An unrecognised DEX name is kept as Unknown
+        let unnamed = UniversalSwapSignal { likely_dex: Some("SomeRouter".to_string()), ..signal.clone() };
+        assert_eq!(TradeSignal::try_from(unnamed).unwrap().dex, DexType::Unknown);
+        let undetected = UniversalSwapSignal { likely_dex: None, ..signal.clone() };
+        assert_eq!(TradeSignal::try_from(undetected).unwrap().dex, DexType::Unknown);
+
+        // The route through an intermediate token would be lost
+        let multi_hop = UniversalSwapSignal {
+            swap_type: SwapType::MultiHop,
+            intermediate_tokens: vec![Pubkey::new_unique()],
+            ..signal
+        };
+        assert!(TradeSignal::try_from(multi_hop).is_err());
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(123_456_789, 6), "123.456789");
+        assert_eq!(format_amount(5_000_000_000, 9), "5");
+        assert_eq!(format_amount(1_500, 3), "1.5");
+        assert_eq!(format_amount(42, 0), "42");
+        assert_eq!(format_amount(7, 9), "0.000000007");
+        assert_eq!(format_amount(u64::MAX, 6), "18446744073709.551615");
+        assert_eq!(format_amount(0, 6), "0");
+        // Past 10^38 a u128 scale would overflow
+        assert_eq!(format_amount(5, 40), format!("0.{}5", "0".repeat(39)));
+        assert_eq!(format_amount(1, 255).len(), 257);
+    }
 }
@@ -0,0 +1,37 @@
+use crate::detection::types::UniversalSwapSignal;
+use crate::executor::trade::{ExecError, TradeExecutor, capped_input_amount};
+use futures::future::BoxFuture;
+use solana_sdk::signature::Signature;
+use tracing::info;
+
+/// Logs the swap it would make and sends nothing
+pub struct DryRunExecutor {
+    max_input_amount: Option<u64>,
+}
+
+impl DryRunExecutor {
+    pub fn new(max_input_amount: Option<u64>) -> Self {
+        Self { max_input_amount }
+    }
+}
+
+impl TradeExecutor for DryRunExecutor {
+    fn name(&self) -> &str {
+        "dry-run"
+    }
+
+    /// Returns the default (all-zero) signature, as nothing was sent
+    fn execute<'a>(
+        &'a self,
+        signal: &'a UniversalSwapSignal,
+    ) -> BoxFuture<'a, Result<Signature, ExecError>> {
+        Box::pin(async move {
+            let amount = capped_input_amount(signal, self.max_input_amount)?;
+            info!(
+                "🧪 DRY RUN: would swap {} {} → {} (copying {})",
+                amount, signal.input_mint, signal.output_mint, signal.signature
+            );
+            Ok(Signature::default())
+        })
+    }
+}
@@ -0,0 +1,406 @@
+use crate::decision::direction::{BaseCurrencies, StablecoinRegistry, TradeDirection};
+use crate::decision::fee_reserve::FeeReserve;
+use crate::decision::liquidity::LiquidityGuard;
+use crate::decision::slippage::{SlippageGate, quoted_slippage_bps};
+use crate::detection::types::UniversalSwapSignal;
+use crate::executor::Executor;
+use crate::executor::trade::{ExecError, TradeExecutor, capped_input_amount};
+use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::types::ExecutorConfig;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use futures::future::BoxFuture;
+use serde_json::{Value, json};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer, read_keypair_file};
+use solana_sdk::transaction::VersionedTransaction;
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Copies swaps through the Jupiter quote and swap API
+///
+/// Jupiter returns an unsigned transaction for the best route; it is signed
+/// with our keypair and submitted through [`Executor`], which simulates it
+/// first unless that is disabled. Before quoting, the wallet's SOL fee
+/// reserve is kept, and buys the trader took too much slippage on or into
+/// thin pools are refused.
+pub struct JupiterExecutor {
+    http: reqwest::Client,
+    api_url: String,
+    slippage_bps: u16,
+    max_input_amount: Option<u64>,
+    fee_reserve: FeeReserve,
+    liquidity: LiquidityGuard,
+    slippage: SlippageGate,
+    /// Tells buys from sells, the same way the decision engine does
+    stablecoins: StablecoinRegistry,
+    keypair: Arc<Keypair>,
+    executor: Arc<Executor>,
+}
+
+impl JupiterExecutor {
+    pub fn new(config: &ExecutorConfig, keypair: Keypair, executor: Arc<Executor>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: config.jupiter_api_url.trim_end_matches('/').to_string(),
+            slippage_bps: config.slippage_bps,
+            max_input_amount: config.max_input_amount,
+            fee_reserve: FeeReserve::from_config(config),
+            liquidity: LiquidityGuard::default(),
+            slippage: SlippageGate::default(),
+            stablecoins: StablecoinRegistry::default(),
+            keypair: Arc::new(keypair),
+            executor,
+        }
+    }
+
+    /// Classify trades with `stablecoins`, e.g. including `extra_stablecoins`
+    pub fn with_stablecoins(mut self, stablecoins: StablecoinRegistry) -> Self {
+        self.stablecoins = stablecoins;
+        self
+    }
+
+    /// Buy, sell or swap, by the configured stablecoins
+    fn direction(&self, signal: &UniversalSwapSignal) -> TradeDirection {
+        signal.direction_with(&self.stablecoins)
+    }
+
+    /// Refuse buys whose quoted round trip loses more than `guard` allows
+    pub fn with_liquidity_guard(mut self, guard: LiquidityGuard) -> Self {
+        self.liquidity = guard;
+        self
+    }
+
+    /// Refuse buys the trader got more slippage on than `gate` allows
+    pub fn with_slippage_gate(mut self, gate: SlippageGate) -> Self {
+        self.slippage = gate;
+        self
+    }
+
+    /// Create an executor signing with the keypair at `config.keypair_path`
+    pub fn from_config(
+        config: &ExecutorConfig,
+        executor: Arc<Executor>,
+    ) -> Result<Self, ExecError> {
+        let path = config
+            .keypair_path
+            .as_deref()
+            .ok_or_else(|| ExecError::Keypair("no keypair_path configured".to_string()))?;
+        let keypair =
+            read_keypair_file(path).map_err(|e| ExecError::Keypair(format!("{}: {}", path, e)))?;
+
+        info!("🔑 Executing as {}", keypair.pubkey());
+        Ok(Self::new(config, keypair, executor))
+    }
+
+    /// Quote request for swapping `amount` of `input_mint` for `output_mint`
+    fn quote_url(&self, input_mint: &Pubkey, output_mint: &Pubkey, amount: u64) -> String {
+        format!(
+            "{}/quote?inputMint={}&outputMint={}&amount={}&slippageBps={}",
+            self.api_url, input_mint, output_mint, amount, self.slippage_bps
+        )
+    }
+
+    /// Best route for the swap, as returned by `/quote`
+    async fn quote(
+        &self,
+        input_mint: &Pubkey,
+        output_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<Value, ExecError> {
+        let response = self
+            .http
+            .get(self.quote_url(input_mint, output_mint, amount))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ExecError::Api(format!("quote: {}", e)))?;
+
+        response
+            .json()
+            .await
+            .map_err(|e| ExecError::Api(format!("quote: {}", e)))
+    }
+
+    /// Output amount Jupiter quotes for swapping `amount` of `input_mint`
+    async fn quoted_out_amount(
+        &self,
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        amount: u64,
+    ) -> MonitorResult<u64> {
+        let quote = self
+            .quote(&input_mint, &output_mint, amount)
+            .await
+            .map_err(|e| MonitorError::InvalidResponse(e.to_string()))?;
+        out_amount(&quote)
+            .ok_or_else(|| MonitorError::InvalidResponse("quote: no outAmount".to_string()))
+    }
+
+    /// Our SOL balance, in lamports
+    async fn wallet_balance(&self) -> Result<u64, ExecError> {
+        // The RPC client blocks; keep it off the async workers
+        let executor = Arc::clone(&self.executor);
+        let owner = self.keypair.pubkey();
+        Ok(
+            tokio::task::spawn_blocking(move || executor.balance(&owner))
+                .await
+                .map_err(|e| ExecError::Api(format!("balance task failed: {}", e)))??,
+        )
+    }
+
+    /// Shrink a buy's `amount` so the fee reserve stays in the wallet, or
+    /// refuse it if the reserve is already short. Sells are never held back:
+    /// they are how the wallet gets its SOL back.
+    async fn keep_fee_reserve(
+        &self,
+        signal: &UniversalSwapSignal,
+        amount: u64,
+    ) -> Result<u64, ExecError> {
+        if !matches!(self.direction(signal), TradeDirection::Buy { .. }) {
+            return Ok(amount);
+        }
+
+        let balance = self.wallet_balance().await?;
+        if BaseCurrencies::is_sol(&signal.input_mint) {
+            self.fee_reserve
+                .size_sol_buy(balance, amount)
+                .map_err(ExecError::Rejected)
+        } else {
+            self.fee_reserve
+                .check_fee_balance(balance)
+                .map(|()| amount)
+                .map_err(ExecError::Rejected)
+        }
+    }
+
+    /// Refuse a buy whose output falls short of a fresh quote for the
+    /// trader's input by more than the slippage gate allows
+    async fn check_slippage(&self, signal: &UniversalSwapSignal) -> Result<(), ExecError> {
+        if !self.slippage.is_enabled()
+            || !matches!(self.direction(signal), TradeDirection::Buy { .. })
+        {
+            return Ok(());
+        }
+
+        let slippage_bps = quoted_slippage_bps(
+            |input, output, amount| self.quoted_out_amount(input, output, amount),
+            signal,
+        )
+        .await?;
+        self.slippage
+            .check(slippage_bps)
+            .map_err(ExecError::Rejected)
+    }
+
+    /// Refuse a buy whose quoted round trip loses more than the guard allows
+    async fn check_liquidity(
+        &self,
+        signal: &UniversalSwapSignal,
+        amount: u64,
+    ) -> Result<(), ExecError> {
+        let TradeDirection::Buy { token, payment } = self.direction(signal) else {
+            return Ok(());
+        };
+
+        let round_trip = self
+            .liquidity
+            .check(
+                |input, output, amount| self.quoted_out_amount(input, output, amount),
+                payment,
+                token,
+                amount,
+            )
+            .await?;
+        if !round_trip.passed {
+            return Err(ExecError::Rejected(format!(
+                "round trip into {} loses {} bps",
+                token, round_trip.loss_bps
+            )));
+        }
+        Ok(())
+    }
+
+    /// Unsigned swap transaction for `quote`, from `/swap`
+    async fn swap_transaction(&self, quote: Value) -> Result<VersionedTransaction, ExecError> {
+        let body = json!({
+            "quoteResponse": quote,
+            "userPublicKey": self.keypair.pubkey().to_string(),
+            "wrapAndUnwrapSol": true,
+            "dynamicComputeUnitLimit": true,
+        });
+
+        let response: Value = self
+            .http
+            .post(format!("{}/swap", self.api_url))
+            .json(&body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ExecError::Api(format!("swap: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| ExecError::Api(format!("swap: {}", e)))?;
+
+        let encoded = response
+            .get("swapTransaction")
+            .and_then(Value::as_str)
+            .ok_or_else(|| ExecError::Api("swap: no swapTransaction in response".to_string()))?;
+
+        decode_swap_transaction(encoded)
+    }
+
+    /// Sign Jupiter's transaction with our keypair
+    fn sign(&self, unsigned: VersionedTransaction) -> Result<VersionedTransaction, ExecError> {
+        VersionedTransaction::try_new(unsigned.message, &[self.keypair.as_ref()])
+            .map_err(|e| ExecError::Signing(e.to_string()))
+    }
+}
+
+impl TradeExecutor for JupiterExecutor {
+    fn name(&self) -> &str {
+        "jupiter"
+    }
+
+    fn execute<'a>(
+        &'a self,
+        signal: &'a UniversalSwapSignal,
+    ) -> BoxFuture<'a, Result<Signature, ExecError>> {
+        Box::pin(async move {
+            let amount = capped_input_amount(signal, self.max_input_amount)?;
+            let amount = self.keep_fee_reserve(signal, amount).await?;
+            self.check_slippage(signal).await?;
+            self.check_liquidity(signal, amount).await?;
+
+            info!(
+                "🪐 Swapping {} {} → {} via Jupiter",
+                amount, signal.input_mint, signal.output_mint
+            );
+
+            let quote = self
+                .quote(&signal.input_mint, &signal.output_mint, amount)
+                .await?;
+            debug!(
+                "Jupiter quote: out {}",
+                out_amount(&quote).map_or("?".to_string(), |out| out.to_string())
+            );
+
+            let transaction = self.sign(self.swap_transaction(quote).await?)?;
+
+            // The RPC client blocks; keep it off the async workers
+            let executor = Arc::clone(&self.executor);
+            let signature = tokio::task::spawn_blocking(move || executor.send(&transaction))
+                .await
+                .map_err(|e| ExecError::Api(format!("send task failed: {}", e)))??;
+
+            Ok(signature)
+        })
+    }
+
+    fn sol_balance(&self) -> BoxFuture<'_, Result<Option<u64>, ExecError>> {
+        Box::pin(async move { self.wallet_balance().await.map(Some) })
+    }
+}
+
+/// A quote's `outAmount`, which Jupiter sends as a string
+fn out_amount(quote: &Value) -> Option<u64> {
+    quote.get("outAmount")?.as_str()?.parse().ok()
+}
+
+/// Decode the base64, bincode-serialized transaction in a `/swap` response
+fn decode_swap_transaction(encoded: &str) -> Result<VersionedTransaction, ExecError> {
+    let bytes = BASE64
+        .decode(encoded)
+        .map_err(|e| ExecError::InvalidTransaction(e.to_string()))?;
+    bincode::deserialize(&bytes).map_err(|e| ExecError::InvalidTransaction(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::rpc_client::RpcClient;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::message::{Message, VersionedMessage};
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::system_instruction;
+
+    fn executor(keypair: Keypair) -> JupiterExecutor {
+        let config = ExecutorConfig {
+            jupiter_api_url: "https://jup.example.com/v6/".to_string(),
+            slippage_bps: 50,
+            ..ExecutorConfig::default()
+        };
+        let rpc = Arc::new(RpcClient::new("http://localhost:8899".to_string()));
+        let executor = Arc::new(Executor::new(rpc, config.clone()));
+        JupiterExecutor::new(&config, keypair, executor)
+    }
+
+    #[test]
+    fn test_quote_url() {
+        let jupiter = executor(Keypair::new());
+        let signal = UniversalSwapSignal::test_swap().with_amounts(5_000, 1_000);
+
+        assert_eq!(
+            jupiter.quote_url(&signal.input_mint, &signal.output_mint, 1_000),
+            format!(
+                "https://jup.example.com/v6/quote?inputMint={}&outputMint={}&amount=1000&slippageBps=50",
+                signal.input_mint, signal.output_mint
+            )
+        );
+    }
+
+    #[test]
+    fn test_direction_uses_configured_stablecoins() {
+        let stable = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+        let signal = UniversalSwapSignal::test_swap().with_mints(stable, token);
+
+        assert!(matches!(
+            executor(Keypair::new()).direction(&signal),
+            TradeDirection::Swap { .. }
+        ));
+        let jupiter =
+            executor(Keypair::new()).with_stablecoins(StablecoinRegistry::with_extra(&[stable]));
+        assert_eq!(
+            jupiter.direction(&signal),
+            TradeDirection::Buy {
+                token,
+                payment: stable
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_and_sign_swap_transaction() {
+        let keypair = Keypair::new();
+        let payer = keypair.pubkey();
+
+        // What Jupiter returns: our key as fee payer, signature left empty
+        let message = Message::new_with_blockhash(
+            &[system_instruction::transfer(
+                &payer,
+                &Pubkey::new_unique(),
+                1,
+            )],
+            Some(&payer),
+            &Hash::new_unique(),
+        );
+        let unsigned = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::Legacy(message),
+        };
+        let encoded = BASE64.encode(bincode::serialize(&unsigned).unwrap());
+
+        let decoded = decode_swap_transaction(&encoded).unwrap();
+        assert_eq!(decoded.message, unsigned.message);
+
+        let signed = executor(keypair).sign(decoded).unwrap();
+        assert_ne!(signed.signatures[0], Signature::default());
+        assert!(signed.verify_with_results().iter().all(|ok| *ok));
+
+        assert!(matches!(
+            decode_swap_transaction("not base64!"),
+            Err(ExecError::InvalidTransaction(_))
+        ));
+    }
+}
@@ -0,0 +1,159 @@
+//! Trade execution
+//!
+//! Every outgoing transaction goes through `simulateTransaction` first so we
+//! don't pay fees for a swap that is going to revert anyway.
+
+pub mod dry_run;
+pub mod jupiter;
+pub mod trade;
+
+pub use dry_run::DryRunExecutor;
+pub use jupiter::JupiterExecutor;
+pub use trade::{ExecError, TradeExecutor, capped_input_amount, executor_from_config};
+
+use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::types::ExecutorConfig;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_client::rpc_response::RpcSimulateTransactionResult;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::transaction::VersionedTransaction;
+use std::sync::Arc;
+use tracing::{debug, error, info, warn};
+
+/// Outcome of a transaction simulation
+#[derive(Debug, Clone, Default)]
+pub struct SimResult {
+    /// Error the transaction would fail with, if any
+    pub err: Option<String>,
+
+    /// Program logs emitted during simulation
+    pub logs: Vec<String>,
+
+    /// Compute units consumed by the simulation
+    pub units_consumed: Option<u64>,
+}
+
+impl SimResult {
+    /// Build a result from the raw RPC simulation response
+    pub fn from_rpc(result: RpcSimulateTransactionResult) -> Self {
+        Self {
+            err: result.err.map(|e| e.to_string()),
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+        }
+    }
+
+    /// Did the simulation succeed?
+    pub fn is_ok(&self) -> bool {
+        self.err.is_none()
+    }
+}
+
+/// Simulates and submits transactions via RPC
+pub struct Executor {
+    rpc_client: Arc<RpcClient>,
+    config: ExecutorConfig,
+}
+
+impl Executor {
+    /// Create a new executor
+    pub fn new(rpc_client: Arc<RpcClient>, config: ExecutorConfig) -> Self {
+        Self { rpc_client, config }
+    }
+
+    /// Simulate a transaction without broadcasting it
+    pub fn simulate(&self, tx: &VersionedTransaction) -> MonitorResult<SimResult> {
+        let response = self.rpc_client.simulate_transaction_with_config(
+            tx,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: false,
+                commitment: Some(self.rpc_client.commitment()),
+                ..RpcSimulateTransactionConfig::default()
+            },
+        )?;
+
+        let sim = SimResult::from_rpc(response.value);
+
+        debug!(
+            "Simulation finished: err={:?}, units={:?}, {} log lines",
+            sim.err,
+            sim.units_consumed,
+            sim.logs.len()
+        );
+
+        Ok(sim)
+    }
+
+    /// Simulate (unless disabled in config) and then broadcast a transaction
+    pub fn send(&self, tx: &VersionedTransaction) -> MonitorResult<Signature> {
+        if self.config.simulate_before_send {
+            let sim = self.simulate(tx)?;
+
+            if let Some(err) = &sim.err {
+                error!("❌ Simulation failed, not broadcasting: {}", err);
+                for line in &sim.logs {
+                    warn!("   {}", line);
+                }
+                return Err(MonitorError::SimulationFailed(err.clone()));
+            }
+
+            info!(
+                "✅ Simulation passed ({} compute units)",
+                sim.units_consumed.unwrap_or(0)
+            );
+        } else {
+            debug!("Skipping simulation (disabled in config)");
+        }
+
+        let signature = self.rpc_client.send_transaction(tx)?;
+        info!("🚀 Transaction sent: {}", signature);
+
+        Ok(signature)
+    }
+
+    /// SOL balance of `pubkey`, in lamports
+    pub fn balance(&self, pubkey: &Pubkey) -> MonitorResult<u64> {
+        Ok(self.rpc_client.get_balance(pubkey)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::InstructionError;
+    use solana_sdk::transaction::TransactionError;
+
+    #[test]
+    fn test_sim_result_from_rpc() {
+        let failed = SimResult::from_rpc(RpcSimulateTransactionResult {
+            err: Some(TransactionError::InstructionError(
+                2,
+                InstructionError::Custom(6001),
+            )),
+            logs: Some(vec!["Program log: slippage exceeded".to_string()]),
+            accounts: None,
+            units_consumed: Some(42_000),
+            return_data: None,
+            inner_instructions: None,
+        });
+
+        assert!(!failed.is_ok());
+        assert_eq!(failed.units_consumed, Some(42_000));
+        assert_eq!(failed.logs.len(), 1);
+
+        let passed = SimResult::from_rpc(RpcSimulateTransactionResult {
+            err: None,
+            logs: None,
+            accounts: None,
+            units_consumed: None,
+            return_data: None,
+            inner_instructions: None,
+        });
+
+        assert!(passed.is_ok());
+        assert!(passed.logs.is_empty());
+    }
+}
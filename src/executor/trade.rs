@@ -0,0 +1,132 @@
+//! Copying detected swaps
+//!
+//! A [`TradeExecutor`] turns a detected swap into our own trade. The pipeline
+//! builds one from config with [`executor_from_config`]: a
+//! [`DryRunExecutor`] by default, or a [`JupiterExecutor`] once a keypair is
+//! configured.
+
+use crate::decision::direction::StablecoinRegistry;
+use crate::decision::liquidity::LiquidityGuard;
+use crate::decision::slippage::SlippageGate;
+use crate::detection::types::UniversalSwapSignal;
+use crate::executor::Executor;
+use crate::executor::dry_run::DryRunExecutor;
+use crate::executor::jupiter::JupiterExecutor;
+use crate::monitor::error::MonitorError;
+use crate::types::{ExecutionMode, MonitorConfig};
+use futures::future::BoxFuture;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::signature::Signature;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::info;
+
+/// Why a copy couldn't be executed
+#[derive(Debug, Error)]
+pub enum ExecError {
+    #[error("Nothing to swap: input amount is 0")]
+    ZeroAmount,
+
+    #[error("Keypair error: {0}")]
+    Keypair(String),
+
+    #[error("Jupiter API error: {0}")]
+    Api(String),
+
+    #[error("Invalid swap transaction: {0}")]
+    InvalidTransaction(String),
+
+    #[error("Signing failed: {0}")]
+    Signing(String),
+
+    #[error("Copy rejected: {0}")]
+    Rejected(String),
+
+    #[error(transparent)]
+    Rpc(#[from] MonitorError),
+}
+
+/// Executes our copy of a detected swap
+pub trait TradeExecutor: Send + Sync {
+    /// Short name used in logs
+    fn name(&self) -> &str;
+
+    /// Swap the signal's input mint for its output mint, returning our
+    /// transaction's signature
+    fn execute<'a>(
+        &'a self,
+        signal: &'a UniversalSwapSignal,
+    ) -> BoxFuture<'a, Result<Signature, ExecError>>;
+
+    /// SOL in the wallet copies spend from, in lamports, if there is one
+    fn sol_balance(&self) -> BoxFuture<'_, Result<Option<u64>, ExecError>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+/// How much of the input token a copy spends: the signal's amount, capped
+/// at `max_input_amount`
+pub fn capped_input_amount(
+    signal: &UniversalSwapSignal,
+    max_input_amount: Option<u64>,
+) -> Result<u64, ExecError> {
+    let amount = match max_input_amount {
+        Some(max) if signal.input_amount > max => {
+            info!(
+                "📉 Capping copy at {} (trader spent {})",
+                max, signal.input_amount
+            );
+            max
+        }
+        _ => signal.input_amount,
+    };
+
+    if amount == 0 {
+        return Err(ExecError::ZeroAmount);
+    }
+    Ok(amount)
+}
+
+/// Build the executor selected by `config.executor.mode`
+pub fn executor_from_config(
+    config: &MonitorConfig,
+    rpc_client: Arc<RpcClient>,
+) -> Result<Box<dyn TradeExecutor>, ExecError> {
+    let executor_config = &config.executor;
+    match executor_config.mode {
+        ExecutionMode::DryRun => Ok(Box::new(DryRunExecutor::new(
+            executor_config.max_input_amount,
+        ))),
+        ExecutionMode::Jupiter => {
+            let executor = Arc::new(Executor::new(rpc_client, executor_config.clone()));
+            let jupiter = JupiterExecutor::from_config(executor_config, executor)?
+                .with_liquidity_guard(LiquidityGuard::from_config(config))
+                .with_slippage_gate(SlippageGate::from_config(config))
+                .with_stablecoins(StablecoinRegistry::from_config(config));
+            Ok(Box::new(jupiter))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signal(input_amount: u64) -> UniversalSwapSignal {
+        UniversalSwapSignal::test_swap().with_amounts(input_amount, 1_000)
+    }
+
+    #[test]
+    fn test_capped_input_amount() {
+        assert_eq!(capped_input_amount(&signal(500), None).unwrap(), 500);
+        assert_eq!(capped_input_amount(&signal(500), Some(1_000)).unwrap(), 500);
+        assert_eq!(
+            capped_input_amount(&signal(5_000), Some(1_000)).unwrap(),
+            1_000
+        );
+        assert!(matches!(
+            capped_input_amount(&signal(0), None),
+            Err(ExecError::ZeroAmount)
+        ));
+    }
+}
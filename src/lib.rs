@@ -1,13 +1,40 @@
+pub mod api;
+pub mod bus;
 pub mod config;
 pub mod decision;
 pub mod detection;
+pub mod executor;
 pub mod monitor;
+pub mod notifier;
 pub mod portfolio;
 pub mod types;
 
-pub use config::{create_default_config, load_config};
+pub use bus::{PortfolioDelta, SignalBus, SignalEvent};
+pub use config::{
+    ConfigWatcher, SharedConfig, create_default_config, load_config, parse_log_level, watch_config,
+};
 pub use decision::*;
-pub use detection::{UniversalParser, types::UniversalSwapSignal};
-pub use monitor::{MonitorError, MonitorResult, TransactionListener, TransactionParser};
+pub use detection::{
+    UniversalParser,
+    dead_letter::{DeadLetter, DeadLetterSink},
+    decimals::{DecimalsCache, MintDecimalsSource},
+    types::{ParseOutcome, SkipReason, UniversalSwapSignal},
+};
+pub use executor::{
+    DryRunExecutor, ExecError, Executor, JupiterExecutor, SimResult, TradeExecutor,
+    executor_from_config,
+};
+pub use monitor::{
+    FinalityChecker, MonitorError, MonitorResult, TransactionFetcher, TransactionListener,
+    TransactionParser,
+};
+pub use notifier::{
+    DiscordNotifier, JsonLogNotifier, Notifier, NotifierSet, TelegramNotifier, TradeEvent,
+    TradeSide,
+};
 pub use portfolio::*;
-pub use types::{DexType, MonitorConfig, TradeSignal};
+pub use types::{
+    ApiConfig, CostBasisMethod, DexPolicy, DexType, ExecutionMode, ExecutorConfig, ListenerMode,
+    MonitorConfig, NotificationsConfig, RiskConfig, SellPolicy, StorageBackend, TradeSignal,
+    WSOL_MINT, wsol_pubkey,
+};
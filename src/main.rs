@@ -1,26 +1,65 @@
+use arc_swap::ArcSwap;
+use copy_tradin::api::{self, ApiState, DisplayCurrency, RecentSignals};
+use copy_tradin::detection::types::SwapType;
 use copy_tradin::{
-    PortfolioTracker, TradeDirection, TransactionListener, UniversalParser, load_config,
-}; // ADD TradeDirection
+    BaseCurrencies, ClosedPosition, DeadLetterSink, DecimalsCache, DecisionEngine, DecisionSkip,
+    Discrepancy, DuplicateBuyFilter, EntryKind, FinalityChecker, JupiterPriceProvider,
+    MarketValuation, MinValueFilter, MonitorConfig, NotifierSet, ParseOutcome, PortfolioDelta,
+    PortfolioTracker, Position, SignalBus, SignalEvent, Storage, StorageBackend, TradeDirection,
+    TradeEvent, TradeExecutor, TraderGate, TransactionListener, UniversalParser,
+    UniversalSwapSignal, Verdict, executor_from_config, fetch_token_balances, load_config,
+    open_storage, parse_log_level, reconcile, watch_config,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
-use tokio::sync::mpsc;
-use tracing::{error, info}; // ADD for thread-safe portfolio
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Duration;
+use tracing::{debug, error, info, warn};
+
+/// Signals kept in memory for `/signals`
+const RECENT_SIGNALS_CAPACITY: usize = 100;
+
+/// How long each task gets to wind down after Ctrl+C
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        .init();
+    let args: Vec<String> = env::args().collect();
 
-    info!("Starting Solana Copy Trading Bot - Universal DEX Detection");
+    // Operational subcommands - these never start the listener
+    if args.get(1).map(String::as_str) == Some("close") {
+        return close_position_command(&args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("status") {
+        return status_command(&args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("reconcile") {
+        return reconcile_command(&args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("export") {
+        return export_command(&args[2..]);
+    }
 
-    let config_path = env::args()
-        .nth(1)
+    let config_path = args
+        .get(1)
+        .cloned()
         .unwrap_or_else(|| "config.toml".to_string());
 
+    // Logging is configured from the config file, so it starts after loading
     let config = match load_config(&config_path) {
-        Ok(cfg) => cfg,
+        Ok(cfg) => {
+            init_logging(&cfg.log_level);
+            cfg
+        }
         Err(e) => {
+            init_logging("info");
             error!("Failed to load config: {}", e);
             info!("Creating default config file...");
             copy_tradin::create_default_config(&config_path)?;
@@ -31,87 +70,394 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             return Ok(());
         }
     };
-    // CREATE PORTFOLIO TRACKER
-    const PORTFOLIO_FILE: &str = "portfolio.json";
 
-    let portfolio = Arc::new(Mutex::new(match PortfolioTracker::load(PORTFOLIO_FILE) {
-        Ok(portfolio) => {
-            info!("📂 Loaded existing portfolio:");
-            let stats = portfolio.get_stats();
-            info!("   Active positions: {}", stats.active_positions);
-            info!("   Closed positions: {}", stats.closed_positions);
-            info!("   Total P&L: {}", stats.total_realized_pnl);
-            portfolio
-        }
-        Err(e) => {
-            info!("🆕 Starting with fresh portfolio: {}", e);
-            PortfolioTracker::new()
-        }
-    }));
+    info!("Starting Solana Copy Trading Bot - Universal DEX Detection");
+    info!("Loaded configuration from {}", config_path);
+
+    // CREATE PORTFOLIO TRACKERS - one per trader
+    let target_wallets = config.monitored_wallets();
+    let portfolios = load_portfolios(&config, &target_wallets)?;
+    // The first wallet's portfolio backs snapshots and the HTTP API
+    let primary = portfolios[&config.target_wallet].clone();
 
-    info!("Monitoring wallet: {}", config.target_wallet);
+    for wallet in &target_wallets {
+        info!("Monitoring wallet: {}", wallet);
+    }
     info!("🌟 Using UNIVERSAL detection - works with ALL DEXs!");
+    if let Some(currency) = config.display_currency {
+        info!("💱 Showing portfolio totals in {}", currency);
+    }
 
     let (tx_sender, mut tx_receiver) = mpsc::unbounded_channel();
-    let parser = UniversalParser::new(config.target_wallet);
-    let mut listener = TransactionListener::new(config.clone(), tx_sender);
+    // One decimals cache for everything that needs to format or convert amounts
+    let decimals_cache = Arc::new(DecimalsCache::new(Arc::new(RpcClient::new(
+        config.rpc_endpoints[0].clone(),
+    ))));
+    let prices = Arc::new(JupiterPriceProvider::new(Arc::clone(&decimals_cache)));
+    let mut parser = UniversalParser::with_config(&config).with_decimals_cache(decimals_cache);
+    if config.log_unknown_patterns {
+        info!("🔍 Logging unclassifiable balance patterns");
+        if let Some(path) = &config.dead_letter_path {
+            parser = parser.with_unknown_pattern_sink(DeadLetterSink::new(
+                path.clone(),
+                config.dead_letter_raw_json,
+            ));
+        }
+    }
 
-    let portfolio = Arc::new(Mutex::new(PortfolioTracker::new()));
-    let portfolio_clone = Arc::clone(&portfolio);
+    // Endpoint changes in the config file take effect on the next reconnect
+    let shared_config = Arc::new(ArcSwap::from_pointee(config.clone()));
+    let _config_watcher = match watch_config(&config_path, Arc::clone(&shared_config)) {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            warn!("Config hot-reload disabled: {}", e);
+            None
+        }
+    };
+    let (shutdown, shutdown_signal) = watch::channel(false);
+    let mut listener = TransactionListener::with_shared_config(shared_config, tx_sender)
+        .with_shutdown(shutdown_signal);
+
+    let parser_portfolios = portfolios.clone();
+    let parser_primary = primary.clone();
+
+    // Optionally re-check buys at "finalized" before persisting them
+    let finality_checker = if config.verify_finalized {
+        info!("🔒 Finality verification enabled - positions are saved once finalized");
+        Some(Arc::new(FinalityChecker::new(&config)))
+    } else {
+        None
+    };
 
-    let target_wallet = config.target_wallet;
-    let listener_handle = tokio::spawn(async move {
-        if let Err(e) = listener.start(target_wallet).await {
+    let mut listener_handle = tokio::spawn(async move {
+        if let Err(e) = listener.start(&target_wallets).await {
             error!("Listener error: {}", e);
         }
     });
 
-    let parser_handle = tokio::spawn(async move {
+    let trader_gate = TraderGate::from_config(&config);
+    let engine = DecisionEngine::from_config(&config);
+    if engine.token_filter.is_enabled() {
+        info!(
+            "🚫 Token filter: {} allowed, {} denied",
+            config.token_allowlist.len(),
+            config.token_denylist.len()
+        );
+    }
+    if let Some(max) = engine.max_open_positions {
+        info!("📦 Opening at most {} positions at a time", max);
+    }
+    let min_value = MinValueFilter::from_config(&config);
+    if let Some(min) = min_value.min_input_value {
+        info!("🪙 Ignoring swaps worth less than ${}", min);
+    }
+    let parser_prices = Arc::clone(&prices);
+    if trader_gate.is_enabled() {
+        info!(
+            "🏆 Copying only while the trader is profitable: {:?}",
+            trader_gate
+        );
+    }
+    // Recent signals, kept for the HTTP API
+    let recent_signals = Arc::new(RecentSignals::new(RECENT_SIGNALS_CAPACITY));
+
+    // Every detected swap goes out on the bus; the API's buffer is fed from
+    // it like any other consumer
+    let signal_bus = SignalBus::default();
+    let mut bus_events = signal_bus.subscribe();
+    let bus_signals = Arc::clone(&recent_signals);
+    tokio::spawn(async move {
+        loop {
+            match bus_events.recv().await {
+                Ok(event) => bus_signals.push(event.signal),
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("⚠️  Recent signals missed {} events", missed)
+                }
+                Err(RecvError::Closed) => break,
+            }
+        }
+    });
+
+    // The SQLite backend also keeps every detected signal
+    if config.storage_backend == StorageBackend::Sqlite {
+        let signal_store = open_storage(&config, &config.portfolio_path)?;
+        let mut bus_events = signal_bus.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match bus_events.recv().await {
+                    Ok(event) => {
+                        if let Err(e) = signal_store.record_signal(&event.signal) {
+                            error!(
+                                "❌ Failed to record signal {}: {}",
+                                event.signal.signature, e
+                            );
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("⚠️  Signal store missed {} events", missed)
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    let executor_config = config.executor.clone();
+    let trade_executor: Arc<dyn TradeExecutor> = match executor_from_config(
+        &config,
+        Arc::new(RpcClient::new(config.rpc_endpoints[0].clone())),
+    ) {
+        Ok(executor) => Arc::from(executor),
+        Err(e) => {
+            error!("Failed to set up trade execution: {}", e);
+            return Err(e.into());
+        }
+    };
+    info!(
+        "⚙️  Executing copies with the {} executor",
+        trade_executor.name()
+    );
+    if engine.sizer.copy_ratio != 1.0 {
+        info!(
+            "📏 Copying at {}x the trader's size",
+            engine.sizer.copy_ratio
+        );
+    }
+    if !config.extra_stablecoins.is_empty() {
+        info!("💵 Extra stablecoins: {:?}", config.extra_stablecoins);
+    }
+    if executor_config.copy_delay_ms > 0 || executor_config.copy_delay_jitter_ms > 0 {
+        info!(
+            "⏳ Copy delay: {}ms + up to {}ms jitter before each buy",
+            executor_config.copy_delay_ms, executor_config.copy_delay_jitter_ms
+        );
+    }
+
+    let dead_letters = config
+        .dead_letter_path
+        .as_ref()
+        .map(|path| DeadLetterSink::new(path.clone(), config.dead_letter_raw_json));
+    if let Some(sink) = &dead_letters {
+        info!("📮 Recording parse failures to {}", sink.path());
+    }
+
+    let mut duplicate_filter = DuplicateBuyFilter::from_config(&config);
+    if duplicate_filter.is_enabled() {
+        info!(
+            "🔁 Skipping repeat buys within {}s",
+            duplicate_filter.window_secs.unwrap_or_default()
+        );
+    }
+
+    let notifiers = Arc::new(NotifierSet::from_config(&config.notifications));
+    // Fire-and-forget so a slow sink never delays the next signal
+    let notify = move |event: TradeEvent| {
+        if notifiers.is_empty() {
+            return;
+        }
+        let notifiers = Arc::clone(&notifiers);
+        tokio::spawn(async move {
+            notifiers.notify_all(&event).await;
+        });
+    };
+
+    // Risk exits are sold through the same executor as copied trades
+    let risk_executor = Arc::clone(&trade_executor);
+
+    let mut parser_handle = tokio::spawn(async move {
         info!("Parser ready, waiting for transactions...");
 
         while let Some(transaction) = tx_receiver.recv().await {
-            match parser.parse(transaction) {
-                Ok(Some(swap_signal)) => {
+            // Parsing consumes the transaction, so capture it for the dead-letter log first
+            let captured = dead_letters.as_ref().map(|sink| sink.capture(&transaction));
+
+            match parser.parse_outcome(transaction) {
+                Ok(ParseOutcome::Signal(swap_signal)) => {
                     info!("═══════════════════════════════════════════════");
                     info!("🎯 SWAP DETECTED (Universal Detection)!");
                     info!("═══════════════════════════════════════════════");
                     info!("Signature: {}", swap_signal.signature);
+                    info!("Slot: {}", swap_signal.slot);
                     info!("Type: {}", swap_signal.swap_type);
-                    info!("Input Token: {}", swap_signal.input_mint);
-                    info!("Input Amount: {}", swap_signal.input_amount);
-                    info!("Output Token: {}", swap_signal.output_mint);
-                    info!("Output Amount: {}", swap_signal.output_amount);
+                    info!("Input:  {}", swap_signal.format_input());
+                    info!("Output: {}", swap_signal.format_output());
                     if let Some(ref dex) = swap_signal.likely_dex {
                         info!("Likely DEX: {} (detected automatically)", dex);
                     }
                     info!("Timestamp: {}", swap_signal.timestamp);
+                    if swap_signal.is_new_mint {
+                        info!("🆕 New mint - fresh launch");
+                    }
+
+                    // Positions are mirrored per trader
+                    let trader_portfolio = parser_portfolios
+                        .get(&swap_signal.trader)
+                        .unwrap_or(&parser_primary);
 
                     // ✅ ADD DIRECTION ANALYSIS HERE
                     info!("");
-                    match swap_signal.direction() {
+                    let direction = engine.direction(&swap_signal);
+                    match &direction {
                         TradeDirection::Buy { token, payment } => {
                             info!("🎯 DIRECTION: BUY (Entry Signal)");
                             info!("   Token:   {}", token);
                             info!("   Payment: {}", payment);
-                            info!("✅ COPYABLE SIGNAL");
+                        }
+                        TradeDirection::Sell { token, receives } => {
+                            info!("📉 DIRECTION: SELL (Exit Signal)");
+                            info!("   Selling:  {} (token being sold)", token);
+                            info!("   For:      {} (receiving)", receives);
+                        }
+                        TradeDirection::Swap {
+                            from_token,
+                            to_token,
+                        } => {
+                            info!("🔄 DIRECTION: TOKEN SWAP");
+                            info!("   From: {}", from_token);
+                            info!("   To:   {}", to_token);
+                        }
+                    }
+
+                    // Dust is valued before the lock; prices come over HTTP
+                    let mut dust = None;
+                    if min_value.is_enabled() {
+                        match MinValueFilter::input_value(
+                            &swap_signal,
+                            &engine.stablecoins,
+                            parser_prices.as_ref(),
+                        )
+                        .await
+                        {
+                            Ok(value) => {
+                                if let Err(reason) = min_value.check(value) {
+                                    debug!(
+                                        "🪙 Dust swap {}: input worth ${}",
+                                        swap_signal.signature, value
+                                    );
+                                    dust = Some(reason);
+                                }
+                            }
+                            Err(e) => warn!(
+                                "⚠️  Couldn't value input {}, not applying min_input_value: {}",
+                                swap_signal.input_mint, e
+                            ),
+                        }
+                    }
+
+                    // SOL-funded buys must leave the fee reserve in our wallet
+                    let sol_balance = if dust.is_none()
+                        && BaseCurrencies::is_sol(&swap_signal.input_mint)
+                    {
+                        trade_executor.sol_balance().await.unwrap_or_else(|e| {
+                            warn!("⚠️  Couldn't read our SOL balance, not applying the fee reserve: {}", e);
+                            None
+                        })
+                    } else {
+                        None
+                    };
+
+                    let verdict = match dust {
+                        Some(reason) => Verdict::Skip {
+                            reason: DecisionSkip::Dust(reason),
+                        },
+                        None => engine.decide_with_balance(
+                            &swap_signal,
+                            &trader_portfolio.tracker.lock().unwrap(),
+                            sol_balance,
+                        ),
+                    };
+                    let delta = match (verdict, direction.clone()) {
+                        (Verdict::Skip { reason }, _) => {
+                            info!("⏭️  Not copying - {}", reason);
+                            None
+                        }
+                        (Verdict::Copy { size }, TradeDirection::Buy { token, payment }) => 'buy: {
+                            if let Err(reason) = duplicate_filter.check(&swap_signal) {
+                                warn!(
+                                    "🔁 Possible duplicate buy {} - {}, skipping",
+                                    swap_signal.signature, reason
+                                );
+                                break 'buy None;
+                            }
 
                             // TRACK THE POSITION
-                            let mut portfolio = portfolio_clone.lock().unwrap();
+                            let mut portfolio = trader_portfolio.tracker.lock().unwrap();
 
-                            if portfolio.has_position(&token) {
-                                info!("📊 Already have position in this token - tracking as add");
-                            } else {
-                                info!("✅ NEW POSITION - Will track this");
+                            // Gate on the trader's mirrored performance; the buy
+                            // is still tracked so the stats stay accurate
+                            let copyable = match trader_gate.check(&portfolio.get_stats()) {
+                                Ok(()) => {
+                                    info!("✅ COPYABLE SIGNAL");
+                                    true
+                                }
+                                Err(reason) => {
+                                    info!("⏭️  Not copying - trader {}", reason);
+                                    false
+                                }
+                            };
+                            let entry_kind = portfolio.classify_entry(&token);
+                            match entry_kind {
+                                EntryKind::Add => info!(
+                                    "📊 Already have position in this token - tracking as add"
+                                ),
+                                EntryKind::New => info!("✅ NEW POSITION - Will track this"),
                             }
+                            let amount = size.output_amount;
+                            let cost = size.input_amount;
+
                             // Simulate opening position (in reality, you'd execute the trade first)
                             portfolio.open_position(
                                 token,
-                                swap_signal.output_amount,
+                                amount,
                                 payment,
-                                swap_signal.input_amount,
+                                cost,
                                 swap_signal.signature.to_string(), // ✅ .to_string()
                             );
-                            portfolio.save_safe(PORTFOLIO_FILE);
+                            if copyable {
+                                // Land after the trader rather than alongside them
+                                spawn_copy(
+                                    &trade_executor,
+                                    &swap_signal,
+                                    cost,
+                                    executor_config.copy_delay(),
+                                );
+                                // Only what we really bought is sold again later
+                                portfolio.record_copy(&token, amount);
+                            }
+
+                            match &finality_checker {
+                                Some(checker) => {
+                                    // Provisional entry - only persist once finalized
+                                    let checker = Arc::clone(checker);
+                                    let portfolio = Arc::clone(&trader_portfolio.tracker);
+                                    let signature = swap_signal.signature;
+                                    let storage = Arc::clone(&trader_portfolio.storage);
+
+                                    tokio::spawn(async move {
+                                        let finalized =
+                                            match checker.wait_for_finalization(&signature).await {
+                                                Ok(finalized) => finalized,
+                                                Err(e) => {
+                                                    error!("Finality check failed: {}", e);
+                                                    false
+                                                }
+                                            };
+
+                                        let mut portfolio = portfolio.lock().unwrap();
+                                        if finalized {
+                                            info!("🔒 Buy finalized: {}", signature);
+                                        } else {
+                                            warn!(
+                                                "⚠️  Buy {} did not finalize - reverting position",
+                                                signature
+                                            );
+                                            portfolio.revert_open(&token, amount, cost);
+                                        }
+                                        storage.save_safe(&portfolio);
+                                    });
+                                }
+                                None => trader_portfolio.storage.save_safe(&portfolio),
+                            }
 
                             // Show portfolio stats
                             let stats = portfolio.get_stats();
@@ -119,108 +465,569 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 "💼 Portfolio: {} active positions, Total invested: {}",
                                 stats.active_positions, stats.total_invested
                             );
+                            notify(
+                                TradeEvent::buy(swap_signal.as_ref().clone())
+                                    .with_entry_kind(entry_kind)
+                                    .with_portfolio(stats),
+                            );
+
+                            portfolio.get_position(&token).cloned().map(|position| {
+                                PortfolioDelta::Opened {
+                                    entry: entry_kind,
+                                    amount,
+                                    cost,
+                                    position,
+                                }
+                            })
                         }
-                        TradeDirection::Sell { token, receives } => {
-                            info!("📉 DIRECTION: SELL (Exit Signal)");
-                            info!("   Selling:  {} (token being sold)", token);
-                            info!("   For:      {} (receiving)", receives);
-                            info!("⏭️  SKIP - Exit trade");
-                            // CHECK IF WE HAVE THIS POSITION
-                            let mut portfolio = portfolio_clone.lock().unwrap();
-
-                            if portfolio.has_position(&token) {
-                                info!("✅ WE OWN THIS! Copying the sell...");
-
-                                // Close the position
-                                match portfolio.close_position(
-                                    &token,
-                                    swap_signal.input_amount,
-                                    swap_signal.output_amount,
-                                    swap_signal.signature.to_string(),
-                                ) {
-                                    Ok(closed) => {
-                                        info!("🏁 Position closed:");
-                                        info!(
-                                            "   P&L: {} ({:.2}%)",
-                                            closed.realized_pnl, closed.realized_pnl_percent
+                        (Verdict::SellExisting { token }, _) => {
+                            let mut portfolio = trader_portfolio.tracker.lock().unwrap();
+                            info!("✅ WE OWN THIS! Copying the sell...");
+
+                            // Close the position
+                            match portfolio.close_mirrored(&token, &swap_signal) {
+                                Ok(closed) => {
+                                    // Sell only tokens our copies bought, not
+                                    // ones mirrored while the trader was gated
+                                    let copied = closed.position.copied_amount;
+                                    if copied > 0 {
+                                        spawn_copy(
+                                            &trade_executor,
+                                            &swap_signal,
+                                            copied,
+                                            Duration::ZERO,
                                         );
-                                        portfolio.save_safe(PORTFOLIO_FILE);
-                                        // Show updated stats
-                                        let stats = portfolio.get_stats();
+                                    } else {
                                         info!(
-                                            "💼 Portfolio: {} active, {} closed, Win rate: {:.1}%",
-                                            stats.active_positions,
-                                            stats.closed_positions,
-                                            stats.win_rate
+                                            "⏭️  None of this position was copied - nothing to sell"
                                         );
                                     }
-                                    Err(e) => {
-                                        error!("Failed to close position: {}", e);
-                                    }
+                                    info!("🏁 Position closed:");
+                                    info!(
+                                        "   P&L: {} ({:.2}%)",
+                                        closed.realized_pnl, closed.realized_pnl_percent
+                                    );
+                                    trader_portfolio.storage.save_safe(&portfolio);
+                                    // Show updated stats
+                                    let stats = portfolio.get_stats();
+                                    info!(
+                                        "💼 Portfolio: {} active, {} closed, Win rate: {:.1}%",
+                                        stats.active_positions,
+                                        stats.closed_positions,
+                                        stats.win_rate
+                                    );
+                                    notify(
+                                        TradeEvent::sell(
+                                            swap_signal.as_ref().clone(),
+                                            closed.realized_pnl_percent,
+                                        )
+                                        .with_portfolio(stats),
+                                    );
+                                    Some(PortfolioDelta::Closed(closed))
+                                }
+                                Err(e) => {
+                                    error!("Failed to close position: {}", e);
+                                    None
                                 }
-                            } else {
-                                info!("⏭️  SKIP - We don't own this token");
                             }
                         }
-                        TradeDirection::Swap {
-                            from_token,
-                            to_token,
-                        } => {
-                            info!("🔄 DIRECTION: TOKEN SWAP");
-                            info!("   From: {}", from_token);
-                            info!("   To:   {}", to_token);
+                        (Verdict::Copy { size }, _) => {
+                            info!("📤 No position, copying the sell anyway (always_copy)");
+                            spawn_copy(
+                                &trade_executor,
+                                &swap_signal,
+                                size.input_amount,
+                                Duration::ZERO,
+                            );
+                            None
                         }
-                    }
+                    };
+
+                    signal_bus.publish(SignalEvent {
+                        signal: swap_signal.as_ref().clone(),
+                        direction,
+                        portfolio: delta,
+                    });
 
                     info!("");
                     info!("🔗 View on Solscan:");
                     info!("   Transaction: {}", swap_signal.solscan_url());
                     info!("   Trader: {}", swap_signal.trader_solscan_url());
                     info!("═══════════════════════════════════════════════");
-
-                    // TODO: Pass to decision engine
-                    // TODO: Execute trade via Jupiter
                 }
-                Ok(None) => {
-                    info!("Transaction processed but no swap detected");
+                Ok(ParseOutcome::Skipped(reason)) => {
+                    info!("Transaction processed but skipped: {}", reason);
                 }
                 Err(e) => {
                     error!("Failed to parse transaction: {}", e);
+                    if let (Some(sink), Some(letter)) = (&dead_letters, captured)
+                        && let Err(write_err) = sink.record(letter, &e)
+                    {
+                        error!("❌ Failed to write dead letter: {}", write_err);
+                    }
+                }
+            }
+        }
+    });
+
+    // Exit positions that hit a stop-loss, take-profit or trailing stop.
+    // Wallets sharing a portfolio file share a tracker; check it once.
+    let mut risk_portfolios: Vec<(Pubkey, TraderPortfolio)> = Vec::new();
+    for (wallet, portfolio) in &portfolios {
+        if !risk_portfolios
+            .iter()
+            .any(|(_, p)| p.path == portfolio.path)
+        {
+            risk_portfolios.push((*wallet, portfolio.clone()));
+        }
+    }
+    let risk = config.risk.clone();
+    let risk_prices = Arc::clone(&prices);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(risk.check_interval_secs));
+        loop {
+            interval.tick().await;
+            for (trader, portfolio) in &risk_portfolios {
+                let positions: Vec<Position> = {
+                    let tracker = portfolio.tracker.lock().unwrap();
+                    if !tracker.has_exit_rules(&risk) {
+                        continue;
+                    }
+                    tracker.get_all_positions().into_iter().cloned().collect()
+                };
+                if positions.is_empty() {
+                    continue;
+                }
+
+                // Price outside the lock - prices come over HTTP
+                let spots = PortfolioTracker::fetch_prices(&positions, risk_prices.as_ref()).await;
+
+                let exits = {
+                    let mut tracker = portfolio.tracker.lock().unwrap();
+                    let now = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_secs())
+                        .unwrap_or(0);
+                    let mut exits = Vec::new();
+                    for trigger in tracker.check_triggers_at(&spots, &risk) {
+                        match tracker.close_triggered(&trigger, format!("risk-exit-{}", now)) {
+                            Ok(closed) => exits.push(closed),
+                            Err(e) => error!("Failed to close position: {}", e),
+                        }
+                    }
+                    if !exits.is_empty() {
+                        portfolio.storage.save_safe(&tracker);
+                    }
+                    exits
+                };
+
+                for closed in exits {
+                    info!(
+                        "🏁 Risk exit: P&L {} ({:.2}%)",
+                        closed.realized_pnl, closed.realized_pnl_percent
+                    );
+                    let copied = closed.position.copied_amount;
+                    if copied > 0 {
+                        spawn_copy(
+                            &risk_executor,
+                            &exit_signal(&closed, *trader),
+                            copied,
+                            Duration::ZERO,
+                        );
+                    }
                 }
             }
         }
     });
 
+    // Optionally append periodic P&L snapshots for equity-curve plotting
+    if let Some(interval_secs) = config.snapshot_interval_secs {
+        let portfolio = Arc::clone(&primary.tracker);
+        let snapshot_path = config.snapshot_path.clone();
+        let snapshot_prices = Arc::clone(&prices);
+        let display_currency = config.display_currency;
+        info!(
+            "📈 Writing portfolio snapshots to {} every {}s",
+            snapshot_path, interval_secs
+        );
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                let (snapshot, positions) = {
+                    let portfolio = portfolio.lock().unwrap();
+                    let positions: Vec<Position> =
+                        portfolio.get_all_positions().into_iter().cloned().collect();
+                    (portfolio.snapshot(), positions)
+                };
+                if let Err(e) = snapshot.append_to(&snapshot_path) {
+                    error!("❌ Failed to write portfolio snapshot: {}", e);
+                }
+
+                // Value open positions outside the lock - prices come over HTTP
+                if !positions.is_empty() {
+                    let valuation = MarketValuation::of(&positions, snapshot_prices.as_ref()).await;
+                    info!(
+                        "📊 Unrealized P&L: {} (market value {}, cost {}){}",
+                        valuation.unrealized_pnl,
+                        valuation.market_value,
+                        valuation.cost_basis,
+                        if valuation.unpriced.is_empty() {
+                            String::new()
+                        } else {
+                            format!(", {} unpriced", valuation.unpriced.len())
+                        }
+                    );
+                }
+
+                if let Some(currency) = &display_currency {
+                    let stats = PortfolioTracker::shared_stats_in(
+                        &portfolio,
+                        snapshot_prices.as_ref(),
+                        currency,
+                    )
+                    .await;
+                    info!("💱 {}", stats.summary());
+                }
+            }
+        });
+    }
+
+    // Optional read-only HTTP API for dashboards
+    if config.api.enabled {
+        let state = ApiState {
+            portfolio: Arc::clone(&primary.tracker),
+            signals: Arc::clone(&recent_signals),
+            display_currency: config.display_currency.map(|currency| DisplayCurrency {
+                currency,
+                prices: Arc::clone(&prices),
+            }),
+        };
+        let addr = config.api.socket_addr();
+        tokio::spawn(async move {
+            if let Err(e) = api::serve(addr, state).await {
+                error!("❌ HTTP API error: {}", e);
+            }
+        });
+    }
+
     info!("Bot is running. Press Ctrl+C to stop.");
 
     tokio::select! {
-        _ = listener_handle => {
+        _ = &mut listener_handle => {
             info!("Listener task ended");
         }
-        _ = parser_handle => {
+        _ = &mut parser_handle => {
             info!("Parser task ended");
         }
         _ = tokio::signal::ctrl_c() => {
             info!("Received shutdown signal");
 
-            // ✅ SAVE PORTFOLIO BEFORE SHUTDOWN
-            let portfolio = portfolio.lock().unwrap();
+            // Stop the listener; dropping it closes the channel, so the
+            // parser finishes the queued transactions and then ends
+            shutdown.send_replace(true);
+            if tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut listener_handle)
+                .await
+                .is_err()
+            {
+                warn!("⚠️  Listener didn't stop in time - aborting it");
+                listener_handle.abort();
+            }
+            if tokio::time::timeout(SHUTDOWN_TIMEOUT, &mut parser_handle)
+                .await
+                .is_err()
+            {
+                warn!("⚠️  Parser didn't finish the queued transactions in time");
+            }
+
+            // ✅ SAVE PORTFOLIOS BEFORE SHUTDOWN
+            let mut saved = HashSet::new();
+            for trader_portfolio in portfolios.values() {
+                if !saved.insert(trader_portfolio.path.as_str()) {
+                    continue;
+                }
+                info!("");
+                info!(
+                    "💾 Saving portfolio {}...",
+                    trader_portfolio.storage.location()
+                );
+                let stats = {
+                    let portfolio = trader_portfolio.tracker.lock().unwrap();
+                    trader_portfolio.storage.save_safe(&portfolio);
+                    portfolio.get_stats()
+                };
 
-            info!("");
-            info!("💾 Saving portfolio...");
-            portfolio.save_safe(PORTFOLIO_FILE);
+                // Print final portfolio stats
+                info!("");
+                info!("📊 FINAL PORTFOLIO STATS:");
+                info!("   Active positions:  {}", stats.active_positions);
+                info!("   Closed positions:  {}", stats.closed_positions);
+                info!("   Total realized P&L: {}", stats.total_realized_pnl);
+                info!("   Win rate: {:.1}%", stats.win_rate);
 
-            // Print final portfolio stats
-            let stats = portfolio.get_stats();
-            info!("");
-            info!("📊 FINAL PORTFOLIO STATS:");
-            info!("   Active positions:  {}", stats.active_positions);
-            info!("   Closed positions:  {}", stats.closed_positions);
-            info!("   Total realized P&L: {}", stats.total_realized_pnl);
-            info!("   Win rate: {:.1}%", stats.win_rate);
+                if let Some(currency) = &config.display_currency {
+                    let stats = PortfolioTracker::shared_stats_in(
+                        &trader_portfolio.tracker,
+                        prices.as_ref(),
+                        currency,
+                    )
+                    .await;
+                    info!("   {}", stats.summary());
+                }
+            }
         }
     }
 
     info!("Shutting down...");
     Ok(())
 }
+
+/// A trader's mirrored portfolio and where it's saved
+#[derive(Clone)]
+struct TraderPortfolio {
+    tracker: Arc<Mutex<PortfolioTracker>>,
+    path: String,
+    storage: Arc<dyn Storage>,
+}
+
+/// Load (or start) each target wallet's portfolio. Wallets whose
+/// `portfolio_path` resolves to the same file share one tracker.
+fn load_portfolios(
+    config: &MonitorConfig,
+    wallets: &[Pubkey],
+) -> Result<HashMap<Pubkey, TraderPortfolio>, Box<dyn std::error::Error>> {
+    let mut by_path: HashMap<String, TraderPortfolio> = HashMap::new();
+    let mut portfolios = HashMap::new();
+
+    for wallet in wallets {
+        let path = config.portfolio_path_for(wallet);
+        if !by_path.contains_key(&path) {
+            let storage = open_storage(config, &path)?;
+            // A missing portfolio loads empty; one that exists but can't be
+            // read must not be replaced by an empty one on the next save
+            let tracker = storage
+                .load()
+                .map_err(|e| format!("Failed to load portfolio {}: {}", storage.location(), e))?
+                .with_cost_basis_method(config.cost_basis_method);
+            info!("📂 Portfolio {}:", storage.location());
+            let stats = tracker.get_stats();
+            info!("   Active positions: {}", stats.active_positions);
+            info!("   Closed positions: {}", stats.closed_positions);
+            info!("   Total P&L: {}", stats.total_realized_pnl);
+            let portfolio = TraderPortfolio {
+                tracker: Arc::new(Mutex::new(tracker)),
+                path: path.clone(),
+                storage,
+            };
+            by_path.insert(path.clone(), portfolio);
+        }
+        portfolios.insert(*wallet, by_path[&path].clone());
+    }
+
+    Ok(portfolios)
+}
+
+/// Execute our copy of `signal`, spending `input_amount`, after `delay` and
+/// in the background so the parser loop keeps up
+fn spawn_copy(
+    executor: &Arc<dyn TradeExecutor>,
+    signal: &UniversalSwapSignal,
+    input_amount: u64,
+    delay: Duration,
+) {
+    let executor = Arc::clone(executor);
+    // Executors compare the output against quotes, so it must match our size
+    let copy = signal.scaled_to_input(input_amount);
+
+    tokio::spawn(async move {
+        if !delay.is_zero() {
+            info!(
+                "⏳ Waiting {}ms before copying {}",
+                delay.as_millis(),
+                copy.signature
+            );
+            tokio::time::sleep(delay).await;
+        }
+
+        match executor.execute(&copy).await {
+            Ok(signature) => info!("🚀 Copy of {} sent: {}", copy.signature, signature),
+            Err(e) => error!("❌ Copy of {} failed: {}", copy.signature, e),
+        }
+    });
+}
+
+/// Our own sell of `closed`, e.g. on a stop-loss, shaped like a trader's
+/// swap so the executor can make it. Decimals are left at 0: executors
+/// only use raw amounts.
+fn exit_signal(closed: &ClosedPosition, trader: Pubkey) -> UniversalSwapSignal {
+    let position = &closed.position;
+    UniversalSwapSignal {
+        signature: Signature::default(),
+        slot: 0,
+        timestamp: closed.exit_time as i64,
+        trader,
+        swap_type: SwapType::Simple,
+        input_mint: position.token,
+        input_amount: position.amount,
+        input_pre_balance: position.amount,
+        input_decimals: 0,
+        output_mint: position.payment_token,
+        output_amount: closed.exit_value,
+        output_decimals: 0,
+        intermediate_tokens: vec![],
+        likely_dex: None,
+        confidence: 1.0,
+        is_new_mint: false,
+        priority_fee_lamports: 0,
+    }
+}
+
+/// Install the tracing subscriber at the configured level (info if invalid)
+fn init_logging(level: &str) {
+    let parsed = parse_log_level(level);
+
+    tracing_subscriber::fmt()
+        .with_max_level(parsed.unwrap_or(tracing::Level::INFO))
+        .init();
+
+    if parsed.is_none() {
+        warn!("Invalid log level '{}' - falling back to info", level);
+    }
+}
+
+/// `sniper close <mint> <exit_value> [config]` - manually close a stuck position
+fn close_position_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    const USAGE: &str = "Usage: sniper close <mint> <exit_value> [config]";
+
+    let mint = args.first().ok_or(USAGE)?;
+    let exit_value = args.get(1).ok_or(USAGE)?;
+    let config_path = args.get(2).map(String::as_str).unwrap_or("config.toml");
+
+    let token = Pubkey::from_str(mint).map_err(|e| format!("Invalid mint {}: {}", mint, e))?;
+    let exit_value: u64 = exit_value
+        .parse()
+        .map_err(|e| format!("Invalid exit value {}: {}", exit_value, e))?;
+
+    let config = load_config(config_path)?;
+    init_logging(&config.log_level);
+    let portfolio_path = config.portfolio_path_for(&config.target_wallet);
+    let storage = open_storage(&config, &portfolio_path)?;
+    let mut portfolio = storage.load()?;
+
+    let amount = portfolio
+        .get_position(&token)
+        .map(|p| p.amount)
+        .ok_or_else(|| format!("No open position for {} in {}", token, portfolio_path))?;
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let closed = portfolio.close_position(
+        &token,
+        amount,
+        exit_value,
+        format!("manual-close-{}", timestamp),
+    )?;
+    storage.save(&portfolio)?;
+
+    info!("✅ Manually closed position in {}", token);
+    info!(
+        "   P&L: {} ({:.2}%)",
+        closed.realized_pnl, closed.realized_pnl_percent
+    );
+    info!(
+        "   Total realized P&L: {}",
+        portfolio.get_stats().total_realized_pnl
+    );
+
+    Ok(())
+}
+
+/// `sniper status [config]` - print active positions and stats, read-only
+fn status_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let config_path = args.first().map(String::as_str).unwrap_or("config.toml");
+
+    let config = load_config(config_path)?;
+    init_logging(&config.log_level);
+    let portfolio =
+        open_storage(&config, &config.portfolio_path_for(&config.target_wallet))?.load()?;
+
+    println!("{}", portfolio.format_table());
+    Ok(())
+}
+
+/// `sniper export <out.csv> [config]` - write closed positions as CSV for
+/// tax reporting
+fn export_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    const USAGE: &str = "Usage: sniper export <out.csv> [config]";
+
+    let out_path = args.first().ok_or(USAGE)?;
+    let config_path = args.get(1).map(String::as_str).unwrap_or("config.toml");
+
+    let config = load_config(config_path)?;
+    init_logging(&config.log_level);
+    let portfolio =
+        open_storage(&config, &config.portfolio_path_for(&config.target_wallet))?.load()?;
+
+    portfolio.export_closed_csv(out_path)?;
+    println!(
+        "Exported {} closed positions to {}",
+        portfolio.get_history().len(),
+        out_path
+    );
+    Ok(())
+}
+
+/// `sniper reconcile [--apply] [config]` - compare mirrored positions with the
+/// trader's current token balances; `--apply` drops positions they've exited
+fn reconcile_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let apply = args.iter().any(|a| a == "--apply");
+    let config_path = args
+        .iter()
+        .find(|a| *a != "--apply")
+        .map(String::as_str)
+        .unwrap_or("config.toml");
+
+    let config = load_config(config_path)?;
+    init_logging(&config.log_level);
+    let portfolio_path = config.portfolio_path_for(&config.target_wallet);
+    let storage = open_storage(&config, &portfolio_path)?;
+    let mut portfolio = storage.load()?;
+
+    let client = RpcClient::new(config.rpc_endpoints[0].clone());
+    let balances = fetch_token_balances(&client, &config.target_wallet)?;
+    let discrepancies = reconcile(&portfolio, &balances);
+
+    if discrepancies.is_empty() {
+        println!("✅ Portfolio matches the trader's holdings");
+        return Ok(());
+    }
+
+    for discrepancy in &discrepancies {
+        println!("{}", discrepancy);
+    }
+
+    if !apply {
+        println!(
+            "\n{} discrepancies. Re-run with --apply to drop phantom positions, \
+             or `sniper close <mint> <exit_value>` to record their P&L.",
+            discrepancies.len()
+        );
+        return Ok(());
+    }
+
+    let mut dropped = 0;
+    for discrepancy in &discrepancies {
+        if let Discrepancy::Phantom { mint, .. } = discrepancy
+            && portfolio.drop_position(mint).is_some()
+        {
+            dropped += 1;
+        }
+    }
+    storage.save(&portfolio)?;
+    println!(
+        "🗑️  Dropped {} phantom positions from {}",
+        dropped, portfolio_path
+    );
+
+    Ok(())
+}
@@ -5,17 +5,20 @@ pub enum MonitorError {
     #[error("WebSocket connection failed: {0}")]
     ConnectionFailed(String),
 
+    #[error("WebSocket connection rejected by server (close code {code}): {reason}")]
+    ConnectionRejected { code: u16, reason: String },
+
     #[error("Failed to parse transaction: {0}")]
     ParseError(String),
 
     #[error("RPC error: {0}")]
-    RpcError(#[from] solana_client::client_error::ClientError),
+    RpcError(Box<solana_client::client_error::ClientError>),
 
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
     #[error("WebSocket error: {0}")]
-    WebSocketError(#[from] tokio_tungstenite::tungstenite::Error),
+    WebSocketError(Box<tokio_tungstenite::tungstenite::Error>),
 
     #[error("Serialization error: {0}")]
     SerializationError(#[from] serde_json::Error),
@@ -29,6 +32,9 @@ pub enum MonitorError {
     #[error("Invalid response format: {0}")]
     InvalidResponse(String),
 
+    #[error("Transaction simulation failed: {0}")]
+    SimulationFailed(String),
+
     #[error("Timeout waiting for response")]
     Timeout,
 
@@ -36,5 +42,17 @@ pub enum MonitorError {
     Unknown(String),
 }
 
+impl From<solana_client::client_error::ClientError> for MonitorError {
+    fn from(e: solana_client::client_error::ClientError) -> Self {
+        MonitorError::RpcError(Box::new(e))
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for MonitorError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        MonitorError::WebSocketError(Box::new(e))
+    }
+}
+
 /// Result type for monitor operations
 pub type MonitorResult<T> = Result<T, MonitorError>;
@@ -0,0 +1,66 @@
+use crate::monitor::error::{MonitorError, MonitorResult};
+use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::str::FromStr;
+
+/// Source of full transaction data, abstracted so the listener can be
+/// tested without a live RPC node
+pub trait TransactionFetcher: Send + Sync {
+    /// Fetch a transaction by signature
+    fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> MonitorResult<EncodedConfirmedTransactionWithStatusMeta>;
+
+    /// Signatures involving `address` newer than `until`, newest first
+    fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        until: Option<Signature>,
+        limit: usize,
+    ) -> MonitorResult<Vec<Signature>>;
+}
+
+impl TransactionFetcher for RpcClient {
+    fn get_transaction(
+        &self,
+        signature: &Signature,
+    ) -> MonitorResult<EncodedConfirmedTransactionWithStatusMeta> {
+        Ok(self.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::JsonParsed),
+                commitment: Some(self.commitment()),
+                max_supported_transaction_version: Some(0), // Support v0 transactions
+            },
+        )?)
+    }
+
+    fn get_signatures_for_address(
+        &self,
+        address: &Pubkey,
+        until: Option<Signature>,
+        limit: usize,
+    ) -> MonitorResult<Vec<Signature>> {
+        let statuses = self.get_signatures_for_address_with_config(
+            address,
+            GetConfirmedSignaturesForAddress2Config {
+                until,
+                limit: Some(limit),
+                commitment: Some(self.commitment()),
+                ..Default::default()
+            },
+        )?;
+
+        statuses
+            .iter()
+            .map(|status| {
+                Signature::from_str(&status.signature)
+                    .map_err(|e| MonitorError::ParseError(format!("Invalid signature: {}", e)))
+            })
+            .collect()
+    }
+}
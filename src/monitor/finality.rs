@@ -0,0 +1,65 @@
+use crate::monitor::error::MonitorResult;
+use crate::types::MonitorConfig;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::Signature;
+use std::sync::Arc;
+use tokio::time::{Duration, sleep};
+use tracing::{debug, warn};
+
+/// How many times to poll for finalization before giving up
+const FINALITY_POLL_ATTEMPTS: u32 = 3;
+
+/// Re-checks signatures seen at "confirmed" against "finalized" commitment
+pub struct FinalityChecker {
+    rpc_client: Arc<RpcClient>,
+    delay: Duration,
+}
+
+impl FinalityChecker {
+    /// Create a new finality checker using the first RPC endpoint
+    pub fn new(config: &MonitorConfig) -> Self {
+        let rpc_client = Arc::new(RpcClient::new_with_commitment(
+            config.rpc_endpoints[0].clone(),
+            CommitmentConfig::finalized(),
+        ));
+
+        Self {
+            rpc_client,
+            delay: Duration::from_secs(config.finality_check_delay_secs),
+        }
+    }
+
+    /// Wait for the transaction to finalize.
+    ///
+    /// Returns `Ok(false)` if the signature is still unknown at finalized
+    /// commitment after all polls, or if it finalized with an error.
+    pub async fn wait_for_finalization(&self, signature: &Signature) -> MonitorResult<bool> {
+        for attempt in 1..=FINALITY_POLL_ATTEMPTS {
+            sleep(self.delay).await;
+
+            let status = self
+                .rpc_client
+                .get_signature_status_with_commitment(signature, CommitmentConfig::finalized())?;
+
+            match status {
+                Some(Ok(())) => {
+                    debug!("Transaction finalized: {}", signature);
+                    return Ok(true);
+                }
+                Some(Err(e)) => {
+                    warn!("Transaction {} finalized with error: {}", signature, e);
+                    return Ok(false);
+                }
+                None => {
+                    debug!(
+                        "Transaction {} not finalized yet (attempt {}/{})",
+                        signature, attempt, FINALITY_POLL_ATTEMPTS
+                    );
+                }
+            }
+        }
+
+        Ok(false)
+    }
+}
@@ -1,27 +1,52 @@
+use crate::config::SharedConfig;
 use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::monitor::fetcher::TransactionFetcher;
+use crate::monitor::retry::{RetryPolicy, retry_with_backoff};
 use crate::monitor::websocket::WebSocketManager;
-use crate::types::MonitorConfig;
+use crate::portfolio::tracker::write_atomic;
+use crate::types::{ListenerMode, MonitorConfig};
 use serde_json::Value;
 use solana_client::rpc_client::RpcClient;
-use solana_client::rpc_config::RpcTransactionConfig;
 use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
-use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
-use std::collections::HashSet;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::str::FromStr;
-use std::sync::Arc;
-use tokio::sync::mpsc;
-use tokio::time::{Duration, sleep};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::{mpsc, watch};
+use tokio::time::{Duration, MissedTickBehavior};
 use tracing::{debug, error, info, warn};
 
 const DEDUP_CACHE_SIZE: usize = 10_000;
 
-/// Listens for transactions from WebSocket and fetches full transaction data
+/// Most signatures fetched per wallet on each poll
+const POLL_LIMIT: usize = 100;
+
+/// Listens for transactions from WebSocket and/or RPC polling and fetches
+/// full transaction data
 pub struct TransactionListener {
     ws_manager: WebSocketManager,
-    rpc_client: Arc<RpcClient>,
-    seen_signatures: HashSet<Signature>,
+    mode: ListenerMode,
+    poll_interval: Duration,
+    /// Newest signature seen by polling, per wallet (`None`: no history yet)
+    poll_cursors: HashMap<Pubkey, Option<Signature>>,
+    fetcher: Box<dyn TransactionFetcher>,
+    retry_policy: RetryPolicy,
+    /// Reloadable config consulted for new endpoints on every reconnect
+    live_config: Option<SharedConfig>,
+    /// Endpoint behind `fetcher`, if it is an RPC client we built
+    rpc_endpoint: Option<String>,
+    /// Replay at most this many missed transactions on startup
+    catch_up_limit: Option<usize>,
+    /// Last forwarded signature is saved here when catch-up is enabled
+    last_signature_path: Option<String>,
+    /// Slot of the newest transaction saved to `last_signature_path`
+    last_saved_slot: AtomicU64,
+    seen_signatures: SeenSignatures,
     tx_sender: mpsc::UnboundedSender<EncodedConfirmedTransactionWithStatusMeta>,
+    /// `start` returns once this turns `true`
+    shutdown: Option<watch::Receiver<bool>>,
 }
 
 impl TransactionListener {
@@ -30,53 +55,181 @@ impl TransactionListener {
         config: MonitorConfig,
         tx_sender: mpsc::UnboundedSender<EncodedConfirmedTransactionWithStatusMeta>,
     ) -> Self {
-        let commitment = if config.use_confirmed_commitment {
-            CommitmentConfig::confirmed()
-        } else {
-            CommitmentConfig::finalized()
+        let rpc_client = rpc_client_for(&config);
+        let rpc_endpoint = rpc_client.url();
+
+        let mut listener = Self::with_fetcher(config, tx_sender, Box::new(rpc_client));
+        listener.rpc_endpoint = Some(rpc_endpoint);
+        listener
+    }
+
+    /// Create a listener that switches to the endpoints of the latest
+    /// reloaded config whenever it reconnects
+    pub fn with_shared_config(
+        config: SharedConfig,
+        tx_sender: mpsc::UnboundedSender<EncodedConfirmedTransactionWithStatusMeta>,
+    ) -> Self {
+        let mut listener = Self::new(config.load().as_ref().clone(), tx_sender);
+        listener.live_config = Some(config);
+        listener
+    }
+
+    /// Create a listener that fetches transactions through a custom fetcher
+    pub fn with_fetcher(
+        config: MonitorConfig,
+        tx_sender: mpsc::UnboundedSender<EncodedConfirmedTransactionWithStatusMeta>,
+        fetcher: Box<dyn TransactionFetcher>,
+    ) -> Self {
+        // Fetch immediately; only back off (starting at fetch_delay_ms) while
+        // the RPC node doesn't have the transaction yet
+        let retry_policy = RetryPolicy {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(config.fetch_delay_ms),
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_delay: None,
         };
 
-        // Use first RPC endpoint for now (can add failover later)
-        let rpc_client = Arc::new(RpcClient::new_with_commitment(
-            config.rpc_endpoints[0].clone(),
-            commitment,
-        ));
+        let (catch_up_limit, last_signature_path) = if config.catch_up {
+            (
+                Some(config.catch_up_limit),
+                Some(config.last_signature_path.clone()),
+            )
+        } else {
+            (None, None)
+        };
 
         Self {
+            mode: config.listener_mode,
+            poll_interval: Duration::from_millis(config.poll_interval_ms),
+            poll_cursors: HashMap::new(),
             ws_manager: WebSocketManager::new(config),
-            rpc_client,
-            seen_signatures: HashSet::new(),
+            fetcher,
+            retry_policy,
+            live_config: None,
+            rpc_endpoint: None,
+            catch_up_limit,
+            last_signature_path,
+            last_saved_slot: AtomicU64::new(0),
+            seen_signatures: SeenSignatures::new(DEDUP_CACHE_SIZE),
             tx_sender,
+            shutdown: None,
         }
     }
 
-    /// Start listening for transactions
-    pub async fn start(&mut self, target_address: solana_sdk::pubkey::Pubkey) -> MonitorResult<()> {
-        info!("Starting transaction listener for {}", target_address);
+    /// Stop listening once `shutdown` is set to `true`. The message being
+    /// processed is finished first, then the WebSocket is closed.
+    pub fn with_shutdown(mut self, shutdown: watch::Receiver<bool>) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
 
-        // Connect to WebSocket
-        self.ws_manager.connect().await?;
+    /// Start listening for transactions of every target wallet
+    pub async fn start(&mut self, target_addresses: &[Pubkey]) -> MonitorResult<()> {
+        info!(
+            "Starting transaction listener for {} wallet(s): {:?}",
+            target_addresses.len(),
+            target_addresses
+        );
+
+        let mut poll_timer = self.mode.uses_polling().then(|| {
+            let mut timer = tokio::time::interval(self.poll_interval);
+            timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            timer
+        });
+        let mut websocket = self.mode.uses_websocket();
+
+        if websocket && let Err(e) = self.subscribe(target_addresses).await {
+            if poll_timer.is_none() {
+                return Err(e);
+            }
+            warn!("⚠️  WebSocket unavailable ({}) - polling only", e);
+            websocket = false;
+        }
 
-        // Subscribe to logs (this gives us transaction signatures)
-        self.ws_manager.subscribe_to_logs(&target_address).await?;
+        // Note where each wallet's history stands before catching up, so
+        // polling starts from here and overlaps are deduplicated
+        if poll_timer.is_some() {
+            self.poll_wallets(target_addresses).await;
+        }
+
+        // Replay what we missed while offline; live notifications queue up
+        // meanwhile and overlaps are dropped by the dedup cache
+        if let Some(limit) = self.catch_up_limit {
+            // The saved signature may belong to any wallet, so it can't
+            // bound the others' histories
+            match target_addresses {
+                [target_address] => self.catch_up(target_address, limit).await,
+                _ => warn!("⚠️  Catch-up is only supported with a single target wallet - skipping"),
+            }
+        }
 
-        info!("Listening for transactions...");
+        info!("Listening for transactions ({:?})...", self.mode);
+        let mut shutdown = self.shutdown.clone();
 
-        // Start the listening loop
+        // Start the listening loop; only waits are raced against shutdown,
+        // so a notification is never dropped half-processed
         loop {
-            match self.listen_once().await {
+            let result = match (&mut poll_timer, websocket) {
+                (Some(timer), false) => {
+                    tokio::select! {
+                        _ = timer.tick() => {}
+                        _ = shutdown_signalled(&mut shutdown) => break,
+                    }
+                    self.poll_wallets(target_addresses).await;
+                    continue;
+                }
+                (Some(timer), true) => {
+                    // Only the receive is raced: it is cancel-safe, while a
+                    // half-processed notification would be lost
+                    let received = tokio::select! {
+                        received = self.ws_manager.receive_message() => Some(received),
+                        _ = timer.tick() => None,
+                        _ = shutdown_signalled(&mut shutdown) => break,
+                    };
+                    match received {
+                        Some(Ok(message)) => self.handle_received(message).await,
+                        Some(Err(e)) => Err(e),
+                        None => {
+                            self.poll_wallets(target_addresses).await;
+                            continue;
+                        }
+                    }
+                }
+                (None, _) => {
+                    let received = tokio::select! {
+                        received = self.ws_manager.receive_message() => received,
+                        _ = shutdown_signalled(&mut shutdown) => break,
+                    };
+                    match received {
+                        Ok(message) => self.handle_received(message).await,
+                        Err(e) => Err(e),
+                    }
+                }
+            };
+
+            match result {
                 Ok(_) => {}
                 Err(MonitorError::ConnectionFailed(_)) | Err(MonitorError::WebSocketError(_)) => {
                     warn!("Connection lost, attempting to reconnect...");
+                    self.refresh_config();
 
-                    // Try to reconnect
-                    if let Err(e) = self.ws_manager.reconnect().await {
+                    if let Err(e) = self.resubscribe(target_addresses).await {
                         error!("Failed to reconnect: {}", e);
+                        if poll_timer.is_none() {
+                            return Err(e);
+                        }
+                        warn!("⚠️  Continuing with polling only");
+                        websocket = false;
+                    }
+                }
+                Err(e @ MonitorError::ConnectionRejected { .. }) => {
+                    error!("Giving up: {}", e);
+                    if poll_timer.is_none() {
                         return Err(e);
                     }
-
-                    // Resubscribe after reconnection
-                    self.ws_manager.subscribe_to_logs(&target_address).await?;
+                    warn!("⚠️  Continuing with polling only");
+                    websocket = false;
                 }
                 Err(e) => {
                     error!("Listener error: {}", e);
@@ -84,11 +237,151 @@ impl TransactionListener {
                 }
             }
         }
+
+        info!("Shutdown requested - stopping listener");
+        self.close().await
+    }
+
+    /// Connect to the WebSocket and subscribe to the wallets' logs (this
+    /// gives us transaction signatures)
+    async fn subscribe(&mut self, target_addresses: &[Pubkey]) -> MonitorResult<()> {
+        self.ws_manager.connect().await?;
+        self.ws_manager
+            .subscribe_to_wallets(target_addresses)
+            .await?;
+        Ok(())
+    }
+
+    /// Reconnect and subscribe again after losing the connection
+    async fn resubscribe(&mut self, target_addresses: &[Pubkey]) -> MonitorResult<()> {
+        self.ws_manager.reconnect().await?;
+        self.ws_manager
+            .subscribe_to_wallets(target_addresses)
+            .await?;
+        Ok(())
+    }
+
+    /// Ask RPC for each wallet's signatures since the last poll and forward
+    /// the new ones, oldest first. A wallet's first poll only records where
+    /// its history stands.
+    async fn poll_wallets(&mut self, target_addresses: &[Pubkey]) {
+        for target_address in target_addresses {
+            let Some(&cursor) = self.poll_cursors.get(target_address) else {
+                match self
+                    .fetcher
+                    .get_signatures_for_address(target_address, None, 1)
+                {
+                    Ok(newest) => {
+                        self.poll_cursors
+                            .insert(*target_address, newest.first().copied());
+                    }
+                    Err(e) => warn!("Polling {} failed: {}", target_address, e),
+                }
+                continue;
+            };
+
+            let signatures =
+                match self
+                    .fetcher
+                    .get_signatures_for_address(target_address, cursor, POLL_LIMIT)
+                {
+                    Ok(signatures) => signatures,
+                    Err(e) => {
+                        warn!("Polling {} failed: {}", target_address, e);
+                        continue;
+                    }
+                };
+
+            let Some(&newest) = signatures.first() else {
+                continue;
+            };
+            self.poll_cursors.insert(*target_address, Some(newest));
+
+            if signatures.len() >= POLL_LIMIT {
+                warn!(
+                    "⚠️  Poll of {} capped at {} transactions - older ones are skipped",
+                    target_address, POLL_LIMIT
+                );
+            }
+
+            // Newest first from RPC; forward in the order they happened
+            for signature in signatures.into_iter().rev() {
+                if self.is_duplicate(&signature) {
+                    debug!("Skipping duplicate transaction: {}", signature);
+                    continue;
+                }
+                if let Err(e) = self.fetch_and_send_transaction(signature).await {
+                    warn!("Polling skipped {}: {}", signature, e);
+                }
+            }
+        }
+    }
+
+    /// Fetch up to `limit` transactions newer than the last processed one,
+    /// oldest first. Without a saved signature there is nothing to catch up.
+    async fn catch_up(&mut self, target_address: &Pubkey, limit: usize) {
+        let Some(until) = self
+            .last_signature_path
+            .as_deref()
+            .and_then(load_last_signature)
+        else {
+            info!("No last processed signature saved - skipping catch-up");
+            return;
+        };
+
+        let signatures =
+            match self
+                .fetcher
+                .get_signatures_for_address(target_address, Some(until), limit)
+            {
+                Ok(signatures) => signatures,
+                Err(e) => {
+                    warn!("Catch-up failed, going live without it: {}", e);
+                    return;
+                }
+            };
+
+        if signatures.len() >= limit {
+            warn!(
+                "⚠️  Catch-up capped at {} transactions - older missed trades are skipped",
+                limit
+            );
+        }
+        info!("⏪ Catching up on {} missed transactions", signatures.len());
+
+        // Newest first from RPC; replay in the order they happened
+        for signature in signatures.into_iter().rev() {
+            if self.is_duplicate(&signature) {
+                continue;
+            }
+            if let Err(e) = self.fetch_and_send_transaction(signature).await {
+                warn!("Catch-up skipped {}: {}", signature, e);
+            }
+        }
+    }
+
+    /// Pick up endpoints from the latest reloaded config, if any
+    fn refresh_config(&mut self) {
+        let Some(live_config) = &self.live_config else {
+            return;
+        };
+        let config = live_config.load().as_ref().clone();
+
+        if let Some(endpoint) = &self.rpc_endpoint
+            && config.rpc_endpoints.first() != Some(endpoint)
+        {
+            let rpc_client = rpc_client_for(&config);
+            info!("RPC endpoint changed: {} → {}", endpoint, rpc_client.url());
+            self.rpc_endpoint = Some(rpc_client.url());
+            self.fetcher = Box::new(rpc_client);
+        }
+
+        self.ws_manager.set_config(config);
     }
 
-    /// Listen for one message and process it
-    async fn listen_once(&mut self) -> MonitorResult<()> {
-        if let Some(message) = self.ws_manager.receive_message().await? {
+    /// Process a received message, if it was one we care about
+    async fn handle_received(&mut self, message: Option<Value>) -> MonitorResult<()> {
+        if let Some(message) = message {
             self.process_message(message).await?;
         }
         Ok(())
@@ -97,12 +390,12 @@ impl TransactionListener {
     /// Process a WebSocket message
     async fn process_message(&mut self, message: Value) -> MonitorResult<()> {
         // Check if this is a logs notification
-        if let Some(params) = message.get("params") {
-            if let Some(result) = params.get("result") {
-                if let Some(value) = result.get("value") {
-                    self.process_log_notification(value).await?;
-                }
-            }
+        if let Some(value) = message
+            .get("params")
+            .and_then(|params| params.get("result"))
+            .and_then(|result| result.get("value"))
+        {
+            self.process_log_notification(value).await?;
         }
         Ok(())
     }
@@ -131,80 +424,56 @@ impl TransactionListener {
 
     /// Check if we've already processed this signature
     fn is_duplicate(&mut self, signature: &Signature) -> bool {
-        if self.seen_signatures.contains(signature) {
-            return true;
-        }
-
-        // Add to cache
-        self.seen_signatures.insert(*signature);
-
-        // Evict old entries if cache is too large
-        if self.seen_signatures.len() > DEDUP_CACHE_SIZE {
-            // Remove oldest 10% of entries (simple approach)
-            let to_remove = DEDUP_CACHE_SIZE / 10;
-            let signatures_to_remove: Vec<Signature> = self
-                .seen_signatures
-                .iter()
-                .take(to_remove)
-                .copied()
-                .collect();
-
-            for sig in signatures_to_remove {
-                self.seen_signatures.remove(&sig);
-            }
-        }
-
-        false
+        !self.seen_signatures.insert(*signature)
     }
 
     /// Fetch full transaction details from RPC and send to parser
     async fn fetch_and_send_transaction(&self, signature: Signature) -> MonitorResult<()> {
         info!("Fetching transaction: {}", signature);
 
-        // Add small delay to ensure transaction is available
-        sleep(Duration::from_millis(500)).await;
+        let fetcher = &self.fetcher;
+        let transaction = retry_with_backoff(
+            &self.retry_policy,
+            |e| matches!(e, MonitorError::RpcError(_)),
+            |_| async move { fetcher.get_transaction(&signature) },
+        )
+        .await
+        .inspect_err(|e| error!("Failed to fetch transaction {}: {}", signature, e))?;
+
+        info!("Successfully fetched transaction: {}", signature);
+        let slot = transaction.slot;
+
+        // Send to parser via channel
+        if let Err(e) = self.tx_sender.send(transaction) {
+            error!("Failed to send transaction to parser: {}", e);
+            return Err(MonitorError::ChannelError);
+        }
 
-        // Try to fetch with retries
-        let max_retries = 3;
-        let mut retries = 0;
+        if let Some(path) = &self.last_signature_path {
+            self.save_last_signature(path, signature, slot).await;
+        }
 
-        loop {
-            match self.rpc_client.get_transaction_with_config(
-                &signature,
-                solana_client::rpc_config::RpcTransactionConfig {
-                    encoding: Some(UiTransactionEncoding::JsonParsed),
-                    commitment: Some(self.rpc_client.commitment()),
-                    max_supported_transaction_version: Some(0), // Support v0 transactions
-                },
-            ) {
-                Ok(transaction) => {
-                    info!("Successfully fetched transaction: {}", signature);
-
-                    // Send to parser via channel
-                    if let Err(e) = self.tx_sender.send(transaction) {
-                        error!("Failed to send transaction to parser: {}", e);
-                        return Err(MonitorError::ChannelError);
-                    }
+        Ok(())
+    }
 
-                    return Ok(());
-                }
-                Err(e) => {
-                    retries += 1;
-                    if retries >= max_retries {
-                        error!(
-                            "Failed to fetch transaction after {} retries: {}",
-                            max_retries, e
-                        );
-                        return Err(MonitorError::RpcError(e));
-                    }
+    /// Save `signature` as the last processed one, unless a transaction from
+    /// a later slot already was. The file is replaced atomically, on the
+    /// blocking pool rather than the async workers.
+    async fn save_last_signature(&self, path: &str, signature: Signature, slot: u64) {
+        if self.last_saved_slot.fetch_max(slot, Ordering::SeqCst) > slot {
+            debug!("Not saving {} - a later slot is already saved", signature);
+            return;
+        }
 
-                    warn!(
-                        "Retry {}/{} - Error fetching transaction: {}",
-                        retries, max_retries, e
-                    );
-                    sleep(Duration::from_millis(1000 * retries)).await;
-                }
-            }
+        let target = path.to_string();
+        let saved = tokio::task::spawn_blocking(move || {
+            write_atomic(&target, signature.to_string().as_bytes())
+        })
+        .await;
+        match saved {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Failed to save last signature to {}: {}", path, e),
+            Err(e) => warn!("Failed to save last signature to {}: {}", path, e),
         }
     }
 
@@ -220,9 +489,76 @@ impl TransactionListener {
     }
 }
 
+/// Resolves once `shutdown` is set to `true`; never without a shutdown
+/// channel, or once its sender is gone
+async fn shutdown_signalled(shutdown: &mut Option<watch::Receiver<bool>>) {
+    if let Some(shutdown) = shutdown
+        && shutdown.wait_for(|stop| *stop).await.is_ok()
+    {
+        return;
+    }
+    std::future::pending().await
+}
+
+/// The most recently seen signatures, bounded at `capacity`; the oldest is
+/// forgotten first
+struct SeenSignatures {
+    capacity: usize,
+    set: HashSet<Signature>,
+    order: VecDeque<Signature>,
+}
+
+impl SeenSignatures {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            set: HashSet::with_capacity(capacity + 1),
+            order: VecDeque::with_capacity(capacity + 1),
+        }
+    }
+
+    /// Remember `signature`; `false` if it was already known
+    fn insert(&mut self, signature: Signature) -> bool {
+        if !self.set.insert(signature) {
+            return false;
+        }
+        self.order.push_back(signature);
+
+        if self.order.len() > self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.set.remove(&oldest);
+        }
+
+        true
+    }
+}
+
+/// Read the last processed signature saved by a previous run
+fn load_last_signature(path: &str) -> Option<Signature> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    Signature::from_str(contents.trim()).ok()
+}
+
+/// RPC client for the first configured endpoint (can add failover later)
+fn rpc_client_for(config: &MonitorConfig) -> RpcClient {
+    let commitment = if config.use_confirmed_commitment {
+        CommitmentConfig::confirmed()
+    } else {
+        CommitmentConfig::finalized()
+    };
+
+    RpcClient::new_with_commitment(config.rpc_endpoints[0].clone(), commitment)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use arc_swap::ArcSwap;
+    use solana_client::client_error::{ClientError, ClientErrorKind};
+    use solana_transaction_status::{EncodedTransaction, EncodedTransactionWithStatusMeta};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_deduplication() {
@@ -234,5 +570,273 @@ mod tests {
 
         assert!(!listener.is_duplicate(&sig));
         assert!(listener.is_duplicate(&sig));
+
+        // A full cache forgets the oldest signature, not the newest
+        let newest = Signature::new_unique();
+        for _ in 1..DEDUP_CACHE_SIZE {
+            listener.is_duplicate(&Signature::new_unique());
+        }
+        assert!(!listener.is_duplicate(&newest));
+        assert!(listener.is_duplicate(&newest));
+        assert!(!listener.is_duplicate(&sig));
+    }
+
+    #[test]
+    fn test_dedup_cache_forgets_oldest_first() {
+        let mut seen = SeenSignatures::new(3);
+        let signatures: Vec<Signature> = (0..4).map(|_| Signature::new_unique()).collect();
+
+        for signature in &signatures {
+            assert!(seen.insert(*signature));
+        }
+        assert_eq!(seen.order.len(), 3);
+
+        // The first one was evicted; the rest are still duplicates
+        assert!(!seen.insert(signatures[2]));
+        assert!(!seen.insert(signatures[3]));
+        assert!(seen.insert(signatures[0]));
+        // ...which in turn pushed out the next oldest
+        assert!(seen.insert(signatures[1]));
+    }
+
+    #[test]
+    fn test_refresh_picks_up_reloaded_endpoints() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let shared: SharedConfig = Arc::new(ArcSwap::from_pointee(MonitorConfig::default()));
+        let mut listener = TransactionListener::with_shared_config(Arc::clone(&shared), tx);
+
+        shared.store(Arc::new(MonitorConfig {
+            rpc_endpoints: vec!["https://rotated.example.com".to_string()],
+            ..MonitorConfig::default()
+        }));
+        listener.refresh_config();
+
+        assert_eq!(
+            listener.rpc_endpoint.as_deref(),
+            Some("https://rotated.example.com")
+        );
+    }
+
+    /// Fails with an RPC error a fixed number of times, then succeeds
+    struct FlakyFetcher {
+        failures: AtomicU32,
+        calls: Arc<AtomicU32>,
+    }
+
+    impl TransactionFetcher for FlakyFetcher {
+        fn get_transaction(
+            &self,
+            _signature: &Signature,
+        ) -> MonitorResult<EncodedConfirmedTransactionWithStatusMeta> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err(
+                    ClientError::from(ClientErrorKind::Custom("not found".to_string())).into(),
+                );
+            }
+
+            Ok(EncodedConfirmedTransactionWithStatusMeta {
+                slot: 42,
+                transaction: EncodedTransactionWithStatusMeta {
+                    transaction: EncodedTransaction::LegacyBinary(String::new()),
+                    meta: None,
+                    version: None,
+                },
+                block_time: None,
+            })
+        }
+
+        fn get_signatures_for_address(
+            &self,
+            _address: &Pubkey,
+            _until: Option<Signature>,
+            _limit: usize,
+        ) -> MonitorResult<Vec<Signature>> {
+            Ok(vec![])
+        }
+    }
+
+    /// A wallet history of `signatures` (oldest first); each transaction's
+    /// slot is its position in the history
+    struct HistoryFetcher {
+        signatures: Arc<Mutex<Vec<Signature>>>,
+    }
+
+    impl HistoryFetcher {
+        fn new(signatures: Vec<Signature>) -> Self {
+            Self {
+                signatures: Arc::new(Mutex::new(signatures)),
+            }
+        }
+    }
+
+    impl TransactionFetcher for HistoryFetcher {
+        fn get_transaction(
+            &self,
+            signature: &Signature,
+        ) -> MonitorResult<EncodedConfirmedTransactionWithStatusMeta> {
+            let signatures = self.signatures.lock().unwrap();
+            let slot = signatures.iter().position(|s| s == signature).unwrap() as u64;
+            Ok(EncodedConfirmedTransactionWithStatusMeta {
+                slot,
+                transaction: EncodedTransactionWithStatusMeta {
+                    transaction: EncodedTransaction::LegacyBinary(String::new()),
+                    meta: None,
+                    version: None,
+                },
+                block_time: None,
+            })
+        }
+
+        fn get_signatures_for_address(
+            &self,
+            _address: &Pubkey,
+            until: Option<Signature>,
+            limit: usize,
+        ) -> MonitorResult<Vec<Signature>> {
+            Ok(self
+                .signatures
+                .lock()
+                .unwrap()
+                .iter()
+                .rev()
+                .take_while(|s| Some(**s) != until)
+                .take(limit)
+                .copied()
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_retries_until_success() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let calls = Arc::new(AtomicU32::new(0));
+        let fetcher = FlakyFetcher {
+            failures: AtomicU32::new(2),
+            calls: Arc::clone(&calls),
+        };
+
+        let config = MonitorConfig {
+            fetch_delay_ms: 0,
+            ..MonitorConfig::default()
+        };
+        let listener = TransactionListener::with_fetcher(config, tx, Box::new(fetcher));
+
+        listener
+            .fetch_and_send_transaction(Signature::new_unique())
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(rx.try_recv().unwrap().slot, 42);
+    }
+
+    #[tokio::test]
+    async fn test_catch_up_replays_missed_in_order() {
+        let path = std::env::temp_dir().join(format!("last-sig-{}.txt", Signature::new_unique()));
+        let signatures: Vec<Signature> = (0..6).map(|_| Signature::new_unique()).collect();
+
+        // Processed up to slot 1 last run; slots 2..=5 were missed
+        std::fs::write(&path, signatures[1].to_string()).unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let config = MonitorConfig {
+            catch_up: true,
+            catch_up_limit: 3,
+            last_signature_path: path.to_string_lossy().into_owned(),
+            ..MonitorConfig::default()
+        };
+        let fetcher = HistoryFetcher::new(signatures.clone());
+        let mut listener = TransactionListener::with_fetcher(config, tx, Box::new(fetcher));
+
+        listener.catch_up(&Pubkey::new_unique(), 3).await;
+
+        // Capped at the 3 newest, replayed oldest first
+        let slots: Vec<u64> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|t| t.slot)
+            .collect();
+        assert_eq!(slots, vec![3, 4, 5]);
+        assert_eq!(
+            load_last_signature(&path.to_string_lossy()),
+            Some(signatures[5])
+        );
+
+        // A late transaction from an earlier slot doesn't move it back
+        listener
+            .fetch_and_send_transaction(signatures[4])
+            .await
+            .unwrap();
+        assert_eq!(
+            load_last_signature(&path.to_string_lossy()),
+            Some(signatures[5])
+        );
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_polling_forwards_only_new_signatures() {
+        let wallet = Pubkey::new_unique();
+        let history: Vec<Signature> = (0..3).map(|_| Signature::new_unique()).collect();
+        let fetcher = HistoryFetcher::new(history);
+        let signatures = Arc::clone(&fetcher.signatures);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let config = MonitorConfig {
+            listener_mode: ListenerMode::Polling,
+            fetch_delay_ms: 0,
+            ..MonitorConfig::default()
+        };
+        let mut listener = TransactionListener::with_fetcher(config, tx, Box::new(fetcher));
+
+        // The first poll only notes where the history stands
+        listener.poll_wallets(&[wallet]).await;
+        assert!(rx.try_recv().is_err());
+
+        // Slots 3..=5 are new, but 3 already arrived over the WebSocket
+        let new: Vec<Signature> = (0..3).map(|_| Signature::new_unique()).collect();
+        signatures.lock().unwrap().extend(&new);
+        assert!(!listener.is_duplicate(&new[0]));
+
+        listener.poll_wallets(&[wallet]).await;
+        let slots: Vec<u64> = std::iter::from_fn(|| rx.try_recv().ok())
+            .map(|t| t.slot)
+            .collect();
+        assert_eq!(slots, vec![4, 5]);
+
+        // Nothing new since
+        listener.poll_wallets(&[wallet]).await;
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_start_returns_on_shutdown() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let config = MonitorConfig {
+            listener_mode: ListenerMode::Polling,
+            poll_interval_ms: 10,
+            ..MonitorConfig::default()
+        };
+        let (stop, shutdown) = watch::channel(false);
+        let mut listener =
+            TransactionListener::with_fetcher(config, tx, Box::new(HistoryFetcher::new(vec![])))
+                .with_shutdown(shutdown);
+
+        let wallet = Pubkey::new_unique();
+        let handle = tokio::spawn(async move { listener.start(&[wallet]).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!handle.is_finished());
+
+        stop.send(true).unwrap();
+        let result = tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("listener stopped within the timeout")
+            .unwrap();
+        assert!(result.is_ok());
+
+        // The listener and its sender are gone, so the channel drains and closes
+        assert!(rx.recv().await.is_none());
     }
 }
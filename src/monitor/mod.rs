@@ -1,11 +1,17 @@
 pub mod error;
+pub mod fetcher;
+pub mod finality;
 pub mod listener;
+pub mod retry;
 pub mod websocket;
 
 pub mod parser;
 
 // pub use error::;
 pub use error::{MonitorError, MonitorResult};
+pub use fetcher::TransactionFetcher;
+pub use finality::FinalityChecker;
 pub use listener::TransactionListener;
 pub use parser::TransactionParser;
+pub use retry::{RetryPolicy, retry_with_backoff};
 pub use websocket::WebSocketManager;
@@ -3,13 +3,22 @@ use crate::types::{DexType, TradeSignal};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
 use solana_transaction_status::UiInstruction;
-use std::str::FromStr;
+use std::collections::HashMap;
 use tracing::{debug, warn};
 
+/// Parse Jupiter v6 swap transactions
+///
+/// The instruction names the user's token accounts, not mints, so
+/// `token_mints` (token account → mint, from the transaction's token
+/// balances) resolves them. The runtime records a balance for every token
+/// account a transaction loads, so no account lookup is needed.
+#[allow(clippy::too_many_arguments)]
 pub fn parse_jupiter_swap(
     instructions: &[UiInstruction],
     account_keys: &[Pubkey],
+    token_mints: &HashMap<Pubkey, Pubkey>,
     signature: Signature,
+    slot: u64,
     timestamp: i64,
     priority_fee: u64,
     trader: Pubkey,
@@ -19,7 +28,9 @@ pub fn parse_jupiter_swap(
         if let Some(signal) = try_parse_instruction(
             instruction,
             account_keys,
+            token_mints,
             signature,
+            slot,
             timestamp,
             priority_fee,
             trader,
@@ -31,10 +42,13 @@ pub fn parse_jupiter_swap(
     Ok(None)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn try_parse_instruction(
     instruction: &UiInstruction,
     account_keys: &[Pubkey],
+    token_mints: &HashMap<Pubkey, Pubkey>,
     signature: Signature,
+    slot: u64,
     timestamp: i64,
     priority_fee: u64,
     trader: Pubkey,
@@ -69,10 +83,12 @@ fn try_parse_instruction(
             let source_token_account = account_keys.get(compiled.accounts[1] as usize).copied();
             let dest_token_account = account_keys.get(compiled.accounts[2] as usize).copied();
 
-            if source_token_account.is_none() || dest_token_account.is_none() {
+            let (Some(source_token_account), Some(dest_token_account)) =
+                (source_token_account, dest_token_account)
+            else {
                 warn!("Could not extract token accounts");
                 return Ok(None);
-            }
+            };
 
             // Parse instruction data to extract amounts
             let data = bs58::decode(&compiled.data)
@@ -98,25 +114,20 @@ fn try_parse_instruction(
                 MonitorError::ParseError("Failed to parse minimum_amount_out".to_string())
             })?);
 
-            // For Jupiter, we need to get the actual token mints
-            // This requires additional RPC calls or parsing token account data
-            // For now, we'll use placeholder mints
-            // TODO: Fetch token account info to get actual mints
-
-            let source_mint = source_token_account.unwrap(); // Placeholder
-            let dest_mint = dest_token_account.unwrap(); // Placeholder
-
-            // Calculate slippage in basis points
-            let slippage_bps = if minimum_amount_out > 0 {
-                let expected_out = amount_in; // This should be calculated from price
-                ((expected_out.saturating_sub(minimum_amount_out)) * 10000 / expected_out.max(1))
-                    as u16
-            } else {
-                0
+            let (Some(&source_mint), Some(&dest_mint)) = (
+                token_mints.get(&source_token_account),
+                token_mints.get(&dest_token_account),
+            ) else {
+                warn!(
+                    "Jupiter swap token accounts {} / {} have no known mint",
+                    source_token_account, dest_token_account
+                );
+                return Ok(None);
             };
 
-            let trade_signal = TradeSignal {
+            let mut trade_signal = TradeSignal {
                 signature,
+                slot,
                 timestamp,
                 dex: DexType::Jupiter,
                 source_mint,
@@ -124,11 +135,12 @@ fn try_parse_instruction(
                 amount_in,
                 amount_out: amount_in, // Should be calculated
                 minimum_amount_out,
-                slippage_bps,
+                slippage_bps: 0,
                 priority_fee_lamports: priority_fee,
                 accounts: account_keys.to_vec(),
                 trader,
             };
+            trade_signal.slippage_bps = trade_signal.calculate_slippage();
 
             debug!(
                 "Parsed Jupiter swap: {} -> {}",
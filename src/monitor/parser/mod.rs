@@ -2,26 +2,46 @@ use crate::monitor::error::{MonitorError, MonitorResult};
 use crate::types::{DexType, TradeSignal, program_ids};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
+use solana_transaction_status::option_serializer::OptionSerializer;
 use solana_transaction_status::{
     EncodedConfirmedTransactionWithStatusMeta, UiInstruction, UiMessage, UiParsedInstruction,
-    UiParsedMessage, UiTransaction,
+    UiParsedMessage, UiTransactionStatusMeta, UiTransactionTokenBalance,
 };
+use std::collections::HashMap;
 use std::str::FromStr;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 
 pub mod jupiter;
 pub mod orca;
 pub mod raydium;
+pub mod raydium_clmm;
 
 /// Main transaction parser that routes to specific DEX parsers
 pub struct TransactionParser {
-    target_wallet: Pubkey,
+    /// Wallets whose swaps are parsed; the first one involved is the trader
+    target_wallets: Vec<Pubkey>,
+    /// Attribute swaps to the DEX found in inner instructions over a top-level one
+    prefer_inner_dex: bool,
 }
 
 impl TransactionParser {
     /// Create a new transaction parser
     pub fn new(target_wallet: Pubkey) -> Self {
-        Self { target_wallet }
+        Self::for_wallets(vec![target_wallet])
+    }
+
+    /// Create a transaction parser that follows several target wallets
+    pub fn for_wallets(target_wallets: Vec<Pubkey>) -> Self {
+        Self {
+            target_wallets,
+            prefer_inner_dex: false,
+        }
+    }
+
+    /// Prefer the DEX found in inner instructions (see [`identify_dex`])
+    pub fn prefer_inner_dex(mut self, prefer: bool) -> Self {
+        self.prefer_inner_dex = prefer;
+        self
     }
 
     /// Parse a transaction and extract trade signal if it's a swap
@@ -32,7 +52,7 @@ impl TransactionParser {
         info!("Parsing transaction...");
 
         // Extract transaction and metadata
-        let ui_transaction = match transaction.transaction.transaction {
+        let ui_transaction = match &transaction.transaction.transaction {
             solana_transaction_status::EncodedTransaction::Json(tx) => tx,
             _ => {
                 warn!("Transaction not in JSON format");
@@ -41,7 +61,7 @@ impl TransactionParser {
         };
 
         // Get message
-        let message = match ui_transaction.message {
+        let message = match &ui_transaction.message {
             UiMessage::Parsed(msg) => msg,
             _ => {
                 warn!("Message not parsed");
@@ -61,39 +81,52 @@ impl TransactionParser {
 
         // Get timestamp
         let timestamp = transaction.block_time.unwrap_or(0);
+        let slot = transaction.slot;
 
-        // Extract all account keys
-        let account_keys: Vec<Pubkey> = message
-            .account_keys
-            .iter()
-            .filter_map(|key| match Pubkey::from_str(&key.pubkey) {
-                Ok(pubkey) => Some(pubkey),
-                Err(_) => {
-                    warn!("Invalid pubkey: {}", key.pubkey);
-                    None
-                }
-            })
-            .collect();
+        // Extract all account keys, including lookup table ones
+        let account_keys = resolve_account_keys(
+            &ui_transaction.message,
+            transaction.transaction.meta.as_ref(),
+        );
         info!("📊 Transaction Details:");
         info!("   • Signature: {}", signature);
         info!("   • Instructions: {}", message.instructions.len());
         info!("   • Solscan: https://solscan.io/tx/{}", signature);
         info!("   • Accounts: {}", account_keys.len());
+        info!("   • Slot: {}", slot);
         info!("   • Timestamp: {}", timestamp);
 
-        // Check if target wallet is involved
-        if !account_keys.contains(&self.target_wallet) {
-            debug!("Target wallet not involved in transaction");
+        // Check if a target wallet is involved
+        let Some(trader) = self
+            .target_wallets
+            .iter()
+            .copied()
+            .find(|wallet| account_keys.contains(wallet))
+        else {
+            debug!("No target wallet involved in transaction");
             return Ok(None);
-        }
+        };
 
-        // Identify DEX type from instructions
-        let dex_type = self.identify_dex(&message.instructions, &account_keys)?;
+        // Identify DEX type from instructions, including CPI calls
+        let inner = inner_instructions(transaction.transaction.meta.as_ref());
+        let (dex_type, from_inner) = identify_dex(
+            &message.instructions,
+            &inner,
+            &account_keys,
+            self.prefer_inner_dex,
+        );
+
+        // A DEX reached through CPI is parsed from its inner instructions
+        let instructions: Vec<UiInstruction> = if from_inner {
+            info!("🔎 {} found in inner instructions", dex_type);
+            message.instructions.iter().chain(&inner).cloned().collect()
+        } else {
+            message.instructions.clone()
+        };
 
         if dex_type == DexType::Unknown {
             // Log what program IDs we found
-            let program_ids: Vec<String> = message
-                .instructions
+            let program_ids: Vec<String> = instructions
                 .iter()
                 .filter_map(|inst| match inst {
                     UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => {
@@ -116,34 +149,63 @@ impl TransactionParser {
         info!("Detected {} swap", dex_type);
 
         // Extract priority fee
-        let priority_fee = self.extract_priority_fee(&message);
+        let priority_fee = extract_priority_fee(message);
+
+        // Observed token account changes (net of any transfer fees)
+        let token_deltas =
+            token_account_deltas(transaction.transaction.meta.as_ref(), &account_keys);
+        let token_mints = token_account_mints(transaction.transaction.meta.as_ref(), &account_keys);
 
         // Route to appropriate parser based on DEX type
         let trade_signal = match dex_type {
             DexType::Jupiter => jupiter::parse_jupiter_swap(
-                &message.instructions,
+                &instructions,
                 &account_keys,
+                &token_mints,
                 signature,
+                slot,
                 timestamp,
                 priority_fee,
-                self.target_wallet,
+                trader,
             )?,
             DexType::Raydium => raydium::parse_raydium_swap(
-                &message.instructions,
+                &instructions,
                 &account_keys,
+                &token_deltas,
+                &token_mints,
                 signature,
+                slot,
                 timestamp,
                 priority_fee,
-                self.target_wallet,
+                trader,
+            )?,
+            DexType::RaydiumClmm => raydium_clmm::parse_raydium_clmm_swap(
+                &instructions,
+                &account_keys,
+                &token_deltas,
+                signature,
+                slot,
+                timestamp,
+                priority_fee,
+                trader,
             )?,
             DexType::Orca => orca::parse_orca_swap(
-                &message.instructions,
+                &instructions,
                 &account_keys,
+                &token_deltas,
+                &token_mints,
                 signature,
+                slot,
                 timestamp,
                 priority_fee,
-                self.target_wallet,
+                trader,
             )?,
+            DexType::Meteora | DexType::Phoenix => {
+                // Detection only for now; the universal parser still picks
+                // these swaps up from balance changes
+                warn!("No instruction parser for {} yet", dex_type);
+                None
+            }
             DexType::Unknown => None,
         };
 
@@ -156,7 +218,7 @@ impl TransactionParser {
             info!("   To: {}", signal.destination_mint);
             info!("   Amount In: {}", signal.amount_in);
             info!("   Min Amount Out: {}", signal.minimum_amount_out);
-            info!("   Slippage: {:.2}%", signal.slippage_bps as f64 / 100.0);
+            info!("   Slippage: {:.2}%", signal.slippage_percent());
             info!("   Priority Fee: {} lamports", signal.priority_fee_lamports);
             info!("");
             info!("🔗 Links:");
@@ -177,55 +239,230 @@ impl TransactionParser {
 
         Ok(trade_signal)
     }
+}
 
-    /// Identify which DEX is being used based on program IDs in instructions
-    fn identify_dex(
-        &self,
-        instructions: &[UiInstruction],
-        account_keys: &[Pubkey],
-    ) -> MonitorResult<DexType> {
-        let jupiter_id = program_ids::jupiter_v6();
-        let raydium_id = program_ids::raydium_v4();
-        let orca_id = program_ids::orca_whirlpool();
-
-        for instruction in instructions {
-            let program_id = match instruction {
-                UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => {
-                    // parsed.program is a String, not a HashMap
-                    // Use the program field directly
-                    Pubkey::from_str(&parsed.program).ok()
+/// Every account a transaction references, in index order: the message's
+/// static keys, then the addresses a v0 transaction loaded from lookup tables
+/// (writable, then readonly). Compiled instructions and token balances index
+/// into this full list.
+///
+/// `jsonParsed` messages usually list the loaded addresses already, so only
+/// the ones missing are appended.
+pub fn resolve_account_keys(
+    message: &UiMessage,
+    meta: Option<&UiTransactionStatusMeta>,
+) -> Vec<Pubkey> {
+    let static_keys: Vec<&str> = match message {
+        UiMessage::Parsed(message) => message
+            .account_keys
+            .iter()
+            .map(|key| key.pubkey.as_str())
+            .collect(),
+        UiMessage::Raw(message) => message.account_keys.iter().map(String::as_str).collect(),
+    };
+
+    let mut account_keys: Vec<Pubkey> = static_keys
+        .into_iter()
+        .filter_map(|key| match Pubkey::from_str(key) {
+            Ok(pubkey) => Some(pubkey),
+            Err(_) => {
+                warn!("Invalid pubkey: {}", key);
+                None
+            }
+        })
+        .collect();
+
+    if let Some(OptionSerializer::Some(loaded)) = meta.map(|m| &m.loaded_addresses) {
+        for address in loaded.writable.iter().chain(&loaded.readonly) {
+            match Pubkey::from_str(address) {
+                Ok(pubkey) if !account_keys.contains(&pubkey) => account_keys.push(pubkey),
+                Ok(_) => {}
+                Err(_) => warn!("Invalid loaded address: {}", address),
+            }
+        }
+    }
+
+    account_keys
+}
+
+/// Top-level instructions of a message, in either encoding
+pub fn message_instructions(message: &UiMessage) -> Vec<UiInstruction> {
+    match message {
+        UiMessage::Parsed(message) => message.instructions.clone(),
+        UiMessage::Raw(message) => message
+            .instructions
+            .iter()
+            .cloned()
+            .map(UiInstruction::Compiled)
+            .collect(),
+    }
+}
+
+/// Program invoked by an instruction
+pub fn instruction_program_id(
+    instruction: &UiInstruction,
+    account_keys: &[Pubkey],
+) -> Option<Pubkey> {
+    match instruction {
+        UiInstruction::Parsed(UiParsedInstruction::Parsed(parsed)) => {
+            Pubkey::from_str(&parsed.program_id).ok()
+        }
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(partial)) => {
+            Pubkey::from_str(&partial.program_id).ok()
+        }
+        UiInstruction::Compiled(compiled) => account_keys
+            .get(compiled.program_id_index as usize)
+            .copied(),
+    }
+}
+
+/// All inner (CPI) instructions of a transaction, in execution order
+pub fn inner_instructions(meta: Option<&UiTransactionStatusMeta>) -> Vec<UiInstruction> {
+    match meta.map(|m| &m.inner_instructions) {
+        Some(OptionSerializer::Some(inner)) => inner
+            .iter()
+            .flat_map(|set| set.instructions.iter().cloned())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// First known DEX among the instructions' programs
+fn first_dex(instructions: &[UiInstruction], account_keys: &[Pubkey]) -> Option<DexType> {
+    instructions
+        .iter()
+        .filter_map(|ix| instruction_program_id(ix, account_keys))
+        .find_map(|program_id| DexType::from_program_id(&program_id))
+}
+
+/// Identify the DEX from top-level and inner instructions.
+///
+/// Aggregators and routers call the actual DEX through CPI, so it only shows
+/// up in the inner instructions. A top-level match wins unless
+/// `prefer_inner` is set (e.g. to attribute Jupiter routes to the pool that
+/// filled them). Returns the DEX and whether it came from an inner instruction.
+pub fn identify_dex(
+    instructions: &[UiInstruction],
+    inner: &[UiInstruction],
+    account_keys: &[Pubkey],
+    prefer_inner: bool,
+) -> (DexType, bool) {
+    let top_level = first_dex(instructions, account_keys);
+    let nested = first_dex(inner, account_keys);
+
+    match (top_level, nested) {
+        (Some(_), Some(dex)) if prefer_inner => (dex, true),
+        (Some(dex), _) => (dex, false),
+        (None, Some(dex)) => (dex, true),
+        (None, None) => (DexType::Unknown, false),
+    }
+}
+
+/// Compute unit limit assumed when a transaction sets a price but no limit
+const DEFAULT_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+
+/// ComputeBudget instruction tags
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Priority fee paid by a transaction, in lamports
+///
+/// Decoded from its ComputeBudget instructions as unit limit × unit price
+/// (micro-lamports per unit). No unit price means no priority fee.
+pub fn extract_priority_fee(message: &UiParsedMessage) -> u64 {
+    let account_keys: Vec<Pubkey> = message
+        .account_keys
+        .iter()
+        .filter_map(|key| Pubkey::from_str(&key.pubkey).ok())
+        .collect();
+    let compute_budget = program_ids::compute_budget();
+
+    let mut unit_limit = None;
+    let mut unit_price = None;
+    for instruction in &message.instructions {
+        if instruction_program_id(instruction, &account_keys) != Some(compute_budget) {
+            continue;
+        }
+        let data = match instruction {
+            UiInstruction::Compiled(compiled) => &compiled.data,
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => &decoded.data,
+            _ => continue,
+        };
+        let Ok(data) = bs58::decode(data).into_vec() else {
+            continue;
+        };
+
+        match data.split_first() {
+            Some((&SET_COMPUTE_UNIT_LIMIT, rest)) => {
+                if let Some(bytes) = rest.get(..4) {
+                    unit_limit = Some(u32::from_le_bytes(bytes.try_into().unwrap()) as u64);
                 }
-                UiInstruction::Compiled(compiled) => {
-                    // For compiled instructions, use program_id_index
-                    account_keys
-                        .get(compiled.program_id_index as usize)
-                        .copied()
+            }
+            Some((&SET_COMPUTE_UNIT_PRICE, rest)) => {
+                if let Some(bytes) = rest.get(..8) {
+                    unit_price = Some(u64::from_le_bytes(bytes.try_into().unwrap()));
                 }
-                _ => None,
-            };
-
-            if let Some(pid) = program_id {
-                if pid == jupiter_id {
-                    return Ok(DexType::Jupiter);
-                } else if pid == raydium_id {
-                    return Ok(DexType::Raydium);
-                } else if pid == orca_id {
-                    return Ok(DexType::Orca);
+            }
+            _ => {}
+        }
+    }
+
+    let Some(unit_price) = unit_price else {
+        return 0;
+    };
+    let unit_limit = unit_limit.unwrap_or(DEFAULT_COMPUTE_UNIT_LIMIT);
+    (unit_limit as u128 * unit_price as u128 / 1_000_000) as u64
+}
+
+/// Net balance change of every token account in the transaction
+fn token_account_deltas(
+    meta: Option<&UiTransactionStatusMeta>,
+    account_keys: &[Pubkey],
+) -> HashMap<Pubkey, i128> {
+    let mut deltas = HashMap::new();
+    let Some(meta) = meta else {
+        return deltas;
+    };
+
+    let mut apply = |balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>, sign: i128| {
+        if let OptionSerializer::Some(balances) = balances {
+            for balance in balances {
+                let account = account_keys.get(balance.account_index as usize);
+                let amount = balance.ui_token_amount.amount.parse::<u64>();
+                if let (Some(account), Ok(amount)) = (account, amount) {
+                    *deltas.entry(*account).or_insert(0) += sign * amount as i128;
                 }
             }
         }
+    };
 
-        Ok(DexType::Unknown)
-    }
+    apply(&meta.pre_token_balances, -1);
+    apply(&meta.post_token_balances, 1);
+    deltas
+}
 
-    fn extract_priority_fee(&self, message: &UiParsedMessage) -> u64 {
-        //                              ^^^^^^^^^^^^^^^^^^^^^
-        // Changed from &UiTransaction to &UiParsedMessage
+/// Mint of every token account with a balance in the transaction
+fn token_account_mints(
+    meta: Option<&UiTransactionStatusMeta>,
+    account_keys: &[Pubkey],
+) -> HashMap<Pubkey, Pubkey> {
+    let mut mints = HashMap::new();
+    let Some(meta) = meta else {
+        return mints;
+    };
 
-        // Parse ComputeBudget instructions from message.instructions
-        // TODO: Implement priority fee extraction
-        0
+    for balances in [&meta.pre_token_balances, &meta.post_token_balances] {
+        if let OptionSerializer::Some(balances) = balances {
+            for balance in balances {
+                let account = account_keys.get(balance.account_index as usize);
+                let mint = Pubkey::from_str(&balance.mint);
+                if let (Some(account), Ok(mint)) = (account, mint) {
+                    mints.insert(*account, mint);
+                }
+            }
+        }
     }
+    mints
 }
 
 #[cfg(test)]
@@ -235,6 +472,306 @@ mod tests {
     #[test]
     fn test_parser_creation() {
         let parser = TransactionParser::new(Pubkey::default());
-        assert_eq!(parser.target_wallet, Pubkey::default());
+        assert_eq!(parser.target_wallets, vec![Pubkey::default()]);
+    }
+
+    #[test]
+    fn test_identify_dex_from_inner_instructions() {
+        use crate::types::program_ids;
+        use solana_transaction_status::UiCompiledInstruction;
+
+        let account_keys = vec![
+            Pubkey::new_unique(),
+            program_ids::jupiter_v6(),
+            program_ids::raydium_v4(),
+            Pubkey::new_unique(), // Some router we don't know
+        ];
+        let call = |program_id_index: u8| {
+            UiInstruction::Compiled(UiCompiledInstruction {
+                program_id_index,
+                accounts: vec![],
+                data: String::new(),
+                stack_height: None,
+            })
+        };
+
+        // Jupiter wrapper on top, Raydium pool called through CPI
+        let top_level = vec![call(1)];
+        let inner = vec![call(2)];
+        assert_eq!(
+            identify_dex(&top_level, &inner, &account_keys, false),
+            (DexType::Jupiter, false)
+        );
+        assert_eq!(
+            identify_dex(&top_level, &inner, &account_keys, true),
+            (DexType::Raydium, true)
+        );
+
+        // Unknown router on top: the inner DEX is used either way
+        assert_eq!(
+            identify_dex(&[call(3)], &inner, &account_keys, false),
+            (DexType::Raydium, true)
+        );
+        assert_eq!(
+            identify_dex(&[call(3)], &[], &account_keys, false),
+            (DexType::Unknown, false)
+        );
+    }
+
+    #[test]
+    fn test_dex_found_through_lookup_table() {
+        let wallet = Pubkey::new_unique();
+        let router = Pubkey::new_unique();
+        let pool = Pubkey::new_unique();
+
+        // A v0 router transaction: the Raydium program it calls through CPI
+        // (index 3) is only in a lookup table
+        let transaction: EncodedConfirmedTransactionWithStatusMeta =
+            serde_json::from_value(serde_json::json!({
+                "slot": 1,
+                "blockTime": null,
+                "version": 0,
+                "transaction": {
+                    "signatures": [Signature::new_unique().to_string()],
+                    "message": {
+                        "header": {
+                            "numRequiredSignatures": 1,
+                            "numReadonlySignedAccounts": 0,
+                            "numReadonlyUnsignedAccounts": 1
+                        },
+                        "accountKeys": [wallet.to_string(), router.to_string()],
+                        "recentBlockhash": "11111111111111111111111111111111",
+                        "instructions": [
+                            {"programIdIndex": 1, "accounts": [0, 2, 3], "data": "", "stackHeight": null}
+                        ],
+                        "addressTableLookups": [{
+                            "accountKey": Pubkey::new_unique().to_string(),
+                            "writableIndexes": [0],
+                            "readonlyIndexes": [1]
+                        }]
+                    }
+                },
+                "meta": {
+                    "err": null,
+                    "status": {"Ok": null},
+                    "fee": 5000,
+                    "preBalances": [0, 0, 0, 0],
+                    "postBalances": [0, 0, 0, 0],
+                    "innerInstructions": [{
+                        "index": 0,
+                        "instructions": [
+                            {"programIdIndex": 3, "accounts": [2], "data": "", "stackHeight": 2}
+                        ]
+                    }],
+                    "loadedAddresses": {
+                        "writable": [pool.to_string()],
+                        "readonly": [program_ids::raydium_v4().to_string()]
+                    }
+                }
+            }))
+            .unwrap();
+
+        let solana_transaction_status::EncodedTransaction::Json(tx) =
+            &transaction.transaction.transaction
+        else {
+            panic!("expected a JSON transaction");
+        };
+        let meta = transaction.transaction.meta.as_ref();
+        let account_keys = resolve_account_keys(&tx.message, meta);
+        assert_eq!(
+            account_keys,
+            vec![wallet, router, pool, program_ids::raydium_v4()]
+        );
+
+        let UiMessage::Raw(message) = &tx.message else {
+            panic!("expected a raw message");
+        };
+        let top_level: Vec<UiInstruction> = message
+            .instructions
+            .iter()
+            .cloned()
+            .map(UiInstruction::Compiled)
+            .collect();
+        assert_eq!(
+            identify_dex(&top_level, &inner_instructions(meta), &account_keys, false),
+            (DexType::Raydium, true)
+        );
+
+        // Without the loaded addresses the program index is out of range
+        assert_eq!(
+            identify_dex(
+                &top_level,
+                &inner_instructions(meta),
+                &account_keys[..2],
+                false
+            ),
+            (DexType::Unknown, false)
+        );
+    }
+
+    /// A parsed message whose instructions are the given ComputeBudget calls
+    fn compute_budget_message(calls: &[Vec<u8>]) -> UiParsedMessage {
+        let instructions: Vec<_> = calls
+            .iter()
+            .map(|data| {
+                serde_json::json!({
+                    "programId": "ComputeBudget111111111111111111111111111111",
+                    "accounts": [],
+                    "data": bs58::encode(data).into_string(),
+                    "stackHeight": null
+                })
+            })
+            .collect();
+
+        serde_json::from_value(serde_json::json!({
+            "accountKeys": [{
+                "pubkey": Pubkey::new_unique().to_string(),
+                "writable": true,
+                "signer": true,
+                "source": "transaction"
+            }],
+            "recentBlockhash": "11111111111111111111111111111111",
+            "instructions": instructions
+        }))
+        .unwrap()
+    }
+
+    fn set_limit(units: u32) -> Vec<u8> {
+        let mut data = vec![SET_COMPUTE_UNIT_LIMIT];
+        data.extend_from_slice(&units.to_le_bytes());
+        data
+    }
+
+    fn set_price(micro_lamports: u64) -> Vec<u8> {
+        let mut data = vec![SET_COMPUTE_UNIT_PRICE];
+        data.extend_from_slice(&micro_lamports.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_extract_priority_fee() {
+        // 400k CU at 50_000 micro-lamports = 20_000 lamports
+        let message = compute_budget_message(&[set_limit(400_000), set_price(50_000)]);
+        assert_eq!(extract_priority_fee(&message), 20_000);
+
+        // Order doesn't matter
+        let message = compute_budget_message(&[set_price(50_000), set_limit(400_000)]);
+        assert_eq!(extract_priority_fee(&message), 20_000);
+
+        // Price only: the default 200k CU limit applies
+        let message = compute_budget_message(&[set_price(1_000_000)]);
+        assert_eq!(extract_priority_fee(&message), 200_000);
+
+        // A limit without a price pays no priority fee
+        let message = compute_budget_message(&[set_limit(400_000)]);
+        assert_eq!(extract_priority_fee(&message), 0);
+        assert_eq!(extract_priority_fee(&compute_budget_message(&[])), 0);
+    }
+
+    #[test]
+    fn test_extract_priority_fee_from_compiled_instructions() {
+        use solana_transaction_status::UiCompiledInstruction;
+
+        let mut message = compute_budget_message(&[]);
+        message.account_keys.push(message.account_keys[0].clone());
+        message.account_keys[1].pubkey = program_ids::compute_budget().to_string();
+        for data in [set_limit(1_000_000), set_price(3)] {
+            message
+                .instructions
+                .push(UiInstruction::Compiled(UiCompiledInstruction {
+                    program_id_index: 1,
+                    accounts: vec![],
+                    data: bs58::encode(data).into_string(),
+                    stack_height: None,
+                }));
+        }
+
+        assert_eq!(extract_priority_fee(&message), 3);
+    }
+
+    #[test]
+    fn test_jupiter_swap_resolves_mints_from_meta() {
+        let wallet = Pubkey::new_unique();
+        let (source_account, dest_account) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (wsol, bonk) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let token_program = "TokenkegQfeZyiNwAJbNk3W1ULSwdpnGW4GqUYFZsWr";
+
+        // discriminator, amount_in 1_000_000, minimum_amount_out 990_000
+        let mut data = vec![0u8; 8];
+        data.extend_from_slice(&1_000_000u64.to_le_bytes());
+        data.extend_from_slice(&990_000u64.to_le_bytes());
+
+        let key = |pubkey: String, signer: bool| {
+            serde_json::json!({
+                "pubkey": pubkey,
+                "writable": true,
+                "signer": signer,
+                "source": "transaction"
+            })
+        };
+        let balance = |index: u8, mint: Pubkey, amount: &str| {
+            serde_json::json!({
+                "accountIndex": index,
+                "mint": mint.to_string(),
+                "uiTokenAmount": {
+                    "uiAmount": null,
+                    "decimals": 6,
+                    "amount": amount,
+                    "uiAmountString": "0"
+                },
+                "owner": wallet.to_string(),
+                "programId": token_program
+            })
+        };
+        let transaction: EncodedConfirmedTransactionWithStatusMeta =
+            serde_json::from_value(serde_json::json!({
+                "slot": 1,
+                "blockTime": null,
+                "transaction": {
+                    "signatures": [Signature::new_unique().to_string()],
+                    "message": {
+                        "accountKeys": [
+                            key(wallet.to_string(), true),
+                            key(source_account.to_string(), false),
+                            key(dest_account.to_string(), false),
+                            key(token_program.to_string(), false),
+                            key(program_ids::jupiter_v6().to_string(), false)
+                        ],
+                        "recentBlockhash": "11111111111111111111111111111111",
+                        "instructions": [{
+                            "programIdIndex": 4,
+                            "accounts": [3, 1, 2],
+                            "data": bs58::encode(&data).into_string(),
+                            "stackHeight": null
+                        }]
+                    }
+                },
+                "meta": {
+                    "err": null,
+                    "status": {"Ok": null},
+                    "fee": 5000,
+                    "preBalances": [0, 0, 0, 0, 0],
+                    "postBalances": [0, 0, 0, 0, 0],
+                    "preTokenBalances": [
+                        balance(1, wsol, "1000000"),
+                        balance(2, bonk, "0")
+                    ],
+                    "postTokenBalances": [
+                        balance(1, wsol, "0"),
+                        balance(2, bonk, "995000")
+                    ]
+                }
+            }))
+            .unwrap();
+
+        let signal = TransactionParser::new(wallet)
+            .parse(transaction)
+            .unwrap()
+            .expect("Jupiter swap parsed");
+        assert_eq!(signal.dex, DexType::Jupiter);
+        assert_eq!(signal.source_mint, wsol);
+        assert_eq!(signal.destination_mint, bonk);
+        assert_eq!(signal.amount_in, 1_000_000);
+        assert_eq!(signal.minimum_amount_out, 990_000);
     }
 }
@@ -1,35 +1,484 @@
-use crate::monitor::error::MonitorResult;
-use crate::types::TradeSignal;
+use crate::decision::slippage::slippage_bps;
+use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::types::{DexType, TradeSignal, program_ids};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
-use solana_transaction_status::UiInstruction;
+use solana_transaction_status::{UiInstruction, UiParsedInstruction};
+use std::collections::HashMap;
+use std::str::FromStr;
 use tracing::{debug, warn};
 
+/// Anchor discriminator for Whirlpool `swap`
+const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+
+/// Anchor discriminator for Whirlpool `swap_v2`
+const SWAP_V2_DISCRIMINATOR: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+/// discriminator + amount + other_amount_threshold + sqrt_price_limit (u128)
+/// + amount_specified_is_input + a_to_b (same for `swap` and `swap_v2`)
+const SWAP_MIN_DATA_LEN: usize = 8 + 8 + 8 + 16 + 1 + 1;
+
+/// `swap` account layout (indices into the instruction's accounts). It names
+/// no mints; they're resolved from the transaction's token balances.
+const V1_OWNER_ACCOUNT_A: usize = 3;
+const V1_OWNER_ACCOUNT_B: usize = 5;
+const V1_MIN_ACCOUNTS: usize = 11;
+
+/// `swap_v2` account layout (indices into the instruction's accounts)
+const V2_TOKEN_PROGRAM_A: usize = 0;
+const V2_TOKEN_PROGRAM_B: usize = 1;
+const V2_MINT_A: usize = 5;
+const V2_MINT_B: usize = 6;
+const V2_OWNER_ACCOUNT_A: usize = 7;
+const V2_OWNER_ACCOUNT_B: usize = 9;
+const V2_MIN_ACCOUNTS: usize = 11;
+
 /// Parse Orca Whirlpool swap transactions
+///
+/// `token_deltas` maps token accounts to their observed balance change in
+/// the transaction. For Token-2022 mints with a transfer fee the amount that
+/// lands in the trader's account is less than what the pool sends, so the
+/// observed change is preferred over the instruction amounts when present.
+/// `token_mints` (token account → mint) resolves the mints of `swap`, which
+/// unlike `swap_v2` doesn't list them.
+#[allow(clippy::too_many_arguments)]
 pub fn parse_orca_swap(
-    _instructions: &[UiInstruction],
-    _account_keys: &[Pubkey],
-    _signature: Signature,
-    _timestamp: i64,
-    _priority_fee: u64,
-    _trader: Pubkey,
+    instructions: &[UiInstruction],
+    account_keys: &[Pubkey],
+    token_deltas: &HashMap<Pubkey, i128>,
+    token_mints: &HashMap<Pubkey, Pubkey>,
+    signature: Signature,
+    slot: u64,
+    timestamp: i64,
+    priority_fee: u64,
+    trader: Pubkey,
 ) -> MonitorResult<Option<TradeSignal>> {
     debug!("Parsing Orca swap");
-    
-    // TODO: Implement Orca Whirlpool-specific parsing
-    // Orca Whirlpool has unique concentrated liquidity model
-    // Requires different parsing approach
-    
-    warn!("Orca parsing not yet implemented");
+
+    for instruction in instructions {
+        let Some((accounts, data)) = decode_whirlpool_instruction(instruction, account_keys)?
+        else {
+            continue;
+        };
+
+        let swap = if data.starts_with(&SWAP_V2_DISCRIMINATOR) {
+            parse_swap_v2(&accounts, &data, token_deltas)?
+        } else if data.starts_with(&SWAP_DISCRIMINATOR) {
+            parse_swap_v1(&accounts, &data, token_deltas, token_mints)?
+        } else {
+            continue;
+        };
+
+        return Ok(swap.map(|swap| TradeSignal {
+            signature,
+            slot,
+            timestamp,
+            dex: DexType::Orca,
+            source_mint: swap.source_mint,
+            destination_mint: swap.destination_mint,
+            amount_in: swap.amount_in,
+            amount_out: swap.amount_out,
+            minimum_amount_out: swap.minimum_amount_out,
+            slippage_bps: slippage_bps(swap.amount_out, swap.minimum_amount_out),
+            priority_fee_lamports: priority_fee,
+            accounts: account_keys.to_vec(),
+            trader,
+        }));
+    }
+
+    warn!("No supported Orca swap instruction found");
     Ok(None)
 }
 
+/// Amounts and mints decoded from a Whirlpool swap
+struct WhirlpoolSwap {
+    source_mint: Pubkey,
+    destination_mint: Pubkey,
+    amount_in: u64,
+    amount_out: u64,
+    minimum_amount_out: u64,
+}
+
+/// Return the accounts and data of a Whirlpool instruction, or `None` for
+/// instructions of other programs
+fn decode_whirlpool_instruction(
+    instruction: &UiInstruction,
+    account_keys: &[Pubkey],
+) -> MonitorResult<Option<(Vec<Pubkey>, Vec<u8>)>> {
+    let whirlpool = program_ids::orca_whirlpool();
+
+    let (accounts, data) = match instruction {
+        UiInstruction::Compiled(compiled) => {
+            if account_keys.get(compiled.program_id_index as usize) != Some(&whirlpool) {
+                return Ok(None);
+            }
+            let accounts = compiled
+                .accounts
+                .iter()
+                .filter_map(|&i| account_keys.get(i as usize).copied())
+                .collect();
+            (accounts, &compiled.data)
+        }
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => {
+            if decoded.program_id != whirlpool.to_string() {
+                return Ok(None);
+            }
+            let accounts = decoded
+                .accounts
+                .iter()
+                .filter_map(|a| Pubkey::from_str(a).ok())
+                .collect();
+            (accounts, &decoded.data)
+        }
+        _ => return Ok(None),
+    };
+
+    let data = bs58::decode(data)
+        .into_vec()
+        .map_err(|e| MonitorError::ParseError(format!("Failed to decode data: {}", e)))?;
+
+    Ok(Some((accounts, data)))
+}
+
+/// Arguments shared by `swap` and `swap_v2`
+struct SwapArgs {
+    amount: u64,
+    other_amount_threshold: u64,
+    amount_specified_is_input: bool,
+    a_to_b: bool,
+}
+
+impl SwapArgs {
+    /// Decode from instruction data at least `SWAP_MIN_DATA_LEN` long
+    fn decode(data: &[u8]) -> MonitorResult<Self> {
+        let read_u64 = |offset: usize| {
+            data[offset..offset + 8]
+                .try_into()
+                .map(u64::from_le_bytes)
+                .map_err(|_| MonitorError::ParseError("Failed to parse swap amount".to_string()))
+        };
+
+        Ok(Self {
+            amount: read_u64(8)?,
+            other_amount_threshold: read_u64(16)?,
+            amount_specified_is_input: data[40] != 0,
+            a_to_b: data[41] != 0,
+        })
+    }
+
+    /// (amount in, amount out, minimum amount out) per the instruction.
+    /// Exact-in: `amount` is spent, the threshold is the minimum out.
+    /// Exact-out: `amount` is received, the threshold is the maximum in.
+    fn amounts(&self) -> (u64, u64, u64) {
+        if self.amount_specified_is_input {
+            (
+                self.amount,
+                self.other_amount_threshold,
+                self.other_amount_threshold,
+            )
+        } else {
+            (self.other_amount_threshold, self.amount, self.amount)
+        }
+    }
+}
+
+/// Decode `swap`, the original layout without mints or Token-2022 support
+fn parse_swap_v1(
+    accounts: &[Pubkey],
+    data: &[u8],
+    token_deltas: &HashMap<Pubkey, i128>,
+    token_mints: &HashMap<Pubkey, Pubkey>,
+) -> MonitorResult<Option<WhirlpoolSwap>> {
+    if data.len() < SWAP_MIN_DATA_LEN || accounts.len() < V1_MIN_ACCOUNTS {
+        warn!("Orca swap instruction too short");
+        return Ok(None);
+    }
+    let args = SwapArgs::decode(data)?;
+
+    let (account_a, account_b) = (accounts[V1_OWNER_ACCOUNT_A], accounts[V1_OWNER_ACCOUNT_B]);
+    let (source_account, destination_account) = if args.a_to_b {
+        (account_a, account_b)
+    } else {
+        (account_b, account_a)
+    };
+
+    let (Some(&source_mint), Some(&destination_mint)) = (
+        token_mints.get(&source_account),
+        token_mints.get(&destination_account),
+    ) else {
+        warn!(
+            "Orca swap token accounts {} / {} have no known mint",
+            source_account, destination_account
+        );
+        return Ok(None);
+    };
+
+    let (mut amount_in, mut amount_out, minimum_amount_out) = args.amounts();
+    if let Some(&spent) = token_deltas.get(&source_account)
+        && spent < 0
+    {
+        amount_in = spent.unsigned_abs() as u64;
+    }
+    if let Some(&received) = token_deltas.get(&destination_account)
+        && received > 0
+    {
+        amount_out = received as u64;
+    }
+
+    Ok(Some(WhirlpoolSwap {
+        source_mint,
+        destination_mint,
+        amount_in,
+        amount_out,
+        minimum_amount_out,
+    }))
+}
+
+/// Decode `swap_v2`, which carries both mints and token programs so
+/// Token-2022 (transfer-fee) mints can be recognized
+fn parse_swap_v2(
+    accounts: &[Pubkey],
+    data: &[u8],
+    token_deltas: &HashMap<Pubkey, i128>,
+) -> MonitorResult<Option<WhirlpoolSwap>> {
+    if data.len() < SWAP_MIN_DATA_LEN || accounts.len() < V2_MIN_ACCOUNTS {
+        warn!("Orca swapV2 instruction too short");
+        return Ok(None);
+    }
+    let args = SwapArgs::decode(data)?;
+
+    let side_a = (
+        accounts[V2_MINT_A],
+        accounts[V2_OWNER_ACCOUNT_A],
+        accounts[V2_TOKEN_PROGRAM_A],
+    );
+    let side_b = (
+        accounts[V2_MINT_B],
+        accounts[V2_OWNER_ACCOUNT_B],
+        accounts[V2_TOKEN_PROGRAM_B],
+    );
+    let (
+        (source_mint, source_account, _),
+        (destination_mint, destination_account, destination_program),
+    ) = if args.a_to_b {
+        (side_a, side_b)
+    } else {
+        (side_b, side_a)
+    };
+
+    let (mut amount_in, mut amount_out, minimum_amount_out) = args.amounts();
+
+    // Observed balance changes are net of any Token-2022 transfer fee
+    if let Some(&spent) = token_deltas.get(&source_account)
+        && spent < 0
+    {
+        amount_in = spent.unsigned_abs() as u64;
+    }
+
+    match token_deltas.get(&destination_account) {
+        Some(&received) if received > 0 => {
+            if received as u64 != amount_out {
+                debug!(
+                    "Orca swapV2 net received {} differs from instruction amount {} (transfer fee)",
+                    received, amount_out
+                );
+            }
+            amount_out = received as u64;
+        }
+        _ if destination_program == program_ids::token_2022() => {
+            warn!(
+                "Token-2022 output {} without an observed balance change - amount may include transfer fees",
+                destination_mint
+            );
+        }
+        _ => {}
+    }
+
+    Ok(Some(WhirlpoolSwap {
+        source_mint,
+        destination_mint,
+        amount_in,
+        amount_out,
+        minimum_amount_out,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use solana_transaction_status::UiCompiledInstruction;
 
     #[test]
     fn test_orca_parser_exists() {
-        assert!(true);
+        let result = parse_orca_swap(
+            &[],
+            &[],
+            &HashMap::new(),
+            &HashMap::new(),
+            Signature::default(),
+            0,
+            0,
+            0,
+            Pubkey::default(),
+        )
+        .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_swap_v2_with_transfer_fee() {
+        let trader = Pubkey::new_unique();
+        let mint_a = Pubkey::new_unique(); // USDC-like, classic SPL token
+        let mint_b = Pubkey::new_unique(); // Token-2022 with a 1% transfer fee
+        let owner_a = Pubkey::new_unique();
+        let owner_b = Pubkey::new_unique();
+
+        // Instruction accounts, in swap_v2 order
+        let mut accounts = vec![Pubkey::new_unique(); 15];
+        accounts[V2_TOKEN_PROGRAM_B] = program_ids::token_2022();
+        accounts[3] = trader;
+        accounts[V2_MINT_A] = mint_a;
+        accounts[V2_MINT_B] = mint_b;
+        accounts[V2_OWNER_ACCOUNT_A] = owner_a;
+        accounts[V2_OWNER_ACCOUNT_B] = owner_b;
+
+        let mut account_keys = accounts.clone();
+        account_keys.push(program_ids::orca_whirlpool());
+
+        // Exact-in A→B: 100_000 in, at least 49_000 out
+        let mut data = SWAP_V2_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&100_000u64.to_le_bytes());
+        data.extend_from_slice(&49_000u64.to_le_bytes());
+        data.extend_from_slice(&0u128.to_le_bytes());
+        data.push(1); // amount_specified_is_input
+        data.push(1); // a_to_b
+        data.push(0); // remaining_accounts_info: None
+
+        let instruction = UiInstruction::Compiled(UiCompiledInstruction {
+            program_id_index: (account_keys.len() - 1) as u8,
+            accounts: (0..accounts.len() as u8).collect(),
+            data: bs58::encode(&data).into_string(),
+            stack_height: None,
+        });
+
+        // Pool sent 50_000, the trader received 49_500 after the fee
+        let token_deltas = HashMap::from([(owner_a, -100_000), (owner_b, 49_500)]);
+
+        let signal = parse_orca_swap(
+            &[instruction],
+            &account_keys,
+            &token_deltas,
+            &HashMap::new(),
+            Signature::new_unique(),
+            1,
+            0,
+            0,
+            trader,
+        )
+        .unwrap()
+        .expect("swapV2 parsed");
+
+        assert_eq!(signal.dex, DexType::Orca);
+        assert_eq!(signal.source_mint, mint_a);
+        assert_eq!(signal.destination_mint, mint_b);
+        assert_eq!(signal.amount_in, 100_000);
+        assert_eq!(signal.amount_out, 49_500);
+        assert_eq!(signal.minimum_amount_out, 49_000);
+        assert_eq!(signal.slippage_bps, 101);
     }
-}
\ No newline at end of file
+
+    /// Build a `swap` (v1) call and parse it with the given balance changes
+    fn parse_v1(
+        a_to_b: bool,
+        amount_specified_is_input: bool,
+        token_deltas: &[(usize, i128)],
+    ) -> (TradeSignal, Pubkey, Pubkey) {
+        let trader = Pubkey::new_unique();
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // Instruction accounts, in swap order
+        let mut accounts: Vec<Pubkey> =
+            (0..V1_MIN_ACCOUNTS).map(|_| Pubkey::new_unique()).collect();
+        accounts[1] = trader;
+        let token_mints = HashMap::from([
+            (accounts[V1_OWNER_ACCOUNT_A], mint_a),
+            (accounts[V1_OWNER_ACCOUNT_B], mint_b),
+        ]);
+        let token_deltas = token_deltas
+            .iter()
+            .map(|&(index, delta)| (accounts[index], delta))
+            .collect();
+
+        let mut account_keys = accounts.clone();
+        account_keys.push(program_ids::orca_whirlpool());
+
+        let mut data = SWAP_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&100_000u64.to_le_bytes());
+        data.extend_from_slice(&49_000u64.to_le_bytes());
+        data.extend_from_slice(&0u128.to_le_bytes());
+        data.push(amount_specified_is_input as u8);
+        data.push(a_to_b as u8);
+
+        let instruction = UiInstruction::Compiled(UiCompiledInstruction {
+            program_id_index: (account_keys.len() - 1) as u8,
+            accounts: (0..accounts.len() as u8).collect(),
+            data: bs58::encode(&data).into_string(),
+            stack_height: None,
+        });
+
+        let signal = parse_orca_swap(
+            &[instruction],
+            &account_keys,
+            &token_deltas,
+            &token_mints,
+            Signature::new_unique(),
+            1,
+            0,
+            0,
+            trader,
+        )
+        .unwrap()
+        .expect("swap parsed");
+        (signal, mint_a, mint_b)
+    }
+
+    #[test]
+    fn test_swap_a_to_b() {
+        // Exact-in: 100_000 A in, at least 49_000 B out, 50_000 received
+        let (signal, mint_a, mint_b) = parse_v1(
+            true,
+            true,
+            &[(V1_OWNER_ACCOUNT_A, -100_000), (V1_OWNER_ACCOUNT_B, 50_000)],
+        );
+
+        assert_eq!(signal.dex, DexType::Orca);
+        assert_eq!(signal.source_mint, mint_a);
+        assert_eq!(signal.destination_mint, mint_b);
+        assert_eq!(signal.amount_in, 100_000);
+        assert_eq!(signal.amount_out, 50_000);
+        assert_eq!(signal.minimum_amount_out, 49_000);
+    }
+
+    #[test]
+    fn test_swap_b_to_a() {
+        let (signal, mint_a, mint_b) = parse_v1(
+            false,
+            true,
+            &[(V1_OWNER_ACCOUNT_B, -100_000), (V1_OWNER_ACCOUNT_A, 50_000)],
+        );
+
+        assert_eq!(signal.source_mint, mint_b);
+        assert_eq!(signal.destination_mint, mint_a);
+        assert_eq!(signal.amount_in, 100_000);
+        assert_eq!(signal.amount_out, 50_000);
+        assert_eq!(signal.minimum_amount_out, 49_000);
+
+        // Exact-out without balance changes: 100_000 A out, at most 49_000 B in
+        let (signal, mint_a, mint_b) = parse_v1(false, false, &[]);
+        assert_eq!(signal.source_mint, mint_b);
+        assert_eq!(signal.destination_mint, mint_a);
+        assert_eq!(signal.amount_in, 49_000);
+        assert_eq!(signal.amount_out, 100_000);
+        assert_eq!(signal.minimum_amount_out, 100_000);
+    }
+}
@@ -1,35 +1,347 @@
-use crate::monitor::error::MonitorResult;
-use crate::types::TradeSignal;
+use crate::decision::slippage::slippage_bps;
+use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::types::{DexType, TradeSignal, program_ids};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
-use solana_transaction_status::UiInstruction;
+use solana_transaction_status::{UiInstruction, UiParsedInstruction};
+use std::collections::HashMap;
+use std::str::FromStr;
 use tracing::{debug, warn};
 
-/// Parse Raydium AMM swap transactions
+/// AMM v4 instruction tags (single byte, not Anchor)
+const SWAP_BASE_IN: u8 = 9;
+const SWAP_BASE_OUT: u8 = 11;
+
+/// tag + two u64 amounts
+const SWAP_DATA_LEN: usize = 1 + 8 + 8;
+
+/// Both swap layouts end with the user's source token account, destination
+/// token account and owner. The older layout has 18 accounts (with the
+/// AMM target orders), the newer one 17, so they're indexed from the end.
+const USER_SOURCE_FROM_END: usize = 3;
+const USER_DESTINATION_FROM_END: usize = 2;
+const MIN_ACCOUNTS: usize = 17;
+
+/// Parse Raydium AMM v4 swap transactions
+///
+/// The instruction names token accounts, not mints, so `token_mints` (token
+/// account → mint, from the transaction's token balances) resolves them.
+/// Observed balance changes in `token_deltas` replace the bound side of the
+/// swap (minimum out / maximum in) when available.
+#[allow(clippy::too_many_arguments)]
 pub fn parse_raydium_swap(
-    _instructions: &[UiInstruction],
-    _account_keys: &[Pubkey],
-    _signature: Signature,
-    _timestamp: i64,
-    _priority_fee: u64,
-    _trader: Pubkey,
+    instructions: &[UiInstruction],
+    account_keys: &[Pubkey],
+    token_deltas: &HashMap<Pubkey, i128>,
+    token_mints: &HashMap<Pubkey, Pubkey>,
+    signature: Signature,
+    slot: u64,
+    timestamp: i64,
+    priority_fee: u64,
+    trader: Pubkey,
 ) -> MonitorResult<Option<TradeSignal>> {
     debug!("Parsing Raydium swap");
 
-    // TODO: Implement Raydium-specific parsing
-    // Raydium AMM uses different instruction layout than Jupiter
-    // Account positions and data format are different
+    for instruction in instructions {
+        let Some((accounts, data)) = decode_amm_instruction(instruction, account_keys)? else {
+            continue;
+        };
+
+        let Some(swap) = parse_swap(&accounts, &data, token_deltas, token_mints)? else {
+            continue;
+        };
 
-    warn!("Raydium parsing not yet implemented");
+        return Ok(Some(TradeSignal {
+            signature,
+            slot,
+            timestamp,
+            dex: DexType::Raydium,
+            source_mint: swap.source_mint,
+            destination_mint: swap.destination_mint,
+            amount_in: swap.amount_in,
+            amount_out: swap.amount_out,
+            minimum_amount_out: swap.minimum_amount_out,
+            slippage_bps: slippage_bps(swap.amount_out, swap.minimum_amount_out),
+            priority_fee_lamports: priority_fee,
+            accounts: account_keys.to_vec(),
+            trader,
+        }));
+    }
+
+    warn!("No supported Raydium swap instruction found");
     Ok(None)
 }
 
+/// Amounts and mints decoded from an AMM v4 swap
+struct AmmSwap {
+    source_mint: Pubkey,
+    destination_mint: Pubkey,
+    amount_in: u64,
+    amount_out: u64,
+    minimum_amount_out: u64,
+}
+
+/// Return the accounts and data of an AMM v4 instruction, or `None` for
+/// instructions of other programs
+fn decode_amm_instruction(
+    instruction: &UiInstruction,
+    account_keys: &[Pubkey],
+) -> MonitorResult<Option<(Vec<Pubkey>, Vec<u8>)>> {
+    let amm = program_ids::raydium_v4();
+
+    let (accounts, data) = match instruction {
+        UiInstruction::Compiled(compiled) => {
+            if account_keys.get(compiled.program_id_index as usize) != Some(&amm) {
+                return Ok(None);
+            }
+            let accounts = compiled
+                .accounts
+                .iter()
+                .filter_map(|&i| account_keys.get(i as usize).copied())
+                .collect();
+            (accounts, &compiled.data)
+        }
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => {
+            if decoded.program_id != amm.to_string() {
+                return Ok(None);
+            }
+            let accounts = decoded
+                .accounts
+                .iter()
+                .filter_map(|a| Pubkey::from_str(a).ok())
+                .collect();
+            (accounts, &decoded.data)
+        }
+        _ => return Ok(None),
+    };
+
+    let data = bs58::decode(data)
+        .into_vec()
+        .map_err(|e| MonitorError::ParseError(format!("Failed to decode data: {}", e)))?;
+
+    Ok(Some((accounts, data)))
+}
+
+/// Decode `swapBaseIn` / `swapBaseOut`; `None` for other AMM instructions
+fn parse_swap(
+    accounts: &[Pubkey],
+    data: &[u8],
+    token_deltas: &HashMap<Pubkey, i128>,
+    token_mints: &HashMap<Pubkey, Pubkey>,
+) -> MonitorResult<Option<AmmSwap>> {
+    let Some(&tag) = data.first() else {
+        return Ok(None);
+    };
+    if tag != SWAP_BASE_IN && tag != SWAP_BASE_OUT {
+        return Ok(None);
+    }
+
+    if data.len() < SWAP_DATA_LEN || accounts.len() < MIN_ACCOUNTS {
+        warn!("Raydium swap instruction too short");
+        return Ok(None);
+    }
+
+    let read_u64 = |offset: usize| {
+        data[offset..offset + 8]
+            .try_into()
+            .map(u64::from_le_bytes)
+            .map_err(|_| MonitorError::ParseError("Failed to parse swap amount".to_string()))
+    };
+    // swapBaseIn: (exact amount in, minimum out).
+    // swapBaseOut: (maximum in, exact amount out).
+    // Either way the second amount is the least we accept out.
+    let mut amount_in = read_u64(1)?;
+    let mut amount_out = read_u64(9)?;
+    let minimum_amount_out = amount_out;
+
+    let source_account = accounts[accounts.len() - USER_SOURCE_FROM_END];
+    let destination_account = accounts[accounts.len() - USER_DESTINATION_FROM_END];
+
+    let (Some(&source_mint), Some(&destination_mint)) = (
+        token_mints.get(&source_account),
+        token_mints.get(&destination_account),
+    ) else {
+        warn!(
+            "Raydium swap token accounts {} / {} have no known mint",
+            source_account, destination_account
+        );
+        return Ok(None);
+    };
+
+    if let Some(&spent) = token_deltas.get(&source_account)
+        && spent < 0
+    {
+        amount_in = spent.unsigned_abs() as u64;
+    }
+    if let Some(&received) = token_deltas.get(&destination_account)
+        && received > 0
+    {
+        amount_out = received as u64;
+    }
+
+    Ok(Some(AmmSwap {
+        source_mint,
+        destination_mint,
+        amount_in,
+        amount_out,
+        minimum_amount_out,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use solana_transaction_status::{UiCompiledInstruction, UiPartiallyDecodedInstruction};
+
+    /// `swapBaseIn` data as found in a transaction: tag 9,
+    /// amount_in 1_000_000_000, minimum_amount_out 148_500_000
+    const SWAP_BASE_IN_DATA: &str = "5uc7oSXmeRfefyqS3icLsrP";
+
+    /// `swapBaseOut` data: tag 11, max_amount_in 1_010_000_000,
+    /// amount_out 150_000_000
+    const SWAP_BASE_OUT_DATA: &str = "7Gasps1G2hJb9rg9EVgQyQb";
+
+    struct Fixture {
+        trader: Pubkey,
+        wsol: Pubkey,
+        usdc: Pubkey,
+        source: Pubkey,
+        destination: Pubkey,
+        /// 18-account swap layout, user accounts last
+        accounts: Vec<Pubkey>,
+        account_keys: Vec<Pubkey>,
+        token_mints: HashMap<Pubkey, Pubkey>,
+    }
+
+    fn fixture() -> Fixture {
+        let trader = Pubkey::new_unique();
+        let (wsol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (source, destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut accounts: Vec<Pubkey> = (0..18).map(|_| Pubkey::new_unique()).collect();
+        accounts[15] = source;
+        accounts[16] = destination;
+        accounts[17] = trader;
+
+        let mut account_keys = accounts.clone();
+        account_keys.push(program_ids::raydium_v4());
+
+        Fixture {
+            trader,
+            wsol,
+            usdc,
+            source,
+            destination,
+            accounts,
+            account_keys,
+            token_mints: HashMap::from([(source, wsol), (destination, usdc)]),
+        }
+    }
+
+    fn parse(
+        f: &Fixture,
+        instructions: &[UiInstruction],
+        deltas: &HashMap<Pubkey, i128>,
+    ) -> TradeSignal {
+        parse_raydium_swap(
+            instructions,
+            &f.account_keys,
+            deltas,
+            &f.token_mints,
+            Signature::new_unique(),
+            1,
+            0,
+            0,
+            f.trader,
+        )
+        .unwrap()
+        .expect("Raydium swap parsed")
+    }
+
+    fn compiled(f: &Fixture, data: &str) -> UiInstruction {
+        UiInstruction::Compiled(UiCompiledInstruction {
+            program_id_index: (f.account_keys.len() - 1) as u8,
+            accounts: (0..f.accounts.len() as u8).collect(),
+            data: data.to_string(),
+            stack_height: None,
+        })
+    }
+
+    #[test]
+    fn test_raydium_parser_ignores_other_instructions() {
+        let result = parse_raydium_swap(
+            &[],
+            &[],
+            &HashMap::new(),
+            &HashMap::new(),
+            Signature::default(),
+            0,
+            0,
+            0,
+            Pubkey::default(),
+        )
+        .unwrap();
+        assert!(result.is_none());
+    }
 
     #[test]
-    fn test_raydium_parser_exists() {
-        assert!(true);
+    fn test_swap_base_in() {
+        let f = fixture();
+        let deltas = HashMap::from([(f.source, -1_000_000_000), (f.destination, 150_000_000)]);
+
+        let signal = parse(&f, &[compiled(&f, SWAP_BASE_IN_DATA)], &deltas);
+        assert_eq!(signal.dex, DexType::Raydium);
+        assert_eq!(signal.source_mint, f.wsol);
+        assert_eq!(signal.destination_mint, f.usdc);
+        assert_eq!(signal.amount_in, 1_000_000_000);
+        assert_eq!(signal.amount_out, 150_000_000);
+        assert_eq!(signal.minimum_amount_out, 148_500_000);
+        assert_eq!(signal.slippage_bps, 100);
+
+        // Without balance changes the minimum out is all we know
+        let signal = parse(&f, &[compiled(&f, SWAP_BASE_IN_DATA)], &HashMap::new());
+        assert_eq!(signal.amount_out, 148_500_000);
+    }
+
+    #[test]
+    fn test_swap_base_out() {
+        let f = fixture();
+        let deltas = HashMap::from([(f.source, -1_003_000_000), (f.destination, 150_000_000)]);
+
+        let signal = parse(&f, &[compiled(&f, SWAP_BASE_OUT_DATA)], &deltas);
+        assert_eq!(signal.amount_in, 1_003_000_000);
+        assert_eq!(signal.amount_out, 150_000_000);
+        assert_eq!(signal.minimum_amount_out, 150_000_000);
+
+        // Without balance changes the maximum in stands in for the spend
+        let signal = parse(&f, &[compiled(&f, SWAP_BASE_OUT_DATA)], &HashMap::new());
+        assert_eq!(signal.amount_in, 1_010_000_000);
+    }
+
+    #[test]
+    fn test_swap_as_inner_instruction() {
+        let f = fixture();
+
+        // A router instruction first, then the AMM call as it appears among
+        // the inner instructions of a routed transaction
+        let router = UiInstruction::Compiled(UiCompiledInstruction {
+            program_id_index: 0,
+            accounts: vec![],
+            data: String::new(),
+            stack_height: None,
+        });
+        let inner = UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(
+            UiPartiallyDecodedInstruction {
+                program_id: program_ids::raydium_v4().to_string(),
+                accounts: f.accounts.iter().map(|a| a.to_string()).collect(),
+                data: SWAP_BASE_IN_DATA.to_string(),
+                stack_height: Some(2),
+            },
+        ));
+
+        let signal = parse(&f, &[router, inner], &HashMap::new());
+        assert_eq!(signal.source_mint, f.wsol);
+        assert_eq!(signal.destination_mint, f.usdc);
+        assert_eq!(signal.amount_in, 1_000_000_000);
     }
 }
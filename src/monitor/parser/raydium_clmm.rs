@@ -0,0 +1,231 @@
+use crate::decision::slippage::slippage_bps;
+use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::types::{DexType, TradeSignal, program_ids};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::{UiInstruction, UiParsedInstruction};
+use std::collections::HashMap;
+use std::str::FromStr;
+use tracing::{debug, warn};
+
+/// Anchor discriminator for CLMM `swap_v2`
+const SWAP_V2_DISCRIMINATOR: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+/// discriminator + amount + other_amount_threshold + sqrt_price_limit_x64 (u128)
+/// + is_base_input
+const SWAP_V2_MIN_DATA_LEN: usize = 8 + 8 + 8 + 16 + 1;
+
+/// `swap_v2` account layout (indices into the instruction's accounts)
+const V2_INPUT_TOKEN_ACCOUNT: usize = 3;
+const V2_OUTPUT_TOKEN_ACCOUNT: usize = 4;
+const V2_INPUT_MINT: usize = 11;
+const V2_OUTPUT_MINT: usize = 12;
+const V2_MIN_ACCOUNTS: usize = 13;
+
+/// Parse Raydium CLMM (concentrated liquidity) swap transactions
+///
+/// Only `swap_v2` is decoded: the original `swap` doesn't name the mints.
+/// Observed balance changes in `token_deltas` are preferred over the
+/// instruction amounts, which are only the exact side and a bound.
+#[allow(clippy::too_many_arguments)]
+pub fn parse_raydium_clmm_swap(
+    instructions: &[UiInstruction],
+    account_keys: &[Pubkey],
+    token_deltas: &HashMap<Pubkey, i128>,
+    signature: Signature,
+    slot: u64,
+    timestamp: i64,
+    priority_fee: u64,
+    trader: Pubkey,
+) -> MonitorResult<Option<TradeSignal>> {
+    debug!("Parsing Raydium CLMM swap");
+
+    for instruction in instructions {
+        let Some((accounts, data)) = decode_clmm_instruction(instruction, account_keys)? else {
+            continue;
+        };
+
+        if data.starts_with(&SWAP_V2_DISCRIMINATOR) {
+            let swap = parse_swap_v2(&accounts, &data, token_deltas)?;
+            return Ok(swap.map(|swap| TradeSignal {
+                signature,
+                slot,
+                timestamp,
+                dex: DexType::RaydiumClmm,
+                source_mint: swap.source_mint,
+                destination_mint: swap.destination_mint,
+                amount_in: swap.amount_in,
+                amount_out: swap.amount_out,
+                minimum_amount_out: swap.minimum_amount_out,
+                slippage_bps: slippage_bps(swap.amount_out, swap.minimum_amount_out),
+                priority_fee_lamports: priority_fee,
+                accounts: account_keys.to_vec(),
+                trader,
+            }));
+        }
+    }
+
+    warn!("No supported Raydium CLMM swap instruction found");
+    Ok(None)
+}
+
+/// Amounts and mints decoded from a CLMM swap
+struct ClmmSwap {
+    source_mint: Pubkey,
+    destination_mint: Pubkey,
+    amount_in: u64,
+    amount_out: u64,
+    minimum_amount_out: u64,
+}
+
+/// Return the accounts and data of a CLMM instruction, or `None` for
+/// instructions of other programs
+fn decode_clmm_instruction(
+    instruction: &UiInstruction,
+    account_keys: &[Pubkey],
+) -> MonitorResult<Option<(Vec<Pubkey>, Vec<u8>)>> {
+    let clmm = program_ids::raydium_clmm();
+
+    let (accounts, data) = match instruction {
+        UiInstruction::Compiled(compiled) => {
+            if account_keys.get(compiled.program_id_index as usize) != Some(&clmm) {
+                return Ok(None);
+            }
+            let accounts = compiled
+                .accounts
+                .iter()
+                .filter_map(|&i| account_keys.get(i as usize).copied())
+                .collect();
+            (accounts, &compiled.data)
+        }
+        UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(decoded)) => {
+            if decoded.program_id != clmm.to_string() {
+                return Ok(None);
+            }
+            let accounts = decoded
+                .accounts
+                .iter()
+                .filter_map(|a| Pubkey::from_str(a).ok())
+                .collect();
+            (accounts, &decoded.data)
+        }
+        _ => return Ok(None),
+    };
+
+    let data = bs58::decode(data)
+        .into_vec()
+        .map_err(|e| MonitorError::ParseError(format!("Failed to decode data: {}", e)))?;
+
+    Ok(Some((accounts, data)))
+}
+
+/// Decode `swap_v2`, which names the input and output mints
+fn parse_swap_v2(
+    accounts: &[Pubkey],
+    data: &[u8],
+    token_deltas: &HashMap<Pubkey, i128>,
+) -> MonitorResult<Option<ClmmSwap>> {
+    if data.len() < SWAP_V2_MIN_DATA_LEN || accounts.len() < V2_MIN_ACCOUNTS {
+        warn!("Raydium CLMM swapV2 instruction too short");
+        return Ok(None);
+    }
+
+    let read_u64 = |offset: usize| {
+        data[offset..offset + 8]
+            .try_into()
+            .map(u64::from_le_bytes)
+            .map_err(|_| MonitorError::ParseError("Failed to parse swapV2 amount".to_string()))
+    };
+
+    let amount = read_u64(8)?;
+    let other_amount_threshold = read_u64(16)?;
+    let is_base_input = data[40] != 0;
+
+    // Exact-in: `amount` is spent, the threshold is the minimum out.
+    // Exact-out: `amount` is received, the threshold is the maximum in.
+    let (mut amount_in, mut amount_out, minimum_amount_out) = if is_base_input {
+        (amount, other_amount_threshold, other_amount_threshold)
+    } else {
+        (other_amount_threshold, amount, amount)
+    };
+
+    if let Some(&spent) = token_deltas.get(&accounts[V2_INPUT_TOKEN_ACCOUNT])
+        && spent < 0
+    {
+        amount_in = spent.unsigned_abs() as u64;
+    }
+    if let Some(&received) = token_deltas.get(&accounts[V2_OUTPUT_TOKEN_ACCOUNT])
+        && received > 0
+    {
+        amount_out = received as u64;
+    }
+
+    Ok(Some(ClmmSwap {
+        source_mint: accounts[V2_INPUT_MINT],
+        destination_mint: accounts[V2_OUTPUT_MINT],
+        amount_in,
+        amount_out,
+        minimum_amount_out,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_transaction_status::UiCompiledInstruction;
+
+    #[test]
+    fn test_swap_v2() {
+        let trader = Pubkey::new_unique();
+        let input_mint = Pubkey::new_unique();
+        let output_mint = Pubkey::new_unique();
+        let input_account = Pubkey::new_unique();
+        let output_account = Pubkey::new_unique();
+
+        // Instruction accounts, in swap_v2 order
+        let mut accounts = vec![Pubkey::new_unique(); V2_MIN_ACCOUNTS];
+        accounts[0] = trader;
+        accounts[V2_INPUT_TOKEN_ACCOUNT] = input_account;
+        accounts[V2_OUTPUT_TOKEN_ACCOUNT] = output_account;
+        accounts[V2_INPUT_MINT] = input_mint;
+        accounts[V2_OUTPUT_MINT] = output_mint;
+
+        let mut account_keys = accounts.clone();
+        account_keys.push(program_ids::raydium_clmm());
+
+        // Exact-in: 100_000 in, at least 49_000 out
+        let mut data = SWAP_V2_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&100_000u64.to_le_bytes());
+        data.extend_from_slice(&49_000u64.to_le_bytes());
+        data.extend_from_slice(&0u128.to_le_bytes());
+        data.push(1); // is_base_input
+
+        let instruction = UiInstruction::Compiled(UiCompiledInstruction {
+            program_id_index: (account_keys.len() - 1) as u8,
+            accounts: (0..accounts.len() as u8).collect(),
+            data: bs58::encode(&data).into_string(),
+            stack_height: None,
+        });
+        let token_deltas = HashMap::from([(input_account, -100_000), (output_account, 50_000)]);
+
+        let signal = parse_raydium_clmm_swap(
+            &[instruction],
+            &account_keys,
+            &token_deltas,
+            Signature::new_unique(),
+            1,
+            0,
+            0,
+            trader,
+        )
+        .unwrap()
+        .expect("swapV2 parsed");
+
+        assert_eq!(signal.dex, DexType::RaydiumClmm);
+        assert_eq!(signal.source_mint, input_mint);
+        assert_eq!(signal.destination_mint, output_mint);
+        assert_eq!(signal.amount_in, 100_000);
+        assert_eq!(signal.amount_out, 50_000);
+        assert_eq!(signal.minimum_amount_out, 49_000);
+    }
+}
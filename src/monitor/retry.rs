@@ -0,0 +1,210 @@
+use crate::monitor::error::{MonitorError, MonitorResult};
+use rand::Rng;
+use std::future::Future;
+use tokio::time::{Duration, sleep};
+use tracing::warn;
+
+/// How often and how patiently to retry a failing operation
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one
+    pub max_attempts: u32,
+
+    /// Delay before the first retry
+    pub base_delay: Duration,
+
+    /// Factor the delay grows by after each retry (1.0 = constant)
+    pub multiplier: f64,
+
+    /// Random spread applied to each delay, as a fraction (0.25 = ±25%)
+    pub jitter: f64,
+
+    /// Ceiling the delay stops growing at, before jitter is applied
+    pub max_delay: Option<Duration>,
+}
+
+impl RetryPolicy {
+    /// Delay to wait after the given (1-based) failed attempt
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        self.delay_with_rng(attempt, &mut rand::thread_rng())
+    }
+
+    /// [`Self::delay_for`], drawing the jitter from `rng`
+    pub fn delay_with_rng(&self, attempt: u32, rng: &mut impl Rng) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let mut secs = self.base_delay.as_secs_f64() * self.multiplier.powi(exponent);
+
+        if let Some(max_delay) = self.max_delay {
+            secs = secs.min(max_delay.as_secs_f64());
+        }
+
+        if self.jitter > 0.0 {
+            let spread = rng.gen_range(-self.jitter..=self.jitter);
+            secs *= 1.0 + spread;
+        }
+
+        Duration::from_secs_f64(secs.max(0.0))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_delay: None,
+        }
+    }
+}
+
+/// Run `operation` until it succeeds, fails with a non-retryable error, or
+/// runs out of attempts. The operation receives the 1-based attempt number.
+pub async fn retry_with_backoff<T, F, Fut, P>(
+    policy: &RetryPolicy,
+    is_retryable: P,
+    mut operation: F,
+) -> MonitorResult<T>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = MonitorResult<T>>,
+    P: Fn(&MonitorError) -> bool,
+{
+    let mut attempt = 1;
+
+    loop {
+        match operation(attempt).await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < policy.max_attempts && is_retryable(&e) => {
+                let delay = policy.delay_for(attempt);
+                warn!(
+                    "Attempt {}/{} failed: {} - retrying in {:?}",
+                    attempt, policy.max_attempts, e, delay
+                );
+                sleep(delay).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    fn instant_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::ZERO,
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_delay: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let mut calls = 0;
+
+        let result = retry_with_backoff(
+            &instant_policy(5),
+            |_| true,
+            |attempt| {
+                calls += 1;
+                async move {
+                    if attempt < 3 {
+                        Err(MonitorError::Timeout)
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let mut calls = 0;
+
+        let result: MonitorResult<()> = retry_with_backoff(
+            &instant_policy(3),
+            |_| true,
+            |_| {
+                calls += 1;
+                async { Err(MonitorError::Timeout) }
+            },
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_fatal_errors() {
+        let mut calls = 0;
+
+        let result: MonitorResult<()> = retry_with_backoff(
+            &instant_policy(3),
+            |e| matches!(e, MonitorError::Timeout),
+            |_| {
+                calls += 1;
+                async { Err(MonitorError::ChannelError) }
+            },
+        )
+        .await;
+
+        assert!(matches!(result, Err(MonitorError::ChannelError)));
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_exponential_delays() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(2),
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_delay: None,
+        };
+
+        assert_eq!(policy.delay_for(1), Duration::from_secs(2));
+        assert_eq!(policy.delay_for(2), Duration::from_secs(4));
+        assert_eq!(policy.delay_for(3), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn test_jittered_delays_stay_within_the_cap() {
+        let policy = RetryPolicy {
+            max_attempts: 100,
+            base_delay: Duration::from_secs(2),
+            multiplier: 2.0,
+            jitter: 0.25,
+            max_delay: Some(Duration::from_secs(60)),
+        };
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for attempt in 1..=100 {
+            let delay = policy.delay_with_rng(attempt, &mut rng).as_secs_f64();
+            assert!(delay >= 1.5, "attempt {}: {}s", attempt, delay);
+            assert!(delay <= 75.0, "attempt {}: {}s", attempt, delay);
+
+            let unjittered = (2.0 * 2f64.powi(attempt as i32 - 1)).min(60.0);
+            assert!((delay / unjittered - 1.0).abs() <= 0.25);
+        }
+
+        // The same seed gives the same delays
+        assert_eq!(
+            policy.delay_with_rng(3, &mut StdRng::seed_from_u64(1)),
+            policy.delay_with_rng(3, &mut StdRng::seed_from_u64(1))
+        );
+    }
+}
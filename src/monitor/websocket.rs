@@ -1,52 +1,276 @@
 use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::monitor::retry::{RetryPolicy, retry_with_backoff};
 use crate::types::MonitorConfig;
-use futures::{SinkExt, StreamExt};
+use futures::{Sink, SinkExt, Stream, StreamExt};
 use serde_json::{Value, json};
 use solana_sdk::pubkey::Pubkey;
-use std::time::Duration;
-use tokio::time::sleep;
-use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async, tungstenite::Message};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::{
+    MaybeTlsStream, WebSocketStream, connect_async_with_config,
+    tungstenite::{Error as WsError, Message},
+};
 use tracing::{debug, error, info, warn};
 
 type WsStream = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
 
+/// Close codes that mean retrying won't help (bad request, policy/auth rejection)
+const FATAL_CLOSE_CODES: &[u16] = &[
+    1002, // Protocol error
+    1003, // Unsupported data
+    1008, // Policy violation
+    4001, // Unauthorized (e.g. bad API key)
+    4003, // Forbidden
+];
+
+/// Open a new WebSocket connection to the endpoint
+async fn open_stream(endpoint: &str, ws_config: WebSocketConfig) -> MonitorResult<WsStream> {
+    info!("Connecting to WebSocket: {}", endpoint);
+    match connect_async_with_config(endpoint, Some(ws_config), false).await {
+        Ok((stream, response)) => {
+            info!("WebSocket connected: {:?}", response.status());
+            Ok(stream)
+        }
+        Err(e) => {
+            error!("Failed to connect to WebSocket: {}", e);
+            Err(MonitorError::ConnectionFailed(e.to_string()))
+        }
+    }
+}
+
+/// Pings a quiet connection and gives up on it when the ping goes unanswered,
+/// so a half-open socket doesn't block reads forever
+#[derive(Debug)]
+struct Keepalive {
+    /// Quiet time before pinging (zero = disabled)
+    interval: Duration,
+    /// How long a ping may go unanswered
+    timeout: Duration,
+    next_ping: tokio::time::Instant,
+    /// When the unanswered ping was sent
+    ping_sent: Option<tokio::time::Instant>,
+}
+
+impl Keepalive {
+    fn new(interval: Duration, timeout: Duration) -> Self {
+        Self {
+            interval,
+            timeout,
+            next_ping: tokio::time::Instant::now() + interval,
+            ping_sent: None,
+        }
+    }
+
+    fn from_config(config: &MonitorConfig) -> Self {
+        Self::new(
+            Duration::from_secs(config.keepalive_interval_secs),
+            Duration::from_secs(config.keepalive_timeout_secs),
+        )
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.interval.is_zero()
+    }
+
+    /// Anything received shows the connection is alive
+    fn received(&mut self) {
+        self.next_ping = tokio::time::Instant::now() + self.interval;
+        self.ping_sent = None;
+    }
+
+    /// When the next ping is due, or the pending one times out
+    fn deadline(&self) -> tokio::time::Instant {
+        match self.ping_sent {
+            Some(sent) => sent + self.timeout,
+            None => self.next_ping,
+        }
+    }
+}
+
+/// Next frame from `stream`, pinging it whenever it has been quiet for the
+/// keepalive interval. A ping unanswered past the timeout fails with
+/// `ConnectionFailed`, which the listener reconnects on.
+async fn next_frame<S>(
+    stream: &mut S,
+    keepalive: &mut Keepalive,
+) -> MonitorResult<Option<Result<Message, WsError>>>
+where
+    S: Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError> + Unpin,
+{
+    if !keepalive.is_enabled() {
+        return Ok(stream.next().await);
+    }
+
+    loop {
+        // Only the read is raced against the timer; it is cancel-safe
+        let frame = tokio::select! {
+            frame = stream.next() => Some(frame),
+            _ = tokio::time::sleep_until(keepalive.deadline()) => None,
+        };
+
+        if let Some(frame) = frame {
+            keepalive.received();
+            return Ok(frame);
+        }
+
+        if keepalive.ping_sent.is_some() {
+            warn!(
+                "💔 No reply to keepalive ping within {:?} - connection is dead",
+                keepalive.timeout
+            );
+            return Err(MonitorError::ConnectionFailed(
+                "Keepalive ping timed out".to_string(),
+            ));
+        }
+
+        stream
+            .send(Message::Ping(vec![]))
+            .await
+            .map_err(MonitorError::from)?;
+        keepalive.ping_sent = Some(tokio::time::Instant::now());
+        debug!("Keepalive ping sent");
+    }
+}
+
+/// Should we reconnect after the server closed with this code?
+pub fn is_recoverable_close(code: u16) -> bool {
+    !FATAL_CLOSE_CODES.contains(&code)
+}
+
+/// Fixed one-second window counter that caps incoming notifications
+#[derive(Debug)]
+struct RateGuard {
+    /// Most messages allowed per window (0 = unlimited)
+    max_per_sec: u32,
+    window_start: Instant,
+    count: u32,
+    dropped: u64,
+}
+
+impl RateGuard {
+    fn new(max_per_sec: u32) -> Self {
+        Self {
+            max_per_sec,
+            window_start: Instant::now(),
+            count: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Count a message arriving at `now`; `false` if it should be dropped
+    fn allow(&mut self, now: Instant) -> bool {
+        if self.max_per_sec == 0 {
+            return true;
+        }
+
+        if now.duration_since(self.window_start) >= Duration::from_secs(1) {
+            if self.dropped > 0 {
+                warn!(
+                    "🌊 Dropped {} WebSocket messages over the {}/s limit",
+                    self.dropped, self.max_per_sec
+                );
+            }
+            self.window_start = now;
+            self.count = 0;
+            self.dropped = 0;
+        }
+
+        if self.count >= self.max_per_sec {
+            if self.dropped == 0 {
+                warn!(
+                    "🌊 WebSocket flood: more than {} messages/s, dropping until the rate falls",
+                    self.max_per_sec
+                );
+            }
+            self.dropped += 1;
+            return false;
+        }
+
+        self.count += 1;
+        true
+    }
+}
+
 pub struct WebSocketManager {
     config: MonitorConfig,
     ws_stream: Option<WsStream>,
-    reconnect_attempts: u32,
     subscription_id: Option<u64>,
+    /// Id for the next outgoing JSON-RPC request
+    next_request_id: u64,
+    /// Notifications that arrived while waiting for a request's response
+    pending: VecDeque<Value>,
+    /// Drops messages arriving faster than `max_notifications_per_sec`
+    rate_guard: RateGuard,
+    keepalive: Keepalive,
 }
 
 impl WebSocketManager {
     pub fn new(config: MonitorConfig) -> Self {
         Self {
+            rate_guard: RateGuard::new(config.max_notifications_per_sec),
+            keepalive: Keepalive::from_config(&config),
             config,
             ws_stream: None,
-            reconnect_attempts: 0,
             subscription_id: None,
+            next_request_id: 1,
+            pending: VecDeque::new(),
         }
     }
 
+    /// Replace the config used by later (re)connects, e.g. after a reload
+    pub fn set_config(&mut self, config: MonitorConfig) {
+        if config.websocket_endpoint != self.config.websocket_endpoint {
+            info!(
+                "WebSocket endpoint changed: {} → {}",
+                self.config.websocket_endpoint, config.websocket_endpoint
+            );
+        }
+        self.rate_guard.max_per_sec = config.max_notifications_per_sec;
+        self.keepalive = Keepalive::from_config(&config);
+        self.config = config;
+    }
+
     pub async fn connect(&mut self) -> MonitorResult<()> {
-        info!(
-            "Connecting to WebSocket: {}",
-            self.config.websocket_endpoint
-        );
-        match connect_async(&self.config.websocket_endpoint).await {
-            Ok((stream, response)) => {
-                info!("WebSocket connected: {:?}", response.status());
-                self.ws_stream = Some(stream);
-                self.reconnect_attempts = 0;
-                Ok(())
-            }
-            Err(e) => {
-                error!("Failed to connect to WebSocket: {}", e);
-                Err(MonitorError::ConnectionFailed(e.to_string()))
-            }
+        let stream = open_stream(&self.config.websocket_endpoint, self.ws_config()).await?;
+        self.ws_stream = Some(stream);
+        self.keepalive.received();
+        Ok(())
+    }
+
+    /// Build the WebSocket protocol config used for every (re)connect.
+    ///
+    /// There is no permessage-deflate: tungstenite 0.21 rejects frames with
+    /// the RSV1 (compressed) bit set, so compression can't be negotiated.
+    fn ws_config(&self) -> WebSocketConfig {
+        WebSocketConfig {
+            max_message_size: Some(self.config.max_ws_message_bytes),
+            max_frame_size: Some(self.config.max_ws_message_bytes),
+            ..WebSocketConfig::default()
         }
     }
 
-    pub async fn subscribe_to_address(&mut self, address: &Pubkey) -> MonitorResult<()> {
+    /// Parse a text frame, unless it's oversized or over the rate limit
+    fn accept_text(&mut self, text: &str) -> MonitorResult<Option<Value>> {
+        if text.len() > self.config.max_ws_message_bytes {
+            error!(
+                "Rejected oversized WebSocket message: {} bytes (max {})",
+                text.len(),
+                self.config.max_ws_message_bytes
+            );
+            return Ok(None);
+        }
+
+        if !self.rate_guard.allow(Instant::now()) {
+            return Ok(None);
+        }
+
+        debug!("Received message: {}", text);
+        Ok(Some(serde_json::from_str(text)?))
+    }
+
+    /// Subscribe to account changes, returning the subscription id
+    pub async fn subscribe_to_address(&mut self, address: &Pubkey) -> MonitorResult<u64> {
         info!("Subscribing to address: {}", address);
 
         let commitment = if self.config.use_confirmed_commitment {
@@ -55,9 +279,10 @@ impl WebSocketManager {
             "finalized"
         };
 
+        let request_id = self.next_id();
         let subscribe_request = json!({
             "jsonrpc": "2.0",
-            "id": 1,
+            "id": request_id,
             "method": "accountSubscribe",
             "params": [
                 address.to_string(),
@@ -71,29 +296,14 @@ impl WebSocketManager {
         self.send_message(&subscribe_request).await?;
 
         // Wait for subscription confirmation
-        if let Some(response) = self.receive_message().await? {
-            if let Some(result) = response.get("result") {
-                if let Some(sub_id) = result.as_u64() {
-                    self.subscription_id = Some(sub_id);
-                    info!("Subscribed with ID: {}", sub_id);
-                    return Ok(());
-                }
-            }
-
-            if let Some(error) = response.get("error") {
-                return Err(MonitorError::InvalidResponse(format!(
-                    "Subscription failed: {:?}",
-                    error
-                )));
-            }
-        }
-
-        Err(MonitorError::InvalidResponse(
-            "No subscription confirmation received".to_string(),
-        ))
+        let sub_id = self.await_subscription(request_id).await?;
+        self.subscription_id = Some(sub_id);
+        info!("Subscribed with ID: {}", sub_id);
+        Ok(sub_id)
     }
 
-    pub async fn subscribe_to_logs(&mut self, address: &Pubkey) -> MonitorResult<()> {
+    /// Subscribe to logs mentioning the address, returning the subscription id
+    pub async fn subscribe_to_logs(&mut self, address: &Pubkey) -> MonitorResult<u64> {
         info!("Subscribing to logs for address: {}", address);
 
         let commitment = if self.config.use_confirmed_commitment {
@@ -102,9 +312,10 @@ impl WebSocketManager {
             "finalized"
         };
         // Subscribe to all transactions mentioning this account
+        let request_id = self.next_id();
         let subscribe_request = json!({
             "jsonrpc": "2.0",
-            "id": 2,
+            "id": request_id,
             "method": "logsSubscribe",
             "params": [
                 {
@@ -118,31 +329,86 @@ impl WebSocketManager {
         self.send_message(&subscribe_request).await?;
 
         // Wait for subscription confirmation
-        if let Some(response) = self.receive_message().await? {
-            if let Some(result) = response.get("result") {
-                if let Some(sub_id) = result.as_u64() {
-                    info!("Logs subscription ID: {}", sub_id);
-                    return Ok(());
+        let sub_id = self.await_subscription(request_id).await?;
+        info!("Logs subscription ID: {}", sub_id);
+        Ok(sub_id)
+    }
+
+    /// Subscribe to logs mentioning each address, returning the subscription ids
+    ///
+    /// `logsSubscribe` accepts a single address in `mentions`, so every
+    /// wallet gets its own subscription on the shared connection.
+    pub async fn subscribe_to_wallets(&mut self, addresses: &[Pubkey]) -> MonitorResult<Vec<u64>> {
+        let mut sub_ids = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            sub_ids.push(self.subscribe_to_logs(address).await?);
+        }
+        Ok(sub_ids)
+    }
+
+    /// Allocate a unique id for an outgoing request
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_request_id;
+        self.next_request_id += 1;
+        id
+    }
+
+    /// Wait for the response to request `id`, buffering any notifications
+    /// that arrive first so `receive_message` still delivers them
+    async fn await_subscription(&mut self, id: u64) -> MonitorResult<u64> {
+        let timeout = Duration::from_secs(self.config.connection_timeout_secs);
+
+        let response = tokio::time::timeout(timeout, async {
+            loop {
+                if let Some(message) = self.read_message().await?
+                    && let Some(response) = self.take_response(message, id)
+                {
+                    return Ok::<_, MonitorError>(response);
                 }
             }
+        })
+        .await
+        .map_err(|_| MonitorError::Timeout)??;
 
-            if let Some(error) = response.get("error") {
-                warn!("Logs subscription failed: {:?}", error);
-            }
+        if let Some(sub_id) = response.get("result").and_then(|r| r.as_u64()) {
+            return Ok(sub_id);
         }
 
-        Ok(())
+        Err(MonitorError::InvalidResponse(format!(
+            "Subscription failed: {:?}",
+            response.get("error")
+        )))
     }
 
-    /// Receive the next message from the WebSocket
+    /// Return the message if it answers request `id`; otherwise buffer it
+    /// (notifications) or drop it (stale responses to other requests)
+    fn take_response(&mut self, message: Value, id: u64) -> Option<Value> {
+        match message.get("id").and_then(|i| i.as_u64()) {
+            Some(response_id) if response_id == id => Some(message),
+            Some(response_id) => {
+                debug!("Ignoring response to request {}", response_id);
+                None
+            }
+            None => {
+                self.pending.push_back(message);
+                None
+            }
+        }
+    }
+
+    /// Receive the next message, draining buffered notifications first
     pub async fn receive_message(&mut self) -> MonitorResult<Option<Value>> {
+        if let Some(message) = self.pending.pop_front() {
+            return Ok(Some(message));
+        }
+        self.read_message().await
+    }
+
+    /// Read the next message from the WebSocket
+    async fn read_message(&mut self) -> MonitorResult<Option<Value>> {
         if let Some(stream) = &mut self.ws_stream {
-            match stream.next().await {
-                Some(Ok(Message::Text(text))) => {
-                    debug!("Received message: {}", text);
-                    let value: Value = serde_json::from_str(&text)?;
-                    Ok(Some(value))
-                }
+            match next_frame(stream, &mut self.keepalive).await? {
+                Some(Ok(Message::Text(text))) => self.accept_text(&text),
                 Some(Ok(Message::Ping(_))) => {
                     debug!("Received ping");
                     Ok(None)
@@ -151,15 +417,42 @@ impl WebSocketManager {
                     debug!("Received pong");
                     Ok(None)
                 }
-                Some(Ok(Message::Close(_))) => {
-                    warn!("WebSocket closed by server");
-                    Err(MonitorError::ConnectionFailed(
-                        "Connection closed".to_string(),
-                    ))
+                Some(Ok(Message::Close(frame))) => {
+                    let Some(frame) = frame else {
+                        warn!("WebSocket closed by server");
+                        return Err(MonitorError::ConnectionFailed(
+                            "Connection closed".to_string(),
+                        ));
+                    };
+
+                    let code = u16::from(frame.code);
+                    if is_recoverable_close(code) {
+                        warn!(
+                            "WebSocket closed by server (code {}): {}",
+                            code, frame.reason
+                        );
+                        Err(MonitorError::ConnectionFailed(format!(
+                            "Connection closed ({}): {}",
+                            code, frame.reason
+                        )))
+                    } else {
+                        error!(
+                            "WebSocket closed by server with non-recoverable code {}: {} - not reconnecting",
+                            code, frame.reason
+                        );
+                        Err(MonitorError::ConnectionRejected {
+                            code,
+                            reason: frame.reason.to_string(),
+                        })
+                    }
+                }
+                Some(Err(WsError::Capacity(e))) => {
+                    error!("Rejected oversized WebSocket frame: {}", e);
+                    Err(MonitorError::from(WsError::Capacity(e)))
                 }
                 Some(Err(e)) => {
                     error!("WebSocket error: {}", e);
-                    Err(MonitorError::WebSocketError(e))
+                    Err(MonitorError::from(e))
                 }
                 None => {
                     warn!("WebSocket stream ended");
@@ -180,38 +473,55 @@ impl WebSocketManager {
             stream
                 .send(Message::Text(text))
                 .await
-                .map_err(|e| MonitorError::WebSocketError(e))?;
+                .map_err(MonitorError::from)?;
             Ok(())
         } else {
             Err(MonitorError::ConnectionFailed("Not connected".to_string()))
         }
     }
 
-    /// Attempt to reconnect with exponential backoff
+    /// Attempt to reconnect with exponential backoff, jittered so clients
+    /// dropped together don't all retry at the same moment
     pub async fn reconnect(&mut self) -> MonitorResult<()> {
-        if self.reconnect_attempts >= self.config.max_reconnect_attempts {
-            error!("Max reconnection attempts reached");
-            return Err(MonitorError::MaxReconnectAttemptsExceeded);
-        }
-
-        self.reconnect_attempts += 1;
-        let backoff_secs = 2u64.pow(self.reconnect_attempts.min(5));
+        let policy = RetryPolicy {
+            max_attempts: self.config.max_reconnect_attempts,
+            base_delay: Duration::from_secs(2),
+            multiplier: 2.0,
+            jitter: 0.25,
+            max_delay: Some(Duration::from_secs(self.config.max_reconnect_backoff_secs)),
+        };
 
-        warn!(
-            "Reconnecting (attempt {}/{}) in {} seconds...",
-            self.reconnect_attempts, self.config.max_reconnect_attempts, backoff_secs
-        );
+        let endpoint = &self.config.websocket_endpoint;
+        let ws_config = self.ws_config();
 
-        sleep(Duration::from_secs(backoff_secs)).await;
+        let result = retry_with_backoff(
+            &policy,
+            |e| {
+                matches!(
+                    e,
+                    MonitorError::ConnectionFailed(_) | MonitorError::WebSocketError(_)
+                )
+            },
+            |attempt| {
+                warn!(
+                    "Reconnecting (attempt {}/{})...",
+                    attempt, policy.max_attempts
+                );
+                open_stream(endpoint, ws_config)
+            },
+        )
+        .await;
 
-        match self.connect().await {
-            Ok(_) => {
+        match result {
+            Ok(stream) => {
                 info!("Reconnected successfully");
+                self.ws_stream = Some(stream);
+                self.keepalive.received();
                 Ok(())
             }
             Err(e) => {
-                error!("Reconnection failed: {}", e);
-                Err(e)
+                error!("Max reconnection attempts reached: {}", e);
+                Err(MonitorError::MaxReconnectAttemptsExceeded)
             }
         }
     }
@@ -221,7 +531,7 @@ impl WebSocketManager {
             stream
                 .send(Message::Ping(vec![]))
                 .await
-                .map_err(|e| MonitorError::WebSocketError(e))?;
+                .map_err(MonitorError::from)?;
             debug!("Health check ping sent");
             Ok(())
         } else {
@@ -238,11 +548,205 @@ impl WebSocketManager {
     pub async fn close(&mut self) -> MonitorResult<()> {
         if let Some(mut stream) = self.ws_stream.take() {
             info!("Closing WebSocket connection");
-            stream
-                .close(None)
-                .await
-                .map_err(|e| MonitorError::WebSocketError(e))?;
+            stream.close(None).await.map_err(MonitorError::from)?;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::channel::mpsc;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    /// A connection that yields whatever is pushed into `incoming` and
+    /// records what is sent on it
+    struct MockStream {
+        incoming: mpsc::UnboundedReceiver<Result<Message, WsError>>,
+        sent: Vec<Message>,
+    }
+
+    impl MockStream {
+        fn new() -> (Self, mpsc::UnboundedSender<Result<Message, WsError>>) {
+            let (tx, rx) = mpsc::unbounded();
+            let stream = Self {
+                incoming: rx,
+                sent: Vec::new(),
+            };
+            (stream, tx)
+        }
+    }
+
+    impl Stream for MockStream {
+        type Item = Result<Message, WsError>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.incoming.poll_next_unpin(cx)
+        }
+    }
+
+    impl Sink<Message> for MockStream {
+        type Error = WsError;
+
+        fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), WsError> {
+            self.sent.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn test_close_code_classification() {
+        // Recoverable: normal shutdown, going away, server error, try again later
+        assert!(is_recoverable_close(1000));
+        assert!(is_recoverable_close(1001));
+        assert!(is_recoverable_close(1011));
+        assert!(is_recoverable_close(1013));
+
+        // Fatal: protocol/policy violations and auth failures
+        assert!(!is_recoverable_close(1002));
+        assert!(!is_recoverable_close(1008));
+        assert!(!is_recoverable_close(4001));
+        assert!(!is_recoverable_close(4003));
+    }
+
+    #[tokio::test]
+    async fn test_interleaved_notifications_are_buffered() {
+        let mut manager = WebSocketManager::new(MonitorConfig::default());
+        let id = manager.next_id();
+        assert_ne!(manager.next_id(), id);
+
+        let notification = json!({"jsonrpc": "2.0", "method": "logsNotification", "params": {}});
+        let stale = json!({"jsonrpc": "2.0", "id": id + 100, "result": 7});
+        let response = json!({"jsonrpc": "2.0", "id": id, "result": 42});
+
+        assert!(manager.take_response(notification.clone(), id).is_none());
+        assert!(manager.take_response(stale, id).is_none());
+        let matched = manager.take_response(response, id).unwrap();
+        assert_eq!(matched["result"], 42);
+
+        // The notification is delivered afterwards, even without a connection
+        assert_eq!(manager.receive_message().await.unwrap(), Some(notification));
+        assert!(manager.receive_message().await.is_err());
+    }
+
+    #[test]
+    fn test_oversized_message_rejected() {
+        let config = MonitorConfig {
+            max_ws_message_bytes: 64,
+            ..MonitorConfig::default()
+        };
+        let mut manager = WebSocketManager::new(config);
+        assert_eq!(manager.ws_config().max_message_size, Some(64));
+
+        let small = json!({"jsonrpc": "2.0", "result": 1}).to_string();
+        assert!(manager.accept_text(&small).unwrap().is_some());
+
+        // Too big: dropped before parsing, so invalid JSON doesn't error either
+        let big = "x".repeat(65);
+        assert!(manager.accept_text(&big).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_rate_guard_drops_flood() {
+        let mut guard = RateGuard::new(3);
+        let start = guard.window_start;
+
+        assert!((0..3).all(|_| guard.allow(start)));
+        assert!(!guard.allow(start + Duration::from_millis(500)));
+        assert_eq!(guard.dropped, 1);
+
+        // The next window starts fresh
+        assert!(guard.allow(start + Duration::from_secs(1)));
+        assert_eq!(guard.dropped, 0);
+
+        let mut unlimited = RateGuard::new(0);
+        assert!((0..1_000).all(|_| unlimited.allow(start)));
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_detects_half_open_connection() {
+        // The server never answers, but the socket stays open
+        let (mut stream, _server) = MockStream::new();
+        let mut keepalive = Keepalive::new(Duration::from_millis(20), Duration::from_millis(20));
+
+        let result = next_frame(&mut stream, &mut keepalive).await;
+
+        assert!(matches!(result, Err(MonitorError::ConnectionFailed(_))));
+        assert_eq!(stream.sent, vec![Message::Ping(vec![])]);
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_pong_keeps_connection() {
+        let (mut stream, server) = MockStream::new();
+        let mut keepalive = Keepalive::new(Duration::from_millis(20), Duration::from_secs(5));
+
+        // Reply after the first ping has gone out
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            server.unbounded_send(Ok(Message::Pong(vec![]))).unwrap();
+        });
+
+        let frame = next_frame(&mut stream, &mut keepalive).await.unwrap();
+
+        assert!(matches!(frame, Some(Ok(Message::Pong(_)))));
+        assert_eq!(stream.sent, vec![Message::Ping(vec![])]);
+        assert!(keepalive.ping_sent.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_opens_a_working_connection() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let config = MonitorConfig {
+            websocket_endpoint: format!("ws://{}", listener.local_addr().unwrap()),
+            max_reconnect_attempts: 3,
+            ..MonitorConfig::default()
+        };
+        let notification = json!({"jsonrpc": "2.0", "method": "logsNotification", "params": {}});
+
+        // Drop the first connection after the handshake; talk on the second
+        let pushed = notification.to_string();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            drop(tokio_tungstenite::accept_async(socket).await.unwrap());
+
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut stream = tokio_tungstenite::accept_async(socket).await.unwrap();
+            stream.send(Message::Text(pushed)).await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let mut manager = WebSocketManager::new(config);
+        manager.connect().await.unwrap();
+        manager.reconnect().await.unwrap();
+
+        assert_eq!(manager.receive_message().await.unwrap(), Some(notification));
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_disabled() {
+        let (mut stream, server) = MockStream::new();
+        let mut keepalive = Keepalive::new(Duration::ZERO, Duration::ZERO);
+        server
+            .unbounded_send(Ok(Message::Text("{}".to_string())))
+            .unwrap();
+
+        let frame = next_frame(&mut stream, &mut keepalive).await.unwrap();
+
+        assert!(matches!(frame, Some(Ok(Message::Text(_)))));
+        assert!(stream.sent.is_empty());
+    }
+}
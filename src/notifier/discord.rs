@@ -0,0 +1,211 @@
+use crate::notifier::{Notifier, TradeEvent, TradeSide};
+use futures::future::BoxFuture;
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use std::time::Duration;
+use tracing::warn;
+
+/// Embed colors
+const BUY_COLOR: u32 = 0x2ecc71;
+const SELL_COLOR: u32 = 0xe74c3c;
+
+/// Times a rate-limited message is retried before giving up
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Longest `retry_after` we are willing to wait, in seconds
+const MAX_RETRY_AFTER_SECS: f64 = 30.0;
+
+/// Posts each event as an embed to a Discord webhook
+pub struct DiscordNotifier {
+    http: reqwest::Client,
+    webhook_url: String,
+}
+
+impl DiscordNotifier {
+    pub fn new(webhook_url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            webhook_url: webhook_url.into(),
+        }
+    }
+}
+
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &str {
+        "discord"
+    }
+
+    fn notify<'a>(&'a self, event: &'a TradeEvent) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let body = json!({ "embeds": [embed(event)] });
+            let mut retries = 0;
+
+            loop {
+                // The webhook URL is a secret, so keep it out of errors
+                let response = self
+                    .http
+                    .post(&self.webhook_url)
+                    .json(&body)
+                    .send()
+                    .await
+                    .map_err(|e| e.without_url().to_string())?;
+
+                if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                    response
+                        .error_for_status()
+                        .map_err(|e| e.without_url().to_string())?;
+                    return Ok(());
+                }
+
+                if retries == MAX_RATE_LIMIT_RETRIES {
+                    return Err("still rate limited, giving up".to_string());
+                }
+                retries += 1;
+
+                // Discord says how long to back off, in (fractional) seconds
+                let retry_after = response
+                    .json::<Value>()
+                    .await
+                    .ok()
+                    .and_then(|body| body.get("retry_after").and_then(Value::as_f64))
+                    .unwrap_or(1.0)
+                    .clamp(0.0, MAX_RETRY_AFTER_SECS);
+                warn!("⏳ Discord rate limited - retrying in {}s", retry_after);
+                tokio::time::sleep(Duration::from_secs_f64(retry_after)).await;
+            }
+        })
+    }
+}
+
+/// Rich embed for `event`; the title links to the transaction
+fn embed(event: &TradeEvent) -> Value {
+    let signal = &event.signal;
+    let (title, color) = match event.side {
+        TradeSide::Buy => (format!("🟢 BUY {}", signal.output_mint), BUY_COLOR),
+        TradeSide::Sell => (format!("🔴 SELL {}", signal.input_mint), SELL_COLOR),
+    };
+
+    let mut fields = vec![
+        json!({ "name": "Swap type", "value": signal.swap_type.to_string(), "inline": true }),
+        json!({
+            "name": "DEX",
+            "value": signal.likely_dex.as_deref().unwrap_or("Unknown"),
+            "inline": true,
+        }),
+        json!({ "name": "Input", "value": signal.format_input() }),
+        json!({ "name": "Output", "value": signal.format_output() }),
+    ];
+    if let Some(entry_kind) = event.entry_kind {
+        fields.push(json!({ "name": "Entry", "value": entry_kind.to_string() }));
+    }
+    if let Some(pnl) = event.realized_pnl_percent {
+        fields.push(json!({ "name": "Realized", "value": format!("{:+.2}%", pnl) }));
+    }
+    fields.push(json!({
+        "name": "Solscan",
+        "value": format!(
+            "[Transaction]({}) · [Trader]({})",
+            signal.solscan_url(),
+            signal.trader_solscan_url()
+        ),
+    }));
+
+    json!({
+        "title": title,
+        "url": signal.solscan_url(),
+        "color": color,
+        "fields": fields,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection::types::UniversalSwapSignal;
+    use crate::portfolio::EntryKind;
+    use axum::Json;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use std::sync::{Arc, Mutex};
+
+    fn signal() -> UniversalSwapSignal {
+        UniversalSwapSignal::test_swap()
+            .with_amounts(250_000_000, 1_000_000_000)
+            .with_decimals(6, 9)
+            .with_dex("Raydium")
+    }
+
+    /// A webhook on localhost that rate limits the first `limited` posts,
+    /// returning its URL and the bodies it accepted
+    async fn mock_webhook(limited: usize) -> (String, Arc<Mutex<Vec<Value>>>) {
+        let accepted = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&accepted);
+        let calls = Arc::new(Mutex::new(0));
+        let app = axum::Router::new().route(
+            "/api/webhooks/1/secret",
+            post(move |Json(body): Json<Value>| async move {
+                let mut calls = calls.lock().unwrap();
+                *calls += 1;
+                if *calls <= limited {
+                    let limit = json!({ "message": "rate limited", "retry_after": 0.01 });
+                    return (StatusCode::TOO_MANY_REQUESTS, Json(limit));
+                }
+                sink.lock().unwrap().push(body);
+                (StatusCode::OK, Json(json!({})))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "http://{}/api/webhooks/1/secret",
+            listener.local_addr().unwrap()
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, accepted)
+    }
+
+    #[test]
+    fn test_embed() {
+        let signal = signal();
+
+        let buy = embed(&TradeEvent::buy(signal.clone()));
+        assert_eq!(buy["color"], BUY_COLOR);
+        assert_eq!(buy["url"], signal.solscan_url());
+        assert_eq!(buy["fields"][1]["value"], "Raydium");
+
+        let opened = embed(&TradeEvent::buy(signal.clone()).with_entry_kind(EntryKind::New));
+        assert_eq!(opened["fields"][4]["value"], "New position");
+
+        let sell = embed(&TradeEvent::sell(signal.clone(), 5.0));
+        assert_eq!(sell["color"], SELL_COLOR);
+        let fields = sell["fields"].as_array().unwrap();
+        assert!(fields.iter().any(|f| f["value"] == "+5.00%"));
+        let links = fields.last().unwrap()["value"].as_str().unwrap();
+        assert!(links.contains(&format!("[Trader]({})", signal.trader_solscan_url())));
+    }
+
+    #[tokio::test]
+    async fn test_retries_after_rate_limit() {
+        let (url, accepted) = mock_webhook(2).await;
+        let discord = DiscordNotifier::new(url);
+
+        discord.notify(&TradeEvent::buy(signal())).await.unwrap();
+
+        let accepted = accepted.lock().unwrap();
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(accepted[0]["embeds"][0]["color"], BUY_COLOR);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_when_still_rate_limited() {
+        let (url, accepted) = mock_webhook(usize::MAX).await;
+        let discord = DiscordNotifier::new(url);
+
+        let error = discord
+            .notify(&TradeEvent::buy(signal()))
+            .await
+            .unwrap_err();
+        assert!(error.contains("rate limited"));
+        assert!(accepted.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,255 @@
+//! Trade notifications
+//!
+//! Every sink implements [`Notifier`]; the pipeline only ever talks to a
+//! [`NotifierSet`], which fans each event out to all configured sinks at
+//! once. A failing or slow sink is logged and never holds up the others.
+
+use crate::detection::types::UniversalSwapSignal;
+use crate::portfolio::{EntryKind, PortfolioStats};
+use crate::types::NotificationsConfig;
+use futures::future::{BoxFuture, join_all};
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+
+pub mod discord;
+pub mod telegram;
+
+pub use discord::DiscordNotifier;
+pub use telegram::TelegramNotifier;
+
+/// Which side of a trade we copied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+/// A copied buy or sell, as sent to every notifier
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeEvent {
+    pub side: TradeSide,
+    pub signal: UniversalSwapSignal,
+    /// Whether a buy opened a position or added to one (buys only)
+    pub entry_kind: Option<EntryKind>,
+    /// Realized P&L of the closed position (sells only)
+    pub realized_pnl_percent: Option<f64>,
+    /// Portfolio stats after the trade, if known
+    pub portfolio: Option<PortfolioStats>,
+}
+
+impl TradeEvent {
+    /// A copied buy
+    pub fn buy(signal: UniversalSwapSignal) -> Self {
+        Self {
+            side: TradeSide::Buy,
+            signal,
+            entry_kind: None,
+            realized_pnl_percent: None,
+            portfolio: None,
+        }
+    }
+
+    /// A copied sell that closed a position at `realized_pnl_percent`
+    pub fn sell(signal: UniversalSwapSignal, realized_pnl_percent: f64) -> Self {
+        Self {
+            side: TradeSide::Sell,
+            signal,
+            entry_kind: None,
+            realized_pnl_percent: Some(realized_pnl_percent),
+            portfolio: None,
+        }
+    }
+
+    /// Note whether the buy opened a position or added to one
+    pub fn with_entry_kind(mut self, entry_kind: EntryKind) -> Self {
+        self.entry_kind = Some(entry_kind);
+        self
+    }
+
+    /// Attach the portfolio stats after the trade
+    pub fn with_portfolio(mut self, stats: PortfolioStats) -> Self {
+        self.portfolio = Some(stats);
+        self
+    }
+}
+
+/// A destination for trade events
+pub trait Notifier: Send + Sync {
+    /// Short name used in logs
+    fn name(&self) -> &str;
+
+    /// Deliver one event
+    fn notify<'a>(&'a self, event: &'a TradeEvent) -> BoxFuture<'a, Result<(), String>>;
+}
+
+/// Appends each event as a JSON line to a file
+pub struct JsonLogNotifier {
+    path: String,
+}
+
+impl JsonLogNotifier {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Notifier for JsonLogNotifier {
+    fn name(&self) -> &str {
+        "json-log"
+    }
+
+    fn notify<'a>(&'a self, event: &'a TradeEvent) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let mut line = serde_json::to_string(event).map_err(|e| e.to_string())?;
+            line.push('\n');
+
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await
+                .map_err(|e| format!("{}: {}", self.path, e))?;
+            file.write_all(line.as_bytes())
+                .await
+                .map_err(|e| format!("{}: {}", self.path, e))?;
+            // tokio files finish writing in the background unless flushed
+            file.flush()
+                .await
+                .map_err(|e| format!("{}: {}", self.path, e))
+        })
+    }
+}
+
+/// All enabled notifiers
+#[derive(Default)]
+pub struct NotifierSet {
+    notifiers: Vec<Box<dyn Notifier>>,
+}
+
+impl NotifierSet {
+    /// Build every notifier enabled in config
+    pub fn from_config(config: &NotificationsConfig) -> Self {
+        let mut set = Self::default();
+        if let Some(path) = &config.json_log_path {
+            set.push(Box::new(JsonLogNotifier::new(path.clone())));
+        }
+        match (&config.telegram_bot_token, &config.telegram_chat_id) {
+            (Some(token), Some(chat_id)) => set.push(Box::new(TelegramNotifier::new(
+                token.clone(),
+                chat_id.clone(),
+            ))),
+            (None, None) => {}
+            _ => warn!("🔕 Telegram needs both telegram_bot_token and telegram_chat_id - disabled"),
+        }
+        if let Some(url) = &config.discord_webhook_url {
+            set.push(Box::new(DiscordNotifier::new(url.clone())));
+        }
+        set
+    }
+
+    /// Add a notifier
+    pub fn push(&mut self, notifier: Box<dyn Notifier>) {
+        info!("🔔 Notifications enabled: {}", notifier.name());
+        self.notifiers.push(notifier);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifiers.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.notifiers.len()
+    }
+
+    /// Send `event` to every notifier concurrently. Failures are logged;
+    /// returns how many sinks failed.
+    pub async fn notify_all(&self, event: &TradeEvent) -> usize {
+        let results = join_all(self.notifiers.iter().map(|n| n.notify(event))).await;
+
+        let mut failed = 0;
+        for (notifier, result) in self.notifiers.iter().zip(results) {
+            if let Err(e) = result {
+                warn!("🔕 {} notification failed: {}", notifier.name(), e);
+                failed += 1;
+            }
+        }
+        failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counting(Arc<AtomicUsize>);
+
+    impl Notifier for Counting {
+        fn name(&self) -> &str {
+            "counting"
+        }
+
+        fn notify<'a>(&'a self, _event: &'a TradeEvent) -> BoxFuture<'a, Result<(), String>> {
+            Box::pin(async move {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+        }
+    }
+
+    struct Failing;
+
+    impl Notifier for Failing {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn notify<'a>(&'a self, _event: &'a TradeEvent) -> BoxFuture<'a, Result<(), String>> {
+            Box::pin(async { Err("unreachable".to_string()) })
+        }
+    }
+
+    fn signal() -> UniversalSwapSignal {
+        UniversalSwapSignal::test_swap()
+    }
+
+    #[tokio::test]
+    async fn test_failing_sink_does_not_block_others() {
+        let delivered = Arc::new(AtomicUsize::new(0));
+        let mut set = NotifierSet::default();
+        set.push(Box::new(Counting(Arc::clone(&delivered))));
+        set.push(Box::new(Failing));
+        set.push(Box::new(Counting(Arc::clone(&delivered))));
+
+        assert_eq!(set.notify_all(&TradeEvent::buy(signal())).await, 1);
+        assert_eq!(delivered.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_json_log_appends_lines() {
+        let path = std::env::temp_dir().join(format!("notify-{}.jsonl", Pubkey::new_unique()));
+        let set = NotifierSet::from_config(&NotificationsConfig {
+            json_log_path: Some(path.to_string_lossy().into_owned()),
+            ..NotificationsConfig::default()
+        });
+        assert_eq!(set.len(), 1);
+
+        assert_eq!(set.notify_all(&TradeEvent::buy(signal())).await, 0);
+        assert_eq!(set.notify_all(&TradeEvent::sell(signal(), 12.5)).await, 0);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let lines: Vec<serde_json::Value> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["side"], "buy");
+        assert_eq!(lines[1]["side"], "sell");
+        assert_eq!(lines[1]["realized_pnl_percent"], 12.5);
+    }
+}
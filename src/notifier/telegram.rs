@@ -0,0 +1,199 @@
+use crate::notifier::{Notifier, TradeEvent, TradeSide};
+use futures::future::BoxFuture;
+use serde_json::json;
+use std::fmt::Write;
+
+/// Telegram Bot API
+pub const DEFAULT_TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// Sends each event as a message from a Telegram bot to one chat
+pub struct TelegramNotifier {
+    http: reqwest::Client,
+    api_url: String,
+    bot_token: String,
+    chat_id: String,
+}
+
+impl TelegramNotifier {
+    pub fn new(bot_token: impl Into<String>, chat_id: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: DEFAULT_TELEGRAM_API_URL.to_string(),
+            bot_token: bot_token.into(),
+            chat_id: chat_id.into(),
+        }
+    }
+
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    fn send_message_url(&self) -> String {
+        format!("{}/bot{}/sendMessage", self.api_url, self.bot_token)
+    }
+}
+
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    fn notify<'a>(&'a self, event: &'a TradeEvent) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let body = json!({
+                "chat_id": self.chat_id,
+                "text": format_message(event),
+                "disable_web_page_preview": true,
+            });
+
+            // The URL holds the bot token, so keep it out of the error
+            self.http
+                .post(self.send_message_url())
+                .json(&body)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| e.without_url().to_string())?;
+            Ok(())
+        })
+    }
+}
+
+/// Plain-text message for `event` (no parse mode, so nothing needs escaping)
+fn format_message(event: &TradeEvent) -> String {
+    let signal = &event.signal;
+    let mut text = String::new();
+
+    match event.side {
+        TradeSide::Buy => {
+            let _ = writeln!(text, "🟢 BUY {}", signal.output_mint);
+            let _ = writeln!(text, "Amount: {}", signal.format_output());
+            let _ = writeln!(text, "Paid: {}", signal.format_input());
+            if let Some(entry_kind) = event.entry_kind {
+                let _ = writeln!(text, "Entry: {}", entry_kind);
+            }
+        }
+        TradeSide::Sell => {
+            let _ = writeln!(text, "🔴 SELL {}", signal.input_mint);
+            let _ = writeln!(text, "Amount: {}", signal.format_input());
+            let _ = writeln!(text, "Received: {}", signal.format_output());
+        }
+    }
+    if let Some(pnl) = event.realized_pnl_percent {
+        let _ = writeln!(text, "Realized: {:+.2}%", pnl);
+    }
+
+    if let Some(stats) = &event.portfolio {
+        let _ = write!(
+            text,
+            "\nPortfolio: {} open, realized P&L {}",
+            stats.active_positions, stats.total_realized_pnl
+        );
+        if let Some(unrealized) = stats.unrealized_pnl {
+            let _ = write!(text, ", unrealized {}", unrealized);
+        }
+        text.push('\n');
+    }
+
+    let _ = write!(
+        text,
+        "\nTx: {}\nTrader: {}",
+        signal.solscan_url(),
+        signal.trader_solscan_url()
+    );
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detection::types::UniversalSwapSignal;
+    use crate::portfolio::{EntryKind, PortfolioStats};
+    use axum::Json;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+    use serde_json::Value;
+    use std::sync::{Arc, Mutex};
+
+    fn signal() -> UniversalSwapSignal {
+        UniversalSwapSignal::test_swap()
+            .with_amounts(250_000_000, 1_000_000_000)
+            .with_decimals(6, 9)
+    }
+
+    /// A Bot API stand-in on localhost, returning its URL and the bodies it
+    /// received
+    async fn mock_bot_api(status: StatusCode) -> (String, Arc<Mutex<Vec<Value>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&received);
+        let app = axum::Router::new().route(
+            "/botTEST-TOKEN/sendMessage",
+            post(move |Json(body): Json<Value>| async move {
+                sink.lock().unwrap().push(body);
+                (status, Json(json!({ "ok": status.is_success() })))
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        (url, received)
+    }
+
+    #[test]
+    fn test_message_contents() {
+        let signal = signal();
+        let event = TradeEvent::sell(signal.clone(), -12.5).with_portfolio(PortfolioStats {
+            active_positions: 2,
+            closed_positions: 1,
+            total_invested: 0,
+            total_realized_pnl: -31_250_000,
+            win_rate: 0.0,
+            market_value: None,
+            unrealized_pnl: None,
+        });
+
+        let text = format_message(&event);
+        assert!(text.starts_with(&format!("🔴 SELL {}", signal.input_mint)));
+        assert!(text.contains("Amount: 250 "));
+        assert!(text.contains("Realized: -12.50%"));
+        assert!(text.contains("Portfolio: 2 open, realized P&L -31250000"));
+        assert!(text.contains(&signal.solscan_url()));
+        assert!(text.contains(&signal.trader_solscan_url()));
+
+        let buy = TradeEvent::buy(signal).with_entry_kind(EntryKind::Add);
+        assert!(format_message(&buy).contains("Entry: Add to position"));
+    }
+
+    #[tokio::test]
+    async fn test_posts_to_bot_api() {
+        let (url, received) = mock_bot_api(StatusCode::OK).await;
+        let telegram = TelegramNotifier::new("TEST-TOKEN", "-1001234").with_api_url(url);
+        let signal = signal();
+
+        telegram
+            .notify(&TradeEvent::buy(signal.clone()))
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0]["chat_id"], "-1001234");
+        let text = received[0]["text"].as_str().unwrap();
+        assert!(text.starts_with(&format!("🟢 BUY {}", signal.output_mint)));
+    }
+
+    #[tokio::test]
+    async fn test_api_error_is_reported_without_token() {
+        let (url, _) = mock_bot_api(StatusCode::UNAUTHORIZED).await;
+        let telegram = TelegramNotifier::new("TEST-TOKEN", "1").with_api_url(url);
+
+        let error = telegram
+            .notify(&TradeEvent::buy(signal()))
+            .await
+            .unwrap_err();
+        assert!(error.contains("401"), "{}", error);
+        assert!(!error.contains("TEST-TOKEN"), "{}", error);
+    }
+}
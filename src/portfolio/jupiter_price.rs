@@ -0,0 +1,154 @@
+use crate::decision::Stablecoins;
+use crate::detection::decimals::DecimalsCache;
+use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::portfolio::price::PriceProvider;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Jupiter price API v2
+pub const DEFAULT_PRICE_API_URL: &str = "https://api.jup.ag/price/v2";
+
+/// Prices from the Jupiter price API.
+///
+/// Jupiter quotes UI amounts (one whole token in whole quote tokens); they
+/// are converted into smallest units with both mints' decimals, as
+/// [`PriceProvider`] expects. `get_price` quotes in USDC unless another
+/// quote mint is set.
+pub struct JupiterPriceProvider {
+    http: reqwest::Client,
+    api_url: String,
+    quote_mint: Pubkey,
+    decimals: Arc<DecimalsCache>,
+}
+
+impl JupiterPriceProvider {
+    pub fn new(decimals: Arc<DecimalsCache>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            api_url: DEFAULT_PRICE_API_URL.to_string(),
+            quote_mint: Pubkey::from_str(Stablecoins::USDC).expect("Invalid USDC pubkey"),
+            decimals,
+        }
+    }
+
+    /// Quote `get_price` in `quote_mint` instead of USDC
+    pub fn with_quote_mint(mut self, quote_mint: Pubkey) -> Self {
+        self.quote_mint = quote_mint;
+        self
+    }
+
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into();
+        self
+    }
+
+    fn price_url(&self, mint: &Pubkey, quote_mint: &Pubkey) -> String {
+        format!("{}?ids={}&vsToken={}", self.api_url, mint, quote_mint)
+    }
+
+    /// Decimals of `mint`, fetching unknown mints off the async workers
+    async fn decimals(&self, mint: &Pubkey) -> MonitorResult<u8> {
+        if let Some(decimals) = self.decimals.get_cached(mint) {
+            return Ok(decimals);
+        }
+
+        let cache = Arc::clone(&self.decimals);
+        let mint = *mint;
+        tokio::task::spawn_blocking(move || cache.get(&mint))
+            .await
+            .map_err(|e| MonitorError::Unknown(format!("decimals lookup failed: {}", e)))?
+    }
+
+    async fn fetch_price(&self, mint: &Pubkey, quote_mint: &Pubkey) -> MonitorResult<f64> {
+        let body: Value = self
+            .http
+            .get(self.price_url(mint, quote_mint))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| MonitorError::InvalidResponse(format!("Jupiter price: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| MonitorError::InvalidResponse(format!("Jupiter price: {}", e)))?;
+
+        let ui_price = parse_ui_price(&body, mint)?;
+        let mint_decimals = self.decimals(mint).await?;
+        let quote_decimals = self.decimals(quote_mint).await?;
+
+        Ok(raw_price(ui_price, mint_decimals, quote_decimals))
+    }
+}
+
+impl PriceProvider for JupiterPriceProvider {
+    async fn get_price(&self, mint: &Pubkey) -> MonitorResult<f64> {
+        self.fetch_price(mint, &self.quote_mint).await
+    }
+
+    async fn get_price_in(&self, mint: &Pubkey, quote_mint: &Pubkey) -> MonitorResult<f64> {
+        self.fetch_price(mint, quote_mint).await
+    }
+}
+
+/// The mint's price in a price API response (`data.<mint>.price`, a string)
+fn parse_ui_price(body: &Value, mint: &Pubkey) -> MonitorResult<f64> {
+    body.get("data")
+        .and_then(|data| data.get(mint.to_string()))
+        .and_then(|entry| entry.get("price"))
+        .and_then(Value::as_str)
+        .and_then(|price| price.parse::<f64>().ok())
+        .ok_or_else(|| MonitorError::InvalidResponse(format!("No Jupiter price for {}", mint)))
+}
+
+/// Convert a whole-token price into smallest quote units per smallest token unit
+fn raw_price(ui_price: f64, mint_decimals: u8, quote_decimals: u8) -> f64 {
+    ui_price * 10f64.powi(quote_decimals as i32 - mint_decimals as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_ui_price() {
+        let mint = Pubkey::new_unique();
+        let body = json!({
+            "data": {
+                mint.to_string(): { "id": mint.to_string(), "type": "derivedPrice", "price": "132.5" }
+            },
+            "timeTaken": 0.003
+        });
+        assert_eq!(parse_ui_price(&body, &mint).unwrap(), 132.5);
+
+        // Unknown tokens come back as null
+        let unknown = Pubkey::new_unique();
+        let body = json!({ "data": { unknown.to_string(): null } });
+        assert!(parse_ui_price(&body, &unknown).is_err());
+    }
+
+    #[test]
+    fn test_raw_price() {
+        // SOL at 150 USDC: 150e6 USDC units per 1e9 lamports
+        assert!((raw_price(150.0, 9, 6) - 0.15).abs() < 1e-12);
+        // A 0-decimal token at 2 USDC
+        assert!((raw_price(2.0, 0, 6) - 2_000_000.0).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_decimals_come_from_the_cache() {
+        let mint = Pubkey::new_unique();
+        let cache = Arc::new(DecimalsCache::offline());
+        cache.insert(mint, 9);
+
+        let prices = JupiterPriceProvider::new(cache);
+        assert_eq!(prices.decimals(&mint).await.unwrap(), 9);
+        assert!(prices.decimals(&Pubkey::new_unique()).await.is_err());
+        assert!(
+            prices
+                .price_url(&mint, &prices.quote_mint)
+                .starts_with("https://api.jup.ag/price/v2?ids=")
+        );
+    }
+}
@@ -1,3 +1,16 @@
+pub mod clock;
+pub mod jupiter_price;
+pub mod price;
+pub mod reconcile;
+pub mod storage;
 pub mod tracker;
 
-pub use tracker::{ClosedPosition, PnL, PortfolioStats, PortfolioTracker, Position};
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use jupiter_price::JupiterPriceProvider;
+pub use price::{PriceProvider, PriceSample};
+pub use reconcile::{Discrepancy, fetch_token_balances, reconcile};
+pub use storage::{JsonStorage, SqliteStorage, Storage, open_storage};
+pub use tracker::{
+    ClosedPosition, EntryKind, Lot, MarketValuation, NormalizedStats, PnL, PortfolioSnapshot,
+    PortfolioStats, PortfolioTracker, Position, TokenStats, Trigger, TriggerKind,
+};
@@ -0,0 +1,65 @@
+use crate::monitor::error::MonitorResult;
+use solana_sdk::pubkey::Pubkey;
+use std::future::Future;
+
+/// Source of current token prices.
+///
+/// Prices are quoted like `Position::avg_entry_price`: payment-token units
+/// per token unit (both in smallest units), so they compare directly with
+/// a position's entry price.
+pub trait PriceProvider: Send + Sync {
+    /// Current price of the mint
+    fn get_price(&self, mint: &Pubkey) -> impl Future<Output = MonitorResult<f64>> + Send;
+
+    /// Current price of the mint in `quote_mint` units. Providers that only
+    /// quote one currency ignore `quote_mint`.
+    fn get_price_in(
+        &self,
+        mint: &Pubkey,
+        _quote_mint: &Pubkey,
+    ) -> impl Future<Output = MonitorResult<f64>> + Send {
+        self.get_price(mint)
+    }
+}
+
+/// Latest spot price and its exponential moving average
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceSample {
+    /// Most recent raw price, kept for display
+    pub spot: f64,
+
+    /// Smoothed price used for trigger decisions
+    pub ema: f64,
+}
+
+impl PriceSample {
+    /// First observation - the EMA starts at the spot price
+    pub fn new(spot: f64) -> Self {
+        Self { spot, ema: spot }
+    }
+
+    /// Fold a new spot price into the EMA with smoothing factor `alpha`
+    pub fn update(&mut self, spot: f64, alpha: f64) {
+        self.spot = spot;
+        self.ema = alpha * spot + (1.0 - alpha) * self.ema;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ema_smooths_spikes() {
+        let mut sample = PriceSample::new(1.0);
+
+        // A single spike moves the EMA only part of the way
+        sample.update(2.0, 0.25);
+        assert_eq!(sample.spot, 2.0);
+        assert!((sample.ema - 1.25).abs() < 1e-9);
+
+        // And it decays back once the spike is gone
+        sample.update(1.0, 0.25);
+        assert!((sample.ema - 1.1875).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,158 @@
+//! Compare the mirrored portfolio against the trader's on-chain holdings
+
+use crate::decision::Stablecoins;
+use crate::monitor::error::{MonitorError, MonitorResult};
+use crate::portfolio::PortfolioTracker;
+use crate::types::{program_ids, wsol_pubkey};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A mismatch between our positions and the trader's wallet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// We hold a position in a token the trader no longer holds
+    Phantom { mint: Pubkey, our_amount: u64 },
+
+    /// The trader holds a token we have no position in
+    Missed { mint: Pubkey, trader_amount: u64 },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::Phantom { mint, our_amount } => write!(
+                f,
+                "PHANTOM {} - we hold {}, the trader holds none",
+                mint, our_amount
+            ),
+            Discrepancy::Missed {
+                mint,
+                trader_amount,
+            } => write!(
+                f,
+                "MISSED  {} - the trader holds {}, we have no position",
+                mint, trader_amount
+            ),
+        }
+    }
+}
+
+/// Find positions the trader has exited and holdings we never mirrored.
+///
+/// Base currencies (stablecoins, WSOL) are what the trader pays with, not
+/// positions, so they're never reported as missed.
+pub fn reconcile(
+    portfolio: &PortfolioTracker,
+    trader_balances: &HashMap<Pubkey, u64>,
+) -> Vec<Discrepancy> {
+    let mut discrepancies: Vec<Discrepancy> = portfolio
+        .get_all_positions()
+        .into_iter()
+        .filter(|p| trader_balances.get(&p.token).copied().unwrap_or(0) == 0)
+        .map(|p| Discrepancy::Phantom {
+            mint: p.token,
+            our_amount: p.amount,
+        })
+        .collect();
+
+    let wsol = wsol_pubkey();
+    discrepancies.extend(
+        trader_balances
+            .iter()
+            .filter(|&(mint, &amount)| {
+                amount > 0
+                    && *mint != wsol
+                    && !Stablecoins::is_stablecoin(mint)
+                    && !portfolio.has_position(mint)
+            })
+            .map(|(&mint, &amount)| Discrepancy::Missed {
+                mint,
+                trader_amount: amount,
+            }),
+    );
+
+    discrepancies.sort_by_key(|d| match d {
+        Discrepancy::Phantom { mint, .. } => (0, *mint),
+        Discrepancy::Missed { mint, .. } => (1, *mint),
+    });
+    discrepancies
+}
+
+/// Current balance per mint across all of `owner`'s token accounts
+/// (SPL Token and Token-2022)
+pub fn fetch_token_balances(
+    client: &RpcClient,
+    owner: &Pubkey,
+) -> MonitorResult<HashMap<Pubkey, u64>> {
+    let mut balances = HashMap::new();
+
+    for program in [program_ids::token(), program_ids::token_2022()] {
+        let accounts =
+            client.get_token_accounts_by_owner(owner, TokenAccountsFilter::ProgramId(program))?;
+
+        for keyed in accounts {
+            // jsonParsed: { "parsed": { "info": { "mint", "tokenAmount": { "amount" } } } }
+            let data = serde_json::to_value(&keyed.account.data)?;
+            let info = &data["parsed"]["info"];
+            let (Some(mint), Some(amount)) = (
+                info["mint"].as_str(),
+                info["tokenAmount"]["amount"].as_str(),
+            ) else {
+                continue;
+            };
+
+            let mint = Pubkey::from_str(mint)
+                .map_err(|e| MonitorError::ParseError(format!("Invalid mint {}: {}", mint, e)))?;
+            let amount: u64 = amount
+                .parse()
+                .map_err(|e| MonitorError::ParseError(format!("Invalid amount: {}", e)))?;
+            *balances.entry(mint).or_insert(0) += amount;
+        }
+    }
+
+    Ok(balances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconcile_reports_both_directions() {
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+        let held = Pubkey::new_unique();
+        let sold = Pubkey::new_unique();
+        let missed = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(held, 1_000, usdc, 100, "sig1".to_string());
+        portfolio.open_position(sold, 2_000, usdc, 100, "sig2".to_string());
+
+        let trader_balances = HashMap::from([
+            (held, 10_000),
+            (sold, 0),
+            (missed, 5_000),
+            // Payment currencies aren't positions
+            (usdc, 1_000_000),
+            (wsol_pubkey(), 1_000_000),
+        ]);
+
+        assert_eq!(
+            reconcile(&portfolio, &trader_balances),
+            vec![
+                Discrepancy::Phantom {
+                    mint: sold,
+                    our_amount: 2_000,
+                },
+                Discrepancy::Missed {
+                    mint: missed,
+                    trader_amount: 5_000,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,521 @@
+//! Where portfolios are persisted
+//!
+//! The pipeline saves through a [`Storage`] after every buy and sell and
+//! loads through it on startup. [`JsonStorage`] is the original one file per
+//! portfolio; [`SqliteStorage`] keeps positions, closed positions and every
+//! detected signal in tables of a single database.
+
+use crate::detection::types::UniversalSwapSignal;
+use crate::portfolio::tracker::{ClosedPosition, Lot, PortfolioTracker, Position};
+use crate::types::{MonitorConfig, StorageBackend};
+use rusqlite::types::Type;
+use rusqlite::{Connection, Row, params};
+use solana_sdk::pubkey::Pubkey;
+use std::error::Error;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Persists one portfolio
+pub trait Storage: Send + Sync {
+    /// Where the portfolio lives, for logs
+    fn location(&self) -> String;
+
+    /// Load the saved portfolio (empty if nothing was saved yet)
+    fn load(&self) -> Result<PortfolioTracker, Box<dyn Error>>;
+
+    /// Save the portfolio as it is now
+    fn save(&self, portfolio: &PortfolioTracker) -> Result<(), Box<dyn Error>>;
+
+    /// Record a detected signal. Backends without a signal log ignore it.
+    fn record_signal(&self, _signal: &UniversalSwapSignal) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    /// Save, logging instead of returning failures
+    fn save_safe(&self, portfolio: &PortfolioTracker) {
+        if let Err(e) = self.save(portfolio) {
+            tracing::error!("❌ Failed to save portfolio {}: {}", self.location(), e);
+        }
+    }
+}
+
+/// Open the configured backend for the portfolio at `portfolio_path`
+pub fn open_storage(
+    config: &MonitorConfig,
+    portfolio_path: &str,
+) -> Result<Arc<dyn Storage>, Box<dyn Error>> {
+    Ok(match config.storage_backend {
+        StorageBackend::Json => Arc::new(JsonStorage::new(portfolio_path)),
+        StorageBackend::Sqlite => {
+            Arc::new(SqliteStorage::open(&config.sqlite_path, portfolio_path)?)
+        }
+    })
+}
+
+/// The whole portfolio as one JSON file, rewritten atomically on every save
+pub struct JsonStorage {
+    path: String,
+}
+
+impl JsonStorage {
+    pub fn new(path: impl Into<String>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Storage for JsonStorage {
+    fn location(&self) -> String {
+        self.path.clone()
+    }
+
+    fn load(&self) -> Result<PortfolioTracker, Box<dyn Error>> {
+        PortfolioTracker::load(&self.path)
+    }
+
+    fn save(&self, portfolio: &PortfolioTracker) -> Result<(), Box<dyn Error>> {
+        portfolio.save(&self.path)
+    }
+}
+
+/// Schema changes, applied in order. `PRAGMA user_version` records how many
+/// have run, so append new ones and never edit old ones.
+const MIGRATIONS: &[&str] = &[
+    // 1: signals, open positions and closed positions
+    "CREATE TABLE signals (
+        signature     TEXT PRIMARY KEY,
+        slot          INTEGER NOT NULL,
+        timestamp     INTEGER NOT NULL,
+        trader        TEXT NOT NULL,
+        input_mint    TEXT NOT NULL,
+        input_amount  INTEGER NOT NULL,
+        output_mint   TEXT NOT NULL,
+        output_amount INTEGER NOT NULL,
+        dex           TEXT,
+        data          TEXT NOT NULL
+    );
+    CREATE INDEX signals_by_trader ON signals (trader, timestamp);
+
+    CREATE TABLE positions (
+        portfolio       TEXT NOT NULL,
+        token           TEXT NOT NULL,
+        payment_token   TEXT NOT NULL,
+        amount          INTEGER NOT NULL,
+        cost_basis      INTEGER NOT NULL,
+        entry_time      INTEGER NOT NULL,
+        entry_signature TEXT NOT NULL,
+        avg_entry_price REAL NOT NULL,
+        lots            TEXT NOT NULL,
+        PRIMARY KEY (portfolio, token)
+    );
+
+    CREATE TABLE closed_positions (
+        id                   INTEGER PRIMARY KEY AUTOINCREMENT,
+        portfolio            TEXT NOT NULL,
+        token                TEXT NOT NULL,
+        payment_token        TEXT NOT NULL,
+        amount               INTEGER NOT NULL,
+        cost_basis           INTEGER NOT NULL,
+        entry_time           INTEGER NOT NULL,
+        entry_signature      TEXT NOT NULL,
+        avg_entry_price      REAL NOT NULL,
+        lots                 TEXT NOT NULL,
+        exit_time            INTEGER NOT NULL,
+        exit_signature       TEXT NOT NULL,
+        exit_value           INTEGER NOT NULL,
+        realized_pnl         INTEGER NOT NULL,
+        realized_pnl_percent REAL NOT NULL
+    );
+    CREATE INDEX closed_positions_by_portfolio ON closed_positions (portfolio, id);",
+    // 2: per-position stop-loss and take-profit
+    "ALTER TABLE positions ADD COLUMN stop_loss_percent REAL;
+    ALTER TABLE positions ADD COLUMN take_profit_percent REAL;",
+    // 3: trailing stops
+    "ALTER TABLE positions ADD COLUMN high_water_mark REAL NOT NULL DEFAULT 0;
+    ALTER TABLE positions ADD COLUMN trailing_stop_percent REAL;",
+    // 4: how much of each position our own copies bought
+    "ALTER TABLE positions ADD COLUMN copied_amount INTEGER NOT NULL DEFAULT 0;",
+];
+
+/// Columns shared by `positions` and `closed_positions`, in the order
+/// [`position_from_row`] reads them
+const POSITION_COLUMNS: &str =
+    "token, payment_token, amount, cost_basis, entry_time, entry_signature, avg_entry_price, lots";
+
+/// Portfolios and signals in a SQLite database. Several portfolios can
+/// share one database; each is stored under its own name.
+pub struct SqliteStorage {
+    path: String,
+    portfolio: String,
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Open (creating and migrating if needed) the database at `path`,
+    /// storing the portfolio called `portfolio`
+    pub fn open(path: &str, portfolio: impl Into<String>) -> rusqlite::Result<Self> {
+        let mut conn = Connection::open(path)?;
+        // Other portfolios may be writing through their own connection
+        conn.busy_timeout(Duration::from_secs(5))?;
+        migrate(&mut conn)?;
+
+        Ok(Self {
+            path: path.to_string(),
+            portfolio: portfolio.into(),
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn location(&self) -> String {
+        format!("{} ({})", self.path, self.portfolio)
+    }
+
+    fn load(&self) -> Result<PortfolioTracker, Box<dyn Error>> {
+        tracing::info!("📂 Loading portfolio from {}", self.location());
+        let conn = self.conn.lock().unwrap();
+
+        let positions = conn
+            .prepare(&format!(
+                "SELECT {}, stop_loss_percent, take_profit_percent, high_water_mark,
+                     trailing_stop_percent, copied_amount
+                 FROM positions WHERE portfolio = ?1",
+                POSITION_COLUMNS
+            ))?
+            .query_map([&self.portfolio], |row| {
+                Ok(Position {
+                    stop_loss_percent: row.get(8)?,
+                    take_profit_percent: row.get(9)?,
+                    high_water_mark: row.get(10)?,
+                    trailing_stop_percent: row.get(11)?,
+                    copied_amount: from_sql_u64(row.get(12)?),
+                    ..position_from_row(row)?
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let closed = conn
+            .prepare(&format!(
+                "SELECT {}, exit_time, exit_signature, exit_value, realized_pnl, realized_pnl_percent
+                 FROM closed_positions WHERE portfolio = ?1 ORDER BY id",
+                POSITION_COLUMNS
+            ))?
+            .query_map([&self.portfolio], |row| {
+                Ok(ClosedPosition {
+                    position: position_from_row(row)?,
+                    exit_time: from_sql_u64(row.get(8)?),
+                    exit_signature: row.get(9)?,
+                    exit_value: from_sql_u64(row.get(10)?),
+                    realized_pnl: row.get(11)?,
+                    realized_pnl_percent: row.get(12)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        let portfolio = PortfolioTracker::from_parts(positions, closed);
+        let stats = portfolio.get_stats();
+        tracing::info!("✅ Portfolio loaded successfully");
+        tracing::info!("   Active positions: {}", stats.active_positions);
+        tracing::info!("   Closed positions: {}", stats.closed_positions);
+        tracing::info!("   Total realized P&L: {}", stats.total_realized_pnl);
+        Ok(portfolio)
+    }
+
+    fn save(&self, portfolio: &PortfolioTracker) -> Result<(), Box<dyn Error>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "DELETE FROM positions WHERE portfolio = ?1",
+            [&self.portfolio],
+        )?;
+        for position in portfolio.get_all_positions() {
+            tx.execute(
+                &format!(
+                    "INSERT INTO positions (portfolio, {}, stop_loss_percent, take_profit_percent,
+                         high_water_mark, trailing_stop_percent, copied_amount)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                    POSITION_COLUMNS
+                ),
+                params![
+                    self.portfolio,
+                    position.token.to_string(),
+                    position.payment_token.to_string(),
+                    to_sql_u64(position.amount),
+                    to_sql_u64(position.cost_basis),
+                    to_sql_u64(position.entry_time),
+                    position.entry_signature,
+                    position.avg_entry_price,
+                    serde_json::to_string(&position.lots)?,
+                    position.stop_loss_percent,
+                    position.take_profit_percent,
+                    position.high_water_mark,
+                    position.trailing_stop_percent,
+                    to_sql_u64(position.copied_amount),
+                ],
+            )?;
+        }
+
+        // History only ever grows, so append whatever isn't stored yet
+        let stored: i64 = tx.query_row(
+            "SELECT COUNT(*) FROM closed_positions WHERE portfolio = ?1",
+            [&self.portfolio],
+            |row| row.get(0),
+        )?;
+        for closed in portfolio.get_history().iter().skip(stored as usize) {
+            let position = &closed.position;
+            tx.execute(
+                &format!(
+                    "INSERT INTO closed_positions (portfolio, {}, exit_time, exit_signature,
+                         exit_value, realized_pnl, realized_pnl_percent)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                    POSITION_COLUMNS
+                ),
+                params![
+                    self.portfolio,
+                    position.token.to_string(),
+                    position.payment_token.to_string(),
+                    to_sql_u64(position.amount),
+                    to_sql_u64(position.cost_basis),
+                    to_sql_u64(position.entry_time),
+                    position.entry_signature,
+                    position.avg_entry_price,
+                    serde_json::to_string(&position.lots)?,
+                    to_sql_u64(closed.exit_time),
+                    closed.exit_signature,
+                    to_sql_u64(closed.exit_value),
+                    closed.realized_pnl,
+                    closed.realized_pnl_percent,
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn record_signal(&self, signal: &UniversalSwapSignal) -> Result<(), Box<dyn Error>> {
+        let conn = self.conn.lock().unwrap();
+        // A signal replayed by catch-up is already stored
+        conn.execute(
+            "INSERT OR IGNORE INTO signals (signature, slot, timestamp, trader, input_mint,
+                 input_amount, output_mint, output_amount, dex, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                signal.signature.to_string(),
+                to_sql_u64(signal.slot),
+                signal.timestamp,
+                signal.trader.to_string(),
+                signal.input_mint.to_string(),
+                to_sql_u64(signal.input_amount),
+                signal.output_mint.to_string(),
+                to_sql_u64(signal.output_amount),
+                signal.likely_dex,
+                serde_json::to_string(signal)?,
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+/// Run the migrations this database hasn't seen yet, each in its own
+/// transaction
+fn migrate(conn: &mut Connection) -> rusqlite::Result<()> {
+    let applied: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(applied as usize) {
+        let version = index as i64 + 1;
+        tracing::info!("🗄️  Applying storage migration {}", version);
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// SQLite integers are signed, so raw amounts are stored bit for bit: they
+/// always read back exactly, though anything above `i64::MAX` looks
+/// negative to plain SQL
+fn to_sql_u64(value: u64) -> i64 {
+    value as i64
+}
+
+fn from_sql_u64(value: i64) -> u64 {
+    value as u64
+}
+
+/// Read the [`POSITION_COLUMNS`] at the start of `row`
+fn position_from_row(row: &Row) -> rusqlite::Result<Position> {
+    let lots: String = row.get(7)?;
+    let lots: Vec<Lot> = serde_json::from_str(&lots)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(7, Type::Text, Box::new(e)))?;
+
+    Ok(Position {
+        token: pubkey_at(row, 0)?,
+        payment_token: pubkey_at(row, 1)?,
+        amount: from_sql_u64(row.get(2)?),
+        cost_basis: from_sql_u64(row.get(3)?),
+        entry_time: from_sql_u64(row.get(4)?),
+        entry_signature: row.get(5)?,
+        avg_entry_price: row.get(6)?,
+        high_water_mark: 0.0,
+        lots,
+        stop_loss_percent: None,
+        take_profit_percent: None,
+        trailing_stop_percent: None,
+        copied_amount: 0,
+    })
+}
+
+fn pubkey_at(row: &Row, index: usize) -> rusqlite::Result<Pubkey> {
+    let text: String = row.get(index)?;
+    Pubkey::from_str(&text)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(index, Type::Text, Box::new(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::CostBasisMethod;
+    use rusqlite::OptionalExtension;
+
+    fn temp_db() -> String {
+        std::env::temp_dir()
+            .join(format!("storage-{}.db", Pubkey::new_unique()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn signal() -> UniversalSwapSignal {
+        UniversalSwapSignal {
+            slot: 7,
+            timestamp: 1_700_000_000,
+            ..UniversalSwapSignal::test_swap()
+                .with_amounts(250_000_000, u64::MAX)
+                .with_decimals(6, 9)
+                .with_dex("Raydium")
+        }
+    }
+
+    #[test]
+    fn test_sqlite_round_trip() {
+        let path = temp_db();
+        let usdc = Pubkey::new_unique();
+        let held = Pubkey::new_unique();
+        let sold = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new().with_cost_basis_method(CostBasisMethod::Fifo);
+        portfolio.open_position(held, 1_000, usdc, 500, "buy1".to_string());
+        portfolio.open_position(held, 1_000, usdc, 700, "buy2".to_string());
+        portfolio
+            .set_risk_limits(&held, Some(20.0), Some(100.0))
+            .unwrap();
+        portfolio.record_copy(&held, 1_000);
+        portfolio.open_position(sold, 2_000, usdc, 1_000, "buy3".to_string());
+        portfolio
+            .close_position(&sold, 500, 400, "sell1".to_string())
+            .unwrap();
+
+        let storage = SqliteStorage::open(&path, "portfolio.json").unwrap();
+        storage.save(&portfolio).unwrap();
+        portfolio
+            .close_position(&sold, 1_500, 900, "sell2".to_string())
+            .unwrap();
+        // Saving again appends only the new history
+        storage.save(&portfolio).unwrap();
+        storage.save(&portfolio).unwrap();
+        drop(storage);
+
+        let loaded = SqliteStorage::open(&path, "portfolio.json")
+            .unwrap()
+            .load()
+            .unwrap();
+        let other = SqliteStorage::open(&path, "other.json")
+            .unwrap()
+            .load()
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let position = loaded.get_position(&held).unwrap();
+        assert_eq!(position.amount, 2_000);
+        assert_eq!(position.cost_basis, 1_200);
+        assert_eq!(position.lots, portfolio.get_position(&held).unwrap().lots);
+        assert_eq!(position.stop_loss_percent, Some(20.0));
+        assert_eq!(position.take_profit_percent, Some(100.0));
+        assert_eq!(position.high_water_mark, 0.7);
+        assert_eq!(position.copied_amount, 1_000);
+        assert!(!loaded.has_position(&sold));
+
+        let history: Vec<&str> = loaded
+            .get_history()
+            .iter()
+            .map(|cp| cp.exit_signature.as_str())
+            .collect();
+        assert_eq!(history, ["sell1", "sell2"]);
+        assert_eq!(
+            loaded.get_stats().total_realized_pnl,
+            portfolio.get_stats().total_realized_pnl
+        );
+
+        // Portfolios sharing the database don't see each other
+        assert_eq!(other.get_stats().active_positions, 0);
+        assert_eq!(other.get_stats().closed_positions, 0);
+    }
+
+    #[test]
+    fn test_migrations_run_once() {
+        let path = temp_db();
+        SqliteStorage::open(&path, "a").unwrap();
+        let storage = SqliteStorage::open(&path, "a").unwrap();
+
+        let conn = storage.conn.lock().unwrap();
+        let version: i64 = conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn test_records_signals_once() {
+        let path = temp_db();
+        let storage = SqliteStorage::open(&path, "portfolio.json").unwrap();
+        let signal = signal();
+
+        storage.record_signal(&signal).unwrap();
+        storage.record_signal(&signal).unwrap();
+
+        let conn = storage.conn.lock().unwrap();
+        let (count, output_amount, dex): (i64, i64, Option<String>) = conn
+            .query_row(
+                "SELECT COUNT(*), output_amount, dex FROM signals WHERE signature = ?1",
+                [signal.signature.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .unwrap()
+            .unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(count, 1);
+        assert_eq!(from_sql_u64(output_amount), u64::MAX);
+        assert_eq!(dex.as_deref(), Some("Raydium"));
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let path = temp_db().replace(".db", ".json");
+        let storage = JsonStorage::new(path.clone());
+        let token = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(token, 1_000, Pubkey::new_unique(), 500, "buy".to_string());
+        storage.save(&portfolio).unwrap();
+        let loaded = storage.load().unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(loaded.get_position(&token).unwrap().cost_basis, 500);
+        assert!(storage.record_signal(&signal()).is_ok());
+    }
+}
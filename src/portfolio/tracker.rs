@@ -1,9 +1,15 @@
+use crate::decision::Stablecoins;
+use crate::detection::types::{UniversalSwapSignal, format_amount};
+use crate::portfolio::clock::{Clock, SystemClock};
+use crate::portfolio::price::{PriceProvider, PriceSample};
+use crate::types::{CostBasisMethod, RiskConfig, WSOL_MINT};
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 use std::collections::HashMap;
-use std::fs;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
 use std::path::Path;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::{Arc, Mutex};
 
 /// Represents a single position in a token
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,10 +34,53 @@ pub struct Position {
 
     /// Average entry price (cost per token)
     pub avg_entry_price: f64,
+
+    /// Highest price seen since entry, starting at the entry price. Saved
+    /// with the position so the trailing stop survives a restart.
+    #[serde(default)]
+    pub high_water_mark: f64,
+
+    /// Individual buys, oldest first, when lots are tracked (FIFO cost
+    /// basis). Empty for averaged positions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lots: Vec<Lot>,
+
+    /// Exit when down this many percent from entry, overriding `[risk]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_loss_percent: Option<f64>,
+
+    /// Exit when up this many percent from entry, overriding `[risk]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub take_profit_percent: Option<f64>,
+
+    /// Exit when this many percent below the high-water mark, overriding `[risk]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailing_stop_percent: Option<f64>,
+
+    /// Part of `amount` our own copies actually bought. Buys we only
+    /// mirrored (e.g. while the trader was gated) are tracked but never sold.
+    #[serde(default)]
+    pub copied_amount: u64,
+}
+
+/// One buy within a position, kept for FIFO cost basis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lot {
+    /// Tokens still held from this buy
+    pub amount: u64,
+
+    /// What they cost
+    pub cost: u64,
+
+    /// When the buy happened
+    pub entry_time: u64,
+
+    /// The buy's transaction signature
+    pub signature: String,
 }
 
 impl Position {
-    /// Create a new position
+    /// Create a new position entered now
     pub fn new(
         token: Pubkey,
         amount: u64,
@@ -39,11 +88,25 @@ impl Position {
         cost_basis: u64,
         entry_signature: String,
     ) -> Self {
-        let entry_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        Self::new_at(
+            token,
+            amount,
+            payment_token,
+            cost_basis,
+            entry_signature,
+            SystemClock.now(),
+        )
+    }
 
+    /// Create a new position entered at `entry_time` (Unix seconds)
+    pub fn new_at(
+        token: Pubkey,
+        amount: u64,
+        payment_token: Pubkey,
+        cost_basis: u64,
+        entry_signature: String,
+        entry_time: u64,
+    ) -> Self {
         let avg_entry_price = if amount > 0 {
             cost_basis as f64 / amount as f64
         } else {
@@ -58,6 +121,12 @@ impl Position {
             entry_time,
             entry_signature,
             avg_entry_price,
+            high_water_mark: avg_entry_price,
+            lots: Vec::new(),
+            stop_loss_percent: None,
+            take_profit_percent: None,
+            trailing_stop_percent: None,
+            copied_amount: 0,
         }
     }
 
@@ -76,13 +145,20 @@ impl Position {
         }
     }
 
+    /// What the position is worth at `price` (payment-token units per
+    /// token unit), in payment-token units
+    pub fn market_value(&self, price: f64) -> u64 {
+        (self.amount as f64 * price).round() as u64
+    }
+
     /// How long have we held this position (in seconds)
     pub fn holding_duration(&self) -> u64 {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        now - self.entry_time
+        self.holding_duration_at(SystemClock.now())
+    }
+
+    /// How long the position has been held as of `now` (Unix seconds)
+    pub fn holding_duration_at(&self, now: u64) -> u64 {
+        now.saturating_sub(self.entry_time)
     }
 
     /// Add more to the position (average up/down)
@@ -99,10 +175,76 @@ impl Position {
         } else {
             0.0
         };
+
+        // Buying is seeing a price too
+        if amount > 0 {
+            self.observe_price(cost as f64 / amount as f64);
+        }
+    }
+
+    /// Record a price seen for the token, raising the high-water mark on a new high
+    pub fn observe_price(&mut self, price: f64) {
+        if price > self.high_water_mark {
+            self.high_water_mark = price;
+        }
+    }
+
+    /// Has `price` fallen `trail_percent` below the high-water mark? The stop
+    /// only arms once the high is in profit by at least `activation_percent`.
+    pub fn trailing_stop_hit(
+        &self,
+        price: f64,
+        trail_percent: f64,
+        activation_percent: f64,
+    ) -> bool {
+        let Some(high_percent) = self.change_percent(self.high_water_mark) else {
+            return false;
+        };
+        high_percent > 0.0
+            && high_percent >= activation_percent
+            && price <= self.high_water_mark * (1.0 - trail_percent / 100.0)
+    }
+
+    /// Is each buy kept as its own lot?
+    pub fn tracks_lots(&self) -> bool {
+        !self.lots.is_empty()
+    }
+
+    /// Start tracking lots, with everything held so far as the first one
+    pub fn track_lots(&mut self) {
+        if self.lots.is_empty() && self.amount > 0 {
+            self.lots.push(Lot {
+                amount: self.amount,
+                cost: self.cost_basis,
+                entry_time: self.entry_time,
+                signature: self.entry_signature.clone(),
+            });
+        }
+    }
+
+    /// Add a buy as a new lot (FIFO cost basis)
+    pub fn add_lot(&mut self, amount: u64, cost: u64, entry_time: u64, signature: String) {
+        self.track_lots();
+        self.add(amount, cost);
+        self.lots.push(Lot {
+            amount,
+            cost,
+            entry_time,
+            signature,
+        });
     }
 
-    /// Remove from position (partial or full exit)
+    /// Remove from position (partial or full exit), returning the cost removed
     pub fn reduce(&mut self, amount: u64) -> Result<u64, String> {
+        self.split_off(amount).map(|exited| exited.cost_basis)
+    }
+
+    /// Take `amount` out of the position and return that slice.
+    ///
+    /// With lots tracked, the slice is made of the oldest lots (FIFO) and
+    /// costs what they cost. Otherwise it's a proportional share of the
+    /// averaged cost basis.
+    pub fn split_off(&mut self, amount: u64) -> Result<Position, String> {
         if amount > self.amount {
             return Err(format!(
                 "Cannot reduce by {} - only have {}",
@@ -110,13 +252,90 @@ impl Position {
             ));
         }
 
-        let exit_percentage = amount as f64 / self.amount as f64;
-        let cost_removed = (self.cost_basis as f64 * exit_percentage) as u64;
+        let (cost_removed, consumed) = if self.tracks_lots() {
+            self.consume_lots(amount)
+        } else if amount == self.amount {
+            (self.cost_basis, Vec::new())
+        } else {
+            // Integer math, rounding down: the remainder stays with the position,
+            // so the cost removed over every exit adds up to the original basis
+            let cost = (self.cost_basis as u128 * amount as u128 / self.amount as u128) as u64;
+            (cost, Vec::new())
+        };
+
+        // Our copied tokens leave in proportion to the slice
+        let copied_removed = if amount == self.amount {
+            self.copied_amount
+        } else {
+            (self.copied_amount as u128 * amount as u128 / self.amount as u128) as u64
+        };
+
+        let mut exited = Position {
+            amount,
+            cost_basis: cost_removed,
+            copied_amount: copied_removed,
+            avg_entry_price: if amount > 0 {
+                cost_removed as f64 / amount as f64
+            } else {
+                0.0
+            },
+            lots: consumed,
+            ..self.clone()
+        };
+        if let Some(oldest) = exited.lots.first() {
+            exited.entry_time = oldest.entry_time;
+            exited.entry_signature = oldest.signature.clone();
+        }
 
         self.amount -= amount;
-        self.cost_basis -= cost_removed;
+        self.cost_basis = self.cost_basis.saturating_sub(cost_removed);
+        self.copied_amount -= copied_removed;
+        if self.tracks_lots() && self.amount > 0 {
+            // What's left is the newer lots, at their own price
+            self.avg_entry_price = self.cost_basis as f64 / self.amount as f64;
+        }
+
+        Ok(exited)
+    }
+
+    /// Remove `amount` from the oldest lots, returning their cost and the
+    /// (possibly split) lots taken
+    fn consume_lots(&mut self, amount: u64) -> (u64, Vec<Lot>) {
+        let mut remaining = amount;
+        let mut cost = 0;
+        let mut consumed = Vec::new();
+
+        while remaining > 0 {
+            let Some(lot) = self.lots.first_mut() else {
+                break;
+            };
+
+            if lot.amount <= remaining {
+                let lot = self.lots.remove(0);
+                remaining -= lot.amount;
+                cost += lot.cost;
+                consumed.push(lot);
+            } else {
+                let lot_cost = (lot.cost as u128 * remaining as u128 / lot.amount as u128) as u64;
+                lot.amount -= remaining;
+                lot.cost -= lot_cost;
+                consumed.push(Lot {
+                    amount: remaining,
+                    cost: lot_cost,
+                    entry_time: lot.entry_time,
+                    signature: lot.signature.clone(),
+                });
+                cost += lot_cost;
+                remaining = 0;
+            }
+        }
+
+        (cost, consumed)
+    }
 
-        Ok(cost_removed)
+    /// Percent move of `price` from the average entry price, if there is one
+    pub fn change_percent(&self, price: f64) -> Option<f64> {
+        (self.avg_entry_price > 0.0).then(|| (price / self.avg_entry_price - 1.0) * 100.0)
     }
 
     /// Is this position empty (fully exited)?
@@ -125,6 +344,63 @@ impl Position {
     }
 }
 
+/// Open positions valued at current market prices
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MarketValuation {
+    /// Current value of the priced positions, summed in raw payment-token
+    /// units like `PortfolioStats::total_invested`
+    pub market_value: u64,
+
+    /// Cost basis of the priced positions
+    pub cost_basis: u64,
+
+    /// `market_value - cost_basis`
+    pub unrealized_pnl: i64,
+
+    /// Unrealized P&L per priced position
+    #[serde(with = "pubkey_map")]
+    pub by_position: HashMap<Pubkey, PnL>,
+
+    /// Tokens with no price, left out of the totals above
+    pub unpriced: Vec<Pubkey>,
+}
+
+impl MarketValuation {
+    /// Value `positions` at `provider`'s current prices, each quoted in the
+    /// position's payment token. Tokens without a price are skipped and
+    /// listed in `unpriced`.
+    pub async fn of<'a, P: PriceProvider>(
+        positions: impl IntoIterator<Item = &'a Position>,
+        provider: &P,
+    ) -> Self {
+        let mut valuation = Self::default();
+
+        for position in positions {
+            let price = match provider
+                .get_price_in(&position.token, &position.payment_token)
+                .await
+            {
+                Ok(price) => price,
+                Err(e) => {
+                    tracing::warn!("⚠️  No price for {}: {}", position.token, e);
+                    valuation.unpriced.push(position.token);
+                    continue;
+                }
+            };
+
+            let value = position.market_value(price);
+            valuation.market_value += value;
+            valuation.cost_basis += position.cost_basis;
+            valuation
+                .by_position
+                .insert(position.token, position.calculate_pnl(value));
+        }
+
+        valuation.unrealized_pnl = valuation.market_value as i64 - valuation.cost_basis as i64;
+        valuation
+    }
+}
+
 /// Profit and Loss calculation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PnL {
@@ -136,6 +412,7 @@ pub struct PnL {
 #[derive(Serialize, Deserialize)]
 pub struct PortfolioTracker {
     /// All active positions (token mint -> Position)
+    #[serde(with = "pubkey_map")]
     positions: HashMap<Pubkey, Position>,
 
     /// Closed positions history (for tracking realized P&L)
@@ -143,6 +420,54 @@ pub struct PortfolioTracker {
 
     /// Total realized profit/loss
     total_realized_pnl: i64,
+
+    /// Latest sampled prices per open position (transient, rebuilt after restart)
+    #[serde(skip)]
+    prices: HashMap<Pubkey, PriceSample>,
+
+    /// Time source for entry/exit times (the system clock outside tests)
+    #[serde(skip, default = "default_clock")]
+    clock: Arc<dyn Clock>,
+
+    /// How buys are recorded: averaged, or as FIFO lots. Set from config
+    /// on load rather than persisted.
+    #[serde(skip)]
+    cost_basis_method: CostBasisMethod,
+}
+
+fn default_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// JSON object keys must be strings, so store mints as base58
+mod pubkey_map {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use solana_sdk::pubkey::Pubkey;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer, V: Serialize>(
+        map: &HashMap<Pubkey, V>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let by_string: HashMap<String, &V> = map.iter().map(|(k, v)| (k.to_string(), v)).collect();
+        by_string.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, V: Deserialize<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<Pubkey, V>, D::Error> {
+        let by_string = HashMap::<String, V>::deserialize(deserializer)?;
+        by_string
+            .into_iter()
+            .map(|(k, v)| {
+                Pubkey::from_str(&k)
+                    .map(|k| (k, v))
+                    .map_err(D::Error::custom)
+            })
+            .collect()
+    }
 }
 
 /// A closed (exited) position
@@ -159,13 +484,42 @@ pub struct ClosedPosition {
 impl PortfolioTracker {
     /// Create a new portfolio tracker
     pub fn new() -> Self {
+        Self::with_clock(default_clock())
+    }
+
+    /// Create a portfolio tracker that reads time from `clock`
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
         Self {
             positions: HashMap::new(),
             closed_positions: Vec::new(),
             total_realized_pnl: 0,
+            prices: HashMap::new(),
+            clock,
+            cost_basis_method: CostBasisMethod::default(),
         }
     }
 
+    /// Rebuild a portfolio from stored positions and history. Realized P&L
+    /// is recomputed from the history, and empty positions are pruned.
+    pub fn from_parts(positions: Vec<Position>, closed_positions: Vec<ClosedPosition>) -> Self {
+        let mut portfolio = Self::new();
+        portfolio.positions = positions.into_iter().map(|p| (p.token, p)).collect();
+        portfolio.closed_positions = closed_positions;
+        portfolio.total_realized_pnl = portfolio
+            .closed_positions
+            .iter()
+            .map(|cp| cp.realized_pnl)
+            .sum();
+        portfolio.prune();
+        portfolio
+    }
+
+    /// Record buys with `method` from now on
+    pub fn with_cost_basis_method(mut self, method: CostBasisMethod) -> Self {
+        self.cost_basis_method = method;
+        self
+    }
+
     /// Check if we have a position in this token
     pub fn has_position(&self, token: &Pubkey) -> bool {
         self.positions.contains_key(token)
@@ -181,6 +535,32 @@ impl PortfolioTracker {
         self.positions.values().collect()
     }
 
+    /// Positions held for longer than `secs` (candidates for time-based exits)
+    pub fn positions_older_than(&self, secs: u64) -> Vec<&Position> {
+        let now = self.clock.now();
+        self.positions
+            .values()
+            .filter(|p| p.holding_duration_at(now) > secs)
+            .collect()
+    }
+
+    /// Positions whose cost basis is at least `min_cost`
+    pub fn positions_over_cost(&self, min_cost: u64) -> Vec<&Position> {
+        self.positions
+            .values()
+            .filter(|p| p.cost_basis >= min_cost)
+            .collect()
+    }
+
+    /// Would a buy of `token` open a new position or add to an existing one?
+    pub fn classify_entry(&self, token: &Pubkey) -> EntryKind {
+        if self.has_position(token) {
+            EntryKind::Add
+        } else {
+            EntryKind::New
+        }
+    }
+
     /// Add a new position (BUY)
     pub fn open_position(
         &mut self,
@@ -192,7 +572,16 @@ impl PortfolioTracker {
     ) {
         if let Some(existing) = self.positions.get_mut(&token) {
             // Already have this token - add to position
-            existing.add(amount, cost);
+            match self.cost_basis_method {
+                CostBasisMethod::Fifo => {
+                    existing.add_lot(amount, cost, self.clock.now(), signature)
+                }
+                CostBasisMethod::Average => {
+                    // Lots from an earlier FIFO run no longer add up
+                    existing.lots.clear();
+                    existing.add(amount, cost);
+                }
+            }
             tracing::info!(
                 "➕ Added to position: {} tokens (new total: {})",
                 amount,
@@ -200,12 +589,116 @@ impl PortfolioTracker {
             );
         } else {
             // New position
-            let position = Position::new(token, amount, payment_token, cost, signature);
+            let mut position = Position::new_at(
+                token,
+                amount,
+                payment_token,
+                cost,
+                signature,
+                self.clock.now(),
+            );
+            if self.cost_basis_method == CostBasisMethod::Fifo {
+                position.track_lots();
+            }
             tracing::info!("🆕 Opened new position: {} tokens @ {} cost", amount, cost);
             self.positions.insert(token, position);
         }
     }
 
+    /// Record that our own copy bought `amount` of `token`, on top of
+    /// tracking it with `open_position`
+    pub fn record_copy(&mut self, token: &Pubkey, amount: u64) {
+        if let Some(position) = self.positions.get_mut(token) {
+            position.copied_amount = (position.copied_amount + amount).min(position.amount);
+        }
+    }
+
+    /// Undo a provisional `open_position` (e.g. the buy never finalized)
+    pub fn revert_open(&mut self, token: &Pubkey, amount: u64, cost: u64) {
+        let Some(position) = self.positions.get_mut(token) else {
+            return;
+        };
+
+        position.amount = position.amount.saturating_sub(amount);
+        position.cost_basis = position.cost_basis.saturating_sub(cost);
+        // Whatever our copy bought can't exceed what's still tracked
+        position.copied_amount = position.copied_amount.min(position.amount);
+        // The provisional buy is the newest lot
+        if let Some(lot) = position.lots.last_mut() {
+            lot.amount = lot.amount.saturating_sub(amount);
+            lot.cost = lot.cost.saturating_sub(cost);
+            if lot.amount == 0 {
+                position.lots.pop();
+            }
+        }
+        position.avg_entry_price = if position.amount > 0 {
+            position.cost_basis as f64 / position.amount as f64
+        } else {
+            0.0
+        };
+
+        if position.is_empty() {
+            self.positions.remove(token);
+        }
+
+        tracing::warn!(
+            "↩️  Reverted provisional entry: {} tokens of {}",
+            amount,
+            token
+        );
+    }
+
+    /// Remove a position without realizing P&L, as its exit value is unknown
+    pub fn drop_position(&mut self, token: &Pubkey) -> Option<Position> {
+        let position = self.positions.remove(token)?;
+        tracing::info!(
+            "🗑️  Dropped position in {} ({} tokens)",
+            token,
+            position.amount
+        );
+        Some(position)
+    }
+
+    /// Copy a trader's sell, sized to the share of their holding they sold.
+    ///
+    /// Selling 50% of their tokens exits 50% of our position, valued at the
+    /// trader's price. Without a known holding the trader's absolute amounts
+    /// are used.
+    pub fn close_mirrored(
+        &mut self,
+        token: &Pubkey,
+        signal: &UniversalSwapSignal,
+    ) -> Result<ClosedPosition, String> {
+        let position = self
+            .positions
+            .get(token)
+            .ok_or_else(|| format!("No position found for token {}", token))?;
+
+        let amount = match signal.sold_fraction() {
+            Some(fraction) => {
+                let amount = (position.amount as f64 * fraction).round() as u64;
+                tracing::info!(
+                    "📐 Trader sold {:.1}% of their holding - exiting {} of our {}",
+                    fraction * 100.0,
+                    amount,
+                    position.amount
+                );
+                amount
+            }
+            None => {
+                tracing::warn!("⚠️  Trader's holding unknown - copying the absolute sell amount");
+                signal.input_amount.min(position.amount)
+            }
+        };
+        let exit_value = if signal.input_amount == 0 {
+            0
+        } else {
+            (signal.output_amount as u128 * amount as u128 / signal.input_amount as u128) as u64
+        };
+
+        self.close_position(token, amount, exit_value, signal.signature.to_string())
+    }
+
     /// Reduce or close a position (SELL)
     pub fn close_position(
         &mut self,
@@ -228,9 +721,16 @@ impl PortfolioTracker {
             Ok(closed_position)
         } else {
             // Partial exit
-            let cost_removed = position.reduce(amount)?;
+            // Only the slice sold is recorded, not what's still held; its cost
+            // comes from the oldest lots when they're tracked
+            let exited = position.split_off(amount)?;
+            let cost_removed = exited.cost_basis;
             let partial_pnl = exit_value as i64 - cost_removed as i64;
-            let pnl_percent = (partial_pnl as f64 / cost_removed as f64) * 100.0;
+            let pnl_percent = if cost_removed > 0 {
+                (partial_pnl as f64 / cost_removed as f64) * 100.0
+            } else {
+                0.0
+            };
 
             tracing::info!(
                 "📉 Partial exit: {} tokens, P&L: {} ({:.2}%)",
@@ -240,13 +740,10 @@ impl PortfolioTracker {
             );
 
             // Track partial exit as a closed position
-            let exit_time = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
+            let exit_time = self.clock.now();
 
             let closed = ClosedPosition {
-                position: position.clone(),
+                position: exited,
                 exit_time,
                 exit_signature: signature,
                 exit_value,
@@ -280,10 +777,7 @@ impl PortfolioTracker {
             0.0
         };
 
-        let exit_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let exit_time = self.clock.now();
 
         let closed_position = ClosedPosition {
             position: position.clone(),
@@ -308,6 +802,204 @@ impl PortfolioTracker {
         Ok(closed_position)
     }
 
+    /// Set (or with `None`, clear) a position's own stop-loss and take-profit,
+    /// which take precedence over the `[risk]` thresholds
+    pub fn set_risk_limits(
+        &mut self,
+        token: &Pubkey,
+        stop_loss_percent: Option<f64>,
+        take_profit_percent: Option<f64>,
+    ) -> Result<(), String> {
+        let position = self
+            .positions
+            .get_mut(token)
+            .ok_or_else(|| format!("No position found for token {}", token))?;
+        position.stop_loss_percent = stop_loss_percent;
+        position.take_profit_percent = take_profit_percent;
+        Ok(())
+    }
+
+    /// Set (or with `None`, clear) a position's own trailing stop, which
+    /// takes precedence over `[risk] trailing_stop_percent`
+    pub fn set_trailing_stop(
+        &mut self,
+        token: &Pubkey,
+        trail_percent: Option<f64>,
+    ) -> Result<(), String> {
+        let position = self
+            .positions
+            .get_mut(token)
+            .ok_or_else(|| format!("No position found for token {}", token))?;
+        position.trailing_stop_percent = trail_percent;
+        Ok(())
+    }
+
+    /// Latest sampled spot/EMA price for a position, if any
+    pub fn price_sample(&self, token: &Pubkey) -> Option<PriceSample> {
+        self.prices.get(token).copied()
+    }
+
+    /// Does anything call for price checks: a `[risk]` threshold, or a
+    /// position with limits of its own?
+    pub fn has_exit_rules(&self, risk: &RiskConfig) -> bool {
+        risk.stop_loss_percent.is_some()
+            || risk.take_profit_percent.is_some()
+            || risk.trailing_stop_percent.is_some()
+            || self.positions.values().any(|p| {
+                p.stop_loss_percent.is_some()
+                    || p.take_profit_percent.is_some()
+                    || p.trailing_stop_percent.is_some()
+            })
+    }
+
+    /// Current price of each of `positions`, quoted in its payment token.
+    /// Tokens without a price are left out.
+    pub async fn fetch_prices<'a, P: PriceProvider>(
+        positions: impl IntoIterator<Item = &'a Position>,
+        provider: &P,
+    ) -> HashMap<Pubkey, f64> {
+        let mut prices = HashMap::new();
+        for position in positions {
+            match provider
+                .get_price_in(&position.token, &position.payment_token)
+                .await
+            {
+                Ok(price) => {
+                    prices.insert(position.token, price);
+                }
+                Err(e) => tracing::warn!("⚠️  No price for {}: {}", position.token, e),
+            }
+        }
+        prices
+    }
+
+    /// Sample current prices and return positions that breached a threshold.
+    ///
+    /// With `ema_alpha` set, thresholds are checked against the EMA of the
+    /// sampled prices instead of the raw spot, so one noisy quote can't
+    /// trigger an exit. Tokens without a price are skipped.
+    pub async fn check_triggers<P: PriceProvider>(
+        &mut self,
+        provider: &P,
+        risk: &RiskConfig,
+    ) -> Vec<Trigger> {
+        let spots = Self::fetch_prices(self.positions.values(), provider).await;
+        self.check_triggers_at(&spots, risk)
+    }
+
+    /// [`check_triggers`](Self::check_triggers) with prices already fetched,
+    /// so the tracker needn't stay locked while they're looked up
+    pub fn check_triggers_at(
+        &mut self,
+        spots: &HashMap<Pubkey, f64>,
+        risk: &RiskConfig,
+    ) -> Vec<Trigger> {
+        // Forget samples for positions that have since closed
+        let positions = &self.positions;
+        self.prices.retain(|token, _| positions.contains_key(token));
+
+        let mut triggers = Vec::new();
+        let tokens: Vec<Pubkey> = self.positions.keys().copied().collect();
+
+        for token in tokens {
+            let Some(&spot) = spots.get(&token) else {
+                continue;
+            };
+
+            let sample = match (self.prices.get_mut(&token), risk.ema_alpha) {
+                (Some(sample), Some(alpha)) => {
+                    sample.update(spot, alpha);
+                    *sample
+                }
+                (_, _) => {
+                    let sample = PriceSample::new(spot);
+                    self.prices.insert(token, sample);
+                    sample
+                }
+            };
+
+            let Some(position) = self.positions.get_mut(&token) else {
+                continue;
+            };
+            let entry_price = position.avg_entry_price;
+            if entry_price <= 0.0 {
+                continue;
+            }
+
+            let valuation = if risk.ema_alpha.is_some() {
+                sample.ema
+            } else {
+                sample.spot
+            };
+            let change_percent = (valuation / entry_price - 1.0) * 100.0;
+
+            position.observe_price(valuation);
+            let trailing_hit = match position
+                .trailing_stop_percent
+                .or(risk.trailing_stop_percent)
+            {
+                Some(trail) => position.trailing_stop_hit(
+                    valuation,
+                    trail,
+                    risk.trailing_stop_activation_percent.unwrap_or(0.0),
+                ),
+                None => false,
+            };
+
+            // A position's own thresholds win over the portfolio-wide ones
+            let stop_loss = position.stop_loss_percent.or(risk.stop_loss_percent);
+            let take_profit = position.take_profit_percent.or(risk.take_profit_percent);
+            let kind = match (stop_loss, take_profit) {
+                (Some(stop_loss), _) if change_percent <= -stop_loss => TriggerKind::StopLoss,
+                _ if trailing_hit => TriggerKind::TrailingStop,
+                (_, Some(take_profit)) if change_percent >= take_profit => TriggerKind::TakeProfit,
+                _ => continue,
+            };
+
+            tracing::info!(
+                "🚨 {} triggered for {}: {:+.2}% (spot {}, ema {})",
+                kind,
+                token,
+                change_percent,
+                sample.spot,
+                sample.ema
+            );
+
+            triggers.push(Trigger {
+                token,
+                kind,
+                change_percent,
+                price: sample,
+            });
+        }
+
+        triggers
+    }
+
+    /// Exit the whole position a trigger fired for, valued at the spot price
+    /// that was checked
+    pub fn close_triggered(
+        &mut self,
+        trigger: &Trigger,
+        signature: String,
+    ) -> Result<ClosedPosition, String> {
+        let amount = self
+            .positions
+            .get(&trigger.token)
+            .map(|position| position.amount)
+            .ok_or_else(|| format!("No position found for token {}", trigger.token))?;
+        let exit_value = (amount as f64 * trigger.price.spot) as u64;
+
+        tracing::info!(
+            "🚨 {} exit: {} tokens of {} for {}",
+            trigger.kind,
+            amount,
+            trigger.token,
+            exit_value
+        );
+        self.close_position(&trigger.token, amount, exit_value, signature)
+    }
+
     /// Get portfolio statistics
     pub fn get_stats(&self) -> PortfolioStats {
         let active_positions_count = self.positions.len();
@@ -335,7 +1027,218 @@ impl PortfolioTracker {
             total_invested,
             total_realized_pnl: self.total_realized_pnl,
             win_rate,
+            market_value: None,
+            unrealized_pnl: None,
+        }
+    }
+
+    /// Closed-trade performance per token, from the full history. Partial
+    /// exits count as separate trades.
+    pub fn stats_by_token(&self) -> HashMap<Pubkey, TokenStats> {
+        let mut by_token: HashMap<Pubkey, TokenStats> = HashMap::new();
+        let mut wins: HashMap<Pubkey, usize> = HashMap::new();
+        let mut held_secs: HashMap<Pubkey, u64> = HashMap::new();
+
+        for closed in &self.closed_positions {
+            let token = closed.position.token;
+            let stats = by_token.entry(token).or_default();
+            stats.trades += 1;
+            stats.total_realized_pnl += closed.realized_pnl;
+            if closed.realized_pnl > 0 {
+                *wins.entry(token).or_default() += 1;
+            }
+            *held_secs.entry(token).or_default() += closed.holding_duration();
+        }
+
+        for (token, stats) in &mut by_token {
+            let trades = stats.trades as f64;
+            stats.win_rate = wins.get(token).copied().unwrap_or(0) as f64 / trades * 100.0;
+            stats.avg_holding_secs = held_secs[token] as f64 / trades;
+        }
+        by_token
+    }
+
+    /// Value every open position at `provider`'s current prices
+    pub async fn unrealized_pnl<P: PriceProvider>(&self, provider: &P) -> MarketValuation {
+        MarketValuation::of(self.positions.values(), provider).await
+    }
+
+    /// [`get_stats`](Self::get_stats) plus the market value and unrealized
+    /// P&L of the positions `provider` can price
+    pub async fn get_stats_at_market<P: PriceProvider>(&self, provider: &P) -> PortfolioStats {
+        let valuation = self.unrealized_pnl(provider).await;
+        PortfolioStats {
+            market_value: Some(valuation.market_value),
+            unrealized_pnl: Some(valuation.unrealized_pnl),
+            ..self.get_stats()
+        }
+    }
+
+    /// Stats with every payment token converted into `currency`.
+    ///
+    /// Payment tokens are priced in `currency` through `provider` (smallest
+    /// units of `currency` per smallest unit of the payment token). Positions
+    /// paid in a token it can't price are left out of the converted totals
+    /// and listed in `excluded_payment_tokens`; the raw per-token totals keep
+    /// them.
+    pub async fn get_stats_in<P: PriceProvider>(
+        &self,
+        provider: &P,
+        currency: &Pubkey,
+    ) -> NormalizedStats {
+        let rates = Self::fetch_rates(self.payment_tokens(), provider, currency).await;
+        self.get_stats_at(&rates, currency)
+    }
+
+    /// [`get_stats_in`](Self::get_stats_in) for a shared tracker, which is
+    /// left unlocked while the rates are fetched
+    pub async fn shared_stats_in<P: PriceProvider>(
+        portfolio: &Mutex<Self>,
+        provider: &P,
+        currency: &Pubkey,
+    ) -> NormalizedStats {
+        let payment_tokens = portfolio.lock().unwrap().payment_tokens();
+        let rates = Self::fetch_rates(payment_tokens, provider, currency).await;
+        portfolio.lock().unwrap().get_stats_at(&rates, currency)
+    }
+
+    /// Every token positions were paid in, open or closed
+    pub fn payment_tokens(&self) -> Vec<Pubkey> {
+        let mut payment_tokens: Vec<Pubkey> = self
+            .positions
+            .values()
+            .map(|p| p.payment_token)
+            .chain(
+                self.closed_positions
+                    .iter()
+                    .map(|c| c.position.payment_token),
+            )
+            .collect();
+        payment_tokens.sort();
+        payment_tokens.dedup();
+        payment_tokens
+    }
+
+    /// Price of each payment token in `currency`; `currency` itself is 1.
+    /// Tokens without a price are left out.
+    pub async fn fetch_rates<P: PriceProvider>(
+        payment_tokens: Vec<Pubkey>,
+        provider: &P,
+        currency: &Pubkey,
+    ) -> HashMap<Pubkey, f64> {
+        let mut rates = HashMap::new();
+        for payment_token in payment_tokens {
+            if payment_token == *currency {
+                rates.insert(payment_token, 1.0);
+                continue;
+            }
+            match provider.get_price_in(&payment_token, currency).await {
+                Ok(rate) => {
+                    rates.insert(payment_token, rate);
+                }
+                Err(e) => tracing::warn!(
+                    "⚠️  No {} price for {} - excluded from totals: {}",
+                    currency,
+                    payment_token,
+                    e
+                ),
+            }
+        }
+        rates
+    }
+
+    /// [`get_stats_in`](Self::get_stats_in) with the rates already fetched
+    pub fn get_stats_at(&self, rates: &HashMap<Pubkey, f64>, currency: &Pubkey) -> NormalizedStats {
+        let mut invested_by_payment_token: HashMap<Pubkey, u64> = HashMap::new();
+        for position in self.positions.values() {
+            *invested_by_payment_token
+                .entry(position.payment_token)
+                .or_default() += position.cost_basis;
+        }
+        let mut realized_pnl_by_payment_token: HashMap<Pubkey, i64> = HashMap::new();
+        for closed in &self.closed_positions {
+            *realized_pnl_by_payment_token
+                .entry(closed.position.payment_token)
+                .or_default() += closed.realized_pnl;
+        }
+
+        let mut total_invested = 0.0;
+        let mut total_realized_pnl = 0.0;
+        let mut excluded_payment_tokens = Vec::new();
+        for payment_token in self.payment_tokens() {
+            let Some(&rate) = rates.get(&payment_token) else {
+                excluded_payment_tokens.push(payment_token);
+                continue;
+            };
+
+            let invested = invested_by_payment_token.get(&payment_token).copied();
+            let pnl = realized_pnl_by_payment_token.get(&payment_token).copied();
+            total_invested += invested.unwrap_or(0) as f64 * rate;
+            total_realized_pnl += pnl.unwrap_or(0) as f64 * rate;
+        }
+
+        NormalizedStats {
+            stats: self.get_stats(),
+            currency: *currency,
+            total_invested,
+            total_realized_pnl,
+            excluded_payment_tokens,
+            invested_by_payment_token,
+            realized_pnl_by_payment_token,
+        }
+    }
+
+    /// Point-in-time summary for equity-curve plotting
+    pub fn snapshot(&self) -> PortfolioSnapshot {
+        let timestamp = self.clock.now();
+
+        PortfolioSnapshot {
+            timestamp,
+            total_realized_pnl: self.total_realized_pnl,
+            active_positions: self.positions.len(),
+            total_invested: self.positions.values().map(|p| p.cost_basis).sum(),
+        }
+    }
+
+    /// Printable table of active positions followed by the portfolio stats
+    pub fn format_table(&self) -> String {
+        let now = self.clock.now();
+
+        let mut positions = self.get_all_positions();
+        positions.sort_by_key(|p| p.entry_time);
+
+        let mut out = String::new();
+        out.push_str(&format!(
+            "{:<44}  {:>20}  {:>16}  {:>12}  {:>10}\n",
+            "Mint", "Amount (raw)", "Cost basis", "Entry time", "Held"
+        ));
+
+        for position in &positions {
+            out.push_str(&format!(
+                "{:<44}  {:>20}  {:>16}  {:>12}  {:>10}\n",
+                position.token.to_string(),
+                position.amount,
+                format_amount(
+                    position.cost_basis,
+                    payment_decimals(&position.payment_token)
+                ),
+                position.entry_time,
+                format_duration(position.holding_duration_at(now)),
+            ));
+        }
+
+        if positions.is_empty() {
+            out.push_str("(no active positions)\n");
         }
+
+        let stats = self.get_stats();
+        out.push('\n');
+        out.push_str(&format!("Active positions: {}\n", stats.active_positions));
+        out.push_str(&format!("Closed positions: {}\n", stats.closed_positions));
+        out.push_str(&format!("Total invested:   {}\n", stats.total_invested));
+        out.push_str(&format!("Realized P&L:     {}\n", stats.total_realized_pnl));
+        out.push_str(&format!("Win rate:         {:.1}%\n", stats.win_rate));
+        out
     }
 
     /// Get closed positions history
@@ -343,11 +1246,30 @@ impl PortfolioTracker {
         &self.closed_positions
     }
 
+    /// Write the closed-position history as CSV (one row per closed
+    /// position, times in UTC ISO-8601) for tax reporting
+    pub fn export_closed_csv(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut csv = String::from(CLOSED_CSV_HEADER);
+        csv.push('\n');
+        for closed in &self.closed_positions {
+            csv.push_str(&closed.csv_row());
+            csv.push('\n');
+        }
+
+        fs::write(path, csv)?;
+        tracing::info!(
+            "🧾 Exported {} closed positions to {}",
+            self.closed_positions.len(),
+            path
+        );
+        Ok(())
+    }
+
     pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
         tracing::info!("💾 Saving portfolio to {}", path);
 
         let json = serde_json::to_string_pretty(self)?;
-        fs::write(path, json)?;
+        write_atomic(path, json.as_bytes())?;
 
         tracing::info!("✅ Portfolio saved successfully");
         Ok(())
@@ -363,7 +1285,8 @@ impl PortfolioTracker {
         }
 
         let json = fs::read_to_string(path)?;
-        let portfolio: Self = serde_json::from_str(&json)?;
+        let mut portfolio: Self = serde_json::from_str(&json)?;
+        portfolio.prune();
 
         tracing::info!("✅ Portfolio loaded successfully");
         tracing::info!("   Active positions: {}", portfolio.positions.len());
@@ -373,6 +1296,34 @@ impl PortfolioTracker {
         Ok(portfolio)
     }
 
+    /// Clean up state that should never have been persisted
+    ///
+    /// Removes empty positions (they should have been closed) and recomputes
+    /// `total_realized_pnl` if it has drifted from the closed-position history.
+    pub fn prune(&mut self) {
+        let empty: Vec<Pubkey> = self
+            .positions
+            .values()
+            .filter(|p| p.is_empty())
+            .map(|p| p.token)
+            .collect();
+
+        for token in &empty {
+            tracing::warn!("🧹 Pruning empty position: {}", token);
+            self.positions.remove(token);
+        }
+
+        let history_pnl: i64 = self.closed_positions.iter().map(|cp| cp.realized_pnl).sum();
+        if history_pnl != self.total_realized_pnl {
+            tracing::warn!(
+                "⚠️  Realized P&L drifted ({} recorded vs {} from history) - recomputing",
+                self.total_realized_pnl,
+                history_pnl
+            );
+            self.total_realized_pnl = history_pnl;
+        }
+    }
+
     /// Save portfolio with error handling
     pub fn save_safe(&self, path: &str) {
         if let Err(e) = self.save(path) {
@@ -381,14 +1332,209 @@ impl PortfolioTracker {
     }
 }
 
+/// Columns of [`PortfolioTracker::export_closed_csv`]
+const CLOSED_CSV_HEADER: &str = "token,payment_token,entry_time,exit_time,cost_basis,exit_value,\
+realized_pnl,realized_pnl_percent,holding_duration_secs,entry_signature,exit_signature";
+
+impl ClosedPosition {
+    /// Seconds between entry and exit
+    pub fn holding_duration(&self) -> u64 {
+        self.position.holding_duration_at(self.exit_time)
+    }
+
+    fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{:.4},{},{},{}",
+            self.position.token,
+            self.position.payment_token,
+            iso8601_utc(self.position.entry_time),
+            iso8601_utc(self.exit_time),
+            self.position.cost_basis,
+            self.exit_value,
+            self.realized_pnl,
+            self.realized_pnl_percent,
+            self.holding_duration(),
+            self.position.entry_signature,
+            self.exit_signature,
+        )
+    }
+}
+
+/// Unix seconds as e.g. `2024-03-01T12:00:00Z`
+fn iso8601_utc(secs: u64) -> String {
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .map(|time| time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+/// Temporary file a save is written to before it replaces `path`
+fn temp_path(path: &str) -> String {
+    format!("{}.tmp", path)
+}
+
+/// Replace `path` with `contents` so a crash leaves either the old file or
+/// the new one, never a truncated mix: write a temp file in the same
+/// directory, fsync it, then rename it over the target.
+pub(crate) fn write_atomic(path: &str, contents: &[u8]) -> std::io::Result<()> {
+    let temp = temp_path(path);
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temp, path)
+}
+
 /// Portfolio statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PortfolioStats {
     pub active_positions: usize,
     pub closed_positions: usize,
     pub total_invested: u64,
     pub total_realized_pnl: i64,
     pub win_rate: f64,
+    /// Current value of the priced open positions, when valued at market
+    pub market_value: Option<u64>,
+    /// Unrealized P&L of the priced open positions, when valued at market
+    pub unrealized_pnl: Option<i64>,
+}
+
+/// Closed-trade performance of one token
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TokenStats {
+    /// Closed positions (partial exits included)
+    pub trades: usize,
+    /// Realized P&L summed in raw payment-token units
+    pub total_realized_pnl: i64,
+    /// Share of trades closed at a profit (%)
+    pub win_rate: f64,
+    /// Mean time from entry to exit, in seconds
+    pub avg_holding_secs: f64,
+}
+
+/// Portfolio stats converted into a single display currency
+#[derive(Debug, Clone, Serialize)]
+pub struct NormalizedStats {
+    /// Counts and win rate, plus the unconverted totals
+    pub stats: PortfolioStats,
+    /// Mint the totals below are expressed in
+    pub currency: Pubkey,
+    /// Cost basis of active positions, in smallest units of `currency`
+    pub total_invested: f64,
+    /// Realized P&L, in smallest units of `currency`
+    pub total_realized_pnl: f64,
+    /// Payment tokens with no price, left out of the totals above
+    pub excluded_payment_tokens: Vec<Pubkey>,
+    /// Raw cost basis of active positions per payment token
+    #[serde(with = "pubkey_map")]
+    pub invested_by_payment_token: HashMap<Pubkey, u64>,
+    /// Raw realized P&L per payment token
+    #[serde(with = "pubkey_map")]
+    pub realized_pnl_by_payment_token: HashMap<Pubkey, i64>,
+}
+
+impl NormalizedStats {
+    /// Human-readable summary, in UI units when the currency's decimals are known
+    pub fn summary(&self) -> String {
+        let scale = 10f64.powi(payment_decimals(&self.currency) as i32);
+        let mut out = format!(
+            "Invested {:.2}, realized P&L {:+.2} (in {})",
+            self.total_invested / scale,
+            self.total_realized_pnl / scale,
+            self.currency
+        );
+        if !self.excluded_payment_tokens.is_empty() {
+            out.push_str(&format!(
+                " - excludes positions paid in {} unpriced token(s)",
+                self.excluded_payment_tokens.len()
+            ));
+        }
+        out
+    }
+}
+
+/// Decimals of common payment tokens (0 = unknown, shown in raw units)
+fn payment_decimals(mint: &Pubkey) -> u8 {
+    match mint.to_string().as_str() {
+        Stablecoins::USDC | Stablecoins::USDT => 6,
+        WSOL_MINT => 9,
+        _ => 0,
+    }
+}
+
+/// Compact "1d 2h", "3h 4m", "5m 6s" style duration
+fn format_duration(secs: u64) -> String {
+    let (days, hours, mins) = (secs / 86_400, secs % 86_400 / 3_600, secs % 3_600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else {
+        format!("{}m {}s", mins, secs % 60)
+    }
+}
+
+/// Whether a buy opens a position or adds to one we already hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryKind {
+    New,
+    Add,
+}
+
+impl std::fmt::Display for EntryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntryKind::New => write!(f, "New position"),
+            EntryKind::Add => write!(f, "Add to position"),
+        }
+    }
+}
+
+/// Why a position should be exited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerKind {
+    StopLoss,
+    TakeProfit,
+    TrailingStop,
+}
+
+impl std::fmt::Display for TriggerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TriggerKind::StopLoss => write!(f, "Stop-loss"),
+            TriggerKind::TakeProfit => write!(f, "Take-profit"),
+            TriggerKind::TrailingStop => write!(f, "Trailing stop"),
+        }
+    }
+}
+
+/// A position that breached a stop-loss, take-profit or trailing-stop threshold
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    pub token: Pubkey,
+    pub kind: TriggerKind,
+    /// Move from entry price, in percent, of the price that was checked
+    pub change_percent: f64,
+    /// Spot and smoothed price at the time of the check
+    pub price: PriceSample,
+}
+
+/// One line of the snapshots file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioSnapshot {
+    pub timestamp: u64,
+    pub total_realized_pnl: i64,
+    pub active_positions: usize,
+    pub total_invested: u64,
+}
+
+impl PortfolioSnapshot {
+    /// Append this snapshot as a JSON line to `path`
+    pub fn append_to(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(self)?)?;
+        Ok(())
+    }
 }
 
 impl Default for PortfolioTracker {
@@ -396,3 +1542,805 @@ impl Default for PortfolioTracker {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_revert_open() {
+        let mut portfolio = PortfolioTracker::new();
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        assert_eq!(portfolio.classify_entry(&token), EntryKind::New);
+        portfolio.open_position(token, 1_000, usdc, 500, "sig1".to_string());
+        assert_eq!(portfolio.classify_entry(&token), EntryKind::Add);
+        portfolio.open_position(token, 1_000, usdc, 700, "sig2".to_string());
+
+        // Second buy didn't finalize
+        portfolio.revert_open(&token, 1_000, 700);
+        let position = portfolio.get_position(&token).unwrap();
+        assert_eq!(position.amount, 1_000);
+        assert_eq!(position.cost_basis, 500);
+
+        // First one didn't either
+        portfolio.revert_open(&token, 1_000, 500);
+        assert!(!portfolio.has_position(&token));
+    }
+
+    #[test]
+    fn test_load_prunes_empty_positions() {
+        let token = Pubkey::new_unique();
+        let stale = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(token, 1_000, usdc, 500, "sig1".to_string());
+        portfolio.open_position(stale, 1_000, usdc, 500, "sig2".to_string());
+        portfolio.positions.get_mut(&stale).unwrap().amount = 0;
+        portfolio.total_realized_pnl = 42; // No closed positions to back this up
+
+        let path = std::env::temp_dir().join(format!("portfolio-prune-{}.json", token));
+        let path = path.to_str().unwrap();
+        portfolio.save(path).unwrap();
+
+        let loaded = PortfolioTracker::load(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert!(loaded.has_position(&token));
+        assert!(!loaded.has_position(&stale));
+        assert_eq!(loaded.get_stats().total_realized_pnl, 0);
+    }
+
+    #[test]
+    fn test_interrupted_save_keeps_the_previous_file() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let path = std::env::temp_dir().join(format!("portfolio-atomic-{}.json", token));
+        let path = path.to_str().unwrap();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(token, 1_000, usdc, 500, "sig1".to_string());
+        portfolio.save(path).unwrap();
+
+        // A crash mid-save leaves a truncated temp file, not a truncated portfolio
+        fs::write(temp_path(path), "{\"positions\": {").unwrap();
+        assert!(PortfolioTracker::load(path).unwrap().has_position(&token));
+
+        // A save that can't write its temp file fails without touching the original
+        fs::remove_file(temp_path(path)).unwrap();
+        fs::create_dir(temp_path(path)).unwrap();
+        portfolio.open_position(Pubkey::new_unique(), 1, usdc, 1, "sig2".to_string());
+        assert!(portfolio.save(path).is_err());
+        assert_eq!(
+            PortfolioTracker::load(path)
+                .unwrap()
+                .get_stats()
+                .active_positions,
+            1
+        );
+
+        // The next good save replaces it
+        fs::remove_dir(temp_path(path)).unwrap();
+        portfolio.save(path).unwrap();
+        assert_eq!(
+            PortfolioTracker::load(path)
+                .unwrap()
+                .get_stats()
+                .active_positions,
+            2
+        );
+        assert!(!Path::new(&temp_path(path)).exists());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_stats_by_token() {
+        let (bonk, wif) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let usdc = Pubkey::new_unique();
+        let clock = Arc::new(crate::portfolio::FixedClock::new(1_000));
+        let mut portfolio = PortfolioTracker::with_clock(clock.clone());
+        assert!(portfolio.stats_by_token().is_empty());
+
+        // BONK: +200 after 60s, -100 after 120s, +50 after 30s
+        for (exit_value, held) in [(1_200, 60), (900, 120), (1_050, 30)] {
+            portfolio.open_position(bonk, 1_000, usdc, 1_000, "buy".to_string());
+            clock.advance(held);
+            portfolio
+                .close_position(&bonk, 1_000, exit_value, "sell".to_string())
+                .unwrap();
+        }
+
+        // WIF: one position closed in two halves, both at a loss
+        portfolio.open_position(wif, 1_000, usdc, 1_000, "buy".to_string());
+        clock.advance(100);
+        portfolio
+            .close_position(&wif, 500, 400, "sell-1".to_string())
+            .unwrap();
+        clock.advance(100);
+        portfolio
+            .close_position(&wif, 500, 300, "sell-2".to_string())
+            .unwrap();
+
+        // Still open, so not counted
+        portfolio.open_position(Pubkey::new_unique(), 1, usdc, 1, "buy".to_string());
+
+        let stats = portfolio.stats_by_token();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(
+            stats[&bonk],
+            TokenStats {
+                trades: 3,
+                total_realized_pnl: 150,
+                win_rate: 2.0 / 3.0 * 100.0,
+                avg_holding_secs: 70.0,
+            }
+        );
+        assert_eq!(
+            stats[&wif],
+            TokenStats {
+                trades: 2,
+                total_realized_pnl: -300,
+                win_rate: 0.0,
+                avg_holding_secs: 150.0,
+            }
+        );
+        assert_eq!(
+            stats.values().map(|s| s.total_realized_pnl).sum::<i64>(),
+            portfolio.get_stats().total_realized_pnl
+        );
+    }
+
+    #[test]
+    fn test_export_closed_csv() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let clock = Arc::new(crate::portfolio::FixedClock::new(1_709_294_400)); // 2024-03-01T12:00:00Z
+
+        let mut portfolio = PortfolioTracker::with_clock(clock.clone());
+        portfolio.open_position(token, 1_000, usdc, 500, "buy-sig".to_string());
+        clock.advance(90);
+        portfolio
+            .close_position(&token, 1_000, 600, "sell-sig".to_string())
+            .unwrap();
+
+        let path = std::env::temp_dir().join(format!("closed-{}.csv", token));
+        let path = path.to_str().unwrap();
+        portfolio.export_closed_csv(path).unwrap();
+        let contents = fs::read_to_string(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines[0], CLOSED_CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            format!(
+                "{},{},2024-03-01T12:00:00Z,2024-03-01T12:01:30Z,500,600,100,20.0000,90,buy-sig,sell-sig",
+                token, usdc
+            )
+        );
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn test_snapshot_appends_lines() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(token, 1_000, usdc, 500, "sig1".to_string());
+
+        let snapshot = portfolio.snapshot();
+        assert_eq!(snapshot.active_positions, 1);
+        assert_eq!(snapshot.total_invested, 500);
+        assert_eq!(snapshot.total_realized_pnl, 0);
+
+        let path = std::env::temp_dir().join(format!("snapshots-{}.jsonl", Pubkey::new_unique()));
+        let path = path.to_str().unwrap();
+        snapshot.append_to(path).unwrap();
+        portfolio.snapshot().append_to(path).unwrap();
+
+        let contents = fs::read_to_string(path).unwrap();
+        let _ = fs::remove_file(path);
+        let lines: Vec<PortfolioSnapshot> = contents
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1].total_invested, 500);
+    }
+
+    #[test]
+    fn test_format_table() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+
+        let mut portfolio = PortfolioTracker::new();
+        assert!(portfolio.format_table().contains("(no active positions)"));
+
+        portfolio.open_position(token, 1_000, usdc, 12_500_000, "sig1".to_string());
+        let table = portfolio.format_table();
+        assert!(table.contains(&token.to_string()));
+        assert!(table.contains("12.5"));
+        assert!(table.contains("Active positions: 1"));
+
+        assert_eq!(format_duration(59), "0m 59s");
+        assert_eq!(format_duration(3_720), "1h 2m");
+        assert_eq!(format_duration(90_000), "1d 1h");
+    }
+
+    #[tokio::test]
+    async fn test_position_risk_limits() {
+        let usdc = Pubkey::new_unique();
+        let loser = Pubkey::new_unique();
+        let winner = Pubkey::new_unique();
+        let unlimited = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        // All entered at 1.0 payment unit per token unit
+        for token in [loser, winner, unlimited] {
+            portfolio.open_position(token, 1_000, usdc, 1_000, "buy".to_string());
+        }
+        portfolio.set_risk_limits(&loser, Some(20.0), None).unwrap();
+        portfolio
+            .set_risk_limits(&winner, Some(20.0), Some(50.0))
+            .unwrap();
+        assert!(
+            portfolio
+                .set_risk_limits(&Pubkey::new_unique(), Some(1.0), None)
+                .is_err()
+        );
+
+        // No [risk] thresholds: only the positions' own limits apply
+        let risk = RiskConfig::default();
+
+        // Inside both bands: nothing to exit
+        let calm = FixedPrices(HashMap::from([
+            (loser, 0.9),
+            (winner, 1.4),
+            (unlimited, 0.1),
+        ]));
+        assert!(portfolio.check_triggers(&calm, &risk).await.is_empty());
+
+        // One falls through its stop, the other clears its target; the
+        // position without limits never triggers
+        let moved = FixedPrices(HashMap::from([
+            (loser, 0.75),
+            (winner, 1.5),
+            (unlimited, 0.1),
+        ]));
+        let mut triggers = portfolio.check_triggers(&moved, &risk).await;
+        triggers.sort_by(|a, b| a.change_percent.total_cmp(&b.change_percent));
+        assert_eq!(triggers.len(), 2);
+        assert_eq!(triggers[0].token, loser);
+        assert_eq!(triggers[0].kind, TriggerKind::StopLoss);
+        assert_eq!(triggers[1].token, winner);
+        assert_eq!(triggers[1].kind, TriggerKind::TakeProfit);
+        assert_eq!(triggers[1].price.spot, 1.5);
+
+        // The limits are saved with the position
+        let json = serde_json::to_string(portfolio.get_position(&winner).unwrap()).unwrap();
+        let restored: Position = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.take_profit_percent, Some(50.0));
+    }
+
+    #[tokio::test]
+    async fn test_position_limits_override_risk_config() {
+        let usdc = Pubkey::new_unique();
+        let token = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(token, 1_000, usdc, 1_000, "buy".to_string());
+        let risk = RiskConfig {
+            take_profit_percent: Some(10.0),
+            ..RiskConfig::default()
+        };
+        let prices = FixedPrices(HashMap::from([(token, 1.2)]));
+
+        // +20% clears the portfolio-wide target...
+        assert_eq!(portfolio.check_triggers(&prices, &risk).await.len(), 1);
+
+        // ...but not this position's own
+        portfolio.set_risk_limits(&token, None, Some(30.0)).unwrap();
+        assert!(portfolio.check_triggers(&prices, &risk).await.is_empty());
+    }
+
+    /// Serves prices from a queue, one per call
+    struct QueuedPrices(std::sync::Mutex<Vec<f64>>);
+
+    impl PriceProvider for QueuedPrices {
+        async fn get_price(&self, _mint: &Pubkey) -> crate::monitor::MonitorResult<f64> {
+            Ok(self.0.lock().unwrap().remove(0))
+        }
+    }
+
+    /// Fixed prices per mint; unknown mints fail
+    struct FixedPrices(HashMap<Pubkey, f64>);
+
+    impl PriceProvider for FixedPrices {
+        async fn get_price(&self, mint: &Pubkey) -> crate::monitor::MonitorResult<f64> {
+            self.0
+                .get(mint)
+                .copied()
+                .ok_or_else(|| crate::monitor::MonitorError::ParseError("no price".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stats_normalized_to_display_currency() {
+        let usdc = Pubkey::new_unique();
+        let wsol = Pubkey::new_unique();
+        let unpriced = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(
+            Pubkey::new_unique(),
+            1_000,
+            usdc,
+            5_000_000,
+            "a".to_string(),
+        );
+        // 0.1 SOL at 150 USDC/SOL (0.15 USDC units per lamport)
+        portfolio.open_position(
+            Pubkey::new_unique(),
+            1_000,
+            wsol,
+            100_000_000,
+            "b".to_string(),
+        );
+        portfolio.open_position(Pubkey::new_unique(), 1_000, unpriced, 42, "c".to_string());
+
+        // Closed at +0.01 SOL
+        let sold = Pubkey::new_unique();
+        portfolio.open_position(sold, 1_000, wsol, 100_000_000, "d".to_string());
+        portfolio
+            .close_position(&sold, 1_000, 110_000_000, "e".to_string())
+            .unwrap();
+
+        let prices = FixedPrices(HashMap::from([(wsol, 0.15)]));
+        let normalized = portfolio.get_stats_in(&prices, &usdc).await;
+
+        assert_eq!(normalized.currency, usdc);
+        assert!((normalized.total_invested - 20_000_000.0).abs() < 1e-6);
+        assert!((normalized.total_realized_pnl - 1_500_000.0).abs() < 1e-6);
+        assert_eq!(normalized.excluded_payment_tokens, vec![unpriced]);
+
+        // Raw figures are kept, including the excluded token
+        assert_eq!(normalized.invested_by_payment_token[&unpriced], 42);
+        assert_eq!(normalized.realized_pnl_by_payment_token[&wsol], 10_000_000);
+        assert_eq!(normalized.stats.active_positions, 3);
+        assert!(normalized.summary().contains("1 unpriced"));
+
+        // Served as JSON by /portfolio/stats
+        let json = serde_json::to_value(&normalized).unwrap();
+        assert_eq!(json["invested_by_payment_token"][unpriced.to_string()], 42);
+    }
+
+    #[tokio::test]
+    async fn test_unrealized_pnl_skips_unpriced_tokens() {
+        let usdc = Pubkey::new_unique();
+        let winner = Pubkey::new_unique();
+        let loser = Pubkey::new_unique();
+        let unpriced = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        // Entry at 1.0: 1,000 for 1,000 tokens each
+        portfolio.open_position(winner, 1_000, usdc, 1_000, "a".to_string());
+        portfolio.open_position(loser, 1_000, usdc, 1_000, "b".to_string());
+        portfolio.open_position(unpriced, 1_000, usdc, 1_000, "c".to_string());
+
+        let prices = FixedPrices(HashMap::from([(winner, 1.5), (loser, 0.75)]));
+        let valuation = portfolio.unrealized_pnl(&prices).await;
+
+        assert_eq!(valuation.market_value, 2_250);
+        assert_eq!(valuation.cost_basis, 2_000);
+        assert_eq!(valuation.unrealized_pnl, 250);
+        assert_eq!(valuation.by_position[&winner].absolute, 500);
+        assert_eq!(valuation.by_position[&loser].absolute, -250);
+        assert_eq!(valuation.unpriced, vec![unpriced]);
+
+        let stats = portfolio.get_stats_at_market(&prices).await;
+        assert_eq!(stats.market_value, Some(2_250));
+        assert_eq!(stats.unrealized_pnl, Some(250));
+        assert_eq!(stats.total_invested, 3_000);
+        assert_eq!(portfolio.get_stats().market_value, None);
+    }
+
+    #[tokio::test]
+    async fn test_ema_ignores_single_spike_for_triggers() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        // Entry price: 1.0 payment unit per token unit
+        portfolio.open_position(token, 1_000, usdc, 1_000, "sig1".to_string());
+
+        let risk = RiskConfig {
+            stop_loss_percent: Some(25.0),
+            take_profit_percent: Some(50.0),
+            ema_alpha: Some(0.25),
+            ..RiskConfig::default()
+        };
+        let prices = QueuedPrices(std::sync::Mutex::new(vec![1.0, 0.5, 0.5, 0.5]));
+
+        // Flat, then a 50% dip: the EMA only falls to 0.875
+        assert!(portfolio.check_triggers(&prices, &risk).await.is_empty());
+        assert!(portfolio.check_triggers(&prices, &risk).await.is_empty());
+        assert_eq!(portfolio.price_sample(&token).unwrap().spot, 0.5);
+
+        // The dip persists: 0.78 - still above the -25% stop
+        assert!(portfolio.check_triggers(&prices, &risk).await.is_empty());
+
+        // Persists again: 0.71 - now the stop-loss fires
+        let triggers = portfolio.check_triggers(&prices, &risk).await;
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].kind, TriggerKind::StopLoss);
+        assert_eq!(triggers[0].price.spot, 0.5);
+
+        // Without smoothing the first dip would have fired immediately
+        let raw = RiskConfig {
+            ema_alpha: None,
+            ..risk
+        };
+        let prices = QueuedPrices(std::sync::Mutex::new(vec![0.5]));
+        assert_eq!(portfolio.check_triggers(&prices, &raw).await.len(), 1);
+    }
+
+    #[test]
+    fn test_position_filters() {
+        let usdc = Pubkey::new_unique();
+        let fresh = Pubkey::new_unique();
+        let stale = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(fresh, 1_000, usdc, 100, "sig1".to_string());
+        portfolio.open_position(stale, 1_000, usdc, 5_000, "sig2".to_string());
+
+        // Backdate one entry by two hours
+        portfolio.positions.get_mut(&stale).unwrap().entry_time -= 7_200;
+
+        let old: Vec<Pubkey> = portfolio
+            .positions_older_than(3_600)
+            .iter()
+            .map(|p| p.token)
+            .collect();
+        assert_eq!(old, vec![stale]);
+
+        let large: Vec<Pubkey> = portfolio
+            .positions_over_cost(1_000)
+            .iter()
+            .map(|p| p.token)
+            .collect();
+        assert_eq!(large, vec![stale]);
+
+        assert_eq!(portfolio.positions_over_cost(0).len(), 2);
+        assert!(portfolio.positions_older_than(u64::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_injected_clock() {
+        let clock = Arc::new(crate::portfolio::FixedClock::new(1_700_000_000));
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::with_clock(clock.clone());
+        portfolio.open_position(token, 1_000, usdc, 500, "buy".to_string());
+        assert_eq!(
+            portfolio.get_position(&token).unwrap().entry_time,
+            1_700_000_000
+        );
+
+        clock.advance(90);
+        assert_eq!(
+            portfolio
+                .get_position(&token)
+                .unwrap()
+                .holding_duration_at(clock.now()),
+            90
+        );
+        assert_eq!(portfolio.positions_older_than(60).len(), 1);
+        assert!(portfolio.positions_older_than(90).is_empty());
+
+        let closed = portfolio
+            .close_position(&token, 1_000, 600, "sell".to_string())
+            .unwrap();
+        assert_eq!(closed.exit_time, 1_700_000_090);
+        assert_eq!(portfolio.snapshot().timestamp, 1_700_000_090);
+    }
+
+    #[test]
+    fn test_close_triggered_exits_at_spot() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(token, 1_000, usdc, 1_000, "sig1".to_string());
+        portfolio.record_copy(&token, 1_000);
+
+        let risk = RiskConfig {
+            stop_loss_percent: Some(20.0),
+            ..RiskConfig::default()
+        };
+        assert!(portfolio.has_exit_rules(&risk));
+        assert!(!portfolio.has_exit_rules(&RiskConfig::default()));
+
+        // Unpriced tokens are skipped
+        assert!(
+            portfolio
+                .check_triggers_at(&HashMap::new(), &risk)
+                .is_empty()
+        );
+
+        let triggers = portfolio.check_triggers_at(&HashMap::from([(token, 0.75)]), &risk);
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].kind, TriggerKind::StopLoss);
+
+        let closed = portfolio
+            .close_triggered(&triggers[0], "risk-exit".to_string())
+            .unwrap();
+        assert_eq!(closed.exit_value, 750);
+        assert_eq!(closed.realized_pnl, -250);
+        assert_eq!(closed.position.copied_amount, 1_000);
+        assert!(!portfolio.has_position(&token));
+    }
+
+    #[tokio::test]
+    async fn test_trailing_stop_follows_peak() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(token, 1_000, usdc, 1_000, "sig1".to_string());
+
+        let risk = RiskConfig {
+            trailing_stop_percent: Some(10.0),
+            trailing_stop_activation_percent: Some(20.0),
+            ..RiskConfig::default()
+        };
+        let prices = QueuedPrices(std::sync::Mutex::new(vec![1.1, 1.0, 1.25, 1.5, 1.4, 1.3]));
+
+        // Up 10%, then back to entry: not armed yet, so no exit
+        assert!(portfolio.check_triggers(&prices, &risk).await.is_empty());
+        assert!(portfolio.check_triggers(&prices, &risk).await.is_empty());
+        assert_eq!(portfolio.get_position(&token).unwrap().high_water_mark, 1.1);
+
+        // Arms at +25%, peak rises to 1.5, 1.4 is within 10% of it
+        for _ in 0..3 {
+            assert!(portfolio.check_triggers(&prices, &risk).await.is_empty());
+        }
+        assert_eq!(portfolio.get_position(&token).unwrap().high_water_mark, 1.5);
+
+        // 1.3 is more than 10% below the 1.5 peak
+        let triggers = portfolio.check_triggers(&prices, &risk).await;
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].kind, TriggerKind::TrailingStop);
+
+        // The peak is saved with the position
+        let json = serde_json::to_string(portfolio.get_position(&token).unwrap()).unwrap();
+        let restored: Position = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.high_water_mark, 1.5);
+    }
+
+    #[tokio::test]
+    async fn test_position_trailing_stop() {
+        let usdc = Pubkey::new_unique();
+        let trailed = Pubkey::new_unique();
+        let untrailed = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        // Both entered at 1.0 payment unit per token unit
+        for token in [trailed, untrailed] {
+            portfolio.open_position(token, 1_000, usdc, 1_000, "buy".to_string());
+        }
+        portfolio.set_trailing_stop(&trailed, Some(20.0)).unwrap();
+        assert!(portfolio.set_trailing_stop(&usdc, Some(20.0)).is_err());
+
+        // No [risk] thresholds: only the position's own trail applies
+        let risk = RiskConfig::default();
+
+        // Drive the price up to 2.0, then down to 1.75 - within 20% of the high
+        for price in [1.5, 2.0, 1.75] {
+            let prices = FixedPrices(HashMap::from([(trailed, price), (untrailed, price)]));
+            assert!(portfolio.check_triggers(&prices, &risk).await.is_empty());
+        }
+        assert_eq!(
+            portfolio.get_position(&trailed).unwrap().high_water_mark,
+            2.0
+        );
+        assert_eq!(
+            portfolio.get_position(&untrailed).unwrap().high_water_mark,
+            2.0
+        );
+
+        // The high survives a save and load
+        let path = std::env::temp_dir().join(format!("trail-{}.json", trailed));
+        let path = path.to_string_lossy().into_owned();
+        portfolio.save(&path).unwrap();
+        let mut portfolio = PortfolioTracker::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            portfolio.get_position(&trailed).unwrap().high_water_mark,
+            2.0
+        );
+
+        // 1.6 is 20% off the high: still in profit, but the trail is hit
+        let prices = FixedPrices(HashMap::from([(trailed, 1.6), (untrailed, 1.6)]));
+        let triggers = portfolio.check_triggers(&prices, &risk).await;
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].token, trailed);
+        assert_eq!(triggers[0].kind, TriggerKind::TrailingStop);
+        assert!((triggers[0].change_percent - 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fifo_lots_price_partial_sells() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+        let clock = Arc::new(crate::portfolio::FixedClock::new(1_000));
+
+        let mut portfolio = PortfolioTracker::with_clock(clock.clone())
+            .with_cost_basis_method(CostBasisMethod::Fifo);
+        portfolio.open_position(token, 1_000, usdc, 1_000, "buy1".to_string());
+        clock.advance(60);
+        portfolio.open_position(token, 1_000, usdc, 3_000, "buy2".to_string());
+        assert_eq!(portfolio.get_position(&token).unwrap().lots.len(), 2);
+
+        // All of the first lot and half the second: 1,000 + 1,500
+        let closed = portfolio
+            .close_position(&token, 1_500, 3_000, "sell1".to_string())
+            .unwrap();
+        assert_eq!(closed.position.cost_basis, 2_500);
+        assert_eq!(closed.realized_pnl, 500);
+        assert_eq!(closed.position.entry_signature, "buy1");
+        assert_eq!(closed.position.entry_time, 1_000);
+        assert_eq!(closed.position.lots.len(), 2);
+
+        // What's left is the rest of the second lot, at its own price
+        let held = portfolio.get_position(&token).unwrap();
+        assert_eq!(held.amount, 500);
+        assert_eq!(held.cost_basis, 1_500);
+        assert_eq!(held.avg_entry_price, 3.0);
+        assert_eq!(
+            held.lots,
+            vec![Lot {
+                amount: 500,
+                cost: 1_500,
+                entry_time: 1_060,
+                signature: "buy2".to_string(),
+            }]
+        );
+
+        // Averaged, the same sell would have cost 1,500 x 2.0
+        let mut averaged = PortfolioTracker::new();
+        averaged.open_position(token, 1_000, usdc, 1_000, "buy1".to_string());
+        averaged.open_position(token, 1_000, usdc, 3_000, "buy2".to_string());
+        let closed = averaged
+            .close_position(&token, 1_500, 3_000, "sell1".to_string())
+            .unwrap();
+        assert_eq!(closed.realized_pnl, 0);
+        assert!(closed.position.lots.is_empty());
+    }
+
+    #[test]
+    fn test_partial_exits_record_only_the_slice_sold() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        // A basis that doesn't split evenly into thirds
+        portfolio.open_position(token, 3_000, usdc, 1_000, "buy".to_string());
+
+        let first = portfolio
+            .close_position(&token, 1_000, 400, "sell1".to_string())
+            .unwrap();
+        assert_eq!(first.position.amount, 1_000);
+        assert_eq!(first.position.cost_basis, 333);
+        assert_eq!(first.realized_pnl, 67);
+
+        let second = portfolio
+            .close_position(&token, 1_000, 300, "sell2".to_string())
+            .unwrap();
+        assert_eq!(second.position.amount, 1_000);
+        assert_eq!(second.position.cost_basis, 333);
+
+        let held = portfolio.get_position(&token).unwrap();
+        assert_eq!(held.amount, 1_000);
+        assert_eq!(held.cost_basis, 334);
+
+        let last = portfolio
+            .close_position(&token, 1_000, 334, "sell3".to_string())
+            .unwrap();
+
+        // Nothing lost to rounding: the recorded slices add up to the original
+        let history = portfolio.get_history();
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            history.iter().map(|c| c.position.cost_basis).sum::<u64>(),
+            1_000
+        );
+        assert_eq!(
+            history.iter().map(|c| c.position.amount).sum::<u64>(),
+            3_000
+        );
+        assert_eq!(last.realized_pnl, 0);
+        assert_eq!(portfolio.get_stats().total_realized_pnl, 67 - 33);
+    }
+
+    #[test]
+    fn test_close_mirrored_partial_exit() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        portfolio.open_position(token, 1_000, usdc, 1_000, "sig1".to_string());
+
+        // Trader held 10x what we do and sold half of it at 2.0
+        let sell = UniversalSwapSignal {
+            input_pre_balance: 10_000,
+            ..UniversalSwapSignal::test_swap()
+                .with_mints(token, usdc)
+                .with_amounts(5_000, 10_000)
+        };
+        assert_eq!(sell.sold_fraction(), Some(0.5));
+
+        let closed = portfolio.close_mirrored(&token, &sell).unwrap();
+        assert_eq!(closed.position.amount, 500);
+        assert_eq!(closed.exit_value, 1_000);
+        assert_eq!(portfolio.get_position(&token).unwrap().amount, 500);
+
+        // Then they sold everything they had left
+        let rest = UniversalSwapSignal {
+            input_amount: 5_000,
+            input_pre_balance: 5_000,
+            ..sell
+        };
+        portfolio.close_mirrored(&token, &rest).unwrap();
+        assert!(!portfolio.has_position(&token));
+
+        // Holding unknown: at most our position is sold, at the trader's price
+        portfolio.open_position(token, 1_000, usdc, 1_000, "sig2".to_string());
+        let unknown = UniversalSwapSignal {
+            input_pre_balance: 0,
+            ..rest
+        };
+        assert_eq!(unknown.sold_fraction(), None);
+        let closed = portfolio.close_mirrored(&token, &unknown).unwrap();
+        assert_eq!(closed.position.amount, 1_000);
+        assert_eq!(closed.exit_value, 2_000);
+        assert!(!portfolio.has_position(&token));
+    }
+
+    #[test]
+    fn test_copied_amount_follows_exits() {
+        let token = Pubkey::new_unique();
+        let usdc = Pubkey::new_unique();
+
+        let mut portfolio = PortfolioTracker::new();
+        // Mirrored only (trader gated), then a buy we copied
+        portfolio.open_position(token, 1_000, usdc, 1_000, "sig1".to_string());
+        portfolio.open_position(token, 1_000, usdc, 1_000, "sig2".to_string());
+        portfolio.record_copy(&token, 1_000);
+        assert_eq!(portfolio.get_position(&token).unwrap().copied_amount, 1_000);
+
+        // Selling half the position sells half of what we bought
+        let closed = portfolio
+            .close_position(&token, 1_000, 1_000, "sell1".to_string())
+            .unwrap();
+        assert_eq!(closed.position.copied_amount, 500);
+        assert_eq!(portfolio.get_position(&token).unwrap().copied_amount, 500);
+
+        let closed = portfolio
+            .close_position(&token, 1_000, 1_000, "sell2".to_string())
+            .unwrap();
+        assert_eq!(closed.position.copied_amount, 500);
+
+        // Nothing copied, nothing to sell
+        portfolio.open_position(token, 1_000, usdc, 1_000, "sig3".to_string());
+        let closed = portfolio
+            .close_position(&token, 1_000, 1_000, "sell3".to_string())
+            .unwrap();
+        assert_eq!(closed.position.copied_amount, 0);
+    }
+}
@@ -1,12 +1,20 @@
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
+use std::collections::HashMap;
 use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::str::FromStr;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeSignal {
     // The transaction signature
     pub signature: Signature,
+    /// Slot the transaction landed in
+    #[serde(default)]
+    pub slot: u64,
     /// Unix timestamp when the transaction was processed
     pub timestamp: i64,
     /// The DEX that was used for this trade
@@ -32,14 +40,10 @@ pub struct TradeSignal {
 }
 
 impl TradeSignal {
-    pub fn calculate_slippage(&self) -> f64 {
-        if self.amount_out == 0 {
-            return 0.0;
-        }
-
-        let slippage =
-            ((self.amount_out - self.minimum_amount_out) as f64 / self.amount_out as f64) * 10000.0;
-        slippage
+    /// Slippage tolerance in basis points: how far `minimum_amount_out`
+    /// sits below `amount_out`. The parsers store this as `slippage_bps`.
+    pub fn calculate_slippage(&self) -> u16 {
+        crate::decision::slippage::slippage_bps(self.amount_out, self.minimum_amount_out)
     }
 
     /// Get a human-readable description of the trade
@@ -51,10 +55,15 @@ impl TradeSignal {
             self.minimum_amount_out,
             self.destination_mint,
             self.dex,
-            self.slippage_bps as f64 / 100.0
+            self.slippage_percent()
         )
     }
 
+    /// `slippage_bps` as a percentage (100 bps = 1%)
+    pub fn slippage_percent(&self) -> f64 {
+        self.slippage_bps as f64 / 100.0
+    }
+
     /// Get Solscan URL for this transaction
     pub fn solscan_url(&self) -> String {
         format!("https://solscan.io/tx/{}", self.signature)
@@ -66,11 +75,17 @@ impl TradeSignal {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DexType {
     Jupiter,
     Raydium,
+    /// Raydium concentrated-liquidity pools
+    RaydiumClmm,
     Orca,
+    /// Meteora DLMM and dynamic AMM pools (detection only, no parser)
+    Meteora,
+    /// Phoenix order book (detection only, no parser)
+    Phoenix,
     Unknown,
 }
 
@@ -79,11 +94,69 @@ impl fmt::Display for DexType {
         match self {
             DexType::Jupiter => write!(f, "Jupiter"),
             DexType::Raydium => write!(f, "Raydium"),
+            DexType::RaydiumClmm => write!(f, "Raydium CLMM"),
             DexType::Orca => write!(f, "Orca"),
+            DexType::Meteora => write!(f, "Meteora"),
+            DexType::Phoenix => write!(f, "Phoenix"),
             DexType::Unknown => write!(f, "Unknown"),
         }
     }
 }
+impl DexType {
+    /// The DEX a program id belongs to, if it's one we know
+    pub fn from_program_id(program_id: &Pubkey) -> Option<DexType> {
+        if *program_id == program_ids::jupiter_v6() {
+            Some(DexType::Jupiter)
+        } else if *program_id == program_ids::raydium_v4() {
+            Some(DexType::Raydium)
+        } else if *program_id == program_ids::raydium_clmm() {
+            Some(DexType::RaydiumClmm)
+        } else if *program_id == program_ids::orca_whirlpool() {
+            Some(DexType::Orca)
+        } else if *program_id == program_ids::meteora_dlmm()
+            || *program_id == program_ids::meteora_pools()
+        {
+            Some(DexType::Meteora)
+        } else if *program_id == program_ids::phoenix() {
+            Some(DexType::Phoenix)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for DexType {
+    type Err = String;
+
+    /// Parse a DEX name, case-insensitively (as used in `[dex.<name>]`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "jupiter" => Ok(DexType::Jupiter),
+            "raydium" => Ok(DexType::Raydium),
+            "raydium_clmm" | "raydium clmm" => Ok(DexType::RaydiumClmm),
+            "orca" => Ok(DexType::Orca),
+            "meteora" => Ok(DexType::Meteora),
+            "phoenix" => Ok(DexType::Phoenix),
+            "unknown" => Ok(DexType::Unknown),
+            other => Err(format!("Unknown DEX: {}", other)),
+        }
+    }
+}
+
+/// Wrapped SOL (WSOL) mint address
+///
+/// Note on native SOL vs WSOL: native SOL is not an SPL token, so SOL spent or
+/// received directly shows up only in the transaction's lamport
+/// `pre_balances`/`post_balances` (mixed in with fees and rent). Only SOL that
+/// has been wrapped into a token account appears in the token balance metadata,
+/// under this mint. The balance analyzer only ever sees the WSOL side.
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// WSOL mint as a `Pubkey`
+pub fn wsol_pubkey() -> Pubkey {
+    Pubkey::from_str(WSOL_MINT).expect("Invalid WSOL mint pubkey")
+}
+
 // g
 pub mod program_ids {
     use solana_sdk::pubkey::Pubkey;
@@ -100,47 +173,668 @@ pub mod program_ids {
             .expect("Invalid Raydium V4 pubkey")
     }
 
+    /// Raydium CLMM (concentrated liquidity)
+    pub fn raydium_clmm() -> Pubkey {
+        Pubkey::from_str("CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK")
+            .expect("Invalid Raydium CLMM pubkey")
+    }
+
     /// Orca Whirlpool
     pub fn orca_whirlpool() -> Pubkey {
         Pubkey::from_str("whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc")
             .expect("Invalid Orca Whirlpool pubkey")
     }
+
+    /// Meteora DLMM
+    pub fn meteora_dlmm() -> Pubkey {
+        Pubkey::from_str("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo")
+            .expect("Invalid Meteora DLMM pubkey")
+    }
+
+    /// Meteora dynamic AMM pools
+    pub fn meteora_pools() -> Pubkey {
+        Pubkey::from_str("Eo7WjKq67rjJQSZxS6z3YkapzY3eMj6Xy8X5EQVn5UaB")
+            .expect("Invalid Meteora pools pubkey")
+    }
+
+    /// Phoenix
+    pub fn phoenix() -> Pubkey {
+        Pubkey::from_str("PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY")
+            .expect("Invalid Phoenix pubkey")
+    }
+
+    /// Compute Budget (compute unit limit and price instructions)
+    pub fn compute_budget() -> Pubkey {
+        Pubkey::from_str("ComputeBudget111111111111111111111111111111")
+            .expect("Invalid Compute Budget pubkey")
+    }
+
+    /// SPL Token
+    pub fn token() -> Pubkey {
+        Pubkey::from_str("TokenkegQfeZyiNwAJbNbGqPFXCWuBvf9Ss623VQ5DA")
+            .expect("Invalid SPL Token pubkey")
+    }
+
+    /// SPL Token-2022 (supports extensions such as transfer fees)
+    pub fn token_2022() -> Pubkey {
+        Pubkey::from_str("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb")
+            .expect("Invalid Token-2022 pubkey")
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MonitorConfig {
-    /// The target wallet address to monitor
+    /// The target wallet address to monitor (first of `target_wallets`)
     pub target_wallet: Pubkey,
 
+    /// Every configured target wallet (inline and from `target_wallets_file`)
+    pub target_wallets: Vec<Pubkey>,
+
     /// List of RPC endpoint URLs (for failover)
     pub rpc_endpoints: Vec<String>,
 
+    /// Delay before the first retry when a fetched transaction isn't
+    /// available yet (doubles on each further retry)
+    pub fetch_delay_ms: u64,
+
+    /// Replay transactions missed while offline before going live
+    pub catch_up: bool,
+
+    /// Most transactions replayed by a catch-up
+    pub catch_up_limit: usize,
+
+    /// Where the last processed signature is kept between runs
+    pub last_signature_path: String,
+
+    /// How new transactions are discovered: WebSocket, RPC polling or both
+    pub listener_mode: ListenerMode,
+
+    /// How often polling asks RPC for new signatures
+    pub poll_interval_ms: u64,
+
     /// WebSocket endpoint URL
     pub websocket_endpoint: String,
 
+    /// Largest WebSocket message accepted, in bytes; bigger ones are rejected unparsed
+    pub max_ws_message_bytes: usize,
+
+    /// Notifications per second above which extra ones are dropped (0 = no limit)
+    pub max_notifications_per_sec: u32,
+
     /// Connection timeout in seconds
     pub connection_timeout_secs: u64,
 
+    /// Ping the WebSocket after this many quiet seconds (0 = never)
+    pub keepalive_interval_secs: u64,
+
+    /// Seconds to wait for a reply to a keepalive ping before reconnecting
+    pub keepalive_timeout_secs: u64,
+
     /// Maximum number of reconnection attempts before giving up
     pub max_reconnect_attempts: u32,
 
+    /// Longest wait between reconnection attempts, in seconds (before ±25% jitter)
+    pub max_reconnect_backoff_secs: u64,
+
     /// Whether to use commitment level "confirmed" (faster) or "finalized" (safer)
     pub use_confirmed_commitment: bool,
+
+    /// Re-check each trade at "finalized" commitment before persisting it.
+    /// Adds latency but avoids recording dropped/forked transactions.
+    pub verify_finalized: bool,
+
+    /// Delay between finality checks in seconds
+    pub finality_check_delay_secs: u64,
+
+    /// Fall back to instruction-based DEX parsing when balance deltas
+    /// don't form a clean swap
+    pub instruction_fallback: bool,
+
+    /// Attribute swaps to the DEX called through CPI (e.g. the pool under a
+    /// Jupiter route) rather than the top-level program
+    pub prefer_inner_dex: bool,
+
+    /// Only emit swaps with a stablecoin or SOL on one side (no token ↔ token)
+    pub require_base_asset: bool,
+
+    /// Drop signals whose confidence score (0.0 - 1.0) is below this
+    pub min_confidence: f32,
+
+    /// Only copy swaps where the trader paid more than this priority fee
+    /// (0 = no filtering)
+    pub min_priority_fee_lamports: u64,
+
+    /// Skip trades whose slippage exceeds this many basis points: the
+    /// trader's tolerance for instruction-parsed swaps, a fresh quote for
+    /// buys sent through the Jupiter executor
+    pub max_slippage_bps: Option<u16>,
+
+    /// Keep NFT-like balance changes (0 decimals, amount 1) in the delta set
+    pub allow_nfts: bool,
+
+    /// Mints whose balance changes are never treated as part of a swap
+    pub ignored_mints: Vec<Pubkey>,
+
+    /// Mints recognized as stablecoins on top of the built-in list
+    pub extra_stablecoins: Vec<Pubkey>,
+
+    /// If non-empty, only buys of these mints are copied
+    pub token_allowlist: Vec<Pubkey>,
+
+    /// Buys of these mints are never copied
+    pub token_denylist: Vec<Pubkey>,
+
+    /// Keep WSOL balance changes as swap legs (SOL-funded trades) instead
+    /// of dropping them as fees
+    pub treat_wsol_as_tradeable: bool,
+
+    /// With `treat_wsol_as_tradeable`, WSOL changes below this many lamports
+    /// are still dropped as fee/rent dust
+    pub min_wsol_delta_lamports: u64,
+
+    /// Only copy buys of tokens the target didn't hold before
+    pub only_new_mints: bool,
+
+    /// If set, a "new mint" must also have been created within this many
+    /// seconds. Costs one extra RPC call per candidate buy.
+    pub new_mint_max_age_secs: Option<u64>,
+
+    /// Only copy buys while the trader's observed win rate (%) is at least this
+    pub min_trader_win_rate: Option<f64>,
+
+    /// Only copy buys while the trader's observed realized P&L is at least this
+    pub min_trader_pnl: Option<i64>,
+
+    /// Skip buys whose quoted buy-then-sell round trip loses more than this
+    /// (basis points). Checked by the Jupiter executor.
+    pub max_round_trip_loss_bps: Option<u16>,
+
+    /// Skip a buy matching one of the same mint and input amount seen within
+    /// this many seconds (catches forked-then-replaced transactions)
+    pub semantic_dedup_window_secs: Option<u64>,
+
+    /// Drop signals whose block time is more than this many seconds ago
+    /// (e.g. replayed after an outage). Unlimited when unset.
+    pub max_signal_age_secs: Option<u64>,
+
+    /// Skip buys that would open a new position while this many are open
+    pub max_open_positions: Option<usize>,
+
+    /// Skip swaps whose input is worth less than this many USD. Inputs
+    /// other than stablecoins cost one price lookup per swap.
+    pub min_input_value: Option<f64>,
+
+    /// What to do with the trader's sells (default: only those we hold)
+    pub sell_policy: SellPolicy,
+
+    /// How partial sells are matched against buys (default: averaged)
+    pub cost_basis_method: CostBasisMethod,
+
+    /// Where the portfolio is saved. A `{wallet}` placeholder is replaced
+    /// with the target wallet address, giving one portfolio per wallet.
+    pub portfolio_path: String,
+
+    /// Where portfolios are kept (default: JSON files at `portfolio_path`)
+    pub storage_backend: StorageBackend,
+
+    /// SQLite database for the `sqlite` backend. Each portfolio is stored
+    /// under its `portfolio_path`, which then only serves as a name.
+    pub sqlite_path: String,
+
+    /// Append a portfolio snapshot every N seconds (disabled when `None`)
+    pub snapshot_interval_secs: Option<u64>,
+
+    /// JSON-lines file the snapshots are appended to
+    pub snapshot_path: String,
+
+    /// Mint that stats are converted into for display (raw per-token figures
+    /// otherwise). Other payment tokens are priced through Jupiter.
+    pub display_currency: Option<Pubkey>,
+
+    /// Append transactions that fail to parse to this JSON-lines file
+    pub dead_letter_path: Option<String>,
+
+    /// Include the full transaction JSON in dead letters (large, but replayable)
+    pub dead_letter_raw_json: bool,
+
+    /// Debug aid: log the balance changes of transactions the classifier
+    /// calls `Unknown` (and record them to the dead-letter file, if set)
+    pub log_unknown_patterns: bool,
+
+    /// Log level from the `[logging]` section (trace/debug/info/warn/error)
+    pub log_level: String,
+
+    /// Trade execution settings
+    pub executor: ExecutorConfig,
+
+    /// Stop-loss / take-profit settings
+    pub risk: RiskConfig,
+
+    /// Read-only HTTP API settings
+    pub api: ApiConfig,
+
+    /// Where copied buys/sells are announced
+    pub notifications: NotificationsConfig,
+
+    /// Per-DEX copy settings; the `Unknown` entry (if any) is the default
+    pub dex_policies: HashMap<DexType, DexPolicy>,
+}
+
+impl MonitorConfig {
+    /// Portfolio file for the given target wallet
+    pub fn portfolio_path_for(&self, wallet: &Pubkey) -> String {
+        self.portfolio_path.replace("{wallet}", &wallet.to_string())
+    }
+
+    /// Wallets to monitor: `target_wallets`, or just `target_wallet` when
+    /// the list is empty (configs built in code)
+    pub fn monitored_wallets(&self) -> Vec<Pubkey> {
+        if self.target_wallets.is_empty() {
+            vec![self.target_wallet]
+        } else {
+            self.target_wallets.clone()
+        }
+    }
+
+    /// Copy policy for a signal's `likely_dex`.
+    ///
+    /// Unrecognized or missing DEX names fall back to the `Unknown` policy,
+    /// then to copying at full size.
+    pub fn dex_policy(&self, likely_dex: Option<&str>) -> DexPolicy {
+        DexPolicy::lookup(&self.dex_policies, likely_dex)
+    }
 }
 
 impl Default for MonitorConfig {
     fn default() -> Self {
         Self {
             target_wallet: Pubkey::default(),
+            target_wallets: vec![],
             rpc_endpoints: vec!["https://api.mainnet-beta.solana.com".to_string()],
+            fetch_delay_ms: 500,
+            catch_up: false,
+            catch_up_limit: 100,
+            listener_mode: ListenerMode::default(),
+            poll_interval_ms: 2_000,
+            last_signature_path: "last_signature.txt".to_string(),
             websocket_endpoint: "wss://api.mainnet-beta.solana.com".to_string(),
+            max_ws_message_bytes: 1024 * 1024,
+            max_notifications_per_sec: 200,
             connection_timeout_secs: 30,
+            keepalive_interval_secs: 30,
+            keepalive_timeout_secs: 10,
             max_reconnect_attempts: 5,
+            max_reconnect_backoff_secs: 60,
             use_confirmed_commitment: true,
+            verify_finalized: false,
+            finality_check_delay_secs: 10,
+            instruction_fallback: false,
+            prefer_inner_dex: false,
+            require_base_asset: false,
+            min_confidence: 0.0,
+            min_priority_fee_lamports: 0,
+            max_slippage_bps: None,
+            allow_nfts: false,
+            ignored_mints: vec![],
+            extra_stablecoins: vec![],
+            token_allowlist: vec![],
+            token_denylist: vec![],
+            treat_wsol_as_tradeable: false,
+            min_wsol_delta_lamports: 10_000_000,
+            only_new_mints: false,
+            new_mint_max_age_secs: None,
+            min_trader_win_rate: None,
+            min_trader_pnl: None,
+            max_round_trip_loss_bps: None,
+            semantic_dedup_window_secs: None,
+            max_signal_age_secs: None,
+            max_open_positions: None,
+            min_input_value: None,
+            sell_policy: SellPolicy::default(),
+            cost_basis_method: CostBasisMethod::default(),
+            portfolio_path: "portfolio.json".to_string(),
+            storage_backend: StorageBackend::default(),
+            sqlite_path: "sniper.db".to_string(),
+            snapshot_interval_secs: None,
+            snapshot_path: "snapshots.jsonl".to_string(),
+            display_currency: None,
+            dead_letter_path: None,
+            dead_letter_raw_json: false,
+            log_unknown_patterns: false,
+            log_level: "info".to_string(),
+            executor: ExecutorConfig::default(),
+            risk: RiskConfig::default(),
+            api: ApiConfig::default(),
+            notifications: NotificationsConfig::default(),
+            dex_policies: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutorConfig {
+    /// Simulate every transaction before broadcasting it.
+    /// Disable only for latency-critical snipes.
+    pub simulate_before_send: bool,
+
+    /// Wait this long before copying a buy, so we don't land in the same
+    /// block as the trader (0 = copy immediately)
+    pub copy_delay_ms: u64,
+
+    /// Up to this much extra random delay on top of `copy_delay_ms`
+    pub copy_delay_jitter_ms: u64,
+
+    /// SOL (lamports) always left in the wallet to pay for exits
+    pub sol_fee_reserve_lamports: u64,
+
+    /// How copies are executed (default: dry run, nothing is sent)
+    pub mode: ExecutionMode,
+
+    /// Copies spend at most this much of the input token (raw units);
+    /// larger trades are copied at this size
+    pub max_input_amount: Option<u64>,
+
+    /// Keypair file that signs copied trades (required for `jupiter`)
+    pub keypair_path: Option<String>,
+
+    /// Jupiter swap API base URL
+    pub jupiter_api_url: String,
+
+    /// Slippage tolerance for copied swaps, in basis points
+    pub slippage_bps: u16,
+
+    /// Fraction of the trader's size to copy (1.0 = same size, 0 = copy nothing)
+    pub copy_ratio: f64,
+
+    /// Largest position to open, in USD. Only applied to trades with a
+    /// stablecoin side, as there is no price source for anything else.
+    pub max_position_usd: Option<f64>,
+
+    /// Copies smaller than this (raw units of the input token) are dust and
+    /// skipped
+    pub min_copy_amount: u64,
+}
+
+impl ExecutorConfig {
+    /// Delay before acting on a buy: `copy_delay_ms` plus random jitter
+    pub fn copy_delay(&self) -> Duration {
+        let jitter = if self.copy_delay_jitter_ms > 0 {
+            rand::thread_rng().gen_range(0..=self.copy_delay_jitter_ms)
+        } else {
+            0
+        };
+        Duration::from_millis(self.copy_delay_ms + jitter)
+    }
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            simulate_before_send: true,
+            copy_delay_ms: 0,
+            copy_delay_jitter_ms: 0,
+            sol_fee_reserve_lamports: 20_000_000,
+            mode: ExecutionMode::default(),
+            max_input_amount: None,
+            keypair_path: None,
+            jupiter_api_url: DEFAULT_JUPITER_API_URL.to_string(),
+            slippage_bps: 100,
+            copy_ratio: 1.0,
+            max_position_usd: None,
+            min_copy_amount: 1,
+        }
+    }
+}
+
+/// How the listener discovers the target wallets' transactions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ListenerMode {
+    /// `logsSubscribe` notifications over the WebSocket
+    #[default]
+    WebSocket,
+    /// `getSignaturesForAddress` over RPC, every `poll_interval_ms`
+    Polling,
+    /// Both, deduplicated; polling carries on if the WebSocket gives up
+    Hybrid,
+}
+
+impl ListenerMode {
+    pub fn uses_websocket(&self) -> bool {
+        matches!(self, ListenerMode::WebSocket | ListenerMode::Hybrid)
+    }
+
+    pub fn uses_polling(&self) -> bool {
+        matches!(self, ListenerMode::Polling | ListenerMode::Hybrid)
+    }
+}
+
+impl FromStr for ListenerMode {
+    type Err = String;
+
+    /// Parse `websocket`, `polling` or `hybrid` (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "websocket" => Ok(ListenerMode::WebSocket),
+            "polling" => Ok(ListenerMode::Polling),
+            "hybrid" => Ok(ListenerMode::Hybrid),
+            other => Err(format!("Unknown listener mode: {}", other)),
+        }
+    }
+}
+
+/// Where portfolios (and, with SQLite, detected signals) are persisted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageBackend {
+    /// One JSON file per portfolio, rewritten on every change
+    #[default]
+    Json,
+    /// Tables in a SQLite database, which also keeps every detected signal
+    Sqlite,
+}
+
+impl FromStr for StorageBackend {
+    type Err = String;
+
+    /// Parse `json` or `sqlite` (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(StorageBackend::Json),
+            "sqlite" => Ok(StorageBackend::Sqlite),
+            other => Err(format!("Unknown storage backend: {}", other)),
+        }
+    }
+}
+
+/// Jupiter v6 swap API
+pub const DEFAULT_JUPITER_API_URL: &str = "https://quote-api.jup.ag/v6";
+
+/// Where copied trades go
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecutionMode {
+    /// Log what would be executed, send nothing
+    #[default]
+    DryRun,
+    /// Swap through the Jupiter API and submit via RPC
+    Jupiter,
+}
+
+impl FromStr for ExecutionMode {
+    type Err = String;
+
+    /// Parse `dry_run` or `jupiter` (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "dry_run" => Ok(ExecutionMode::DryRun),
+            "jupiter" => Ok(ExecutionMode::Jupiter),
+            other => Err(format!("Unknown execution mode: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskConfig {
+    /// Exit when a position is down this many percent from entry
+    pub stop_loss_percent: Option<f64>,
+
+    /// Exit when a position is up this many percent from entry
+    pub take_profit_percent: Option<f64>,
+
+    /// EMA smoothing factor (0 - 1] applied to sampled prices before
+    /// checking triggers. `None` uses the raw spot price.
+    pub ema_alpha: Option<f64>,
+
+    /// Exit when a position retraces this many percent from its peak
+    pub trailing_stop_percent: Option<f64>,
+
+    /// Only start tracking the peak once a position is up this many percent
+    /// (default: as soon as it is in profit)
+    pub trailing_stop_activation_percent: Option<f64>,
+
+    /// How often open positions are priced and checked against the above
+    pub check_interval_secs: u64,
+}
+
+impl Default for RiskConfig {
+    fn default() -> Self {
+        Self {
+            stop_loss_percent: None,
+            take_profit_percent: None,
+            ema_alpha: None,
+            trailing_stop_percent: None,
+            trailing_stop_activation_percent: None,
+            check_interval_secs: 30,
+        }
+    }
+}
+
+/// How the trader's sells are handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SellPolicy {
+    /// Mirror sells only for tokens we hold (strict mirroring)
+    #[default]
+    OnlyOwned,
+    /// Copy every sell, even without a position (e.g. to go short)
+    AlwaysCopy,
+    /// Never act on sells, just log them
+    LogOnly,
+}
+
+impl FromStr for SellPolicy {
+    type Err = String;
+
+    /// Parse `only_owned`, `always_copy` or `log_only` (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('-', "_").as_str() {
+            "only_owned" => Ok(SellPolicy::OnlyOwned),
+            "always_copy" => Ok(SellPolicy::AlwaysCopy),
+            "log_only" => Ok(SellPolicy::LogOnly),
+            other => Err(format!("Unknown sell policy: {}", other)),
+        }
+    }
+}
+
+/// How the cost of a partial sell is worked out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CostBasisMethod {
+    /// Every buy is blended into one average entry price
+    #[default]
+    Average,
+    /// Each buy is kept as a lot and sells consume the oldest lots first
+    Fifo,
+}
+
+impl FromStr for CostBasisMethod {
+    type Err = String;
+
+    /// Parse `average` or `fifo` (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "average" => Ok(CostBasisMethod::Average),
+            "fifo" => Ok(CostBasisMethod::Fifo),
+            other => Err(format!("Unknown cost basis method: {}", other)),
+        }
+    }
+}
+
+/// How trades routed through one DEX are copied
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DexPolicy {
+    /// Copy trades on this DEX at all
+    pub enabled: bool,
+
+    /// Scales the copied size
+    pub size_multiplier: f64,
+}
+
+impl DexPolicy {
+    /// `likely_dex`'s entry in `policies`, falling back to the `Unknown`
+    /// entry, then to copying at full size
+    pub fn lookup(policies: &HashMap<DexType, DexPolicy>, likely_dex: Option<&str>) -> Self {
+        let dex = likely_dex
+            .and_then(|name| name.parse().ok())
+            .unwrap_or(DexType::Unknown);
+
+        policies
+            .get(&dex)
+            .or_else(|| policies.get(&DexType::Unknown))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl Default for DexPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            size_multiplier: 1.0,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    /// Serve the portfolio and recent signals over HTTP
+    pub enabled: bool,
+
+    /// Address the API binds to (localhost unless set otherwise)
+    pub bind_address: IpAddr,
+
+    /// Port the API listens on
+    pub port: u16,
+}
+
+impl ApiConfig {
+    pub fn socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.port)
+    }
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: 8080,
+        }
+    }
+}
+
+/// Notification sinks; each one is enabled independently
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// Append every copied buy/sell as a JSON line to this file
+    pub json_log_path: Option<String>,
+
+    /// Bot token for Telegram messages (sent only with `telegram_chat_id`)
+    pub telegram_bot_token: Option<String>,
+
+    /// Chat the Telegram bot posts to
+    pub telegram_chat_id: Option<String>,
+
+    /// Discord webhook that receives an embed per trade
+    pub discord_webhook_url: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -149,6 +843,7 @@ mod tests {
     fn test_slippage_calculation() {
         let signal = TradeSignal {
             signature: Signature::default(),
+            slot: 0,
             timestamp: 0,
             dex: DexType::Jupiter,
             source_mint: Pubkey::default(),
@@ -162,7 +857,143 @@ mod tests {
             trader: Pubkey::default(),
         };
 
-        let slippage = signal.calculate_slippage();
-        assert!((slippage - 100.0).abs() < 0.01); // Within 1% tolerance
+        assert_eq!(signal.calculate_slippage(), 100);
+        assert_eq!(signal.calculate_slippage(), signal.slippage_bps);
+        assert!(signal.description().contains("(Slippage: 1.00%)"));
+
+        // A minimum above the quote is no slippage, not an underflow
+        let above = TradeSignal {
+            minimum_amount_out: 1_100_000,
+            ..signal.clone()
+        };
+        assert_eq!(above.calculate_slippage(), 0);
+
+        let no_output = TradeSignal {
+            amount_out: 0,
+            ..signal
+        };
+        assert_eq!(no_output.calculate_slippage(), 0);
+    }
+
+    #[test]
+    fn test_portfolio_path_per_wallet() {
+        let wallet = Pubkey::new_unique();
+        let mut config = MonitorConfig::default();
+        assert_eq!(config.portfolio_path_for(&wallet), "portfolio.json");
+
+        config.portfolio_path = "portfolios/{wallet}.json".to_string();
+        assert_eq!(
+            config.portfolio_path_for(&wallet),
+            format!("portfolios/{}.json", wallet)
+        );
+    }
+
+    #[test]
+    fn test_monitored_wallets() {
+        let wallet = Pubkey::new_unique();
+        let config = MonitorConfig {
+            target_wallet: wallet,
+            ..MonitorConfig::default()
+        };
+        assert_eq!(config.monitored_wallets(), vec![wallet]);
+
+        let other = Pubkey::new_unique();
+        let config = MonitorConfig {
+            target_wallets: vec![wallet, other],
+            ..config
+        };
+        assert_eq!(config.monitored_wallets(), vec![wallet, other]);
+    }
+
+    #[test]
+    fn test_meteora_and_phoenix_are_recognized() {
+        for program_id in [program_ids::meteora_dlmm(), program_ids::meteora_pools()] {
+            assert_eq!(
+                DexType::from_program_id(&program_id),
+                Some(DexType::Meteora)
+            );
+        }
+        assert_eq!(
+            DexType::from_program_id(&program_ids::phoenix()),
+            Some(DexType::Phoenix)
+        );
+
+        assert_eq!("meteora".parse::<DexType>(), Ok(DexType::Meteora));
+        assert_eq!("Phoenix".parse::<DexType>(), Ok(DexType::Phoenix));
+    }
+
+    #[test]
+    fn test_wsol_pubkey() {
+        assert_eq!(wsol_pubkey().to_string(), WSOL_MINT);
+    }
+
+    #[test]
+    fn test_copy_delay() {
+        let instant = ExecutorConfig::default();
+        assert!(instant.copy_delay().is_zero());
+
+        let delayed = ExecutorConfig {
+            copy_delay_ms: 200,
+            copy_delay_jitter_ms: 50,
+            ..ExecutorConfig::default()
+        };
+        for _ in 0..20 {
+            let delay = delayed.copy_delay();
+            assert!(delay >= Duration::from_millis(200));
+            assert!(delay <= Duration::from_millis(250));
+        }
+    }
+
+    #[test]
+    fn test_raydium_clmm_is_recognized() {
+        assert_eq!(
+            DexType::from_program_id(&program_ids::raydium_clmm()),
+            Some(DexType::RaydiumClmm)
+        );
+        assert_eq!(
+            DexType::from_program_id(&program_ids::raydium_v4()),
+            Some(DexType::Raydium)
+        );
+
+        // The display name (used as `likely_dex`) parses back for DEX policies
+        let name = DexType::RaydiumClmm.to_string();
+        assert_eq!(name, "Raydium CLMM");
+        assert_eq!(name.parse::<DexType>(), Ok(DexType::RaydiumClmm));
+        assert_eq!("raydium_clmm".parse::<DexType>(), Ok(DexType::RaydiumClmm));
+    }
+
+    #[test]
+    fn test_dex_policy_lookup() {
+        assert_eq!("raydium".parse::<DexType>(), Ok(DexType::Raydium));
+        assert_eq!(" Jupiter ".parse::<DexType>(), Ok(DexType::Jupiter));
+        assert!("pumpfun".parse::<DexType>().is_err());
+
+        let mut config = MonitorConfig::default();
+        // Nothing configured: copy everything at full size
+        assert_eq!(config.dex_policy(Some("Orca")), DexPolicy::default());
+
+        config.dex_policies = HashMap::from([
+            (
+                DexType::Raydium,
+                DexPolicy {
+                    enabled: true,
+                    size_multiplier: 0.5,
+                },
+            ),
+            (
+                DexType::Unknown,
+                DexPolicy {
+                    enabled: false,
+                    size_multiplier: 1.0,
+                },
+            ),
+        ]);
+
+        assert_eq!(config.dex_policy(Some("Raydium")).size_multiplier, 0.5);
+
+        // Unlisted, unrecognized and missing DEXs use the Unknown policy
+        assert!(!config.dex_policy(Some("Orca")).enabled);
+        assert!(!config.dex_policy(Some("Unknown DEX")).enabled);
+        assert!(!config.dex_policy(None).enabled);
     }
 }
@@ -0,0 +1,121 @@
+//! Builders for realistic RPC transaction fixtures
+
+use serde_json::{Value, json};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
+
+const TOKEN_PROGRAM: &str = "TokenkegQfeZyiNwAJbNbGqPFXCWuBvf9Ss623VQ5DA";
+
+/// Builds a `jsonParsed` transaction as returned by `getTransaction`
+pub struct TxFixture {
+    signature: Signature,
+    slot: u64,
+    block_time: i64,
+    owner: Pubkey,
+    pre_token_balances: Vec<Value>,
+    post_token_balances: Vec<Value>,
+}
+
+impl TxFixture {
+    /// A transaction signed by `owner` with no balance changes yet
+    pub fn new(owner: Pubkey) -> Self {
+        Self {
+            signature: Signature::new_unique(),
+            slot: 250_000_000,
+            block_time: 1_700_000_000,
+            owner,
+            pre_token_balances: vec![],
+            post_token_balances: vec![],
+        }
+    }
+
+    pub fn slot(mut self, slot: u64) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    pub fn block_time(mut self, block_time: i64) -> Self {
+        self.block_time = block_time;
+        self
+    }
+
+    /// Add the owner's token account for `mint` with its pre/post amounts
+    pub fn balance(mut self, mint: Pubkey, decimals: u8, pre: u64, post: u64) -> Self {
+        let account_index = self.pre_token_balances.len() + 1;
+        self.pre_token_balances.push(token_balance(
+            account_index,
+            &mint,
+            &self.owner,
+            decimals,
+            pre,
+        ));
+        self.post_token_balances.push(token_balance(
+            account_index,
+            &mint,
+            &self.owner,
+            decimals,
+            post,
+        ));
+        self
+    }
+
+    pub fn signature(&self) -> Signature {
+        self.signature
+    }
+
+    pub fn build(self) -> EncodedConfirmedTransactionWithStatusMeta {
+        let fixture = json!({
+            "slot": self.slot,
+            "blockTime": self.block_time,
+            "transaction": {
+                "signatures": [self.signature.to_string()],
+                "message": {
+                    "accountKeys": [{
+                        "pubkey": self.owner.to_string(),
+                        "writable": true,
+                        "signer": true,
+                        "source": "transaction"
+                    }],
+                    "recentBlockhash": "11111111111111111111111111111111",
+                    "instructions": []
+                }
+            },
+            "meta": {
+                "err": null,
+                "status": { "Ok": null },
+                "fee": 5000,
+                "preBalances": [],
+                "postBalances": [],
+                "innerInstructions": [],
+                "logMessages": [],
+                "preTokenBalances": self.pre_token_balances,
+                "postTokenBalances": self.post_token_balances,
+                "rewards": []
+            }
+        });
+
+        serde_json::from_value(fixture).expect("valid transaction fixture")
+    }
+}
+
+fn token_balance(
+    account_index: usize,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    decimals: u8,
+    amount: u64,
+) -> Value {
+    json!({
+        "accountIndex": account_index,
+        "mint": mint.to_string(),
+        "owner": owner.to_string(),
+        "programId": TOKEN_PROGRAM,
+        "uiTokenAmount": {
+            "uiAmount": amount as f64 / 10f64.powi(decimals as i32),
+            "decimals": decimals,
+            "amount": amount.to_string(),
+            "uiAmountString": (amount as f64 / 10f64.powi(decimals as i32)).to_string()
+        }
+    })
+}
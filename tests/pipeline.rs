@@ -0,0 +1,268 @@
+//! End-to-end dry run: detection → direction → portfolio, as `main.rs` wires it
+
+mod common;
+
+use common::TxFixture;
+use copy_tradin::api::{self, ApiState, RecentSignals};
+use copy_tradin::decision::Stablecoins;
+use copy_tradin::{
+    MonitorConfig, ParseOutcome, PortfolioTracker, SkipReason, TradeDirection, UniversalParser,
+};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[test]
+fn test_buy_then_sell_realizes_pnl() {
+    let trader = Pubkey::new_unique();
+    let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+    let token = Pubkey::new_unique();
+
+    let parser = UniversalParser::new(trader);
+    let mut portfolio = PortfolioTracker::new();
+
+    // Buy: 100 USDC → 1,000 tokens
+    let buy_tx = TxFixture::new(trader)
+        .balance(usdc, 6, 500_000_000, 400_000_000)
+        .balance(token, 6, 0, 1_000_000_000);
+    let buy_signature = buy_tx.signature();
+
+    let buy = parser.parse(buy_tx.build()).unwrap().expect("buy detected");
+    assert_eq!(buy.signature, buy_signature);
+    assert_eq!(buy.slot, 250_000_000);
+    assert_eq!(buy.input_amount, 100_000_000);
+    assert_eq!(buy.output_amount, 1_000_000_000);
+    assert!(buy.is_new_mint);
+
+    let TradeDirection::Buy {
+        token: bought,
+        payment,
+    } = buy.direction()
+    else {
+        panic!("expected a buy, got {:?}", buy.direction());
+    };
+    assert_eq!(bought, token);
+    assert_eq!(payment, usdc);
+
+    portfolio.open_position(
+        bought,
+        buy.output_amount,
+        payment,
+        buy.input_amount,
+        buy.signature.to_string(),
+    );
+    assert_eq!(portfolio.get_stats().active_positions, 1);
+
+    // Sell: all 1,000 tokens → 150 USDC
+    let sell = parser
+        .parse(
+            TxFixture::new(trader)
+                .slot(250_000_100)
+                .balance(token, 6, 1_000_000_000, 0)
+                .balance(usdc, 6, 400_000_000, 550_000_000)
+                .build(),
+        )
+        .unwrap()
+        .expect("sell detected");
+
+    let TradeDirection::Sell { token: sold, .. } = sell.direction() else {
+        panic!("expected a sell, got {:?}", sell.direction());
+    };
+    assert_eq!(sold, token);
+
+    let closed = portfolio
+        .close_position(
+            &sold,
+            sell.input_amount,
+            sell.output_amount,
+            sell.signature.to_string(),
+        )
+        .unwrap();
+
+    assert_eq!(closed.realized_pnl, 50_000_000);
+    assert!((closed.realized_pnl_percent - 50.0).abs() < 1e-9);
+
+    let stats = portfolio.get_stats();
+    assert_eq!(stats.active_positions, 0);
+    assert_eq!(stats.closed_positions, 1);
+    assert_eq!(stats.total_realized_pnl, 50_000_000);
+}
+
+#[test]
+fn test_unrelated_wallet_is_ignored() {
+    let trader = Pubkey::new_unique();
+    let someone_else = Pubkey::new_unique();
+    let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+
+    let parser = UniversalParser::new(trader);
+    let tx = TxFixture::new(someone_else)
+        .balance(usdc, 6, 500_000_000, 400_000_000)
+        .balance(Pubkey::new_unique(), 6, 0, 1_000_000_000)
+        .build();
+
+    let outcome = parser.parse_outcome(tx).unwrap();
+    assert!(matches!(
+        outcome,
+        ParseOutcome::Skipped(SkipReason::NotASwap)
+    ));
+}
+
+#[test]
+fn test_low_priority_fee_is_skipped() {
+    let trader = Pubkey::new_unique();
+    let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+
+    let config = MonitorConfig {
+        target_wallet: trader,
+        min_priority_fee_lamports: 10_000,
+        ..MonitorConfig::default()
+    };
+    let parser = UniversalParser::with_config(&config);
+
+    // No ComputeBudget instructions, so no priority fee was paid
+    let tx = TxFixture::new(trader)
+        .balance(usdc, 6, 500_000_000, 400_000_000)
+        .balance(Pubkey::new_unique(), 6, 0, 1_000_000_000)
+        .build();
+
+    let outcome = parser.parse_outcome(tx).unwrap();
+    assert!(matches!(
+        outcome,
+        ParseOutcome::Skipped(SkipReason::BelowThreshold {
+            priority_fee_lamports: 0,
+            min: 10_000,
+        })
+    ));
+}
+
+#[test]
+fn test_stale_signal_is_skipped() {
+    let trader = Pubkey::new_unique();
+    let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+
+    let config = MonitorConfig {
+        target_wallet: trader,
+        max_signal_age_secs: Some(60),
+        ..MonitorConfig::default()
+    };
+    let parser = UniversalParser::with_config(&config);
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64;
+    let swap = |block_time: i64| {
+        TxFixture::new(trader)
+            .block_time(block_time)
+            .balance(usdc, 6, 500_000_000, 400_000_000)
+            .balance(Pubkey::new_unique(), 6, 0, 1_000_000_000)
+            .build()
+    };
+
+    // Replayed ten minutes after it happened
+    let outcome = parser.parse_outcome(swap(now - 600)).unwrap();
+    assert!(matches!(
+        outcome,
+        ParseOutcome::Skipped(SkipReason::Stale { age_secs, max: 60 }) if age_secs >= 600
+    ));
+
+    // A fresh one still goes through
+    assert!(parser.parse(swap(now - 5)).unwrap().is_some());
+}
+
+#[test]
+fn test_signal_names_the_wallet_that_traded() {
+    let first = Pubkey::new_unique();
+    let second = Pubkey::new_unique();
+    let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+
+    let config = MonitorConfig {
+        target_wallet: first,
+        target_wallets: vec![first, second],
+        ..MonitorConfig::default()
+    };
+    let parser = UniversalParser::with_config(&config);
+
+    let swap = |trader: Pubkey| {
+        TxFixture::new(trader)
+            .balance(usdc, 6, 500_000_000, 400_000_000)
+            .balance(Pubkey::new_unique(), 6, 0, 1_000_000_000)
+            .build()
+    };
+
+    let signal = parser.parse(swap(second)).unwrap().expect("swap detected");
+    assert_eq!(signal.trader, second);
+    assert_eq!(signal.input_amount, 100_000_000);
+
+    let signal = parser.parse(swap(first)).unwrap().expect("swap detected");
+    assert_eq!(signal.trader, first);
+
+    // Wallets outside the list are still ignored
+    assert!(parser.parse(swap(Pubkey::new_unique())).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_api_serves_live_portfolio() {
+    let usdc = Pubkey::from_str(Stablecoins::USDC).unwrap();
+    let token = Pubkey::new_unique();
+    let portfolio = Arc::new(Mutex::new(PortfolioTracker::new()));
+
+    let state = ApiState {
+        portfolio: Arc::clone(&portfolio),
+        signals: Arc::new(RecentSignals::new(10)),
+        display_currency: None,
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, api::router(state)).await });
+
+    let get = |path: &str| {
+        let url = format!("{}{}", base, path);
+        async move {
+            reqwest::get(url)
+                .await
+                .unwrap()
+                .error_for_status()
+                .unwrap()
+                .json::<Value>()
+                .await
+                .unwrap()
+        }
+    };
+
+    // Opened while the server runs: the same tracker is read back
+    portfolio.lock().unwrap().open_position(
+        token,
+        1_000_000_000,
+        usdc,
+        100_000_000,
+        "buy".to_string(),
+    );
+
+    let positions = get("/portfolio/positions").await;
+    assert_eq!(positions.as_array().unwrap().len(), 1);
+    assert_eq!(positions[0]["amount"], 1_000_000_000u64);
+    assert_eq!(positions[0]["cost_basis"], 100_000_000u64);
+    assert_eq!(positions[0]["entry_signature"], "buy");
+    assert_eq!(get("/portfolio/stats").await["active_positions"], 1);
+    assert_eq!(get("/portfolio/history").await, serde_json::json!([]));
+
+    // Sold for 150 USDC
+    portfolio
+        .lock()
+        .unwrap()
+        .close_position(&token, 1_000_000_000, 150_000_000, "sell".to_string())
+        .unwrap();
+
+    let history = get("/portfolio/history").await;
+    assert_eq!(history.as_array().unwrap().len(), 1);
+    assert_eq!(history[0]["realized_pnl"], 50_000_000);
+    assert_eq!(history[0]["exit_signature"], "sell");
+
+    let stats = get("/portfolio/stats").await;
+    assert_eq!(stats["active_positions"], 0);
+    assert_eq!(stats["closed_positions"], 1);
+    assert_eq!(get("/portfolio/positions").await, serde_json::json!([]));
+}